@@ -0,0 +1,145 @@
+use crate::model::CommitInfo;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Author identities merged for reporting, read from a file kept outside the
+/// repository (unlike `.mailmap`). Each line maps aliases onto one author:
+///
+/// ```text
+/// Alice Smith <alice@corp.example> = alice, asmith@old.example
+/// "Bob" = ["bob-laptop", "bob@home.example"]
+/// ```
+///
+/// Aliases match an author name or email, case-insensitively. Blank lines,
+/// `#`/`;` comments and `[section]` headers are ignored, so both INI- and
+/// TOML-style files work.
+#[derive(Debug, Clone, Default)]
+pub struct AuthorAliases {
+    canonical: Vec<Canonical>,
+    by_alias: HashMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Canonical {
+    name: String,
+    email: Option<String>,
+}
+
+/// Serialized with aliases in sorted order, so equal alias sets always encode
+/// the same way.
+impl Serialize for AuthorAliases {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let by_alias: std::collections::BTreeMap<_, _> = self.by_alias.iter().collect();
+        (&self.canonical, by_alias).serialize(serializer)
+    }
+}
+
+impl AuthorAliases {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut aliases = Self::default();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';', '[']) {
+                continue;
+            }
+            let Some((canonical, list)) = line.split_once('=') else {
+                return Err(format!("line {}: expected `canonical = alias, ...`", n + 1));
+            };
+            let canonical = unquote(canonical);
+            let (name, email) = match canonical.split_once('<') {
+                Some((name, email)) => (
+                    name.trim().to_string(),
+                    Some(email.trim_end_matches('>').trim().to_string()),
+                ),
+                None => (canonical.to_string(), None),
+            };
+            let index = aliases.canonical.len();
+            let keys = list
+                .trim()
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .split(',')
+                .map(unquote)
+                .chain([name.as_str()])
+                .chain(email.as_deref());
+            for key in keys.filter(|k| !k.is_empty()) {
+                match aliases.by_alias.insert(key.to_lowercase(), index) {
+                    Some(other) if other != index => {
+                        return Err(format!(
+                            "line {}: '{key}' is already an alias of '{}'",
+                            n + 1,
+                            aliases.canonical[other].name
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+            aliases.canonical.push(Canonical { name, email });
+        }
+        Ok(aliases)
+    }
+
+    /// The reported name and email for an author; unchanged when neither the
+    /// name nor the email is a known alias.
+    pub fn resolve<'a>(&'a self, name: &'a str, email: &'a str) -> (&'a str, &'a str) {
+        let found = self
+            .by_alias
+            .get(&name.to_lowercase())
+            .or_else(|| self.by_alias.get(&email.to_lowercase()));
+        match found.map(|&i| &self.canonical[i]) {
+            Some(c) => (&c.name, c.email.as_deref().unwrap_or(email)),
+            None => (name, email),
+        }
+    }
+
+    /// Rewrite `info`'s author in place; the cached commit is untouched.
+    pub fn apply(&self, info: &mut CommitInfo) {
+        let (name, email) = self.resolve(&info.author_name, &info.author_email);
+        let (name, email) = (name.to_string(), email.to_string());
+        info.author_name = name;
+        info.author_email = email;
+    }
+}
+
+fn unquote(s: &str) -> &str {
+    s.trim().trim_matches('"').trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_resolve_by_name_or_email() {
+        let aliases = AuthorAliases::parse(
+            "# team\n[authors]\nAlice Smith <alice@corp.example> = alice, asmith@old.example\n\"Bob\" = [\"bob-laptop\"]\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            aliases.resolve("ALICE", "a@home.example"),
+            ("Alice Smith", "alice@corp.example")
+        );
+        assert_eq!(
+            aliases.resolve("A. Smith", "asmith@old.example"),
+            ("Alice Smith", "alice@corp.example")
+        );
+        assert_eq!(
+            aliases.resolve("bob-laptop", "bob@home.example"),
+            ("Bob", "bob@home.example")
+        );
+        assert_eq!(
+            aliases.resolve("Carol", "carol@example.com"),
+            ("Carol", "carol@example.com")
+        );
+        assert!(AuthorAliases::parse("no equals sign").is_err());
+        assert!(AuthorAliases::parse("A = x\nB = x").is_err());
+    }
+}
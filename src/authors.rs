@@ -0,0 +1,104 @@
+use crate::cache::Cache;
+use crate::cli::CommonArgs;
+use crate::error::{GmapError, Result};
+use crate::filter::CommitFilter;
+use crate::heat::fetch_commit_stats_reported;
+use crate::model::{AuthorEntry, AuthorsOutput, CommitStats, SCHEMA_VERSION};
+use crate::util::to_json;
+use anyhow::Context;
+use chrono::Utc;
+use console::style;
+use std::collections::HashMap;
+
+/// List the distinct author identities in range with their commit counts, to
+/// find the exact strings `--author` and `--author-email` will match.
+pub fn exec(common: CommonArgs, json: bool, compact: bool) -> anyhow::Result<()> {
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let mut cache = common
+        .open_cache(&repo)
+        .context("Failed to initialize cache")?;
+
+    let range = common
+        .resolve_range(&repo)
+        .context("Failed to resolve date range")?;
+
+    let (stats, report) = fetch_commit_stats_reported(
+        &repo,
+        &mut cache,
+        &range,
+        common.merge_policy(),
+        common.binary_policy(),
+        common.recompute,
+        true,
+    )?;
+
+    let authors = compute_authors(&stats, &cache, &common.commit_filter())
+        .context("Failed to list authors")?;
+
+    if json {
+        let output = AuthorsOutput {
+            version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            repository_path: repo.path().display().to_string(),
+            since: common.since_arg().map(str::to_string),
+            until: common.until_arg().map(str::to_string),
+            authors,
+        };
+        println!("{}", to_json(&output, compact)?);
+    } else {
+        output_table(&authors);
+    }
+    report.announce();
+    Ok(())
+}
+
+/// Commits per author name/email pair, most active first. Aliased authors are
+/// reported under their canonical identity.
+pub fn compute_authors(
+    stats: &[CommitStats],
+    cache: &Cache,
+    filter: &CommitFilter,
+) -> Result<Vec<AuthorEntry>> {
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+    for cs in stats {
+        let mut info = cache
+            .get_commit_info(&cs.commit_id)?
+            .ok_or_else(|| GmapError::Cache("Commit info not found".to_string()))?;
+        if !filter.matches(&info) {
+            continue;
+        }
+        filter.resolve_author(&mut info);
+        *counts
+            .entry((info.author_name, info.author_email))
+            .or_default() += 1;
+    }
+    let mut authors: Vec<AuthorEntry> = counts
+        .into_iter()
+        .map(|((name, email), commits)| AuthorEntry {
+            name,
+            email,
+            commits,
+        })
+        .collect();
+    authors.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then_with(|| (&a.name, &a.email).cmp(&(&b.name, &b.email)))
+    });
+    Ok(authors)
+}
+
+fn output_table(authors: &[AuthorEntry]) {
+    println!(
+        "{:>8}  {:<30} {}",
+        style("Commits").bold(),
+        style("Name").bold(),
+        style("Email").bold()
+    );
+    println!("{}", "─".repeat(70));
+    for a in authors {
+        println!("{:>8}  {:<30} {}", a.commits, a.name, a.email);
+    }
+}
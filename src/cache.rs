@@ -1,31 +1,144 @@
 use crate::error::{GmapError, Result};
-use crate::model::{CommitInfo, CommitStats, DateRange, FileStats, SCHEMA_VERSION};
+use crate::model::{
+    CommitInfo, CommitStats, DateKind, DateRange, FileStats, MergeChurn, MergeDiff, SignatureStatus,
+};
 use chrono::{TimeZone, Utc};
-use rusqlite::{params, Connection, ToSql};
+use rusqlite::{params, Connection, ErrorCode, ToSql};
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// On-disk layout version, tracked independently of the JSON output version.
+/// 2 added `files.size`, 3 `commits.signature`, 4 `files.bytes_added` and
+/// `files.bytes_deleted`, 5 `files.summarized`.
+const CACHE_SCHEMA_VERSION: u32 = 5;
+
+/// Errors meaning the file itself is damaged, as opposed to locked or unwritable.
+fn is_corruption(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+    )
+}
 
 pub struct Cache {
     conn: Connection,
 }
 
 impl Cache {
+    /// Open the cache for `date_kind`. Commit timestamps are stored under the
+    /// selected kind, so author- and committer-dated caches live in separate files.
     pub fn new<CP: AsRef<Path>, RP: AsRef<Path>>(
         cache_path: Option<CP>,
         repo_path: RP,
+        date_kind: DateKind,
+    ) -> Result<Self> {
+        Self::open(
+            cache_path,
+            repo_path,
+            date_kind,
+            MergeDiff::default(),
+            MergeChurn::default(),
+            true,
+        )
+    }
+
+    /// Like [`Cache::new`]. Combined merge diffs and resolution-only merge churn
+    /// give merges different stats, so they get their own files too. With
+    /// `reset_on_error`, a database SQLite reports as corrupt is moved aside to
+    /// `<name>.corrupt` and replaced by an empty one.
+    pub fn open<CP: AsRef<Path>, RP: AsRef<Path>>(
+        cache_path: Option<CP>,
+        repo_path: RP,
+        date_kind: DateKind,
+        merge_diff: MergeDiff,
+        merge_churn: MergeChurn,
+        reset_on_error: bool,
     ) -> Result<Self> {
-        let cache_dir = match cache_path {
+        let cache_dir = Self::dir(cache_path, repo_path);
+        std::fs::create_dir_all(&cache_dir)?;
+        let db_path = cache_dir.join(Self::db_file_name(date_kind, merge_diff, merge_churn));
+        match Self::open_db(&db_path) {
+            Err(GmapError::Database(err)) if reset_on_error && is_corruption(&err) => {
+                let backup = Self::backup_path(&db_path);
+                std::fs::rename(&db_path, &backup)?;
+                eprintln!(
+                    "warning: cache {} is unreadable ({err}); moved it to {} and starting a fresh cache",
+                    db_path.display(),
+                    backup.display()
+                );
+                Self::open_db(&db_path)
+            }
+            result => result,
+        }
+    }
+
+    /// Directory holding the cache databases: `cache_path`, or `.gmap` in the repo.
+    pub fn dir<CP: AsRef<Path>, RP: AsRef<Path>>(cache_path: Option<CP>, repo_path: RP) -> PathBuf {
+        match cache_path {
             Some(path) => path.as_ref().to_path_buf(),
             None => repo_path.as_ref().join(".gmap"),
-        };
-        std::fs::create_dir_all(&cache_dir)?;
-        let db_path = cache_dir.join("cache.db");
-        let conn = Connection::open(&db_path)?;
+        }
+    }
+
+    /// Delete gmap's databases (every date kind and merge diff, SQLite side files and
+    /// `.corrupt` backups) from `cache_dir`, returning each file and its size.
+    /// With `dry_run` nothing is removed. Other files in the directory are left
+    /// alone; the directory itself is removed only if that leaves it empty.
+    pub fn clean(cache_dir: &Path, dry_run: bool) -> Result<Vec<(PathBuf, u64)>> {
+        let mut removed = Vec::new();
+        for kind in [DateKind::Committer, DateKind::Author] {
+            for merge_diff in [MergeDiff::FirstParent, MergeDiff::Combined] {
+                for merge_churn in [MergeChurn::Full, MergeChurn::Resolution] {
+                    for suffix in ["", "-journal", "-wal", "-shm", ".corrupt"] {
+                        let db = Self::db_file_name(kind, merge_diff, merge_churn);
+                        let path = cache_dir.join(format!("{db}{suffix}"));
+                        let Ok(meta) = std::fs::metadata(&path) else {
+                            continue;
+                        };
+                        if !dry_run {
+                            std::fs::remove_file(&path)?;
+                        }
+                        removed.push((path, meta.len()));
+                    }
+                }
+            }
+        }
+        if !dry_run {
+            // fails harmlessly when the directory holds anything else
+            let _ = std::fs::remove_dir(cache_dir);
+        }
+        Ok(removed)
+    }
+
+    fn open_db(db_path: &Path) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
         let mut cache = Self { conn };
         cache.initialize()?;
         Ok(cache)
     }
 
+    fn backup_path(db_path: &Path) -> PathBuf {
+        let mut name = db_path.as_os_str().to_owned();
+        name.push(".corrupt");
+        PathBuf::from(name)
+    }
+
+    fn db_file_name(date_kind: DateKind, merge_diff: MergeDiff, merge_churn: MergeChurn) -> String {
+        let date = match date_kind {
+            DateKind::Committer => "",
+            DateKind::Author => "-author",
+        };
+        let merges = match merge_diff {
+            MergeDiff::FirstParent => "",
+            MergeDiff::Combined => "-combined",
+        };
+        let churn = match merge_churn {
+            MergeChurn::Full => "",
+            MergeChurn::Resolution => "-resolution",
+        };
+        format!("cache{date}{merges}{churn}.db")
+    }
+
     fn initialize(&mut self) -> Result<()> {
         self.conn.execute_batch(
             "
@@ -35,7 +148,8 @@ impl Cache {
                 author_email TEXT NOT NULL,
                 message TEXT NOT NULL,
                 timestamp INTEGER NOT NULL,
-                parent_ids TEXT NOT NULL
+                parent_ids TEXT NOT NULL,
+                signature TEXT
             );
             CREATE TABLE IF NOT EXISTS files (
                 commit_id TEXT NOT NULL,
@@ -43,9 +157,18 @@ impl Cache {
                 added_lines INTEGER NOT NULL,
                 deleted_lines INTEGER NOT NULL,
                 is_binary INTEGER NOT NULL,
+                size INTEGER,
+                bytes_added INTEGER NOT NULL DEFAULT 0,
+                bytes_deleted INTEGER NOT NULL DEFAULT 0,
+                summarized INTEGER,
                 PRIMARY KEY (commit_id, path),
                 FOREIGN KEY (commit_id) REFERENCES commits(id)
             );
+            CREATE TABLE IF NOT EXISTS week_aggregates (
+                key TEXT PRIMARY KEY,
+                fingerprint TEXT NOT NULL,
+                weeks TEXT NOT NULL
+            );
             CREATE INDEX IF NOT EXISTS idx_commits_timestamp ON commits(timestamp);
             CREATE INDEX IF NOT EXISTS idx_files_path ON files(path);
             ",
@@ -60,11 +183,46 @@ impl Cache {
             .query_row("PRAGMA user_version;", [], |row| row.get(0))?;
 
         if user_version == 0 {
-            let set_stmt = format!("PRAGMA user_version = {SCHEMA_VERSION};");
+            let set_stmt = format!("PRAGMA user_version = {CACHE_SCHEMA_VERSION};");
             self.conn.execute_batch(&set_stmt)?;
-        } else if user_version != SCHEMA_VERSION as i64 {
+        } else if (1..CACHE_SCHEMA_VERSION as i64).contains(&user_version) {
+            // Caches before 4 hold no byte deltas, and runs without
+            // --binary stored no rows for binary files at all, so no cached
+            // commit can be trusted to list its binaries. Every commit is
+            // dropped to be analyzed again, along with aggregates built
+            // from them. Schema 4 summarized commits under a `<N files>`
+            // path instead of `summarized`; only those are analyzed again.
+            let size = if user_version < 2 {
+                "ALTER TABLE files ADD COLUMN size INTEGER;"
+            } else {
+                ""
+            };
+            let signature = if user_version < 3 {
+                "ALTER TABLE commits ADD COLUMN signature TEXT;"
+            } else {
+                ""
+            };
+            let stale = if user_version < 4 {
+                "ALTER TABLE files ADD COLUMN bytes_added INTEGER NOT NULL DEFAULT 0;
+                 ALTER TABLE files ADD COLUMN bytes_deleted INTEGER NOT NULL DEFAULT 0;
+                 DELETE FROM files;
+                 DELETE FROM commits;"
+            } else {
+                "DELETE FROM commits WHERE id IN
+                     (SELECT commit_id FROM files WHERE path GLOB '<[0-9]* files>');
+                 DELETE FROM files WHERE commit_id NOT IN (SELECT id FROM commits);"
+            };
+            self.conn.execute_batch(&format!(
+                "{size}
+                 {signature}
+                 {stale}
+                 ALTER TABLE files ADD COLUMN summarized INTEGER;
+                 DELETE FROM week_aggregates;
+                 PRAGMA user_version = {CACHE_SCHEMA_VERSION};"
+            ))?;
+        } else if user_version != CACHE_SCHEMA_VERSION as i64 {
             return Err(GmapError::Cache(format!(
-                "Schema version mismatch: expected {SCHEMA_VERSION}, found {user_version}"
+                "Schema version mismatch: expected {CACHE_SCHEMA_VERSION}, found {user_version}"
             )));
         }
 
@@ -73,7 +231,8 @@ impl Cache {
 
     pub fn get_commit_stats(&self, range: &DateRange) -> Result<Vec<CommitStats>> {
         let mut query = String::from(
-            "SELECT c.id, c.timestamp, f.path, f.added_lines, f.deleted_lines, f.is_binary
+            "SELECT c.id, c.timestamp, f.path, f.added_lines, f.deleted_lines, f.is_binary, f.size,
+                    f.bytes_added, f.bytes_deleted, f.summarized
              FROM commits c
              LEFT JOIN files f ON c.id = f.commit_id
              WHERE 1=1",
@@ -99,6 +258,10 @@ impl Cache {
             let added_opt: Option<u32> = row.get(3)?;
             let deleted_opt: Option<u32> = row.get(4)?;
             let is_binary_opt: Option<i64> = row.get(5)?;
+            let size: Option<u64> = row.get(6)?;
+            let bytes_added: Option<u64> = row.get(7)?;
+            let bytes_deleted: Option<u64> = row.get(8)?;
+            let summarized: Option<usize> = row.get(9)?;
             let mut files = Vec::new();
             if let (Some(path), Some(added), Some(deleted), Some(is_binary_int)) =
                 (path_opt, added_opt, deleted_opt, is_binary_opt)
@@ -109,6 +272,11 @@ impl Cache {
                     added_lines: added,
                     deleted_lines: deleted,
                     is_binary,
+                    size,
+                    bytes_added: bytes_added.unwrap_or(0),
+                    bytes_deleted: bytes_deleted.unwrap_or(0),
+                    hunks: Vec::new(),
+                    summarized_files: summarized,
                 });
             }
             Ok((commit_id, ts, files))
@@ -117,6 +285,9 @@ impl Cache {
         let mut commits_map: HashMap<String, (i64, Vec<FileStats>)> = HashMap::new();
         for row in rows {
             let (commit_id, ts, mut files) = row?;
+            if !range.contains_commit(&commit_id) {
+                continue;
+            }
             let entry = commits_map.entry(commit_id).or_insert((ts, Vec::new()));
             // keep the earliest timestamp seen for determinism (should be identical per commit)
             if ts < entry.0 {
@@ -129,11 +300,15 @@ impl Cache {
             .into_iter()
             .map(|(id, (ts, files))| (id, ts, files))
             .collect();
-        items.sort_by(|a, b| a.1.cmp(&b.1));
+        items.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
 
         Ok(items
             .into_iter()
-            .map(|(commit_id, _ts, files)| CommitStats { commit_id, files })
+            .map(|(commit_id, _ts, files)| CommitStats {
+                commit_id,
+                files,
+                binary_paths: Vec::new(),
+            })
             .collect())
     }
 
@@ -145,13 +320,13 @@ impl Cache {
         let tx = self.conn.transaction()?;
 
         let mut insert_commit_stmt = tx.prepare(
-            "INSERT OR REPLACE INTO commits (id, author_name, author_email, message, timestamp, parent_ids)
-             VALUES (?, ?, ?, ?, ?, ?)",
+            "INSERT OR REPLACE INTO commits (id, author_name, author_email, message, timestamp, parent_ids, signature)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
         )?;
         let mut delete_files_stmt = tx.prepare("DELETE FROM files WHERE commit_id = ?")?;
         let mut insert_file_stmt = tx.prepare(
-            "INSERT INTO files (commit_id, path, added_lines, deleted_lines, is_binary)
-             VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO files (commit_id, path, added_lines, deleted_lines, is_binary, size, bytes_added, bytes_deleted, summarized)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )?;
 
         for stats in commits {
@@ -162,7 +337,8 @@ impl Cache {
                     info.author_email,
                     info.message,
                     info.timestamp.timestamp(),
-                    serde_json::to_string(&info.parent_ids)?
+                    serde_json::to_string(&info.parent_ids)?,
+                    info.signature.map(SignatureStatus::as_str)
                 ])?;
 
                 delete_files_stmt.execute(params![stats.commit_id])?;
@@ -175,7 +351,11 @@ impl Cache {
                             f.path,
                             f.added_lines,
                             f.deleted_lines,
-                            if f.is_binary { 1 } else { 0 }
+                            if f.is_binary { 1 } else { 0 },
+                            f.size,
+                            f.bytes_added,
+                            f.bytes_deleted,
+                            f.summarized_files
                         ])?;
                     }
                 }
@@ -213,7 +393,7 @@ impl Cache {
 
     pub fn get_commit_info(&self, commit_id: &str) -> Result<Option<CommitInfo>> {
         let result = self.conn.query_row(
-            "SELECT id, author_name, author_email, message, timestamp, parent_ids FROM commits WHERE id = ?",
+            "SELECT id, author_name, author_email, message, timestamp, parent_ids, signature FROM commits WHERE id = ?",
             params![commit_id],
             |row| {
                 let ts: i64 = row.get(4)?;
@@ -244,6 +424,10 @@ impl Cache {
                     message: row.get(3)?,
                     timestamp,
                     parent_ids,
+                    signature: row
+                        .get::<_, Option<String>>(6)?
+                        .as_deref()
+                        .and_then(SignatureStatus::parse),
                 })
             },
         );
@@ -253,4 +437,151 @@ impl Cache {
             Err(e) => Err(e.into()),
         }
     }
+
+    /// The serialized weekly aggregate stored under `key`, if it was computed
+    /// from the commits `fingerprint` identifies.
+    pub fn get_week_aggregate(&self, key: &str, fingerprint: &str) -> Result<Option<String>> {
+        let result = self.conn.query_row(
+            "SELECT weeks FROM week_aggregates WHERE key = ? AND fingerprint = ?",
+            params![key, fingerprint],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(weeks) => Ok(Some(weeks)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Store a weekly aggregate, replacing whatever `key` held before.
+    pub fn store_week_aggregate(&self, key: &str, fingerprint: &str, weeks: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO week_aggregates (key, fingerprint, weeks) VALUES (?, ?, ?)",
+            params![key, fingerprint, weeks],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrading_to_byte_deltas_drops_every_cached_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join(Cache::db_file_name(
+            DateKind::Committer,
+            MergeDiff::default(),
+            MergeChurn::default(),
+        ));
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE commits (
+                 id TEXT PRIMARY KEY, author_name TEXT NOT NULL,
+                 author_email TEXT NOT NULL, message TEXT NOT NULL,
+                 timestamp INTEGER NOT NULL, parent_ids TEXT NOT NULL, signature TEXT
+             );
+             CREATE TABLE files (
+                 commit_id TEXT NOT NULL, path TEXT NOT NULL,
+                 added_lines INTEGER NOT NULL, deleted_lines INTEGER NOT NULL,
+                 is_binary INTEGER NOT NULL, size INTEGER,
+                 PRIMARY KEY (commit_id, path)
+             );
+             CREATE TABLE week_aggregates (
+                 key TEXT PRIMARY KEY, fingerprint TEXT NOT NULL, weeks TEXT NOT NULL
+             );
+             INSERT INTO commits VALUES ('text', 'a', 'a@x', 'm', 1, '', NULL);
+             INSERT INTO commits VALUES ('asset', 'a', 'a@x', 'm', 2, '', NULL);
+             INSERT INTO files VALUES ('text', 'a.rs', 3, 1, 0, 10);
+             INSERT INTO files VALUES ('asset', 'logo.png', 0, 0, 1, 100);
+             INSERT INTO files VALUES ('asset', 'b.rs', 1, 0, 0, 5);
+             -- Built without --binary: the commit also added logo.png, but
+             -- only its text file was stored.
+             INSERT INTO commits VALUES ('mixed', 'a', 'a@x', 'm', 3, '', NULL);
+             INSERT INTO files VALUES ('mixed', 'c.rs', 2, 0, 0, 8);
+             INSERT INTO week_aggregates VALUES ('k', 'f', '[]');
+             PRAGMA user_version = 3;",
+        )
+        .unwrap();
+        drop(conn);
+
+        let cache = Cache::new(Some(dir.path()), dir.path(), DateKind::Committer).unwrap();
+        let stats = cache.get_commit_stats(&DateRange::new()).unwrap();
+        assert!(stats.is_empty());
+        let files: i64 = cache
+            .conn
+            .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(files, 0);
+        assert!(cache.get_week_aggregate("k", "f").unwrap().is_none());
+    }
+
+    #[test]
+    fn upgrading_to_summary_markers_drops_only_summarized_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join(Cache::db_file_name(
+            DateKind::Committer,
+            MergeDiff::default(),
+            MergeChurn::default(),
+        ));
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE commits (
+                 id TEXT PRIMARY KEY, author_name TEXT NOT NULL,
+                 author_email TEXT NOT NULL, message TEXT NOT NULL,
+                 timestamp INTEGER NOT NULL, parent_ids TEXT NOT NULL, signature TEXT
+             );
+             CREATE TABLE files (
+                 commit_id TEXT NOT NULL, path TEXT NOT NULL,
+                 added_lines INTEGER NOT NULL, deleted_lines INTEGER NOT NULL,
+                 is_binary INTEGER NOT NULL, size INTEGER,
+                 bytes_added INTEGER NOT NULL DEFAULT 0,
+                 bytes_deleted INTEGER NOT NULL DEFAULT 0,
+                 PRIMARY KEY (commit_id, path)
+             );
+             CREATE TABLE week_aggregates (
+                 key TEXT PRIMARY KEY, fingerprint TEXT NOT NULL, weeks TEXT NOT NULL
+             );
+             INSERT INTO commits VALUES ('plain', 'a', 'a@x', 'm', 1, '', NULL);
+             INSERT INTO commits VALUES ('vendor', 'a', 'a@x', 'm', 2, '', NULL);
+             INSERT INTO files VALUES ('plain', 'a.rs', 3, 1, 0, 10, 10, 0);
+             INSERT INTO files VALUES ('vendor', '<300 files>', 900, 0, 0, 50, 50, 0);
+             PRAGMA user_version = 4;",
+        )
+        .unwrap();
+        drop(conn);
+
+        let mut cache = Cache::new(Some(dir.path()), dir.path(), DateKind::Committer).unwrap();
+        let stats = cache.get_commit_stats(&DateRange::new()).unwrap();
+        let ids: Vec<&str> = stats.iter().map(|c| c.commit_id.as_str()).collect();
+        assert_eq!(ids, ["plain"]);
+        assert_eq!(stats[0].files[0].summarized_files, None);
+
+        // Summary rows now round-trip through the marker column.
+        let summarized = CommitStats {
+            commit_id: "vendor".to_string(),
+            files: vec![FileStats::summary(300, 50, 50, 0)],
+            binary_paths: Vec::new(),
+        };
+        let info = CommitInfo {
+            id: "vendor".to_string(),
+            author_name: "a".to_string(),
+            author_email: "a@x".to_string(),
+            message: "m".to_string(),
+            timestamp: Utc.timestamp_opt(2, 0).unwrap(),
+            parent_ids: Vec::new(),
+            signature: None,
+        };
+        cache
+            .store_commit_stats(
+                std::slice::from_ref(&summarized),
+                &HashMap::from([("vendor".to_string(), info)]),
+            )
+            .unwrap();
+        let stats = cache.get_commit_stats(&DateRange::new()).unwrap();
+        let stored = stats.iter().find(|c| c.commit_id == "vendor").unwrap();
+        assert_eq!(stored.summarized_files(), Some(300));
+        assert_eq!(stored.files[0].path, "");
+    }
 }
@@ -1,100 +1,307 @@
 use crate::cache::Cache;
-use crate::cli::CommonArgs;
+use crate::cli::{CommonArgs, OutputArgs};
 use crate::error::Result;
-use crate::git::GitRepo;
-use crate::heat::fetch_commit_stats_with_progress;
-use crate::model::{ChurnEntry, ChurnOutput, CommitStats};
-use crate::util::path_excluded;
-use anyhow::Context;
+use crate::filter::CommitFilter;
+use crate::group::{compute_groups, write_groups_json, write_groups_table};
+use crate::heat::fetch_commit_stats_reported;
+use crate::model::{
+    BinaryPolicy, ChurnEntry, ChurnSort, ChurnTreeNode, ChurnTreeOutput, CommitStats, GroupBy,
+    PeriodSeries, SCHEMA_VERSION,
+};
+use crate::output::{to_stdout, FormatRegistry, OutputContext};
+use crate::util::{
+    commit_size, dense_period_keys, files_matching, path_excluded, period_key, recency_weight,
+    threshold_exit, to_json, GitIgnoreMatcher, OutlierThreshold, PeriodSpec,
+};
+use anyhow::{bail, Context};
 use chrono::Utc;
 use console::style;
-use std::collections::HashMap;
-
-pub fn exec(
-    common: CommonArgs,
-    depth: Option<u32>,
-    json: bool,
-    ndjson: bool,
-    path: Option<String>,
-) -> anyhow::Result<()> {
-    let repo = GitRepo::open(common.repo.as_ref()).context("Failed to open git repository")?;
-    let mut cache =
-        Cache::new(common.cache.as_deref(), repo.path()).context("Failed to initialize cache")?;
-
-    let range = repo
-        .resolve_range(common.since.as_deref(), common.until.as_deref())
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::process::ExitCode;
+
+/// Options for `gmap churn`, on top of [`CommonArgs`].
+#[derive(Clone, Debug)]
+pub struct ChurnOptions {
+    pub output: OutputArgs,
+    /// Directory depth to aggregate paths at
+    pub depth: Option<u32>,
+    /// Drop commits this threshold flags as outliers before aggregating
+    pub exclude_outliers: Option<OutlierThreshold>,
+    /// Half-life of the recency weighting, when weighted scores are wanted
+    pub half_life_days: Option<f64>,
+    pub sort: ChurnSort,
+    /// Exit with a failure code when a path changes more lines than this
+    pub max_file_churn: Option<u64>,
+    pub group_by: Option<GroupBy>,
+    pub tree: bool,
+    pub velocity: bool,
+    pub spark: bool,
+    pub path: Option<String>,
+}
+
+/// Paths a churn aggregation covers and how they are rolled up, shared by
+/// [`compute_churn`] and [`churn_series`].
+#[derive(Clone, Copy)]
+pub struct ChurnScope<'a> {
+    pub cache: &'a Cache,
+    /// Directory depth to aggregate paths at
+    pub depth: Option<u32>,
+    pub path_prefix: Option<&'a str>,
+    pub filter: &'a CommitFilter,
+    pub excludes: &'a [String],
+    pub git_ignore: Option<&'a GitIgnoreMatcher>,
+}
+
+pub fn exec(common: CommonArgs, options: ChurnOptions) -> anyhow::Result<ExitCode> {
+    let ChurnOptions {
+        output,
+        depth,
+        exclude_outliers,
+        half_life_days,
+        sort,
+        max_file_churn,
+        group_by,
+        tree,
+        velocity,
+        spark,
+        path,
+    } = options;
+    let registry = FormatRegistry::builtin();
+    let format = registry.get(output.format_name()?)?;
+    if group_by.is_some() && !matches!(format.name(), "table" | "json") {
+        bail!("Grouped churn supports table and JSON output only");
+    }
+    if tree && !matches!(format.name(), "table" | "json") {
+        bail!("--tree supports table and JSON output only");
+    }
+    if spark && format.name() != "table" {
+        bail!("--spark supports table output only");
+    }
+
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let mut cache = common
+        .open_cache(&repo)
+        .context("Failed to initialize cache")?;
+
+    let range = common
+        .resolve_range(&repo)
         .context("Failed to resolve date range")?;
 
-    let cached = fetch_commit_stats_with_progress(
+    let (mut cached, report) = fetch_commit_stats_reported(
         &repo,
         &mut cache,
         &range,
-        common.include_merges,
-        common.binary,
-        false,
+        common.merge_policy(),
+        common.binary_policy(),
+        common.recompute,
+        true,
     )?;
 
-    let churn = compute_churn(
-        &cached,
-        &cache,
+    let gi = common.git_ignore(&repo);
+    if let Some(threshold) = exclude_outliers {
+        // Ranked among the commits the filters keep and sized by their kept
+        // paths, as `export --flag-outliers` does.
+        let filter = common.commit_filter();
+        let mut kept = Vec::with_capacity(cached.len());
+        for cs in cached {
+            if cache
+                .get_commit_info(&cs.commit_id)?
+                .is_some_and(|info| filter.matches(&info))
+            {
+                kept.push(cs);
+            }
+        }
+        let sizes: Vec<u64> = kept
+            .iter()
+            .map(|cs| {
+                commit_size(
+                    &cs.files,
+                    &common.exclude,
+                    filter.case_sensitive(),
+                    gi.as_ref(),
+                )
+            })
+            .collect();
+        let mut flags = threshold.flag(&sizes).into_iter();
+        kept.retain(|_| !flags.next().unwrap_or(false));
+        cached = kept;
+    }
+    if let Some(group_by) = group_by {
+        let groups = compute_groups(
+            &cached,
+            &cache,
+            path.as_deref(),
+            &common.commit_filter(),
+            &common.exclude,
+            gi.as_ref(),
+            group_by,
+        )
+        .context("Failed to group churn statistics")?;
+        let ctx = OutputContext {
+            repository_path: repo.path().display().to_string(),
+            since: common.since_arg(),
+            until: common.until_arg(),
+            path_prefix: path.as_deref(),
+            compact: output.compact,
+            ..Default::default()
+        };
+        to_stdout(|out| match format.name() {
+            "json" => write_groups_json(out, &ctx, group_by, &groups),
+            _ => write_groups_table(out, group_by, &groups),
+        })?;
+        report.announce();
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if tree {
+        let root = compute_churn_tree(
+            &cached,
+            &cache,
+            path.as_deref(),
+            &common.commit_filter(),
+            &common.exclude,
+            gi.as_ref(),
+        )
+        .context("Failed to compute churn tree")?;
+        to_stdout(|out| match format.name() {
+            "json" => {
+                let doc = ChurnTreeOutput {
+                    version: SCHEMA_VERSION,
+                    generated_at: Utc::now(),
+                    repository_path: repo.path().display().to_string(),
+                    since: common.since_arg().map(str::to_string),
+                    until: common.until_arg().map(str::to_string),
+                    tree: root,
+                };
+                writeln!(out, "{}", to_json(&doc, output.compact)?)?;
+                Ok(())
+            }
+            _ => write_tree_table(out, &root),
+        })?;
+        report.announce();
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let filter = common.commit_filter();
+    let scope = ChurnScope {
+        cache: &cache,
         depth,
-        path.as_deref(),
-        common.author.as_deref(),
-        common.author_email.as_deref(),
-        &common.exclude,
-    )
-    .context("Failed to compute churn statistics")?;
-
-    if json {
-        output_json(&churn, &repo, &common, depth)?;
-    } else if ndjson {
-        output_ndjson(&churn)?;
-    } else {
-        output_table(&churn)?;
+        path_prefix: path.as_deref(),
+        filter: &filter,
+        excludes: &common.exclude,
+        git_ignore: gi.as_ref(),
+    };
+    let mut churn = compute_churn(&cached, &scope, half_life_days)
+        .context("Failed to compute churn statistics")?;
+    let velocity = velocity || sort == ChurnSort::Velocity;
+    if velocity {
+        for entry in &mut churn {
+            entry.velocity = Some(entry.lines_per_day());
+        }
     }
+    match sort {
+        ChurnSort::Total => {}
+        ChurnSort::Weighted => {
+            churn.sort_by(|a, b| {
+                b.weighted_score
+                    .total_cmp(&a.weighted_score)
+                    .then_with(|| a.path.cmp(&b.path))
+            });
+        }
+        ChurnSort::Velocity => sort_by_velocity(&mut churn),
+    }
+    let series = if spark {
+        Some(
+            churn_series(&cached, &scope, common.period_spec(false))
+                .context("Failed to compute churn series")?,
+        )
+    } else {
+        None
+    };
+
+    let ctx = OutputContext {
+        repository_path: repo.path().display().to_string(),
+        since: common.since_arg(),
+        until: common.until_arg(),
+        path_prefix: path.as_deref(),
+        depth,
+        compact: output.compact,
+        weighted: half_life_days.is_some(),
+        velocity,
+        churn_series: series.as_ref(),
+        ndjson_header: output.ndjson_header,
+        bytes: common.binary_policy() != BinaryPolicy::Exclude,
+        ..Default::default()
+    };
+    to_stdout(|out| format.write_churn(out, &ctx, &churn))?;
+    report.announce();
 
-    Ok(())
+    let breaches: Vec<String> = max_file_churn
+        .map(|limit| {
+            churn
+                .iter()
+                .filter(|e| e.total_lines > limit)
+                .map(|e| {
+                    format!(
+                        "{}: {} lines changed (--max-file-churn {limit})",
+                        e.path, e.total_lines
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(threshold_exit(&breaches))
 }
 
-fn compute_churn(
+/// Aggregate churn per path. With `half_life_days`, each commit also counts
+/// toward `weighted_score` by its recency relative to now.
+pub fn compute_churn(
     stats: &[CommitStats],
-    cache: &Cache,
-    depth: Option<u32>,
-    path_prefix: Option<&str>,
-    author: Option<&str>,
-    author_email: Option<&str>,
-    excludes: &[String],
+    scope: &ChurnScope,
+    half_life_days: Option<f64>,
 ) -> Result<Vec<ChurnEntry>> {
+    let ChurnScope {
+        cache,
+        depth,
+        path_prefix,
+        filter,
+        excludes,
+        git_ignore,
+    } = *scope;
+    let _phase = crate::timing::phase("aggregation");
+    let now = Utc::now();
     let mut map: HashMap<String, ChurnEntry> = HashMap::new();
     for cs in stats {
-        let info = cache
+        let mut info = cache
             .get_commit_info(&cs.commit_id)?
             .ok_or_else(|| crate::error::GmapError::Cache("Commit info not found".to_string()))?;
 
-        if let Some(a) = author {
-            if !info.author_name.to_lowercase().contains(&a.to_lowercase()) {
-                continue;
-            }
-        }
-        if let Some(ae) = author_email {
-            if !info
-                .author_email
-                .to_lowercase()
-                .contains(&ae.to_lowercase())
-            {
-                continue;
-            }
+        if !filter.matches(&info) {
+            continue;
         }
+        filter.resolve_author(&mut info);
+        let weight = half_life_days.map_or(1.0, |half_life| {
+            let age_days = (now - info.timestamp).num_seconds() as f64 / 86_400.0;
+            recency_weight(age_days, half_life)
+        });
 
-        for f in &cs.files {
+        for f in cs.files.iter().filter(|f| !f.is_summary()) {
             if let Some(prefix) = path_prefix {
                 if !f.path.starts_with(prefix) {
                     continue;
                 }
             }
-            if path_excluded(&f.path, excludes) {
+            if path_excluded(&f.path, excludes, filter.case_sensitive()) {
                 continue;
             }
+            if let Some(gi) = git_ignore {
+                if gi.is_ignored(&f.path) {
+                    continue;
+                }
+            }
             let agg = if let Some(d) = depth {
                 aggregate_path(&f.path, d)
             } else {
@@ -103,51 +310,206 @@ fn compute_churn(
             let entry = map
                 .entry(agg.clone())
                 .or_insert_with(|| ChurnEntry::new(agg));
-            entry.add_stats(f, &info.author_name);
+            entry.add_stats(f, &info.author_name, weight, info.timestamp);
         }
     }
     let mut entries: Vec<_> = map.into_values().collect();
-    entries.sort_by(|a, b| b.total_lines.cmp(&a.total_lines));
+    // binaries rank by bytes among themselves; path breaks the remaining
+    // ties so output doesn't depend on HashMap order
+    entries.sort_by(|a, b| {
+        b.total_lines
+            .cmp(&a.total_lines)
+            .then_with(|| b.bytes_changed().cmp(&a.bytes_changed()))
+            .then_with(|| a.path.cmp(&b.path))
+    });
     Ok(entries)
 }
 
-fn aggregate_path(path: &str, depth: u32) -> String {
-    let parts: Vec<&str> = path.split('/').collect();
-    if depth == 0 || parts.len() <= depth as usize {
-        path.to_string()
-    } else {
-        parts[..depth as usize].join("/")
+/// Commits and lines per period for every path [`compute_churn`] lists with
+/// the same scope, over each period from the first to the last change.
+pub fn churn_series(
+    stats: &[CommitStats],
+    scope: &ChurnScope,
+    period: PeriodSpec,
+) -> Result<HashMap<String, PeriodSeries>> {
+    let ChurnScope {
+        cache,
+        depth,
+        path_prefix,
+        filter,
+        excludes,
+        git_ignore,
+    } = *scope;
+    // (period, path, lines) per file change
+    let mut changes = Vec::new();
+    for cs in stats {
+        let info = cache
+            .get_commit_info(&cs.commit_id)?
+            .ok_or_else(|| crate::error::GmapError::Cache("Commit info not found".to_string()))?;
+        if !filter.matches(&info) {
+            continue;
+        }
+        let key = period_key(&info.timestamp, period);
+        for f in files_matching(&cs.files, path_prefix).filter(|f| !f.is_summary()) {
+            if path_excluded(&f.path, excludes, filter.case_sensitive())
+                || git_ignore.is_some_and(|gi| gi.is_ignored(&f.path))
+            {
+                continue;
+            }
+            let path = match depth {
+                Some(d) => aggregate_path(&f.path, d),
+                None => f.path.clone(),
+            };
+            changes.push((key.clone(), path, (f.added_lines + f.deleted_lines) as u64));
+        }
+    }
+
+    let keys = dense_period_keys(changes.iter().map(|(key, _, _)| key.as_str()));
+    let column: HashMap<&str, usize> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| (key.as_str(), i))
+        .collect();
+    let mut series: HashMap<String, PeriodSeries> = HashMap::new();
+    for (key, path, lines) in &changes {
+        let entry = series.entry(path.clone()).or_insert_with(|| PeriodSeries {
+            commits: vec![0; keys.len()],
+            lines: vec![0; keys.len()],
+        });
+        let i = column[key.as_str()];
+        entry.commits[i] += 1;
+        entry.lines[i] += lines;
     }
+    Ok(series)
 }
 
-fn output_json(
-    churn_data: &[ChurnEntry],
-    repo: &GitRepo,
-    common: &CommonArgs,
-    depth: Option<u32>,
-) -> anyhow::Result<()> {
-    let output = ChurnOutput {
-        version: crate::model::SCHEMA_VERSION,
-        generated_at: Utc::now(),
-        repository_path: repo.path().to_string_lossy().to_string(),
-        since: common.since.clone(),
-        until: common.until.clone(),
-        depth,
-        entries: churn_data.to_vec(),
-    };
-    println!("{}", serde_json::to_string_pretty(&output)?);
-    Ok(())
+/// Most lines per active day first, ties broken by total lines, then path.
+fn sort_by_velocity(entries: &mut [ChurnEntry]) {
+    entries.sort_by(|a, b| {
+        b.lines_per_day()
+            .total_cmp(&a.lines_per_day())
+            .then(b.total_lines.cmp(&a.total_lines))
+            .then_with(|| a.path.cmp(&b.path))
+    });
 }
 
-fn output_ndjson(churn_data: &[ChurnEntry]) -> anyhow::Result<()> {
-    for e in churn_data {
-        println!("{}", serde_json::to_string(e)?);
+/// Per-file churn rolled up into a directory tree rooted at `.`. Each node
+/// counts a commit once, however many of its files the commit touched.
+pub fn compute_churn_tree(
+    stats: &[CommitStats],
+    cache: &Cache,
+    path_prefix: Option<&str>,
+    filter: &CommitFilter,
+    excludes: &[String],
+    git_ignore: Option<&GitIgnoreMatcher>,
+) -> Result<ChurnTreeNode> {
+    let _phase = crate::timing::phase("aggregation");
+    // every node by path (the root is ""), with its authors
+    let mut nodes: HashMap<String, (ChurnTreeNode, HashSet<String>)> = HashMap::new();
+    for cs in stats {
+        let mut info = cache
+            .get_commit_info(&cs.commit_id)?
+            .ok_or_else(|| crate::error::GmapError::Cache("Commit info not found".to_string()))?;
+        if !filter.matches(&info) {
+            continue;
+        }
+        filter.resolve_author(&mut info);
+
+        let mut touched = HashSet::new();
+        for f in files_matching(&cs.files, path_prefix).filter(|f| !f.is_summary()) {
+            if path_excluded(&f.path, excludes, filter.case_sensitive()) {
+                continue;
+            }
+            if let Some(gi) = git_ignore {
+                if gi.is_ignored(&f.path) {
+                    continue;
+                }
+            }
+            let ancestors = f.path.match_indices('/').map(|(i, _)| &f.path[..i]);
+            for node_path in std::iter::once("")
+                .chain(ancestors)
+                .chain([f.path.as_str()])
+            {
+                let (node, authors) = nodes
+                    .entry(node_path.to_string())
+                    .or_insert_with(|| (empty_node(node_path), HashSet::new()));
+                node.added_lines += f.added_lines as u64;
+                node.deleted_lines += f.deleted_lines as u64;
+                node.total_lines += (f.added_lines + f.deleted_lines) as u64;
+                if touched.insert(node_path) {
+                    node.commit_count += 1;
+                }
+                authors.insert(info.author_name.clone());
+                node.authors = authors.len() as u32;
+            }
+        }
+    }
+
+    let mut children: HashMap<String, Vec<ChurnTreeNode>> = HashMap::new();
+    let mut root = None;
+    // deepest first, so every node's children are complete before it moves
+    let mut paths: Vec<String> = nodes.keys().cloned().collect();
+    paths.sort_by_key(|p| std::cmp::Reverse(p.matches('/').count() + usize::from(!p.is_empty())));
+    for path in paths {
+        let Some((mut node, _)) = nodes.remove(&path) else {
+            continue;
+        };
+        node.children = children.remove(&path).unwrap_or_default();
+        node.children
+            .sort_by(|a, b| b.total_lines.cmp(&a.total_lines).then(a.name.cmp(&b.name)));
+        if path.is_empty() {
+            root = Some(node);
+        } else {
+            let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+            children.entry(parent.to_string()).or_default().push(node);
+        }
+    }
+    Ok(root.unwrap_or_else(|| empty_node("")))
+}
+
+fn empty_node(path: &str) -> ChurnTreeNode {
+    let name = match path.rsplit_once('/') {
+        _ if path.is_empty() => ".",
+        Some((_, name)) => name,
+        None => path,
+    };
+    ChurnTreeNode {
+        name: name.to_string(),
+        path: path.to_string(),
+        added_lines: 0,
+        deleted_lines: 0,
+        total_lines: 0,
+        commit_count: 0,
+        authors: 0,
+        children: Vec::new(),
     }
-    Ok(())
 }
 
-fn output_table(churn_data: &[ChurnEntry]) -> anyhow::Result<()> {
-    println!(
+/// Indented tree, directories marked with a trailing `/`.
+fn write_tree_table(out: &mut dyn Write, root: &ChurnTreeNode) -> anyhow::Result<()> {
+    fn walk(out: &mut dyn Write, node: &ChurnTreeNode, depth: usize) -> anyhow::Result<()> {
+        let name = if node.children.is_empty() {
+            node.name.clone()
+        } else {
+            format!("{}/", node.name)
+        };
+        writeln!(
+            out,
+            "{:<50} {:>8} {:>8} {:>8} {:>6} {:>8}",
+            format!("{}{name}", "  ".repeat(depth)),
+            node.added_lines,
+            node.deleted_lines,
+            node.total_lines,
+            node.commit_count,
+            node.authors
+        )?;
+        for child in &node.children {
+            walk(out, child, depth + 1)?;
+        }
+        Ok(())
+    }
+    writeln!(
+        out,
         "{:<50} {:>8} {:>8} {:>8} {:>6} {:>8}",
         style("Path").bold(),
         style("Added").bold(),
@@ -155,21 +517,16 @@ fn output_table(churn_data: &[ChurnEntry]) -> anyhow::Result<()> {
         style("Total").bold(),
         style("Commits").bold(),
         style("Authors").bold()
-    );
-    println!("{}", "─".repeat(98));
-    for e in churn_data.iter().take(50) {
-        println!(
-            "{:<50} {:>8} {:>8} {:>8} {:>6} {:>8}",
-            e.path,
-            e.added_lines,
-            e.deleted_lines,
-            e.total_lines,
-            e.commit_count,
-            e.authors.len()
-        );
-    }
-    if churn_data.len() > 50 {
-        println!("\n... and {} more entries", churn_data.len() - 50);
-    }
-    Ok(())
+    )?;
+    writeln!(out, "{}", "─".repeat(98))?;
+    walk(out, root, 0)
+}
+
+pub(crate) fn aggregate_path(path: &str, depth: u32) -> String {
+    let parts: Vec<&str> = path.split('/').collect();
+    if depth == 0 || parts.len() <= depth as usize {
+        path.to_string()
+    } else {
+        parts[..depth as usize].join("/")
+    }
 }
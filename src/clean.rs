@@ -0,0 +1,25 @@
+use crate::cache::Cache;
+use crate::cli::CommonArgs;
+use crate::git::GitRepo;
+use anyhow::Context;
+use console::style;
+
+/// Remove the cache databases for the selected repository.
+pub fn exec(common: CommonArgs, dry_run: bool) -> anyhow::Result<()> {
+    let repo = GitRepo::open(common.repo.as_ref()).context("Failed to open git repository")?;
+    let dir = Cache::dir(common.cache.as_deref(), repo.path());
+    let removed = Cache::clean(&dir, dry_run).context("Failed to remove cache")?;
+
+    if removed.is_empty() {
+        println!("No cache found in {}", dir.display());
+        return Ok(());
+    }
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for (path, bytes) in &removed {
+        println!("{verb} {} ({bytes} bytes)", path.display());
+    }
+    let total: u64 = removed.iter().map(|(_, bytes)| bytes).sum();
+    let summary = if dry_run { "Would free" } else { "Freed" };
+    println!("{summary} {} bytes", style(total).cyan());
+    Ok(())
+}
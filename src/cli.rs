@@ -1,6 +1,19 @@
-use anyhow::{anyhow, Result};
-use clap::{Args, Parser, Subcommand, ValueHint};
+use crate::alias::AuthorAliases;
+use crate::cache::Cache;
+use crate::error::GmapError;
+use crate::filter::{CommitFilter, BOT_PATTERNS};
+use crate::git::GitRepo;
+use crate::model::{
+    BinaryPolicy, BoundKind, ChurnSort, DateKind, DateRange, GroupBy, MergeChurn, MergeDiff,
+    MergePolicy, Normalize,
+};
+use crate::tui::draw::IntensityScale;
+use crate::util::{GitIgnoreMatcher, OutlierThreshold, PeriodSpec, Sprints, WeekFormat, WeekStart};
+use anyhow::{anyhow, bail, Result};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueHint};
 use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "gmap")]
@@ -22,55 +35,647 @@ pub struct CommonArgs {
     #[arg(long, help = "Path to cache database", value_hint = ValueHint::DirPath)]
     pub cache: Option<PathBuf>,
 
+    #[arg(
+        long,
+        value_name = "BOOL",
+        default_value_t = true,
+        action = ArgAction::Set,
+        help = "Back up and recreate a corrupt cache instead of failing"
+    )]
+    pub reset_cache_on_error: bool,
+
     #[arg(long, help = "Include merge commits", default_value_t = false)]
     pub include_merges: bool,
 
+    #[arg(
+        long,
+        conflicts_with = "include_merges",
+        help = "Analyze merge commits only (more than one parent)"
+    )]
+    pub merges_only: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MergeDiff::FirstParent,
+        help = "Measure merges against the first parent or every parent (combined; counts octopus-merged changes)"
+    )]
+    pub merge_diff: MergeDiff,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MergeChurn::Full,
+        conflicts_with = "merge_diff",
+        help = "Count a merge's full diff, or only its changes against the automatic merge of its parents (resolution)"
+    )]
+    pub merge_churn: MergeChurn,
+
+    #[arg(
+        long,
+        help = "Walk history from the default branch (origin/HEAD, main or master) instead of HEAD"
+    )]
+    pub default_branch: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+        help = "Skip the commits listed in FILE, one hash per line ('#' starts a comment)"
+    )]
+    pub ignore_revs: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "HASH",
+        help = "Skip this commit in all analysis (repeatable)"
+    )]
+    pub ignore_rev: Vec<String>,
+
     #[arg(long, help = "Include binary files", default_value_t = false)]
     pub binary: bool,
 
+    #[arg(
+        long,
+        conflicts_with = "binary",
+        help = "Analyze binary files only, charting bytes changed instead of lines (asset-heavy repos)"
+    )]
+    pub only_binary: bool,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = crate::git::repo::DEFAULT_BINARY_SCAN_BYTES as u64,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Leading bytes of each file checked for NULs and control characters to detect binaries"
+    )]
+    pub binary_scan_bytes: u64,
+
+    #[arg(
+        long,
+        help = "Recompute every commit in range, overwriting cached results"
+    )]
+    pub recompute: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Count a commit changing more than N files (e.g. a vendored import) as one summary entry instead of per file"
+    )]
+    pub max_files_per_commit: Option<u64>,
+
     #[arg(long = "exclude", help = "Exclude paths matching any of these substrings", num_args = 1.., value_delimiter = ',')]
     pub exclude: Vec<String>,
 
+    #[arg(
+        long,
+        help = "Count committed files that .gitignore or .git/info/exclude ignore (skipped by default)"
+    )]
+    pub no_gitignore: bool,
+
     #[arg(long, help = "Filter by author name (substring, case-insensitive)")]
     pub author: Option<String>,
 
     #[arg(long, help = "Filter by author email (substring, case-insensitive)")]
     pub author_email: Option<String>,
 
+    #[arg(
+        long,
+        help = "Match author filters, --exclude and TUI searches with exact case"
+    )]
+    pub case_sensitive: bool,
+
+    #[arg(
+        long = "exclude-author",
+        value_name = "PATTERN",
+        help = "Drop commits whose author name or email contains PATTERN (repeatable)"
+    )]
+    pub exclude_author: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Drop commits from common bots (dependabot, renovate, [bot], ...)"
+    )]
+    pub exclude_bots: bool,
+
+    #[arg(
+        long,
+        help = "Drop root commits (no parents) from aggregation; an imported initial snapshot isn't churn"
+    )]
+    pub exclude_root: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+        value_parser = parse_aliases,
+        help = "Merge author identities for reporting: lines of `canonical = alias, ...`"
+    )]
+    pub aliases: Option<AuthorAliases>,
+
     #[arg(
         long,
         help = "Start from this commit or date (RFC3339, YYYY-MM-DD, or natural language)"
     )]
     pub since: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "COMMIT",
+        conflicts_with_all = ["since", "since_date"],
+        help = "Start from this commit's date; never read as a date"
+    )]
+    pub since_commit: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "DATE",
+        conflicts_with_all = ["since", "since_commit"],
+        help = "Start from this date; never read as a commit"
+    )]
+    pub since_date: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["since", "since_commit", "since_date", "range"],
+        help = "Only commits after the newest tag reachable from HEAD"
+    )]
+    pub since_last_tag: bool,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        requires = "since_last_tag",
+        help = "Tags that count for --since-last-tag (e.g. 'v*')"
+    )]
+    pub tag_pattern: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "REVSPEC",
+        help = "Only commits in a revision range: A..B (in B, not A) or A...B (in either, not both)"
+    )]
+    pub range: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with = "range",
+        help = "Only walk N generations of history back from the tip, whatever their dates"
+    )]
+    pub max_depth: Option<u32>,
+
     #[arg(
         long,
         help = "End at this commit or date (RFC3339, YYYY-MM-DD, or natural language)"
     )]
     pub until: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "COMMIT",
+        conflicts_with_all = ["until", "until_date"],
+        help = "End at this commit's date; never read as a date"
+    )]
+    pub until_commit: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "DATE",
+        conflicts_with_all = ["until", "until_commit"],
+        help = "End at this date; never read as a commit"
+    )]
+    pub until_date: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = WeekStart::Monday,
+        help = "First day of weekly buckets (mon = ISO weeks)"
+    )]
+    pub week_start: WeekStart,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = WeekFormat::Iso,
+        help = "How weekly periods are labelled in output: iso (2024-W05), date (week start), short (W5 2024)"
+    )]
+    pub week_format: WeekFormat,
+
+    #[arg(
+        long,
+        value_name = "DATE",
+        requires = "sprint_length",
+        help = "Bucket by sprints starting on this date (YYYY-MM-DD) instead of weeks or months"
+    )]
+    pub sprint_start: Option<chrono::NaiveDate>,
+
+    #[arg(
+        long,
+        value_name = "DAYS",
+        requires = "sprint_start",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Length of each sprint in days (with --sprint-start)"
+    )]
+    pub sprint_length: Option<u32>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["sprint_start", "week_start"],
+        help = "Restart weeks at each month (days 1-7 are week 1, 29-31 week 5) so weeks roll up into months"
+    )]
+    pub month_weeks: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = DateKind::Committer,
+        help = "Commit date used for buckets and --since/--until (author = when written, committer = when applied)"
+    )]
+    pub date: DateKind,
+
+    #[arg(short, long, action = ArgAction::Count, help = "Log phase timings to stderr (-vv adds cache and commit counts)")]
+    pub verbose: u8,
+
+    #[arg(long, help = "Log all diagnostics to stderr (same as -vv)")]
+    pub debug: bool,
+
+    #[arg(
+        long,
+        help = "After the command, print a one-line JSON summary (exit code, range, duration, commit and cache counts) to stderr"
+    )]
+    pub summary_json_stderr: bool,
+}
+
+impl CommonArgs {
+    pub fn commit_filter(&self) -> CommitFilter {
+        let bots = if self.exclude_bots { BOT_PATTERNS } else { &[] };
+        CommitFilter::new()
+            .with_author(self.author.as_deref())
+            .with_author_email(self.author_email.as_deref())
+            .with_excluded_authors(&self.exclude_author)
+            .with_excluded_authors(bots)
+            .with_aliases(self.aliases.clone().unwrap_or_default())
+            .with_case_sensitive(self.case_sensitive)
+            .with_exclude_root(self.exclude_root)
+    }
+
+    /// Open the repository with the date, merge-diff, merge-churn, file limit,
+    /// binary scan, ignored commit and start-branch options applied.
+    pub fn open_repo(&self) -> crate::error::Result<GitRepo> {
+        let repo = GitRepo::open(self.repo.as_ref())?
+            .with_date_kind(self.date)
+            .with_merge_diff(self.merge_diff)
+            .with_merge_churn(self.merge_churn)
+            .with_max_files_per_commit(self.max_files_per_commit.map(|n| n as usize))
+            .with_binary_scan_bytes(self.binary_scan_bytes as usize)
+            .with_ignored_revs(&self.ignored_revs()?)?;
+        if self.default_branch {
+            repo.with_default_branch()
+        } else {
+            Ok(repo)
+        }
+    }
+
+    /// Revisions from `--ignore-revs` followed by each `--ignore-rev`. Like
+    /// git's `blame.ignoreRevsFile`, blank lines and `#` comments are skipped.
+    fn ignored_revs(&self) -> crate::error::Result<Vec<String>> {
+        let mut revs = Vec::new();
+        if let Some(path) = &self.ignore_revs {
+            let text = std::fs::read_to_string(path).map_err(|e| {
+                GmapError::Other(format!("Cannot read --ignore-revs {}: {e}", path.display()))
+            })?;
+            revs.extend(
+                text.lines()
+                    .map(|line| line.split('#').next().unwrap_or_default().trim())
+                    .filter(|rev| !rev.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        revs.extend(self.ignore_rev.iter().cloned());
+        Ok(revs)
+    }
+
+    /// Matcher for `repo`'s ignore rules, or `None` with `--no-gitignore`.
+    pub fn git_ignore(&self, repo: &GitRepo) -> Option<GitIgnoreMatcher> {
+        (!self.no_gitignore).then(|| GitIgnoreMatcher::new(repo.path()))
+    }
+
+    /// Open the cache matching these options for `repo`.
+    pub fn open_cache(&self, repo: &GitRepo) -> crate::error::Result<Cache> {
+        Cache::open(
+            self.cache.as_deref(),
+            repo.path(),
+            self.date,
+            self.merge_diff,
+            self.merge_churn,
+            self.reset_cache_on_error,
+        )
+    }
+
+    /// The commit selection from `--since/--until/--range`, or from
+    /// `--since-last-tag` in place of `--since`.
+    pub fn resolve_range(&self, repo: &GitRepo) -> crate::error::Result<DateRange> {
+        let mut range = repo.resolve_range(
+            self.since_bound(),
+            self.until_bound(),
+            self.range.as_deref(),
+        )?;
+        range.max_depth = self.max_depth;
+        if !self.since_last_tag {
+            return Ok(range);
+        }
+        let tag = repo.last_tag(self.tag_pattern.as_deref())?.ok_or_else(|| {
+            GmapError::GitRepo(match &self.tag_pattern {
+                Some(pattern) => format!("No tag matching '{pattern}' is reachable from HEAD"),
+                None => "No tag is reachable from HEAD".to_string(),
+            })
+        })?;
+        crate::timing::log(crate::timing::COUNTS, || {
+            format!("since last tag: {} ({})", tag.name, tag.timestamp)
+        });
+        // strictly after the tagged commit; git timestamps are whole seconds
+        Ok(range.with_since(tag.timestamp + chrono::Duration::seconds(1)))
+    }
+
+    /// Start bound from `--since`, `--since-commit` or `--since-date`, with
+    /// how to read it.
+    fn since_bound(&self) -> Option<(&str, BoundKind)> {
+        bound(&self.since, &self.since_commit, &self.since_date)
+    }
+
+    fn until_bound(&self) -> Option<(&str, BoundKind)> {
+        bound(&self.until, &self.until_commit, &self.until_date)
+    }
+
+    /// The start bound as typed, whichever flag gave it; for output metadata.
+    pub fn since_arg(&self) -> Option<&str> {
+        self.since_bound().map(|(s, _)| s)
+    }
+
+    /// The end bound as typed, whichever flag gave it; for output metadata.
+    pub fn until_arg(&self) -> Option<&str> {
+        self.until_bound().map(|(u, _)| u)
+    }
+
+    pub fn binary_policy(&self) -> BinaryPolicy {
+        if self.only_binary {
+            BinaryPolicy::Only
+        } else if self.binary {
+            BinaryPolicy::Include
+        } else {
+            BinaryPolicy::Exclude
+        }
+    }
+
+    pub fn merge_policy(&self) -> MergePolicy {
+        if self.merges_only {
+            MergePolicy::Only
+        } else if self.include_merges {
+            MergePolicy::Include
+        } else {
+            MergePolicy::Exclude
+        }
+    }
+
+    /// Diagnostic level for [`crate::timing`].
+    pub fn verbosity(&self) -> u8 {
+        if self.debug {
+            self.verbose.max(crate::timing::COUNTS)
+        } else {
+            self.verbose
+        }
+    }
+
+    /// Period bucketing for weekly or monthly grouping under these options;
+    /// `--month-weeks` changes the weeks, `--sprint-start` replaces either
+    /// with sprints.
+    pub fn period_spec(&self, monthly: bool) -> PeriodSpec {
+        PeriodSpec::new(monthly, self.week_start)
+            .with_month_weeks(self.month_weeks)
+            .with_sprints(self.sprints())
+    }
+
+    pub fn sprints(&self) -> Option<Sprints> {
+        Some(Sprints {
+            start: self.sprint_start?,
+            length_days: self.sprint_length?,
+        })
+    }
+}
+
+fn bound<'a>(
+    auto: &'a Option<String>,
+    commit: &'a Option<String>,
+    date: &'a Option<String>,
+) -> Option<(&'a str, BoundKind)> {
+    [
+        (auto, BoundKind::Auto),
+        (commit, BoundKind::Commit),
+        (date, BoundKind::Date),
+    ]
+    .into_iter()
+    .find_map(|(value, kind)| value.as_deref().map(|v| (v, kind)))
+}
+
+fn parse_aliases(s: &str) -> std::result::Result<AuthorAliases, String> {
+    AuthorAliases::load(std::path::Path::new(s))
+}
+
+fn parse_half_life(s: &str) -> std::result::Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(days) if days > 0.0 && days.is_finite() => Ok(days),
+        Ok(_) => Err("half-life must be a positive number of days".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_outlier_factor(s: &str) -> std::result::Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(factor) if factor > 0.0 && factor.is_finite() => Ok(factor),
+        Ok(_) => Err("outlier factor must be a positive number".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct OutputArgs {
+    #[arg(
+        long,
+        help = "Output as JSON (same as --format json)",
+        conflicts_with_all = ["ndjson", "prometheus", "format"]
+    )]
+    pub json: bool,
+
+    #[arg(
+        long,
+        alias = "jsonl",
+        help = "Output as NDJSON (same as --format ndjson)",
+        conflicts_with_all = ["json", "prometheus", "format"]
+    )]
+    pub ndjson: bool,
+
+    #[arg(
+        long,
+        help = "Output Prometheus text exposition (same as --format prometheus)",
+        conflicts_with_all = ["json", "ndjson", "format"]
+    )]
+    pub prometheus: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Output format: table (default), json, ndjson, csv, prometheus"
+    )]
+    pub format: Option<String>,
+
+    #[arg(long, help = "Emit minified JSON (with --json)")]
+    pub compact: bool,
+
+    #[arg(
+        long,
+        help = "Start NDJSON output with a {\"type\":\"meta\"} line carrying the schema version and run details"
+    )]
+    pub ndjson_header: bool,
+}
+
+impl OutputArgs {
+    /// Name of the selected output format, `table` unless a flag picks another.
+    /// Clap rejects combining the format flags, so at most one of them is set.
+    pub fn format_name(&self) -> Result<&str> {
+        let name = if self.json {
+            "json"
+        } else if self.ndjson {
+            "ndjson"
+        } else if self.prometheus {
+            "prometheus"
+        } else {
+            self.format.as_deref().unwrap_or("table")
+        };
+        if self.compact && name != "json" {
+            bail!("--compact requires JSON output (--json or --format json)");
+        }
+        Ok(name)
+    }
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct OutlierArgs {
+    #[arg(
+        long,
+        help = "Lines changed above which a commit is an outlier (overrides --outlier-factor)"
+    )]
+    pub outlier_lines: Option<u64>,
+
+    #[arg(
+        long,
+        default_value_t = 10.0,
+        value_parser = parse_outlier_factor,
+        help = "Outlier threshold as a multiple of the median commit size"
+    )]
+    pub outlier_factor: f64,
+}
+
+impl OutlierArgs {
+    pub fn threshold(&self) -> OutlierThreshold {
+        match self.outlier_lines {
+            Some(lines) => OutlierThreshold::Lines(lines),
+            None => OutlierThreshold::MedianFactor(self.outlier_factor),
+        }
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     Churn {
-        #[arg(long, help = "Output as JSON", conflicts_with = "ndjson")]
-        json: bool,
-
-        #[arg(long, help = "Output as NDJSON", conflicts_with = "json")]
-        ndjson: bool,
+        #[clap(flatten)]
+        output: OutputArgs,
 
         #[arg(long, help = "Directory depth for aggregation")]
         depth: Option<u32>,
 
+        #[arg(long, help = "Leave outlier commits out of churn totals")]
+        exclude_outliers: bool,
+
+        #[arg(
+            long,
+            value_name = "DAYS",
+            value_parser = parse_half_life,
+            help = "Weight each commit by recency, halving every DAYS days (adds a weighted column)"
+        )]
+        half_life: Option<f64>,
+
+        #[arg(long, value_enum, default_value_t = ChurnSort::Total, help = "Sort entries by total lines, weighted score, or lines per day")]
+        sort: ChurnSort,
+
+        #[arg(
+            long,
+            help = "Add a lines-per-day column: total lines over the days between a path's first and last change"
+        )]
+        velocity: bool,
+
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Exit non-zero if any entry changed more than N lines"
+        )]
+        max_file_churn: Option<u64>,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["depth", "half_life", "max_file_churn"],
+            help = "Total churn per author email domain instead of per path"
+        )]
+        by_domain: bool,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["by_domain", "depth", "half_life", "max_file_churn"],
+            help = "Total churn per Conventional Commits type (feat, fix, ...; others as \"other\")"
+        )]
+        by_type: bool,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["by_domain", "by_type", "depth", "half_life", "max_file_churn"],
+            help = "Total churn per file size bucket (small < 4 KiB, medium < 64 KiB, large)"
+        )]
+        size_bucket: bool,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["by_domain", "by_type", "size_bucket", "depth", "half_life", "max_file_churn"],
+            help = "Roll churn up the directory tree, each directory totaling the files beneath it"
+        )]
+        tree: bool,
+
+        #[clap(flatten)]
+        outliers: OutlierArgs,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "Check HEAD every SECONDS and reprint the output when it moves, until Ctrl-C"
+        )]
+        interval: Option<u64>,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["by_domain", "by_type", "size_bucket", "tree"],
+            help = "Add sparklines of each path's commits and lines per week (table output)"
+        )]
+        spark: bool,
+
         #[arg(help = "Path prefix to analyze")]
         path: Option<String>,
     },
     Heat {
-        #[arg(long, help = "Output as JSON", conflicts_with = "ndjson")]
-        json: bool,
-
-        #[arg(long, help = "Output as NDJSON", conflicts_with = "json")]
-        ndjson: bool,
+        #[clap(flatten)]
+        output: OutputArgs,
 
         #[arg(
             long = "interactive",
@@ -83,41 +688,540 @@ pub enum Commands {
         #[arg(long, help = "Group by month instead of week")]
         monthly: bool,
 
+        #[arg(
+            long,
+            help = "Track net line growth (added - deleted) and its running total"
+        )]
+        growth: bool,
+
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Exit non-zero if any period has more than N commits"
+        )]
+        max_week_commits: Option<u32>,
+
+        #[arg(
+            long,
+            value_name = "GLOB",
+            num_args = 0..=1,
+            default_missing_value = "*",
+            help = "Mark periods containing tags matching GLOB (e.g. 'v*'; all tags if omitted)"
+        )]
+        tag_pattern: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Show at most N chars of commit messages in TUI tables (default: fill the column)"
+        )]
+        message_width: Option<usize>,
+
+        #[arg(
+            long,
+            env = "PAGER",
+            value_name = "CMD",
+            help = "Pager for showing commits in the TUI (default: less -R; more on Windows)"
+        )]
+        pager: Option<String>,
+
+        #[arg(
+            long,
+            env = "VISUAL",
+            value_name = "CMD",
+            help = "Editor for opening files from the TUI (default: $EDITOR)"
+        )]
+        editor: Option<String>,
+
+        #[arg(long, env = "EDITOR", hide = true)]
+        fallback_editor: Option<String>,
+
+        #[arg(
+            long,
+            requires = "interactive",
+            help = "Start the TUI heatmap with one line per period (toggle with d)"
+        )]
+        dense: bool,
+
+        #[arg(
+            long,
+            requires = "interactive",
+            help = "Start the TUI showing full commit hashes (toggle with H)"
+        )]
+        full_hash: bool,
+
+        #[arg(
+            long,
+            requires = "interactive",
+            help = "Show whether the selected commit is signed in the TUI details"
+        )]
+        show_signatures: bool,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with = "interactive",
+            help = "Write the heatmap grid as a PNG image to FILE instead of printing it",
+            value_hint = ValueHint::FilePath
+        )]
+        export_image: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            value_parser = clap::value_parser!(u64).range(1..),
+            conflicts_with_all = ["interactive", "export_image"],
+            help = "Check HEAD every SECONDS and reprint the output when it moves, until Ctrl-C"
+        )]
+        interval: Option<u64>,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = IntensityScale::Linear,
+            help = "Scale heat intensity linearly, logarithmically, or by percentile rank"
+        )]
+        scale: IntensityScale,
+
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1..),
+            help = "Chart commits as an N-period moving average (table bars, --export-image, TUI timeline; toggle with a)"
+        )]
+        smooth: Option<u32>,
+
+        #[arg(
+            long,
+            help = "Add zero-commit periods between the first and last period"
+        )]
+        fill_gaps: bool,
+
+        #[arg(
+            long,
+            help = "Leave zero-commit periods out of the table listing (still counted in totals and series)"
+        )]
+        hide_empty: bool,
+
         #[arg(help = "Path prefix to analyze")]
         path: Option<String>,
     },
     Export {
-        #[arg(long, help = "Output as JSON", conflicts_with = "ndjson")]
+        #[clap(flatten)]
+        output: OutputArgs,
+
+        #[arg(long, help = "Mark unusually large commits with is_outlier")]
+        flag_outliers: bool,
+
+        #[clap(flatten)]
+        outliers: OutlierArgs,
+
+        #[arg(long, help = "Include changed line ranges per file (JSON and NDJSON)")]
+        hunks: bool,
+
+        #[arg(
+            long,
+            help = "List changed paths only, without reading file contents (JSON and NDJSON)",
+            conflicts_with_all = ["hunks", "flag_outliers"]
+        )]
+        name_only: bool,
+
+        #[arg(
+            long,
+            help = "Include whether each commit is signed (gpg, ssh, x509 or unsigned)"
+        )]
+        show_signatures: bool,
+
+        #[arg(
+            long,
+            help = "Emit per-commit files_changed/added/deleted totals instead of file lists (JSON and NDJSON)",
+            conflicts_with_all = ["hunks", "name_only"]
+        )]
+        summary_entries: bool,
+
+        #[arg(
+            long,
+            help = "Emit per-period aggregates as shown in the TUI instead of commits (JSON and NDJSON)",
+            conflicts_with_all = ["hunks", "name_only", "summary_entries", "flag_outliers", "show_signatures"]
+        )]
+        group_by_week: bool,
+    },
+    /// Print the repository statistics from the TUI Stats view
+    Stats {
+        #[arg(long, help = "Output as JSON")]
+        json: bool,
+
+        #[arg(long, help = "Emit minified JSON (with --json)", requires = "json")]
+        compact: bool,
+
+        #[arg(long, help = "Group by month instead of week")]
+        monthly: bool,
+
+        #[arg(
+            long,
+            help = "Compare against the equal-length window just before --since (e.g. this quarter vs last)"
+        )]
+        vs_previous_period: bool,
+
+        #[arg(help = "Path prefix to analyze")]
+        path: Option<String>,
+    },
+    /// Compare activity in --since/--until against a baseline window
+    Compare {
+        #[arg(long, help = "Start of the baseline window")]
+        baseline_since: String,
+
+        #[arg(long, help = "End of the baseline window")]
+        baseline_until: String,
+
+        #[arg(long, help = "Output as JSON")]
+        json: bool,
+
+        #[arg(long, help = "Emit minified JSON (with --json)", requires = "json")]
+        compact: bool,
+
+        #[arg(help = "Path prefix to analyze")]
+        path: Option<String>,
+    },
+    /// List files that change together, optionally as a Graphviz graph
+    Coupling {
+        #[arg(long, value_name = "FILE", help = "Write the coupling graph in DOT format to FILE", value_hint = ValueHint::FilePath)]
+        dot: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 2,
+            help = "Drop pairs that changed together in fewer than N commits"
+        )]
+        min_coupling: u32,
+
+        #[arg(help = "Path prefix to analyze")]
+        path: Option<String>,
+    },
+    /// Lines changed per directory and period, as a shaded grid
+    Matrix {
+        #[arg(long, default_value_t = 1, help = "Directory depth of the rows")]
+        depth: u32,
+
+        #[arg(long, help = "Group by month instead of week")]
+        monthly: bool,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = Normalize::Global,
+            help = "Scale shading by the row, column or global maximum"
+        )]
+        normalize: Normalize,
+
+        #[arg(long, help = "Output as JSON")]
+        json: bool,
+
+        #[arg(long, help = "Emit minified JSON (with --json)", requires = "json")]
+        compact: bool,
+
+        #[arg(help = "Path prefix to analyze")]
+        path: Option<String>,
+    },
+    /// Show the files and line counts changed by HEAD, skipping history and cache
+    Last {
+        #[arg(long, help = "Output as JSON")]
+        json: bool,
+
+        #[arg(long, help = "Emit minified JSON (with --json)", requires = "json")]
+        compact: bool,
+
+        #[arg(long, help = "Show the full commit hash instead of 7 characters")]
+        full_hash: bool,
+    },
+    /// Count files, lines and bytes per extension in HEAD's tree
+    Size {
+        #[arg(long, help = "Output as JSON")]
         json: bool,
 
-        #[arg(long, help = "Output as NDJSON", conflicts_with = "json")]
-        ndjson: bool,
+        #[arg(long, help = "Emit minified JSON (with --json)", requires = "json")]
+        compact: bool,
+
+        #[arg(help = "Path prefix to analyze")]
+        path: Option<String>,
     },
+    /// List author names and emails in range with their commit counts
+    Authors {
+        #[arg(long, help = "Output as JSON")]
+        json: bool,
+
+        #[arg(long, help = "Emit minified JSON (with --json)", requires = "json")]
+        compact: bool,
+    },
+    /// Delete the cache databases for this repository
+    Clean {
+        #[arg(long, help = "Only report what would be deleted")]
+        dry_run: bool,
+    },
+}
+
+impl Commands {
+    /// Subcommand name as typed on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Churn { .. } => "churn",
+            Commands::Heat { .. } => "heat",
+            Commands::Export { .. } => "export",
+            Commands::Stats { .. } => "stats",
+            Commands::Compare { .. } => "compare",
+            Commands::Coupling { .. } => "coupling",
+            Commands::Matrix { .. } => "matrix",
+            Commands::Last { .. } => "last",
+            Commands::Size { .. } => "size",
+            Commands::Authors { .. } => "authors",
+            Commands::Clean { .. } => "clean",
+        }
+    }
 }
 
 impl Cli {
-    pub fn execute(self) -> Result<()> {
+    /// Run the command, then print the `--summary-json-stderr` line if asked.
+    pub fn execute(self) -> Result<ExitCode> {
+        if !self.common.summary_json_stderr {
+            return self.run();
+        }
+        let start = std::time::Instant::now();
+        let command = self.command.name();
+        let (since, until) = (
+            self.common.since_arg().map(str::to_string),
+            self.common.until_arg().map(str::to_string),
+        );
+        let result = self.run();
+        let exit_code = match &result {
+            Ok(code) if *code == ExitCode::SUCCESS => 0,
+            Err(err) if matches!(err.downcast_ref(), Some(GmapError::Interrupted { .. })) => 130,
+            _ => 1,
+        };
+        let summary = crate::timing::RunSummary::new(
+            command,
+            exit_code,
+            since.as_deref(),
+            until.as_deref(),
+            start.elapsed(),
+        );
+        if let Ok(line) = serde_json::to_string(&summary) {
+            eprintln!("{line}");
+        }
+        result
+    }
+
+    /// Run the command; failure exit codes come from breached `--max-*` limits.
+    fn run(self) -> Result<ExitCode> {
+        // stderr diagnostics would corrupt the interactive screen, which also
+        // handles Ctrl-C itself
+        if !matches!(
+            self.command,
+            Commands::Heat {
+                interactive: true,
+                ..
+            }
+        ) {
+            crate::timing::set_verbosity(self.common.verbosity());
+            crate::heat::fetch::install_interrupt_handler();
+        }
         match self.command {
             Commands::Churn {
-                json,
-                ndjson,
+                output,
                 depth,
+                exclude_outliers,
+                outliers,
+                half_life,
+                sort,
+                max_file_churn,
+                by_domain,
+                by_type,
+                size_bucket,
+                tree,
+                velocity,
+                interval,
+                spark,
                 path,
-            } => crate::churn::exec(self.common, depth, json, ndjson, path),
+            } => {
+                let group_by = if by_domain {
+                    Some(GroupBy::Domain)
+                } else if by_type {
+                    Some(GroupBy::Type)
+                } else {
+                    size_bucket.then_some(GroupBy::Size)
+                };
+                let options = crate::churn::ChurnOptions {
+                    output,
+                    depth,
+                    exclude_outliers: exclude_outliers.then(|| outliers.threshold()),
+                    half_life_days: half_life,
+                    sort,
+                    max_file_churn,
+                    group_by,
+                    tree,
+                    velocity,
+                    spark,
+                    path,
+                };
+                let churn = |common: CommonArgs| crate::churn::exec(common, options.clone());
+                match interval {
+                    Some(secs) => {
+                        crate::watch::run(&self.common, Duration::from_secs(secs), || {
+                            churn(self.common.clone())
+                        })
+                    }
+                    None => churn(self.common),
+                }
+            }
             Commands::Heat {
-                json,
-                ndjson,
+                output,
                 interactive,
                 monthly,
+                growth,
+                max_week_commits,
+                tag_pattern,
+                message_width,
+                pager,
+                editor,
+                fallback_editor,
+                dense,
+                full_hash,
+                show_signatures,
+                export_image,
+                interval,
+                scale,
+                smooth,
+                fill_gaps,
+                hide_empty,
                 path,
             } => {
                 if interactive {
-                    crate::tui::run(&self.common, path, monthly).map_err(|e| anyhow!(e))
+                    let options = crate::tui::TuiOptions {
+                        path,
+                        monthly,
+                        growth,
+                        tag_pattern,
+                        message_width,
+                        pager,
+                        editor: editor.or(fallback_editor),
+                        dense,
+                        full_hash,
+                        show_signatures,
+                        scale,
+                        smooth,
+                    };
+                    crate::tui::run(&self.common, options)
+                        .map(|()| ExitCode::SUCCESS)
+                        .map_err(|e| anyhow!(e))
                 } else {
-                    crate::heat::exec(self.common, json, ndjson, path, monthly)
+                    let options = crate::heat::HeatOptions {
+                        output,
+                        path,
+                        monthly,
+                        growth,
+                        max_week_commits,
+                        tag_pattern,
+                        scale,
+                        smooth,
+                        fill_gaps,
+                        hide_empty,
+                        export_image,
+                    };
+                    let heat = |common: CommonArgs| crate::heat::exec(common, options.clone());
+                    match interval {
+                        Some(secs) => {
+                            crate::watch::run(&self.common, Duration::from_secs(secs), || {
+                                heat(self.common.clone())
+                            })
+                        }
+                        None => heat(self.common),
+                    }
                 }
             }
-            Commands::Export { json, ndjson } => crate::export::exec(self.common, json, ndjson),
+            Commands::Export {
+                output,
+                flag_outliers,
+                outliers,
+                hunks,
+                name_only,
+                show_signatures,
+                summary_entries,
+                group_by_week,
+            } => {
+                let options = crate::export::ExportOptions {
+                    output,
+                    flag_outliers: flag_outliers.then(|| outliers.threshold()),
+                    hunks,
+                    name_only,
+                    show_signatures,
+                    summary_entries,
+                    group_by_week,
+                };
+                crate::export::exec(self.common, options).map(|()| ExitCode::SUCCESS)
+            }
+            Commands::Stats {
+                json,
+                compact,
+                monthly,
+                vs_previous_period,
+                path,
+            } => crate::stats::exec(
+                self.common,
+                json,
+                compact,
+                path,
+                monthly,
+                vs_previous_period,
+            )
+            .map(|()| ExitCode::SUCCESS),
+            Commands::Compare {
+                baseline_since,
+                baseline_until,
+                json,
+                compact,
+                path,
+            } => crate::compare::exec(
+                self.common,
+                baseline_since,
+                baseline_until,
+                json,
+                compact,
+                path,
+            )
+            .map(|()| ExitCode::SUCCESS),
+            Commands::Coupling {
+                dot,
+                min_coupling,
+                path,
+            } => crate::coupling::exec(self.common, dot, min_coupling, path)
+                .map(|()| ExitCode::SUCCESS),
+            Commands::Matrix {
+                depth,
+                monthly,
+                normalize,
+                json,
+                compact,
+                path,
+            } => crate::matrix::exec(self.common, depth, monthly, normalize, json, compact, path)
+                .map(|()| ExitCode::SUCCESS),
+            Commands::Last {
+                json,
+                compact,
+                full_hash,
+            } => {
+                crate::last::exec(self.common, json, compact, full_hash).map(|()| ExitCode::SUCCESS)
+            }
+            Commands::Size {
+                json,
+                compact,
+                path,
+            } => crate::size::exec(self.common, json, compact, path).map(|()| ExitCode::SUCCESS),
+            Commands::Authors { json, compact } => {
+                crate::authors::exec(self.common, json, compact).map(|()| ExitCode::SUCCESS)
+            }
+            Commands::Clean { dry_run } => {
+                crate::clean::exec(self.common, dry_run).map(|()| ExitCode::SUCCESS)
+            }
         }
     }
 }
@@ -0,0 +1,334 @@
+use crate::cache::Cache;
+use crate::churn::{compute_churn, ChurnScope};
+use crate::cli::CommonArgs;
+use crate::git::GitRepo;
+use crate::heat::{compute_heat, fetch_commit_stats_reported, FetchReport};
+use crate::model::{
+    BoundKind, ChurnEntry, CommitStats, CompareDelta, CompareOutput, DateRange, HeatBucket,
+    PeriodComparison, PeriodSummary, SCHEMA_VERSION,
+};
+use crate::util::to_json;
+use anyhow::{bail, Context};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use console::style;
+
+/// Compare the `--since/--until` window against a baseline window.
+pub fn exec(
+    common: CommonArgs,
+    baseline_since: String,
+    baseline_until: String,
+    json: bool,
+    compact: bool,
+    path: Option<String>,
+) -> anyhow::Result<()> {
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let mut cache = common
+        .open_cache(&repo)
+        .context("Failed to initialize cache")?;
+
+    let baseline_range = repo
+        .resolve_range(
+            Some((&baseline_since, BoundKind::Auto)),
+            Some((&baseline_until, BoundKind::Auto)),
+            None,
+        )
+        .context("Failed to resolve date range")?;
+    let (baseline, baseline_report) = summarize_window(
+        &repo,
+        &mut cache,
+        &common,
+        &baseline_range,
+        Some(&baseline_since),
+        Some(&baseline_until),
+        path.as_deref(),
+    )
+    .context("Failed to summarize baseline period")?;
+    let current_range = common
+        .resolve_range(&repo)
+        .context("Failed to resolve date range")?;
+    let (current, mut report) = summarize_window(
+        &repo,
+        &mut cache,
+        &common,
+        &current_range,
+        common.since_arg(),
+        common.until_arg(),
+        path.as_deref(),
+    )
+    .context("Failed to summarize current period")?;
+
+    report += baseline_report;
+    let delta = compare_periods(&baseline, &current);
+
+    if json {
+        let output = CompareOutput {
+            version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            repository_path: repo.path().display().to_string(),
+            path_prefix: path.unwrap_or_default(),
+            baseline,
+            current,
+            delta,
+        };
+        println!("{}", to_json(&output, compact)?);
+    } else {
+        output_table(&baseline, &current, &delta);
+    }
+    report.announce();
+
+    Ok(())
+}
+
+/// The window of the same length as `range` ending the second before it
+/// starts (bounds are inclusive), with an open end read as `now`. The
+/// revision range and depth limit carry over, so both windows draw on the
+/// same history. `None` without a start, since the length is then unknown.
+pub fn previous_period(range: &DateRange, now: DateTime<Utc>) -> Option<DateRange> {
+    let since = range.since?;
+    let length = range.until.unwrap_or(now) - since;
+    let until = since - Duration::seconds(1);
+    Some(DateRange {
+        since: Some(until - length),
+        until: Some(until),
+        ..range.clone()
+    })
+}
+
+/// Summarize `range`, whose commits `current_stats` already holds, and the
+/// window just before it, for `stats --vs-previous-period`. Only the
+/// previous window is fetched, so the report covers just that.
+pub fn vs_previous_period(
+    repo: &GitRepo,
+    cache: &mut Cache,
+    common: &CommonArgs,
+    range: &DateRange,
+    current_stats: &[CommitStats],
+    path_prefix: Option<&str>,
+) -> anyhow::Result<(PeriodComparison, FetchReport)> {
+    let now = Utc::now();
+    let Some(baseline_range) = previous_period(range, now) else {
+        bail!("--vs-previous-period needs a start bound such as --since");
+    };
+    let label = |bound: Option<DateTime<Utc>>| {
+        bound.map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+    };
+    let (baseline, report) = summarize_window(
+        repo,
+        cache,
+        common,
+        &baseline_range,
+        label(baseline_range.since).as_deref(),
+        label(baseline_range.until).as_deref(),
+        path_prefix,
+    )
+    .context("Failed to summarize previous period")?;
+    let current = summarize_stats(
+        repo,
+        cache,
+        common,
+        current_stats,
+        common
+            .since_arg()
+            .map(str::to_string)
+            .or(label(range.since))
+            .as_deref(),
+        common.until_arg(),
+        path_prefix,
+    )
+    .context("Failed to summarize current period")?;
+    let delta = compare_periods(&baseline, &current);
+    Ok((
+        PeriodComparison {
+            baseline,
+            current,
+            delta,
+        },
+        report,
+    ))
+}
+
+fn summarize_window(
+    repo: &GitRepo,
+    cache: &mut Cache,
+    common: &CommonArgs,
+    range: &DateRange,
+    since: Option<&str>,
+    until: Option<&str>,
+    path_prefix: Option<&str>,
+) -> anyhow::Result<(PeriodSummary, FetchReport)> {
+    let (stats, report) = fetch_commit_stats_reported(
+        repo,
+        cache,
+        range,
+        common.merge_policy(),
+        common.binary_policy(),
+        common.recompute,
+        true,
+    )?;
+    let summary = summarize_stats(repo, cache, common, &stats, since, until, path_prefix)?;
+    Ok((summary, report))
+}
+
+/// Totals over already fetched `stats` under the common filters.
+fn summarize_stats(
+    repo: &GitRepo,
+    cache: &Cache,
+    common: &CommonArgs,
+    stats: &[CommitStats],
+    since: Option<&str>,
+    until: Option<&str>,
+    path_prefix: Option<&str>,
+) -> anyhow::Result<PeriodSummary> {
+    let filter = common.commit_filter();
+    let gi = common.git_ignore(repo);
+    let buckets = compute_heat(
+        stats,
+        cache,
+        path_prefix,
+        &filter,
+        common.period_spec(false),
+        &common.exclude,
+        gi.as_ref(),
+    )?;
+    let scope = ChurnScope {
+        cache,
+        depth: None,
+        path_prefix,
+        filter: &filter,
+        excludes: &common.exclude,
+        git_ignore: gi.as_ref(),
+    };
+    let churn = compute_churn(stats, &scope, None)?;
+
+    Ok(summarize(since, until, &buckets, &churn))
+}
+
+/// Fold heat buckets and churn entries for one window into totals.
+fn summarize(
+    since: Option<&str>,
+    until: Option<&str>,
+    buckets: &[HeatBucket],
+    churn: &[ChurnEntry],
+) -> PeriodSummary {
+    PeriodSummary {
+        since: since.map(str::to_string),
+        until: until.map(str::to_string),
+        commits: buckets.iter().map(|b| b.commit_count as u64).sum(),
+        lines_added: buckets.iter().map(|b| b.lines_added).sum(),
+        lines_deleted: buckets.iter().map(|b| b.lines_deleted).sum(),
+        lines_changed: buckets.iter().map(|b| b.lines_changed).sum(),
+        active_files: churn.len() as u64,
+    }
+}
+
+fn compare_periods(baseline: &PeriodSummary, current: &PeriodSummary) -> CompareDelta {
+    let diff = |b: u64, c: u64| c as i64 - b as i64;
+    CompareDelta {
+        commits: diff(baseline.commits, current.commits),
+        lines_added: diff(baseline.lines_added, current.lines_added),
+        lines_deleted: diff(baseline.lines_deleted, current.lines_deleted),
+        lines_changed: diff(baseline.lines_changed, current.lines_changed),
+        active_files: diff(baseline.active_files, current.active_files),
+    }
+}
+
+pub fn output_table(baseline: &PeriodSummary, current: &PeriodSummary, delta: &CompareDelta) {
+    let window = |s: &PeriodSummary| {
+        format!(
+            "{} .. {}",
+            s.since.as_deref().unwrap_or("start"),
+            s.until.as_deref().unwrap_or("now")
+        )
+    };
+
+    println!("{}", style("Activity Comparison").bold());
+    println!("Baseline: {}", style(window(baseline)).dim());
+    println!("Current:  {}", style(window(current)).dim());
+    println!("{}", "─".repeat(60));
+    println!(
+        "{:<16} {:>10} {:>10} {:>20}",
+        style("Metric").bold(),
+        style("Baseline").bold(),
+        style("Current").bold(),
+        style("Change").bold()
+    );
+
+    let rows = [
+        ("Commits", baseline.commits, current.commits, delta.commits),
+        (
+            "Lines added",
+            baseline.lines_added,
+            current.lines_added,
+            delta.lines_added,
+        ),
+        (
+            "Lines deleted",
+            baseline.lines_deleted,
+            current.lines_deleted,
+            delta.lines_deleted,
+        ),
+        (
+            "Lines changed",
+            baseline.lines_changed,
+            current.lines_changed,
+            delta.lines_changed,
+        ),
+        (
+            "Active files",
+            baseline.active_files,
+            current.active_files,
+            delta.active_files,
+        ),
+    ];
+
+    for (label, before, after, change) in rows {
+        let pct = if before > 0 {
+            format!(" ({:+.1}%)", change as f64 * 100.0 / before as f64)
+        } else {
+            String::new()
+        };
+        let text = format!("{change:+}{pct}");
+        let change = match change.signum() {
+            1 => style(format!("▲ {text}")).green(),
+            -1 => style(format!("▼ {text}")).red(),
+            _ => style("= 0".to_string()).dim(),
+        };
+        println!("{label:<16} {before:>10} {after:>10} {change:>20}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn previous_period_is_contiguous_and_as_long() {
+        let at = |month, day| Utc.with_ymd_and_hms(2024, month, day, 0, 0, 0).unwrap();
+        let quarter = DateRange::new()
+            .with_since(at(4, 1))
+            .with_until(at(6, 30))
+            .with_max_depth(50)
+            .with_commits(["abc".to_string()].into());
+        let previous = previous_period(&quarter, at(10, 1)).unwrap();
+        assert_eq!(previous.max_depth, Some(50));
+        assert_eq!(previous.commits, quarter.commits);
+        let until = previous.until.unwrap();
+        assert_eq!(until + Duration::seconds(1), at(4, 1));
+        assert_eq!(
+            until - previous.since.unwrap(),
+            at(6, 30) - at(4, 1),
+            "{previous:?}"
+        );
+
+        // An open end runs to now.
+        let open = DateRange::new().with_since(at(9, 1));
+        let previous = previous_period(&open, at(9, 11)).unwrap();
+        assert_eq!(previous.since, Some(at(8, 22) - Duration::seconds(1)));
+        assert_eq!(previous.until, Some(at(9, 1) - Duration::seconds(1)));
+
+        assert!(previous_period(&DateRange::new(), at(9, 11)).is_none());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::cache::Cache;
+use crate::cli::CommonArgs;
+use crate::error::Result;
+use crate::filter::CommitFilter;
+use crate::heat::fetch_commit_stats_reported;
+use crate::model::{CommitStats, CouplingEdge};
+use crate::util::path_excluded;
+use anyhow::Context;
+use console::style;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+/// Commits touching more files than this are skipped: mass renames and
+/// formatting sweeps would otherwise couple everything to everything.
+pub const MAX_FILES_PER_COMMIT: usize = 50;
+
+pub fn exec(
+    common: CommonArgs,
+    dot: Option<PathBuf>,
+    min_coupling: u32,
+    path: Option<String>,
+) -> anyhow::Result<()> {
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let mut cache = common
+        .open_cache(&repo)
+        .context("Failed to initialize cache")?;
+
+    let range = common
+        .resolve_range(&repo)
+        .context("Failed to resolve date range")?;
+
+    let (stats, report) = fetch_commit_stats_reported(
+        &repo,
+        &mut cache,
+        &range,
+        common.merge_policy(),
+        common.binary_policy(),
+        common.recompute,
+        true,
+    )?;
+
+    let mut edges = compute_coupling(
+        &stats,
+        &cache,
+        path.as_deref(),
+        &common.commit_filter(),
+        &common.exclude,
+    )
+    .context("Failed to compute file coupling")?;
+    edges.retain(|e| e.count >= min_coupling);
+
+    match dot {
+        Some(file) => {
+            std::fs::write(&file, to_dot(&edges))
+                .with_context(|| format!("Failed to write {}", file.display()))?;
+            eprintln!(
+                "Wrote {} edges to {}",
+                edges.len(),
+                style(file.display()).cyan()
+            );
+        }
+        None => output_table(&edges),
+    }
+    report.announce();
+    Ok(())
+}
+
+/// Count how often each pair of files changes in the same commit.
+pub fn compute_coupling(
+    stats: &[CommitStats],
+    cache: &Cache,
+    path_prefix: Option<&str>,
+    filter: &CommitFilter,
+    excludes: &[String],
+) -> Result<Vec<CouplingEdge>> {
+    let _phase = crate::timing::phase("aggregation");
+    let mut changesets = Vec::new();
+    for cs in stats {
+        let info = cache
+            .get_commit_info(&cs.commit_id)?
+            .ok_or_else(|| crate::error::GmapError::Cache("Commit info not found".to_string()))?;
+        if !filter.matches(&info) {
+            continue;
+        }
+        let files: Vec<&str> = cs
+            .files
+            .iter()
+            .filter(|f| !f.is_summary())
+            .map(|f| f.path.as_str())
+            .filter(|p| path_prefix.is_none_or(|prefix| p.starts_with(prefix)))
+            .filter(|p| !path_excluded(p, excludes, filter.case_sensitive()))
+            .collect();
+        changesets.push(files);
+    }
+    Ok(count_co_changes(&changesets))
+}
+
+/// Pairwise co-occurrence counts over changesets, strongest first.
+pub fn count_co_changes(changesets: &[Vec<&str>]) -> Vec<CouplingEdge> {
+    let mut counts: HashMap<(&str, &str), u32> = HashMap::new();
+    for files in changesets {
+        let files: BTreeSet<&str> = files.iter().copied().collect();
+        if files.len() > MAX_FILES_PER_COMMIT {
+            continue;
+        }
+        let files: Vec<&str> = files.into_iter().collect();
+        for (i, a) in files.iter().enumerate() {
+            for b in &files[i + 1..] {
+                *counts.entry((a, b)).or_default() += 1;
+            }
+        }
+    }
+    let mut edges: Vec<CouplingEdge> = counts
+        .into_iter()
+        .map(|((a, b), count)| CouplingEdge {
+            a: a.to_string(),
+            b: b.to_string(),
+            count,
+        })
+        .collect();
+    edges.sort_by(|x, y| {
+        y.count
+            .cmp(&x.count)
+            .then_with(|| (&x.a, &x.b).cmp(&(&y.a, &y.b)))
+    });
+    edges
+}
+
+fn dot_id(path: &str) -> String {
+    format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Undirected Graphviz graph: one node per file, edges weighted by co-changes.
+pub fn to_dot(edges: &[CouplingEdge]) -> String {
+    let nodes: BTreeSet<&str> = edges
+        .iter()
+        .flat_map(|e| [e.a.as_str(), e.b.as_str()])
+        .collect();
+
+    let mut dot = String::from("graph coupling {\n");
+    dot.push_str("  node [shape=box];\n");
+    for node in nodes {
+        let _ = writeln!(dot, "  {};", dot_id(node));
+    }
+    for e in edges {
+        let _ = writeln!(
+            dot,
+            "  {} -- {} [weight={}, label=\"{}\"];",
+            dot_id(&e.a),
+            dot_id(&e.b),
+            e.count,
+            e.count
+        );
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn output_table(edges: &[CouplingEdge]) {
+    println!(
+        "{:<45} {:<45} {:>8}",
+        style("File").bold(),
+        style("Changes with").bold(),
+        style("Commits").bold()
+    );
+    println!("{}", "─".repeat(100));
+    for e in edges.iter().take(50) {
+        println!("{:<45} {:<45} {:>8}", e.a, e.b, e.count);
+    }
+    if edges.len() > 50 {
+        println!("\n... and {} more pairs", edges.len() - 50);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_declares_nodes_and_weighted_edge() {
+        let changesets = vec![
+            vec!["src/a.rs", "src/b.rs"],
+            vec!["src/b.rs", "src/a.rs"],
+            vec!["src/a.rs"],
+        ];
+        let edges = count_co_changes(&changesets);
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].count, 2);
+
+        let dot = to_dot(&edges);
+        assert!(dot.starts_with("graph coupling {\n"), "{dot}");
+        assert!(dot.contains("  \"src/a.rs\";\n"), "{dot}");
+        assert!(dot.contains("  \"src/b.rs\";\n"), "{dot}");
+        assert!(
+            dot.contains("  \"src/a.rs\" -- \"src/b.rs\" [weight=2, label=\"2\"];\n"),
+            "{dot}"
+        );
+        assert!(dot.ends_with("}\n"));
+    }
+}
@@ -22,6 +22,16 @@ pub enum GmapError {
     InvalidDate(String),
     #[error("Other: {0}")]
     Other(String),
+    #[error("{context}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<GmapError>,
+    },
+    #[error("interrupted; {saved} newly analyzed commits were saved to the cache")]
+    Interrupted { saved: usize },
+    #[error("object {oid} referenced by commit {commit} could not be found")]
+    MissingObject { oid: String, commit: String },
     #[error("Object find error: {0}")]
     ObjectFind(#[from] Box<gix::object::find::existing::Error>),
     #[error("Commit error: {0}")]
@@ -40,6 +50,21 @@ pub enum GmapError {
     GitDiscover(#[from] Box<gix::discover::Error>),
 }
 
+/// Attach the operation being performed to an error, e.g.
+/// "while reading tree for commit X", as the outer layer of its source chain.
+pub trait ErrorContext<T> {
+    fn with_context<F: FnOnce() -> String>(self, context: F) -> Result<T>;
+}
+
+impl<T, E: Into<GmapError>> ErrorContext<T> for std::result::Result<T, E> {
+    fn with_context<F: FnOnce() -> String>(self, context: F) -> Result<T> {
+        self.map_err(|err| GmapError::Context {
+            context: context(),
+            source: Box::new(err.into()),
+        })
+    }
+}
+
 // Manual From implementations for unboxed to boxed conversions
 impl From<gix::open::Error> for GmapError {
     fn from(err: gix::open::Error) -> Self {
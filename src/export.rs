@@ -1,55 +1,187 @@
 use crate::cache::Cache;
-use crate::cli::CommonArgs;
+use crate::cli::{CommonArgs, OutputArgs};
 use crate::error::Result;
+use crate::filter::CommitFilter;
 use crate::git::GitRepo;
-use crate::heat::fetch_commit_stats_with_progress;
-use crate::model::{CommitStats, ExportEntry, ExportOutput};
-use anyhow::Context;
+use crate::heat::{aggregate_weeks_cached, fetch_commit_stats_reported};
+use crate::model::{
+    CommitStats, CommitSummary, CommitSummaryOutput, DateRange, ExportEntry, FileStats,
+    NameOnlyEntry, NameOnlyOutput, NdjsonHeader, WeekStatsOutput, SCHEMA_VERSION,
+};
+use crate::output::{to_stdout, FormatRegistry, OutputContext};
+use crate::util::{commit_size, path_excluded, to_json, GitIgnoreMatcher, OutlierThreshold};
+use anyhow::{bail, Context};
 use chrono::Utc;
-use std::collections::HashSet;
+use serde::Serialize;
 
-pub fn exec(common: CommonArgs, json: bool, ndjson: bool) -> anyhow::Result<()> {
-    let repo = GitRepo::open(common.repo.as_ref()).context("Failed to open git repository")?;
-    let mut cache =
-        Cache::new(common.cache.as_deref(), repo.path()).context("Failed to initialize cache")?;
+/// Options for `gmap export`, on top of [`CommonArgs`].
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+    pub output: OutputArgs,
+    /// Mark commits this threshold flags as outliers
+    pub flag_outliers: Option<OutlierThreshold>,
+    pub hunks: bool,
+    pub name_only: bool,
+    pub show_signatures: bool,
+    pub summary_entries: bool,
+    pub group_by_week: bool,
+}
+
+pub fn exec(common: CommonArgs, options: ExportOptions) -> anyhow::Result<()> {
+    let ExportOptions {
+        output,
+        flag_outliers,
+        hunks,
+        name_only,
+        show_signatures,
+        summary_entries,
+        group_by_week,
+    } = options;
+    let registry = FormatRegistry::builtin();
+    let format = registry.get(output.format_name()?)?;
+    if (hunks || name_only || summary_entries || group_by_week)
+        && !matches!(format.name(), "json" | "ndjson")
+    {
+        bail!(
+            "--hunks, --name-only, --summary-entries and --group-by-week support JSON and NDJSON output only"
+        );
+    }
 
-    let range = repo
-        .resolve_range(common.since.as_deref(), common.until.as_deref())
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let range = common
+        .resolve_range(&repo)
         .context("Failed to resolve date range")?;
 
-    let cached_stats = fetch_commit_stats_with_progress(
+    if name_only {
+        return export_name_only(
+            &repo,
+            &common,
+            &range,
+            &output,
+            format.name(),
+            show_signatures,
+        );
+    }
+    let mut cache = common
+        .open_cache(&repo)
+        .context("Failed to initialize cache")?;
+
+    let (cached_stats, report) = fetch_commit_stats_reported(
         &repo,
         &mut cache,
         &range,
-        common.include_merges,
-        common.binary,
-        false,
+        common.merge_policy(),
+        common.binary_policy(),
+        common.recompute,
+        true,
     )?;
 
-    let export_data = prepare_export_data(
+    if group_by_week {
+        let gi = common.git_ignore(&repo);
+        let weeks = aggregate_weeks_cached(
+            &cached_stats,
+            &cache,
+            None,
+            &common.commit_filter(),
+            common.period_spec(false),
+            &common.exclude,
+            gi.as_ref(),
+        );
+        let header = ndjson_header(&output, &repo, &common);
+        print_entries(format.name(), output.compact, header, weeks, |weeks| {
+            WeekStatsOutput {
+                version: SCHEMA_VERSION,
+                generated_at: Utc::now(),
+                repository_path: repo.path().display().to_string(),
+                since: common.since_arg().map(str::to_string),
+                until: common.until_arg().map(str::to_string),
+                weeks,
+            }
+        })?;
+        report.announce();
+        return Ok(());
+    }
+
+    let gi = common.git_ignore(&repo);
+    let mut export_data = prepare_export_data(
         &cached_stats,
         &cache,
-        common.author.as_deref(),
-        common.author_email.as_deref(),
+        &common.commit_filter(),
+        &common.exclude,
+        gi.as_ref(),
     )
     .context("Failed to prepare export data")?;
 
-    if json {
-        output_json(&export_data, &repo, &common)?;
-    } else if ndjson {
-        output_ndjson(&export_data)?;
+    if let Some(threshold) = flag_outliers {
+        mark_outliers(&mut export_data, threshold, &common, gi.as_ref());
+    }
+    if hunks {
+        // Hunks are not cached, so each commit is diffed again here.
+        for entry in &mut export_data {
+            let mut by_path = repo
+                .commit_hunks(&entry.commit_id)
+                .context("Failed to compute hunks")?;
+            for file in &mut entry.files {
+                file.hunks = by_path.remove(&file.path).unwrap_or_default();
+            }
+        }
+    }
+    if show_signatures {
+        for entry in &mut export_data {
+            // Rows cached before signatures were recorded are read again.
+            if entry.signature.is_none() {
+                entry.signature = repo
+                    .get_commit_info(&entry.commit_id)
+                    .context("Failed to read commit signature")?
+                    .signature;
+            }
+        }
     } else {
-        output_summary(&export_data)?;
+        for entry in &mut export_data {
+            entry.signature = None;
+        }
     }
 
+    if summary_entries {
+        let entries = export_data.into_iter().map(CommitSummary::from).collect();
+        let header = ndjson_header(&output, &repo, &common);
+        print_entries(format.name(), output.compact, header, entries, |entries| {
+            CommitSummaryOutput {
+                version: SCHEMA_VERSION,
+                generated_at: Utc::now(),
+                repository_path: repo.path().display().to_string(),
+                since: common.since_arg().map(str::to_string),
+                until: common.until_arg().map(str::to_string),
+                entries,
+            }
+        })?;
+        report.announce();
+        return Ok(());
+    }
+
+    let ctx = OutputContext {
+        repository_path: repo.path().display().to_string(),
+        since: common.since_arg(),
+        until: common.until_arg(),
+        compact: output.compact,
+        ndjson_header: output.ndjson_header,
+        ..Default::default()
+    };
+    to_stdout(|out| format.write_export(out, &ctx, &export_data))?;
+    report.announce();
     Ok(())
 }
 
+/// Export entries for the commits `filter` keeps, without files `--exclude`
+/// or the gitignore rules drop; commits left with no files are skipped.
 fn prepare_export_data(
     stats: &[CommitStats],
     cache: &Cache,
-    author: Option<&str>,
-    author_email: Option<&str>,
+    filter: &CommitFilter,
+    excludes: &[String],
+    git_ignore: Option<&GitIgnoreMatcher>,
 ) -> Result<Vec<ExportEntry>> {
     let mut entries = Vec::with_capacity(stats.len());
 
@@ -58,24 +190,23 @@ fn prepare_export_data(
             .get_commit_info(&commit_stats.commit_id)?
             .ok_or_else(|| crate::error::GmapError::Cache("Commit info not found".to_string()))?;
 
-        if let Some(a) = author {
-            if !commit_info
-                .author_name
-                .to_lowercase()
-                .contains(&a.to_lowercase())
-            {
-                continue;
-            }
-        }
-        if let Some(ae) = author_email {
-            if !commit_info
-                .author_email
-                .to_lowercase()
-                .contains(&ae.to_lowercase())
-            {
-                continue;
-            }
+        if !filter.matches(&commit_info) {
+            continue;
         }
+        let (summary, files): (Vec<FileStats>, Vec<FileStats>) = commit_stats
+            .files
+            .iter()
+            .cloned()
+            .partition(FileStats::is_summary);
+        let Some(files) = kept_files(
+            files,
+            |f| &f.path,
+            excludes,
+            filter.case_sensitive(),
+            git_ignore,
+        ) else {
+            continue;
+        };
 
         entries.push(ExportEntry {
             commit_id: commit_info.id,
@@ -83,76 +214,161 @@ fn prepare_export_data(
             author_email: commit_info.author_email,
             timestamp: commit_info.timestamp,
             message: commit_info.message,
-            files: commit_stats.files.clone(),
+            files,
+            summarized_files: summary.first().and_then(|f| f.summarized_files),
+            is_outlier: false,
+            signature: commit_info.signature,
         });
     }
 
-    entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    entries.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.commit_id.cmp(&b.commit_id))
+    });
     Ok(entries)
 }
 
-fn output_json(
-    export_data: &[ExportEntry],
+/// Changed paths per commit straight from the trees: no blobs are read and the
+/// cache is neither consulted nor filled.
+fn export_name_only(
     repo: &GitRepo,
     common: &CommonArgs,
+    range: &DateRange,
+    output: &OutputArgs,
+    format: &str,
+    show_signatures: bool,
 ) -> anyhow::Result<()> {
-    let output = ExportOutput {
-        version: crate::model::SCHEMA_VERSION,
-        generated_at: Utc::now(),
-        repository_path: repo.path().to_string_lossy().to_string(),
-        since: common.since.clone(),
-        until: common.until.clone(),
-        entries: export_data.to_vec(),
+    let filter = common.commit_filter();
+    let gi = common.git_ignore(repo);
+    let ids = {
+        let _phase = crate::timing::phase("commit listing");
+        repo.list_commit_ids(range, common.merge_policy())
+            .context("Failed to list commits from repository")?
     };
+    let mut entries = Vec::with_capacity(ids.len());
+    {
+        let _phase = crate::timing::phase("path collection");
+        for oid in ids {
+            let info = repo
+                .get_commit_info(&oid.to_string())
+                .context("Failed to read commit")?;
+            if !filter.matches(&info) {
+                continue;
+            }
+            let paths = repo
+                .commit_paths(oid)
+                .context("Failed to list changed paths")?;
+            let Some(files) = kept_files(
+                paths,
+                |path| path,
+                &common.exclude,
+                filter.case_sensitive(),
+                gi.as_ref(),
+            ) else {
+                continue;
+            };
+            entries.push(NameOnlyEntry {
+                files,
+                commit_id: info.id,
+                author_name: info.author_name,
+                author_email: info.author_email,
+                timestamp: info.timestamp,
+                message: info.message,
+                signature: info.signature.filter(|_| show_signatures),
+            });
+        }
+    }
+    entries.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.commit_id.cmp(&b.commit_id))
+    });
+    crate::timing::log(crate::timing::COUNTS, || {
+        format!("blobs read: {}", repo.blob_reads())
+    });
 
-    println!("{}", serde_json::to_string_pretty(&output)?);
-    Ok(())
+    let header = ndjson_header(output, repo, common);
+    print_entries(format, output.compact, header, entries, |entries| {
+        NameOnlyOutput {
+            version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            repository_path: repo.path().display().to_string(),
+            since: common.since_arg().map(str::to_string),
+            until: common.until_arg().map(str::to_string),
+            entries,
+        }
+    })
 }
 
-fn output_ndjson(export_data: &[ExportEntry]) -> anyhow::Result<()> {
-    for entry in export_data {
-        println!("{}", serde_json::to_string(entry)?);
-    }
-    Ok(())
+/// The `--ndjson-header` line for an export, if it was asked for.
+fn ndjson_header(output: &OutputArgs, repo: &GitRepo, common: &CommonArgs) -> Option<NdjsonHeader> {
+    output.ndjson_header.then(|| {
+        NdjsonHeader::new(
+            "export",
+            repo.path().display().to_string(),
+            common.since_arg(),
+            common.until_arg(),
+        )
+    })
 }
 
-fn output_summary(export_data: &[ExportEntry]) -> anyhow::Result<()> {
-    use console::style;
+/// Print `entries` one per line for NDJSON, after `header` if given, else
+/// inside the document `wrap` builds around them.
+fn print_entries<T: Serialize, D: Serialize>(
+    format: &str,
+    compact: bool,
+    header: Option<NdjsonHeader>,
+    entries: Vec<T>,
+    wrap: impl FnOnce(Vec<T>) -> D,
+) -> anyhow::Result<()> {
+    to_stdout(|out| {
+        if format == "ndjson" {
+            if let Some(header) = &header {
+                serde_json::to_writer(&mut *out, header)?;
+                writeln!(out)?;
+            }
+            for entry in &entries {
+                serde_json::to_writer(&mut *out, entry)?;
+                writeln!(out)?;
+            }
+        } else {
+            writeln!(out, "{}", to_json(&wrap(entries), compact)?)?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
 
-    println!("{}", style("Export Summary").bold());
-    println!("{}", "─".repeat(50));
+/// `files` without those `--exclude` or the gitignore rules drop, or `None`
+/// when that drops every one. A commit with no files to begin with is kept.
+fn kept_files<T>(
+    mut files: Vec<T>,
+    path: impl Fn(&T) -> &str,
+    excludes: &[String],
+    case_sensitive: bool,
+    git_ignore: Option<&GitIgnoreMatcher>,
+) -> Option<Vec<T>> {
+    let before = files.len();
+    files.retain(|f| {
+        let path = path(f);
+        !path_excluded(path, excludes, case_sensitive)
+            && git_ignore.is_none_or(|gi| !gi.is_ignored(path))
+    });
+    (files.len() == before || !files.is_empty()).then_some(files)
+}
 
-    let total_commits = export_data.len();
-    let total_files: usize = export_data.iter().map(|e| e.files.len()).sum();
-    let total_added: u64 = export_data
-        .iter()
-        .flat_map(|e| &e.files)
-        .map(|f| f.added_lines as u64)
-        .sum();
-    let total_deleted: u64 = export_data
+fn mark_outliers(
+    entries: &mut [ExportEntry],
+    threshold: OutlierThreshold,
+    common: &CommonArgs,
+    git_ignore: Option<&GitIgnoreMatcher>,
+) {
+    let sizes: Vec<u64> = entries
         .iter()
-        .flat_map(|e| &e.files)
-        .map(|f| f.deleted_lines as u64)
-        .sum();
-
-    let unique_authors: HashSet<_> = export_data.iter().map(|e| &e.author_name).collect();
-
-    println!("Total commits: {}", style(total_commits).cyan());
-    println!("Total files changed: {}", style(total_files).cyan());
-    println!("Total lines added: {}", style(total_added).green());
-    println!("Total lines deleted: {}", style(total_deleted).red());
-    println!("Unique authors: {}", style(unique_authors.len()).yellow());
-
-    if !export_data.is_empty() {
-        let first_commit = &export_data[0];
-        let last_commit = &export_data[export_data.len() - 1];
-        println!(
-            "Date range: {} to {}",
-            style(first_commit.timestamp.format("%Y-%m-%d")).dim(),
-            style(last_commit.timestamp.format("%Y-%m-%d")).dim()
-        );
+        .map(|e| commit_size(&e.files, &common.exclude, common.case_sensitive, git_ignore))
+        .collect();
+    for (entry, is_outlier) in entries.iter_mut().zip(threshold.flag(&sizes)) {
+        entry.is_outlier = is_outlier;
     }
-
-    println!("\nUse --json or --ndjson flags to export the raw data.");
-    Ok(())
 }
@@ -0,0 +1,184 @@
+use crate::alias::AuthorAliases;
+use crate::model::CommitInfo;
+use crate::util::contains_text;
+use serde::Serialize;
+
+/// Author patterns that `--exclude-bots` expands to.
+pub const BOT_PATTERNS: &[&str] = &[
+    "[bot]",
+    "dependabot",
+    "renovate",
+    "github-actions",
+    "greenkeeper",
+];
+
+/// Decides which commits take part in aggregation, based on their author
+/// and, with [`with_exclude_root`](Self::with_exclude_root), their parents.
+///
+/// Every pattern is a substring, case-insensitive unless
+/// [`with_case_sensitive`](Self::with_case_sensitive). A commit is kept when it
+/// satisfies the include filters and matches none of the exclusions. Patterns
+/// see authors after `--aliases` has merged them.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CommitFilter {
+    author: Option<String>,
+    author_email: Option<String>,
+    exclude_authors: Vec<String>,
+    aliases: AuthorAliases,
+    case_sensitive: bool,
+    exclude_root: bool,
+}
+
+impl CommitFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only commits whose author name contains `author`.
+    pub fn with_author(mut self, author: Option<&str>) -> Self {
+        self.author = author.map(str::to_string);
+        self
+    }
+
+    /// Keep only commits whose author email contains `email`.
+    pub fn with_author_email(mut self, email: Option<&str>) -> Self {
+        self.author_email = email.map(str::to_string);
+        self
+    }
+
+    /// Drop commits whose author name or email contains any of `patterns`.
+    pub fn with_excluded_authors<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.exclude_authors
+            .extend(patterns.into_iter().map(|p| p.as_ref().to_string()));
+        self
+    }
+
+    /// Report aliased authors under their canonical identity.
+    pub fn with_aliases(mut self, aliases: AuthorAliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Match patterns with exact case instead of ignoring it.
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Drop parentless commits, whose diff is the whole initial snapshot.
+    pub fn with_exclude_root(mut self, exclude_root: bool) -> Self {
+        self.exclude_root = exclude_root;
+        self
+    }
+
+    /// Whether patterns match with exact case; path exclusions follow it too.
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    /// Rewrite `info`'s author to its canonical identity, for code that groups
+    /// by author.
+    pub fn resolve_author(&self, info: &mut CommitInfo) {
+        self.aliases.apply(info);
+    }
+
+    pub fn matches(&self, info: &CommitInfo) -> bool {
+        if self.exclude_root && info.parent_ids.is_empty() {
+            return false;
+        }
+        let (name, email) = self.aliases.resolve(&info.author_name, &info.author_email);
+        let contains =
+            |text: &str, pattern: &str| contains_text(text, pattern, self.case_sensitive);
+
+        if let Some(a) = &self.author {
+            if !contains(name, a) {
+                return false;
+            }
+        }
+        if let Some(ae) = &self.author_email {
+            if !contains(email, ae) {
+                return false;
+            }
+        }
+
+        !self
+            .exclude_authors
+            .iter()
+            .any(|p| contains(name, p) || contains(email, p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn info(name: &str, email: &str) -> CommitInfo {
+        CommitInfo {
+            id: "abc".to_string(),
+            author_name: name.to_string(),
+            author_email: email.to_string(),
+            message: String::new(),
+            timestamp: Utc::now(),
+            parent_ids: Vec::new(),
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn exclusions_apply_to_name_and_email() {
+        let filter = CommitFilter::new().with_excluded_authors(BOT_PATTERNS);
+        let human = info("Alice", "alice@example.com");
+        let bot = info(
+            "dependabot[bot]",
+            "49699333+dependabot[bot]@users.noreply.github.com",
+        );
+        let ci = info("CI", "github-actions@example.com");
+
+        assert!(filter.matches(&human));
+        assert!(!filter.matches(&bot));
+        assert!(!filter.matches(&ci));
+    }
+
+    #[test]
+    fn include_and_exclude_combine() {
+        let filter = CommitFilter::new()
+            .with_author(Some("ALICE"))
+            .with_excluded_authors(["old@"]);
+
+        assert!(filter.matches(&info("Alice", "alice@example.com")));
+        assert!(!filter.matches(&info("Alice", "old@example.com")));
+        assert!(!filter.matches(&info("Bob", "bob@example.com")));
+    }
+
+    #[test]
+    fn case_sensitive_patterns_require_exact_case() {
+        let alice = info("Alice", "alice@example.com");
+
+        assert!(CommitFilter::new()
+            .with_author(Some("alice"))
+            .matches(&alice));
+        let exact = CommitFilter::new().with_case_sensitive(true);
+        assert!(!exact.clone().with_author(Some("alice")).matches(&alice));
+        assert!(exact.clone().with_author(Some("Alice")).matches(&alice));
+        assert!(exact.with_excluded_authors(["ALICE"]).matches(&alice));
+    }
+
+    #[test]
+    fn exclude_root_drops_only_parentless_commits() {
+        let filter = CommitFilter::new().with_exclude_root(true);
+        let root = info("Alice", "alice@example.com");
+        let child = CommitInfo {
+            parent_ids: vec!["abc".to_string()],
+            ..root.clone()
+        };
+
+        assert!(CommitFilter::new().matches(&root));
+        assert!(!filter.matches(&root));
+        assert!(filter.matches(&child));
+    }
+}
@@ -1,24 +1,37 @@
-use crate::error::{GmapError, Result};
-use crate::model::{CommitInfo, CommitStats, DateRange, FileStats};
+use crate::error::{ErrorContext, GmapError, Result};
+use crate::model::{
+    BoundKind, CommitInfo, CommitStats, DateKind, DateRange, FileStats, Hunk, MergeChurn,
+    MergeDiff, MergePolicy, ReleaseTag, SignatureStatus, TreeFile,
+};
 use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, TimeZone, Utc};
 use gix::object::tree::diff::ChangeDetached;
 use gix::{discover, ObjectId, Repository};
-use indicatif::{ProgressBar, ProgressStyle};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::cell::Cell;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 
-#[derive(Clone)]
-struct CommitMeta {
-    timestamp: DateTime<Utc>,
-    author_name: String,
-    author_email: String,
-    message_title: String,
-    parent_ids: Vec<ObjectId>,
-}
+/// Leading bytes of a blob examined for binary content, as git does.
+pub const DEFAULT_BINARY_SCAN_BYTES: usize = 8192;
+
+/// Share of control characters in the scanned bytes above which a blob with
+/// no NUL still counts as binary.
+const BINARY_CONTROL_RATIO: f64 = 0.3;
 
 pub struct GitRepo {
     repo: Repository,
     path: PathBuf,
+    date_kind: DateKind,
+    merge_diff: MergeDiff,
+    merge_churn: MergeChurn,
+    blob_reads: Cell<u64>,
+    /// Where history walks start; `None` means HEAD.
+    tip: Option<ObjectId>,
+    /// Commits left out of every listing; history is still walked through them.
+    ignored: HashSet<ObjectId>,
+    /// Commits changing more files than this are recorded as one summary row.
+    max_files_per_commit: Option<usize>,
+    /// Leading bytes of each blob [`looks_binary`] examines.
+    binary_scan_bytes: usize,
 }
 
 impl GitRepo {
@@ -31,17 +44,143 @@ impl GitRepo {
         let repo = discover(&repo_path)?;
         let path = repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf();
 
-        Ok(Self { repo, path })
+        Ok(Self {
+            repo,
+            path,
+            date_kind: DateKind::default(),
+            merge_diff: MergeDiff::default(),
+            merge_churn: MergeChurn::default(),
+            blob_reads: Cell::new(0),
+            tip: None,
+            ignored: HashSet::new(),
+            max_files_per_commit: None,
+            binary_scan_bytes: DEFAULT_BINARY_SCAN_BYTES,
+        })
+    }
+
+    /// Use the author or committer date for timestamps and range bounds.
+    pub fn with_date_kind(mut self, date_kind: DateKind) -> Self {
+        self.date_kind = date_kind;
+        self
+    }
+
+    /// Diff merge commits against their first parent or against every parent.
+    pub fn with_merge_diff(mut self, merge_diff: MergeDiff) -> Self {
+        self.merge_diff = merge_diff;
+        self
+    }
+
+    /// Count a two-parent merge's full diff, or only its changes against the
+    /// automatic merge of its parents.
+    pub fn with_merge_churn(mut self, merge_churn: MergeChurn) -> Self {
+        self.merge_churn = merge_churn;
+        self
+    }
+
+    /// Record commits changing more than `max` files as a single
+    /// [`FileStats::summary`] row instead of one row per file.
+    pub fn with_max_files_per_commit(mut self, max: Option<usize>) -> Self {
+        self.max_files_per_commit = max;
+        self
+    }
+
+    pub fn max_files_per_commit(&self) -> Option<usize> {
+        self.max_files_per_commit
+    }
+
+    /// Examine the first `bytes` bytes of each blob to tell binary from text.
+    pub fn with_binary_scan_bytes(mut self, bytes: usize) -> Self {
+        self.binary_scan_bytes = bytes;
+        self
+    }
+
+    /// Leave these commits (hashes, abbreviated hashes or any revision) out of
+    /// commit listings, e.g. a repo-wide reformat.
+    pub fn with_ignored_revs(mut self, revs: &[String]) -> Result<Self> {
+        for rev in revs {
+            let id = self
+                .repo
+                .rev_parse_single(rev.as_str())
+                .map_err(|e| GmapError::Parse(format!("Invalid ignored revision '{rev}': {e}")))?;
+            let commit = id.object()?.try_into_commit().map_err(|_| {
+                GmapError::Parse(format!("Invalid ignored revision '{rev}': not a commit"))
+            })?;
+            self.ignored.insert(commit.id);
+        }
+        Ok(self)
+    }
+
+    /// Walk history from the default branch instead of HEAD: `origin/HEAD`,
+    /// else a local or `origin` `main`, else `master`.
+    pub fn with_default_branch(mut self) -> Result<Self> {
+        const CANDIDATES: [&str; 5] = [
+            "refs/remotes/origin/HEAD",
+            "refs/heads/main",
+            "refs/remotes/origin/main",
+            "refs/heads/master",
+            "refs/remotes/origin/master",
+        ];
+        for name in CANDIDATES {
+            let Ok(Some(mut reference)) = self.repo.try_find_reference(name) else {
+                continue;
+            };
+            let Ok(tip) = reference.peel_to_commit().map(|commit| commit.id) else {
+                continue;
+            };
+            crate::timing::log(crate::timing::COUNTS, || {
+                format!("default branch: {name} at {tip}")
+            });
+            self.tip = Some(tip);
+            return Ok(self);
+        }
+        Err(GmapError::GitRepo(
+            "No default branch found (tried origin/HEAD, main, master)".to_string(),
+        ))
     }
 
     pub fn path(&self) -> &Path {
         &self.path
     }
 
-    pub fn resolve_range(&self, since: Option<&str>, until: Option<&str>) -> Result<DateRange> {
+    /// Objects loaded so far to count or diff their lines.
+    pub fn blob_reads(&self) -> u64 {
+        self.blob_reads.get()
+    }
+
+    fn commit_timestamp(&self, commit: &gix::Commit<'_>) -> Result<DateTime<Utc>> {
+        let secs = match self.date_kind {
+            DateKind::Committer => commit.time()?.seconds,
+            DateKind::Author => {
+                commit
+                    .author()?
+                    .time()
+                    .map_err(|e| GmapError::InvalidDate(format!("Invalid author date: {e}")))?
+                    .seconds
+            }
+        };
+        Utc.timestamp_opt(secs, 0)
+            .single()
+            .ok_or_else(|| GmapError::InvalidDate(format!("Invalid timestamp: {secs}")))
+    }
+
+    pub fn resolve_range(
+        &self,
+        since: Option<(&str, BoundKind)>,
+        until: Option<(&str, BoundKind)>,
+        revspec: Option<&str>,
+    ) -> Result<DateRange> {
+        let _phase = crate::timing::phase("range resolution");
         let mut range = DateRange::new();
-        let since_dt = since.map(|s| self.parse_commit_or_date(s)).transpose()?;
-        let until_dt = until.map(|u| self.parse_commit_or_date(u)).transpose()?;
+        if let Some(spec) = revspec {
+            let ids = self.resolve_revspec(spec)?;
+            range = range.with_commits(ids.iter().map(|id| id.to_string()).collect());
+        }
+        let since_dt = since
+            .map(|(s, kind)| self.resolve_bound("since", s, kind))
+            .transpose()?;
+        let until_dt = until
+            .map(|(u, kind)| self.resolve_bound("until", u, kind))
+            .transpose()?;
         if let (Some(s), Some(u)) = (since_dt, until_dt) {
             if s > u {
                 return Err(GmapError::InvalidDate(format!(
@@ -60,196 +199,446 @@ impl GitRepo {
         Ok(range)
     }
 
-    fn parse_commit_or_date(&self, input: &str) -> Result<DateTime<Utc>> {
-        // RFC3339
-        if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
-            return Ok(dt.with_timezone(&Utc));
+    /// Resolve `A..B` (reachable from B but not A), `A...B` (reachable from
+    /// either but not both) or a single revision (all its ancestors). An empty
+    /// side means `HEAD`, as in git.
+    pub fn resolve_revspec(&self, spec: &str) -> Result<HashSet<ObjectId>> {
+        if let Some((left, right)) = spec.split_once("...") {
+            let left = self.ancestors(self.resolve_revision(spec, left)?)?;
+            let right = self.ancestors(self.resolve_revision(spec, right)?)?;
+            Ok(left.symmetric_difference(&right).copied().collect())
+        } else if let Some((left, right)) = spec.split_once("..") {
+            let excluded = self.ancestors(self.resolve_revision(spec, left)?)?;
+            let included = self.ancestors(self.resolve_revision(spec, right)?)?;
+            Ok(included.difference(&excluded).copied().collect())
+        } else {
+            self.ancestors(self.resolve_revision(spec, spec)?)
         }
+    }
 
-        // YYYY-MM-DD
-        if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
-            if let Some(datetime) = date.and_hms_opt(0, 0, 0) {
-                return Ok(Utc.from_utc_datetime(&datetime));
+    fn resolve_revision(&self, spec: &str, rev: &str) -> Result<ObjectId> {
+        let rev = if rev.is_empty() { "HEAD" } else { rev };
+        let id = self
+            .repo
+            .rev_parse_single(rev)
+            .map_err(|e| GmapError::Parse(format!("Invalid revision range '{spec}': {e}")))?;
+        let commit = id.object()?.try_into_commit().map_err(|_| {
+            GmapError::Parse(format!(
+                "Invalid revision range '{spec}': '{rev}' is not a commit"
+            ))
+        })?;
+        Ok(commit.id)
+    }
+
+    fn ancestors(&self, tip: ObjectId) -> Result<HashSet<ObjectId>> {
+        let mut seen: HashSet<ObjectId> = HashSet::new();
+        let mut stack = vec![tip];
+        while let Some(commit_id) = stack.pop() {
+            if !seen.insert(commit_id) {
+                continue;
             }
+            let commit = self
+                .repo
+                .find_commit(commit_id)
+                .with_context(|| format!("while reading commit {commit_id}"))?;
+            stack.extend(commit.parent_ids().map(ObjectId::from));
         }
+        Ok(seen)
+    }
 
-        // Relative duration (e.g., "2 weeks ago")
-        if let Some(duration) = parse_natural_duration(input) {
-            let now = Utc::now();
-            let target = now - duration;
-            return Ok(target);
+    /// Read one range bound as `kind` says, logging at `--debug` whether it
+    /// was taken as a date or a commit.
+    fn resolve_bound(&self, name: &str, input: &str, kind: BoundKind) -> Result<DateTime<Utc>> {
+        let date = match kind {
+            BoundKind::Commit => None,
+            BoundKind::Auto | BoundKind::Date => parse_date(input),
+        };
+        if let Some(dt) = date {
+            crate::timing::log(crate::timing::COUNTS, || {
+                format!("{name} '{input}' read as date {dt}")
+            });
+            return Ok(dt);
+        }
+        if kind == BoundKind::Date {
+            return Err(GmapError::InvalidDate(format!(
+                "Not a date: '{input}' (expected RFC3339, YYYY-MM-DD or e.g. '2 weeks ago')"
+            )));
         }
 
-        // Fallback to Git ref
-        let id = self
-            .repo
-            .rev_parse_single(input)
-            .map_err(|e| GmapError::Parse(format!("Invalid commit or date '{input}': {e}")))?;
-
+        let id = self.repo.rev_parse_single(input).map_err(|e| {
+            let what = if kind == BoundKind::Commit {
+                "commit"
+            } else {
+                "commit or date"
+            };
+            GmapError::Parse(format!("Invalid {what} '{input}': {e}"))
+        })?;
         let commit = id
             .object()?
             .try_into_commit()
             .map_err(|_| GmapError::Parse(format!("Not a commit: {input}")))?;
-
-        let secs = commit.time()?.seconds;
-        Utc.timestamp_opt(secs, 0)
-            .single()
-            .ok_or_else(|| GmapError::InvalidDate(format!("Invalid timestamp: {secs}")))
+        let dt = self.commit_timestamp(&commit)?;
+        crate::timing::log(crate::timing::COUNTS, || {
+            format!("{name} '{input}' read as commit {} dated {dt}", commit.id)
+        });
+        Ok(dt)
     }
 
-    pub fn collect_commits(
+    /// Files changed by a commit. Merges use the first parent unless the
+    /// combined mode is on, in which case every path changed relative to any
+    /// parent is reported with its largest change. In resolution mode a
+    /// two-parent merge only reports what differs from the automatic merge.
+    fn commit_files(
         &self,
-        range: &DateRange,
-        include_merges: bool,
+        commit_id: ObjectId,
+        parents: &[ObjectId],
         binary: bool,
-        progress: bool,
-    ) -> Result<Vec<CommitStats>> {
-        let mut head = self.repo.head()?;
-        let head_commit = head.peel_to_commit_in_place()?;
+    ) -> Result<Vec<FileStats>> {
+        if let (MergeChurn::Resolution, [ours, theirs]) = (self.merge_churn, parents) {
+            return self.resolution_files(commit_id, *ours, *theirs, binary);
+        }
+        if self.merge_diff == MergeDiff::FirstParent || parents.len() < 2 {
+            return self.diff_files(commit_id, parents.first().copied(), binary);
+        }
+        let mut by_path: HashMap<String, FileStats> = HashMap::new();
+        for parent in parents {
+            for f in self.diff_files(commit_id, Some(*parent), binary)? {
+                let lines = |f: &FileStats| f.added_lines + f.deleted_lines;
+                match by_path.get(&f.path) {
+                    Some(seen) if lines(seen) >= lines(&f) => {}
+                    _ => {
+                        by_path.insert(f.path.clone(), f);
+                    }
+                }
+            }
+        }
+        let mut files: Vec<FileStats> = by_path.into_values().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
 
-        let mut commits = Vec::new();
-        let mut seen: HashSet<ObjectId> = HashSet::new();
-        let mut stack: VecDeque<ObjectId> = VecDeque::from([head_commit.id]);
-        let mut commit_cache: HashMap<ObjectId, CommitMeta> = HashMap::new();
-        let pb = if progress {
-            ProgressBar::new_spinner()
+    fn tree_changes(
+        &self,
+        commit_id: ObjectId,
+        parent_id: Option<ObjectId>,
+    ) -> Result<Vec<ChangeDetached>> {
+        let commit_tree = self
+            .repo
+            .find_commit(commit_id)
+            .with_context(|| format!("while reading commit {commit_id}"))?
+            .tree()
+            .with_context(|| format!("while reading tree for commit {commit_id}"))?;
+        let parent_tree = if let Some(pid) = parent_id {
+            Some(
+                self.repo
+                    .find_commit(pid)
+                    .with_context(|| format!("while reading parent {pid} of commit {commit_id}"))?
+                    .tree()
+                    .with_context(|| format!("while reading tree for commit {pid}"))?,
+            )
         } else {
-            ProgressBar::hidden()
+            None
         };
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
-                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
-        );
-        pb.set_message("Collecting commits...");
+        self.repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), None)
+            .with_context(|| format!("while diffing commit {commit_id}"))
+    }
 
-        while let Some(commit_id) = stack.pop_back() {
-            if !seen.insert(commit_id) {
-                continue;
+    /// Paths a commit changed, found from the trees alone without reading any
+    /// blob, so binary files are included. Merges follow the merge-diff mode.
+    pub fn commit_paths(&self, commit_id: ObjectId) -> Result<Vec<String>> {
+        let parents: Vec<Option<ObjectId>> = {
+            let commit = self
+                .repo
+                .find_commit(commit_id)
+                .with_context(|| format!("while reading commit {commit_id}"))?;
+            let ids: Vec<ObjectId> = commit.parent_ids().map(ObjectId::from).collect();
+            match (self.merge_diff, ids.first()) {
+                (_, None) => vec![None],
+                (MergeDiff::FirstParent, Some(first)) => vec![Some(*first)],
+                (MergeDiff::Combined, Some(_)) => ids.into_iter().map(Some).collect(),
             }
+        };
+        let mut paths = BTreeSet::new();
+        for parent in parents {
+            insert_file_paths(self.tree_changes(commit_id, parent)?, &mut paths);
+        }
+        Ok(paths.into_iter().collect())
+    }
 
-            let meta = if let Some(cached) = commit_cache.get(&commit_id) {
-                cached.clone()
-            } else {
-                let commit = self.repo.find_commit(commit_id)?;
-                let secs = commit.time()?.seconds;
-                let timestamp = Utc
-                    .timestamp_opt(secs, 0)
-                    .single()
-                    .ok_or_else(|| GmapError::InvalidDate(format!("Invalid timestamp: {secs}")))?;
-                let author = commit.author()?;
-                let message = commit.message()?;
-                let parents: Vec<ObjectId> = commit.parent_ids().map(|id| id.into()).collect();
-                let entry = CommitMeta {
-                    timestamp,
-                    author_name: author.name.to_string(),
-                    author_email: author.email.to_string(),
-                    message_title: message.title.to_string(),
-                    parent_ids: parents.clone(),
-                };
-                commit_cache.insert(commit_id, entry.clone());
-                entry
-            };
+    /// Files of a two-parent merge that differ from what merging `theirs` into
+    /// `ours` from their merge base would give, like `git show --remerge-diff`.
+    /// Paths both sides changed differently are merged line by line; where
+    /// hunks overlap the automatic result carries conflict markers, so the
+    /// resolution counts as replacing them.
+    fn resolution_files(
+        &self,
+        commit_id: ObjectId,
+        ours: ObjectId,
+        theirs: ObjectId,
+        binary: bool,
+    ) -> Result<Vec<FileStats>> {
+        let base = match self.repo.merge_base(ours, theirs) {
+            Ok(id) => Some(id.detach()),
+            // unrelated histories merge against an empty tree
+            Err(gix::repository::merge_base::Error::NotFound { .. }) => None,
+            Err(err) => {
+                return Err(GmapError::GitRepo(format!(
+                    "while finding the merge base of commit {commit_id}: {err}"
+                )))
+            }
+        };
+        let mut paths = BTreeSet::new();
+        insert_file_paths(self.tree_changes(commit_id, Some(ours))?, &mut paths);
+        insert_file_paths(self.tree_changes(theirs, base)?, &mut paths);
 
-            let timestamp = meta.timestamp;
-            let parents = meta.parent_ids.clone();
+        let tree = |id: ObjectId| {
+            self.repo
+                .find_commit(id)
+                .with_context(|| format!("while reading commit {id}"))?
+                .tree()
+                .with_context(|| format!("while reading tree for commit {id}"))
+        };
+        let trees = [
+            base.map(tree).transpose()?,
+            Some(tree(ours)?),
+            Some(tree(theirs)?),
+            Some(tree(commit_id)?),
+        ];
 
-            if !range.contains(&timestamp) {
-                for pid in &parents {
-                    stack.push_back(*pid);
+        let mut files = Vec::new();
+        for path in paths {
+            let mut ids = [None; 4];
+            for (slot, tree) in ids.iter_mut().zip(&trees) {
+                if let Some(tree) = tree {
+                    *slot = tree
+                        .lookup_entry_by_path(&path)
+                        .with_context(|| format!("while reading tree for commit {commit_id}"))?
+                        .filter(|e| !e.mode().is_tree() && !e.mode().is_commit())
+                        .map(|e| e.object_id());
                 }
-                pb.inc(1);
-                continue;
             }
-
-            if !include_merges && parents.len() > 1 {
-                for pid in &parents {
-                    stack.push_back(*pid);
-                }
-                pb.inc(1);
+            let [base_id, ours_id, theirs_id, merged_id] = ids;
+            let clean = if ours_id == theirs_id || base_id == theirs_id {
+                Some(ours_id)
+            } else if base_id == ours_id {
+                Some(theirs_id)
+            } else {
+                None
+            };
+            if clean == Some(merged_id) {
                 continue;
             }
 
-            let commit_info = CommitInfo {
-                id: commit_id.to_string(),
-                author_name: meta.author_name.clone(),
-                author_email: meta.author_email.clone(),
-                message: meta.message_title.clone(),
-                timestamp,
-                parent_ids: parents.iter().map(|id| id.to_string()).collect(),
+            let read = |id: Option<ObjectId>| {
+                id.map(|id| {
+                    self.inspect_object(id, commit_id)
+                        .map(|(is_binary, _, obj)| (is_binary, obj.detach().data))
+                })
+                .transpose()
+            };
+            let merged = read(merged_id)?;
+            let auto = match clean {
+                Some(id) => read(id)?,
+                None => match (read(base_id)?, read(ours_id)?, read(theirs_id)?) {
+                    (Some((false, b)), Some((false, o)), Some((false, t))) => {
+                        let text = |data: &[u8]| std::str::from_utf8(data).unwrap_or("").to_owned();
+                        let auto = merge_lines(&text(&b), &text(&o), &text(&t));
+                        Some((false, auto.into_bytes()))
+                    }
+                    // binary, add/add and modify/delete conflicts keep the
+                    // first parent's side, as git leaves it in the worktree
+                    (_, ours_side, _) => ours_side,
+                },
             };
 
-            let stats = if let Some(parent_id) = parents.first() {
-                self.compute_commit_stats(&commit_info, commit_id, Some(*parent_id), binary)?
+            let is_binary = [&auto, &merged]
+                .into_iter()
+                .any(|side| side.as_ref().is_some_and(|(is_binary, _)| *is_binary));
+            if is_binary && !binary {
+                continue;
+            }
+            let (added, deleted) = if is_binary {
+                (0, 0)
             } else {
-                self.compute_commit_stats(&commit_info, commit_id, None, binary)?
+                fn text(side: &Option<(bool, Vec<u8>)>) -> &str {
+                    side.as_ref()
+                        .map_or("", |(_, data)| std::str::from_utf8(data).unwrap_or(""))
+                }
+                line_counts(text(&auto), text(&merged))
             };
-
-            commits.push(stats);
-            for pid in &parents {
-                stack.push_back(*pid);
+            if !is_binary && added + deleted == 0 {
+                continue;
             }
-
-            pb.inc(1);
+            let len = |side: &Option<(bool, Vec<u8>)>| side.as_ref().map_or(0, |(_, d)| d.len());
+            let (bytes_added, bytes_deleted) = byte_delta(len(&auto), len(&merged));
+            files.push(FileStats {
+                path,
+                added_lines: added,
+                deleted_lines: deleted,
+                is_binary,
+                size: merged.or(auto).map(|(_, data)| data.len() as u64),
+                bytes_added,
+                bytes_deleted,
+                hunks: Vec::new(),
+                summarized_files: None,
+            });
         }
+        Ok(files)
+    }
+
+    /// Changed line ranges per path against the first parent, like the hunk
+    /// headers of `git diff -U0`. Binary files have none.
+    pub fn commit_hunks(&self, commit_id: &str) -> Result<HashMap<String, Vec<Hunk>>> {
+        let oid = ObjectId::from_hex(commit_id.as_bytes())
+            .map_err(|e| GmapError::Parse(format!("Invalid commit ID: {e}")))?;
+        let parent = self
+            .repo
+            .find_commit(oid)
+            .with_context(|| format!("while reading commit {oid}"))?
+            .parent_ids()
+            .next()
+            .map(ObjectId::from);
 
-        pb.finish_with_message("Commits collected");
-        Ok(commits)
+        let mut hunks = HashMap::new();
+        for change in self.tree_changes(oid, parent)? {
+            let (old_id, new_id, location) = match change {
+                ChangeDetached::Addition { id, location, .. } => (None, Some(id), location),
+                ChangeDetached::Deletion { id, location, .. } => (Some(id), None, location),
+                ChangeDetached::Modification {
+                    previous_id,
+                    id,
+                    location,
+                    ..
+                } => (Some(previous_id), Some(id), location),
+                ChangeDetached::Rewrite {
+                    source_id,
+                    id,
+                    location,
+                    ..
+                } => (Some(source_id), Some(id), location),
+            };
+            let old = old_id.map(|id| self.inspect_object(id, oid)).transpose()?;
+            let new = new_id.map(|id| self.inspect_object(id, oid)).transpose()?;
+            let text = |side: &Option<(bool, u32, gix::Object<'_>)>| match side {
+                Some((true, ..)) => None,
+                Some((false, _, obj)) => {
+                    Some(std::str::from_utf8(&obj.data).unwrap_or("").to_owned())
+                }
+                None => Some(String::new()),
+            };
+            if let (Some(old_text), Some(new_text)) = (text(&old), text(&new)) {
+                hunks.insert(location.to_string(), line_hunks(&old_text, &new_text));
+            }
+        }
+        Ok(hunks)
     }
 
-    fn compute_commit_stats(
+    fn diff_files(
         &self,
-        commit_info: &CommitInfo,
         commit_id: ObjectId,
         parent_id: Option<ObjectId>,
         binary: bool,
-    ) -> Result<CommitStats> {
-        let commit_tree = self.repo.find_commit(commit_id)?.tree()?;
-        let parent_tree = if let Some(pid) = parent_id {
-            Some(self.repo.find_commit(pid)?.tree()?)
-        } else {
-            None
-        };
-        let changes: Vec<ChangeDetached> =
-            self.repo
-                .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), None)?;
+    ) -> Result<Vec<FileStats>> {
+        let changes = self.tree_changes(commit_id, parent_id)?;
         let mut files = Vec::new();
+        let changed = changes.iter().filter(|c| !c.entry_mode().is_tree()).count();
+        if self.over_file_limit(changed) {
+            return self
+                .summarize_changes(commit_id, &changes, changed)
+                .map(|f| vec![f]);
+        }
         for change in changes {
-            self.handle_change(change, binary, &mut files)?;
+            self.handle_change(commit_id, change, binary, &mut files)?;
         }
+        Ok(files)
+    }
 
-        Ok(CommitStats {
-            commit_id: commit_info.id.clone(),
-            files,
-        })
+    /// One [`FileStats::summary`] row for the `files` files of `changes`,
+    /// sized from object headers so no blob is read: vendored imports are
+    /// what the limit exists for, and reading them is the slow part.
+    fn summarize_changes(
+        &self,
+        commit_id: ObjectId,
+        changes: &[ChangeDetached],
+        files: usize,
+    ) -> Result<FileStats> {
+        let blob_size = |id: &ObjectId| -> Result<u64> {
+            let header = self.repo.find_header(*id).map_err(|err| match err {
+                gix::object::find::existing::Error::NotFound { .. } => GmapError::MissingObject {
+                    oid: id.to_string(),
+                    commit: commit_id.to_string(),
+                },
+                err => err.into(),
+            })?;
+            Ok(header.size())
+        };
+        let (mut size, mut bytes_added, mut bytes_deleted) = (0u64, 0u64, 0u64);
+        for change in changes.iter().filter(|c| !c.entry_mode().is_tree()) {
+            let (old, new) = match change {
+                ChangeDetached::Addition { id, .. } => (0, blob_size(id)?),
+                ChangeDetached::Deletion { id, .. } => (blob_size(id)?, 0),
+                ChangeDetached::Modification {
+                    previous_id, id, ..
+                } => (blob_size(previous_id)?, blob_size(id)?),
+                ChangeDetached::Rewrite { source_id, id, .. } => {
+                    (blob_size(source_id)?, blob_size(id)?)
+                }
+            };
+            size += new;
+            bytes_added += new.saturating_sub(old);
+            bytes_deleted += old.saturating_sub(new);
+        }
+        Ok(FileStats::summary(files, size, bytes_added, bytes_deleted))
+    }
+
+    /// Whether a commit changing `files` files is over `--max-files-per-commit`.
+    pub fn over_file_limit(&self, files: usize) -> bool {
+        self.max_files_per_commit.is_some_and(|max| files > max)
     }
 
     fn handle_change(
         &self,
+        commit_id: ObjectId,
         change: ChangeDetached,
         binary: bool,
         files: &mut Vec<FileStats>,
     ) -> Result<()> {
+        // Directories appear alongside their files; their tree objects would
+        // otherwise read as binary blobs.
+        if change.entry_mode().is_tree() {
+            return Ok(());
+        }
         match change {
             ChangeDetached::Addition { id, location, .. } => {
-                let (is_binary, lines, _) = self.inspect_object(id)?;
+                let (is_binary, lines, obj) = self.inspect_object(id, commit_id)?;
                 if binary || !is_binary {
                     files.push(FileStats {
                         path: location.to_string(),
                         added_lines: if is_binary { 0 } else { lines },
                         deleted_lines: 0,
                         is_binary,
+                        size: Some(obj.data.len() as u64),
+                        bytes_added: obj.data.len() as u64,
+                        bytes_deleted: 0,
+                        hunks: Vec::new(),
+                        summarized_files: None,
                     });
                 }
             }
             ChangeDetached::Deletion { id, location, .. } => {
-                let (is_binary, lines, _) = self.inspect_object(id)?;
+                let (is_binary, lines, obj) = self.inspect_object(id, commit_id)?;
                 if binary || !is_binary {
                     files.push(FileStats {
                         path: location.to_string(),
                         added_lines: 0,
                         deleted_lines: if is_binary { 0 } else { lines },
                         is_binary,
+                        size: Some(obj.data.len() as u64),
+                        bytes_added: 0,
+                        bytes_deleted: obj.data.len() as u64,
+                        hunks: Vec::new(),
+                        summarized_files: None,
                     });
                 }
             }
@@ -259,8 +648,8 @@ impl GitRepo {
                 location,
                 ..
             } => {
-                let (old_is_binary, _, old_obj) = self.inspect_object(previous_id)?;
-                let (new_is_binary, _, new_obj) = self.inspect_object(id)?;
+                let (old_is_binary, _, old_obj) = self.inspect_object(previous_id, commit_id)?;
+                let (new_is_binary, _, new_obj) = self.inspect_object(id, commit_id)?;
                 let is_binary = old_is_binary || new_is_binary;
                 if binary || !is_binary {
                     let (added, deleted) = if is_binary {
@@ -268,11 +657,18 @@ impl GitRepo {
                     } else {
                         self.compute_line_diff(&old_obj, &new_obj)?
                     };
+                    let (bytes_added, bytes_deleted) =
+                        byte_delta(old_obj.data.len(), new_obj.data.len());
                     files.push(FileStats {
                         path: location.to_string(),
                         added_lines: added,
                         deleted_lines: deleted,
                         is_binary,
+                        size: Some(new_obj.data.len() as u64),
+                        bytes_added,
+                        bytes_deleted,
+                        hunks: Vec::new(),
+                        summarized_files: None,
                     });
                 }
             }
@@ -284,8 +680,8 @@ impl GitRepo {
                 copy,
                 ..
             } => {
-                let (old_is_binary, _, old_obj) = self.inspect_object(source_id)?;
-                let (new_is_binary, _, new_obj) = self.inspect_object(id)?;
+                let (old_is_binary, _, old_obj) = self.inspect_object(source_id, commit_id)?;
+                let (new_is_binary, _, new_obj) = self.inspect_object(id, commit_id)?;
                 let is_binary = old_is_binary || new_is_binary;
                 if binary || !is_binary {
                     let (added, deleted) = if is_binary {
@@ -293,17 +689,29 @@ impl GitRepo {
                     } else {
                         self.compute_line_diff(&old_obj, &new_obj)?
                     };
+                    let (bytes_added, bytes_deleted) =
+                        byte_delta(old_obj.data.len(), new_obj.data.len());
                     files.push(FileStats {
                         path: source_location.to_string(),
                         added_lines: 0,
                         deleted_lines: if copy { 0 } else { deleted },
                         is_binary,
+                        size: Some(old_obj.data.len() as u64),
+                        bytes_added: 0,
+                        bytes_deleted: if copy { 0 } else { bytes_deleted },
+                        hunks: Vec::new(),
+                        summarized_files: None,
                     });
                     files.push(FileStats {
                         path: location.to_string(),
                         added_lines: if copy { added } else { 0 },
                         deleted_lines: 0,
                         is_binary,
+                        size: Some(new_obj.data.len() as u64),
+                        bytes_added,
+                        bytes_deleted: 0,
+                        hunks: Vec::new(),
+                        summarized_files: None,
                     });
                 }
             }
@@ -311,8 +719,21 @@ impl GitRepo {
         Ok(())
     }
 
-    fn inspect_object(&self, id: gix::ObjectId) -> Result<(bool, u32, gix::Object<'_>)> {
-        let obj = self.repo.find_object(id)?;
+    /// Load a blob for line counting; `commit_id` names the commit whose diff
+    /// referenced it, for the not-found error.
+    fn inspect_object(
+        &self,
+        id: gix::ObjectId,
+        commit_id: ObjectId,
+    ) -> Result<(bool, u32, gix::Object<'_>)> {
+        self.blob_reads.set(self.blob_reads.get() + 1);
+        let obj = self.repo.find_object(id).map_err(|err| match err {
+            gix::object::find::existing::Error::NotFound { .. } => GmapError::MissingObject {
+                oid: id.to_string(),
+                commit: commit_id.to_string(),
+            },
+            err => err.into(),
+        })?;
         let is_binary = self.is_binary_object(&obj);
         let lines = if is_binary {
             0
@@ -323,7 +744,7 @@ impl GitRepo {
     }
 
     fn is_binary_object(&self, object: &gix::Object) -> bool {
-        object.data.as_slice().iter().take(8192).any(|&b| b == 0)
+        looks_binary(&object.data, self.binary_scan_bytes)
     }
 
     fn count_lines(&self, object: &gix::Object) -> Result<u32> {
@@ -339,38 +760,22 @@ impl GitRepo {
     ) -> Result<(u32, u32)> {
         let old_text = std::str::from_utf8(old_object.data.as_slice()).unwrap_or("");
         let new_text = std::str::from_utf8(new_object.data.as_slice()).unwrap_or("");
-
-        let mut added = 0u32;
-        let mut deleted = 0u32;
-
-        let diff = similar::TextDiff::from_lines(old_text, new_text);
-        for op in diff.ops() {
-            use similar::DiffTag::*;
-            match op.tag() {
-                Insert => added += op.new_range().len() as u32,
-                Delete => deleted += op.old_range().len() as u32,
-                Replace => {
-                    deleted += op.old_range().len() as u32;
-                    added += op.new_range().len() as u32;
-                }
-                Equal => {}
-            }
-        }
-
-        Ok((added, deleted))
+        Ok(line_counts(old_text, new_text))
     }
 
     pub fn get_commit_info(&self, commit_id: &str) -> Result<CommitInfo> {
         let oid = ObjectId::from_hex(commit_id.as_bytes())
             .map_err(|e| GmapError::Parse(format!("Invalid commit ID: {e}")))?;
         let commit = self.repo.find_commit(oid)?;
-        let secs = commit.time()?.seconds;
-        let timestamp = Utc
-            .timestamp_opt(secs, 0)
-            .single()
-            .ok_or_else(|| GmapError::InvalidDate(format!("Invalid timestamp: {secs}")))?;
+        let timestamp = self.commit_timestamp(&commit)?;
         let author = commit.author()?;
         let message = commit.message()?;
+        let signature = commit
+            .decode()
+            .map_err(|e| GmapError::GitRepo(format!("Cannot decode commit {commit_id}: {e}")))?
+            .extra_headers()
+            .pgp_signature()
+            .map(|sig| sig.to_vec());
         Ok(CommitInfo {
             id: commit_id.to_string(),
             author_name: author.name.to_string(),
@@ -378,93 +783,445 @@ impl GitRepo {
             message: message.title.to_string(),
             timestamp,
             parent_ids: commit.parent_ids().map(|id| id.to_string()).collect(),
+            signature: Some(SignatureStatus::from_header(signature.as_deref())),
         })
     }
 
-    /// List commit IDs within range, honoring include_merges, without computing diffs.
-    pub fn list_commit_ids(
-        &self,
-        range: &DateRange,
-        include_merges: bool,
-    ) -> Result<Vec<ObjectId>> {
-        let mut head = self.repo.head()?;
-        let head_commit = head.peel_to_commit_in_place()?;
+    /// List commit IDs within range, honoring the merge policy, without computing diffs.
+    pub fn list_commit_ids(&self, range: &DateRange, merges: MergePolicy) -> Result<Vec<ObjectId>> {
+        if let Some(commits) = &range.commits {
+            return self.filter_commit_ids(commits, range, merges);
+        }
 
         let mut seen: HashSet<ObjectId> = HashSet::new();
-        let mut stack: VecDeque<ObjectId> = VecDeque::from([head_commit.id]);
+        // Each id carries its generation, the tip being 1. A depth limit walks
+        // breadth-first so every commit is first reached at its shallowest.
+        let mut stack: VecDeque<(ObjectId, u32)> = VecDeque::from([(self.start_commit()?, 1)]);
         let mut result: Vec<ObjectId> = Vec::new();
 
-        while let Some(commit_id) = stack.pop_back() {
+        loop {
+            let next = match range.max_depth {
+                Some(_) => stack.pop_front(),
+                None => stack.pop_back(),
+            };
+            let Some((commit_id, depth)) = next else {
+                break;
+            };
             if !seen.insert(commit_id) {
                 continue;
             }
 
-            let commit = self.repo.find_commit(commit_id)?;
-            let secs = commit.time()?.seconds;
-            let timestamp = Utc
-                .timestamp_opt(secs, 0)
-                .single()
-                .ok_or_else(|| GmapError::InvalidDate(format!("Invalid timestamp: {secs}")))?;
+            let commit = self
+                .repo
+                .find_commit(commit_id)
+                .with_context(|| format!("while reading commit {commit_id}"))?;
+            let timestamp = self.commit_timestamp(&commit)?;
 
             let parents: Vec<ObjectId> = commit.parent_ids().map(|id| id.into()).collect();
+            if range.max_depth.is_none_or(|max| depth < max) {
+                stack.extend(parents.iter().map(|&pid| (pid, depth + 1)));
+            }
 
             if !range.contains(&timestamp) {
-                for pid in &parents {
-                    stack.push_back(*pid);
-                }
                 continue;
             }
-
-            if !include_merges && parents.len() > 1 {
-                for pid in &parents {
-                    stack.push_back(*pid);
-                }
+            if !merges.keeps(parents.len()) || self.ignored.contains(&commit_id) {
                 continue;
             }
-
             result.push(commit_id);
-            for pid in &parents {
-                stack.push_back(*pid);
+        }
+
+        Ok(result)
+    }
+
+    /// Apply the date bounds and merge policy to a revision-range selection.
+    fn filter_commit_ids(
+        &self,
+        commits: &HashSet<String>,
+        range: &DateRange,
+        merges: MergePolicy,
+    ) -> Result<Vec<ObjectId>> {
+        let mut result = Vec::with_capacity(commits.len());
+        for id in commits {
+            let oid = ObjectId::from_hex(id.as_bytes())
+                .map_err(|e| GmapError::Parse(format!("Invalid commit ID: {e}")))?;
+            let commit = self.repo.find_commit(oid)?;
+            if !range.contains(&self.commit_timestamp(&commit)?) {
+                continue;
+            }
+            if !merges.keeps(commit.parent_ids().count()) || self.ignored.contains(&oid) {
+                continue;
             }
+            result.push(oid);
         }
+        Ok(result)
+    }
+
+    /// Tags pointing (directly or through an annotated tag) at a commit, with
+    /// that commit's timestamp; `pattern` is a glob on the short tag name.
+    pub fn tags(&self, pattern: Option<&str>) -> Result<Vec<ReleaseTag>> {
+        Ok(self
+            .tag_commits(pattern)?
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect())
+    }
+
+    /// The newest tag (by its commit's date) reachable from the analyzed tip, if any
+    /// matches `pattern`.
+    pub fn last_tag(&self, pattern: Option<&str>) -> Result<Option<ReleaseTag>> {
+        let reachable = self.ancestors(self.start_commit()?)?;
+        Ok(self
+            .tag_commits(pattern)?
+            .into_iter()
+            .filter(|(_, id)| reachable.contains(id))
+            .map(|(tag, _)| tag)
+            .max_by(|a, b| {
+                a.timestamp
+                    .cmp(&b.timestamp)
+                    .then_with(|| a.name.cmp(&b.name))
+            }))
+    }
 
+    fn tag_commits(&self, pattern: Option<&str>) -> Result<Vec<(ReleaseTag, ObjectId)>> {
+        let refs = self
+            .repo
+            .references()
+            .map_err(|e| GmapError::GitRepo(format!("Failed to read references: {e}")))?;
+        let tags = refs
+            .tags()
+            .map_err(|e| GmapError::GitRepo(format!("Failed to read tags: {e}")))?;
+        let mut result = Vec::new();
+        for reference in tags {
+            let mut reference =
+                reference.map_err(|e| GmapError::GitRepo(format!("Failed to read tag: {e}")))?;
+            let name = reference.name().shorten().to_string();
+            if pattern.is_some_and(|p| !crate::util::glob_match(p, &name)) {
+                continue;
+            }
+            // tags of trees or blobs have no place on a timeline
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+            let tag = ReleaseTag {
+                name,
+                timestamp: self.commit_timestamp(&commit)?,
+            };
+            result.push((tag, commit.id));
+        }
         Ok(result)
     }
 
-    /// Compute commit stats for a single commit by ID, using first parent when present.
+    /// The commit history walks start from: the default branch tip when
+    /// selected, otherwise HEAD.
+    fn start_commit(&self) -> Result<ObjectId> {
+        match self.tip {
+            Some(tip) => Ok(tip),
+            None => self.head_commit_id(),
+        }
+    }
+
+    /// The commit HEAD points at.
+    pub fn head_commit_id(&self) -> Result<ObjectId> {
+        let mut head = self
+            .repo
+            .head()
+            .with_context(|| "while reading HEAD".to_string())?;
+        let commit = head
+            .peel_to_commit_in_place()
+            .with_context(|| "while resolving HEAD to a commit".to_string())?;
+        Ok(commit.id)
+    }
+
+    /// Every blob in the tree of `commit_id` with its size and line count.
+    /// Submodules are skipped; symlinks count as their target path.
+    pub fn tree_files(&self, commit_id: ObjectId) -> Result<Vec<TreeFile>> {
+        let tree = self
+            .repo
+            .find_commit(commit_id)
+            .with_context(|| format!("while reading commit {commit_id}"))?
+            .tree()
+            .with_context(|| format!("while reading tree for commit {commit_id}"))?;
+        let entries = tree.traverse().breadthfirst.files().map_err(|e| {
+            GmapError::GitRepo(format!("Failed to walk tree of commit {commit_id}: {e}"))
+        })?;
+        let mut files = Vec::new();
+        for entry in entries {
+            if !entry.mode.is_blob_or_symlink() {
+                continue;
+            }
+            let (is_binary, lines, obj) = self.inspect_object(entry.oid, commit_id)?;
+            files.push(TreeFile {
+                path: entry.filepath.to_string(),
+                lines: lines as u64,
+                bytes: obj.data.len() as u64,
+                is_binary,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Contents of the blob at `path` in the tree of commit `commit_id`.
+    pub fn file_at(&self, commit_id: &str, path: &str) -> Result<Vec<u8>> {
+        let oid = ObjectId::from_hex(commit_id.as_bytes())
+            .map_err(|e| GmapError::Parse(format!("Invalid commit ID: {e}")))?;
+        let entry = self
+            .repo
+            .find_commit(oid)
+            .with_context(|| format!("while reading commit {oid}"))?
+            .tree()
+            .with_context(|| format!("while reading tree for commit {oid}"))?
+            .lookup_entry_by_path(path)
+            .with_context(|| format!("while looking up {path} in commit {oid}"))?
+            .filter(|entry| entry.mode().is_blob_or_symlink())
+            .ok_or_else(|| GmapError::GitRepo(format!("No file {path} in commit {oid}")))?;
+        Ok(entry
+            .object()
+            .with_context(|| format!("while reading {path} in commit {oid}"))?
+            .detach()
+            .data)
+    }
+
+    /// Compute commit stats for a single commit by ID, diffing merges per the merge-diff mode.
     pub fn compute_commit_stats_for(
         &self,
         commit_id: ObjectId,
         binary: bool,
     ) -> Result<CommitStats> {
-        let commit = self.repo.find_commit(commit_id)?;
-        let parent_id: Option<ObjectId> = commit.parent_ids().next().map(|id| id.into());
-
-        let commit_tree = commit.tree()?;
-        let parent_tree = if let Some(pid) = parent_id {
-            Some(self.repo.find_commit(pid)?.tree()?)
-        } else {
-            None
+        let commit = self
+            .repo
+            .find_commit(commit_id)
+            .with_context(|| format!("while reading commit {commit_id}"))?;
+        let parents: Vec<ObjectId> = commit.parent_ids().map(|id| id.into()).collect();
+        let mut stats = CommitStats {
+            commit_id: commit.id.to_string(),
+            files: self.commit_files(commit_id, &parents, binary)?,
+            binary_paths: Vec::new(),
         };
+        // Merges combine several diffs, each of which may be under the limit
+        // or already summarized.
+        if self.over_file_limit(stats.files.len()) || stats.files.iter().any(FileStats::is_summary)
+        {
+            stats.summarize();
+        }
+        Ok(stats)
+    }
+}
 
-        let changes: Vec<ChangeDetached> =
-            self.repo
-                .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), None)?;
-        let mut files = Vec::new();
-        for change in changes {
-            self.handle_change(change, binary, &mut files)?;
+/// Whether `data` looks binary from its first `window` bytes: a NUL byte, as
+/// git checks, or failing that a large share of control characters other than
+/// the whitespace, form feed and escape text files use.
+fn looks_binary(data: &[u8], window: usize) -> bool {
+    let head = &data[..data.len().min(window)];
+    if head.contains(&0) {
+        return true;
+    }
+    let control = head
+        .iter()
+        .filter(|&&b| (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b)) || b == 0x7f)
+        .count();
+    !head.is_empty() && control as f64 > head.len() as f64 * BINARY_CONTROL_RATIO
+}
+
+/// Bytes a blob grew and shrank by going from `old` to `new` bytes long.
+fn byte_delta(old: usize, new: usize) -> (u64, u64) {
+    let (old, new) = (old as u64, new as u64);
+    (new.saturating_sub(old), old.saturating_sub(new))
+}
+
+/// Lines added and deleted turning `old` into `new`.
+fn line_counts(old: &str, new: &str) -> (u32, u32) {
+    let mut added = 0u32;
+    let mut deleted = 0u32;
+
+    let diff = similar::TextDiff::from_lines(old, new);
+    for op in diff.ops() {
+        use similar::DiffTag::*;
+        match op.tag() {
+            Insert => added += op.new_range().len() as u32,
+            Delete => deleted += op.old_range().len() as u32,
+            Replace => {
+                deleted += op.old_range().len() as u32;
+                added += op.new_range().len() as u32;
+            }
+            Equal => {}
         }
+    }
+    (added, deleted)
+}
 
-        Ok(CommitStats {
-            commit_id: commit.id.to_string(),
-            files,
+/// Record the file paths (not directories) touched by `changes`; a rename
+/// touches both its source and destination.
+fn insert_file_paths(changes: Vec<ChangeDetached>, paths: &mut BTreeSet<String>) {
+    for change in changes {
+        match change {
+            ChangeDetached::Addition {
+                location,
+                entry_mode,
+                ..
+            }
+            | ChangeDetached::Deletion {
+                location,
+                entry_mode,
+                ..
+            }
+            | ChangeDetached::Modification {
+                location,
+                entry_mode,
+                ..
+            } => {
+                if !entry_mode.is_tree() {
+                    paths.insert(location.to_string());
+                }
+            }
+            ChangeDetached::Rewrite {
+                source_location,
+                location,
+                entry_mode,
+                ..
+            } if !entry_mode.is_tree() => {
+                paths.insert(source_location.to_string());
+                paths.insert(location.to_string());
+            }
+            ChangeDetached::Rewrite { .. } => {}
+        }
+    }
+}
+
+/// Three-way line merge of `ours` and `theirs` from `base`. Changes from one
+/// side apply cleanly; overlapping or adjacent changes that differ become a
+/// `<<<<<<<`/`=======`/`>>>>>>>` conflict block, as `git merge-file` writes.
+fn merge_lines(base: &str, ours: &str, theirs: &str) -> String {
+    struct Edit<'a> {
+        start: usize,
+        end: usize,
+        lines: &'a [&'a str],
+    }
+    fn edits<'a>(base: &[&str], side: &'a [&'a str]) -> Vec<Edit<'a>> {
+        similar::capture_diff_slices(similar::Algorithm::Myers, base, side)
+            .into_iter()
+            .filter(|op| op.tag() != similar::DiffTag::Equal)
+            .map(|op| Edit {
+                start: op.old_range().start,
+                end: op.old_range().end,
+                lines: &side[op.new_range()],
+            })
+            .collect()
+    }
+    /// `base[start..end]` with `edits` (all inside that range) applied.
+    fn apply(base: &[&str], start: usize, end: usize, edits: &[Edit<'_>]) -> String {
+        let mut out = String::new();
+        let mut pos = start;
+        for e in edits {
+            out.extend(base[pos..e.start].iter().copied());
+            out.extend(e.lines.iter().copied());
+            pos = e.end;
+        }
+        out.extend(base[pos..end].iter().copied());
+        out
+    }
+    fn terminated(mut text: String) -> String {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text
+    }
+
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours_lines: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs_lines: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let ours = edits(&base, &ours_lines);
+    let theirs = edits(&base, &theirs_lines);
+
+    let mut out = String::new();
+    let mut pos = 0;
+    let (mut i, mut j) = (0, 0);
+    while i < ours.len() || j < theirs.len() {
+        // grow a group from the earliest edit until no edit on either side
+        // touches its base range
+        let mut start = usize::MAX;
+        let mut end = 0;
+        let (oi, tj) = (i, j);
+        loop {
+            let touches = |e: &Edit<'_>| start == usize::MAX || e.start <= end;
+            if i < ours.len()
+                && touches(&ours[i])
+                && (j >= theirs.len() || start != usize::MAX || ours[i].start <= theirs[j].start)
+            {
+                start = start.min(ours[i].start);
+                end = end.max(ours[i].end);
+                i += 1;
+            } else if j < theirs.len() && touches(&theirs[j]) {
+                start = start.min(theirs[j].start);
+                end = end.max(theirs[j].end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+        out.extend(base[pos..start].iter().copied());
+        let ours_text = apply(&base, start, end, &ours[oi..i]);
+        let theirs_text = apply(&base, start, end, &theirs[tj..j]);
+        if oi == i || ours_text == theirs_text {
+            out.push_str(&theirs_text);
+        } else if tj == j {
+            out.push_str(&ours_text);
+        } else {
+            out.push_str("<<<<<<< ours\n");
+            out.push_str(&terminated(ours_text));
+            out.push_str("=======\n");
+            out.push_str(&terminated(theirs_text));
+            out.push_str(">>>>>>> theirs\n");
+        }
+        pos = end;
+    }
+    out.extend(base[pos..].iter().copied());
+    out
+}
+
+/// Zero-context hunks between two texts, numbered the way git numbers them.
+fn line_hunks(old: &str, new: &str) -> Vec<Hunk> {
+    let side = |range: std::ops::Range<usize>| {
+        let len = range.len() as u32;
+        let start = range.start as u32 + u32::from(len > 0);
+        (start, len)
+    };
+    similar::TextDiff::from_lines(old, new)
+        .grouped_ops(0)
+        .iter()
+        .filter_map(|group| {
+            let (first, last) = (group.first()?, group.last()?);
+            let (old_start, old_lines) = side(first.old_range().start..last.old_range().end);
+            let (new_start, new_lines) = side(first.new_range().start..last.new_range().end);
+            Some(Hunk {
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+            })
         })
+        .collect()
+}
+
+/// RFC3339, `YYYY-MM-DD` (midnight UTC) or a relative phrase such as
+/// `2 weeks ago`; `None` for anything else.
+fn parse_date(input: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        if let Some(datetime) = date.and_hms_opt(0, 0, 0) {
+            return Some(Utc.from_utc_datetime(&datetime));
+        }
     }
+    parse_natural_duration(input).map(|duration| Utc::now() - duration)
 }
 
+type DurationFn = fn(i64) -> ChronoDuration;
+
 fn parse_natural_duration(input: &str) -> Option<ChronoDuration> {
     let input = input.trim().to_lowercase();
-    let patterns: &[(&str, fn(i64) -> ChronoDuration)] = &[
+    let patterns: &[(&str, DurationFn)] = &[
         (" days ago", ChronoDuration::days),
         (" day ago", ChronoDuration::days),
         (" weeks ago", ChronoDuration::weeks),
@@ -487,3 +1244,86 @@ fn parse_natural_duration(input: &str) -> Option<ChronoDuration> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_lines_applies_one_sided_edits_and_marks_conflicts() {
+        let base = "a\nb\nc\nd\ne\n";
+        assert_eq!(
+            merge_lines(base, "A\nb\nc\nd\ne\n", "a\nb\nc\nd\nE\n"),
+            "A\nb\nc\nd\nE\n"
+        );
+        assert_eq!(
+            merge_lines(base, "a\nb\nX\nd\ne\n", "a\nb\nY\nd\ne\n"),
+            "a\nb\n<<<<<<< ours\nX\n=======\nY\n>>>>>>> theirs\nd\ne\n"
+        );
+        // the same edit on both sides is no conflict
+        assert_eq!(merge_lines(base, "a\nc\n", "a\nc\n"), "a\nc\n");
+    }
+
+    #[test]
+    fn late_nul_is_found_within_a_wider_window() {
+        let mut data = b"text\n".repeat(2000);
+        data.push(0);
+        assert!(!looks_binary(&data, DEFAULT_BINARY_SCAN_BYTES));
+        assert!(looks_binary(&data, 16384));
+        assert!(looks_binary(b"\0", DEFAULT_BINARY_SCAN_BYTES));
+    }
+
+    #[test]
+    fn control_heavy_data_is_binary_but_plain_text_is_not() {
+        let control: Vec<u8> = (1..=8u8).cycle().take(100).collect();
+        assert!(looks_binary(&control, DEFAULT_BINARY_SCAN_BYTES));
+        // Mostly text with the odd control byte, like an ANSI-colored log.
+        let log = b"\x1b[31merror\x1b[0m:\tfailed\r\n\x0cnext page\x07\n";
+        assert!(!looks_binary(log, DEFAULT_BINARY_SCAN_BYTES));
+        assert!(!looks_binary(
+            "naïve café\n".as_bytes(),
+            DEFAULT_BINARY_SCAN_BYTES
+        ));
+        assert!(!looks_binary(b"", DEFAULT_BINARY_SCAN_BYTES));
+    }
+
+    #[test]
+    fn file_at_reads_the_blob_of_a_file_deleted_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .env("GIT_AUTHOR_NAME", "Test")
+                .env("GIT_AUTHOR_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_NAME", "Test")
+                .env("GIT_COMMITTER_EMAIL", "test@example.com")
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/old.rs"), "fn old() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "add"]);
+        git(&["rm", "-q", "src/old.rs"]);
+        git(&["commit", "-q", "-m", "remove"]);
+
+        let repo = GitRepo::open(Some(dir.path())).unwrap();
+        let head = repo.head_commit_id().unwrap();
+        let added = repo
+            .repo
+            .find_commit(head)
+            .unwrap()
+            .parent_ids()
+            .next()
+            .unwrap();
+        assert_eq!(
+            repo.file_at(&added.to_string(), "src/old.rs").unwrap(),
+            b"fn old() {}\n"
+        );
+        assert!(repo.file_at(&head.to_string(), "src/old.rs").is_err());
+        assert!(repo.file_at(&added.to_string(), "src").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::cache::Cache;
+use crate::error::{GmapError, Result};
+use crate::filter::CommitFilter;
+use crate::model::{CommitStats, GroupBy, GroupEntry, GroupOutput, SCHEMA_VERSION};
+use crate::output::OutputContext;
+use crate::util::{path_excluded, to_json, GitIgnoreMatcher};
+use chrono::Utc;
+use console::style;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+/// Fold commits into per-group totals. Only files under `path_prefix` and not
+/// excluded or gitignored count; commits with no such files are skipped.
+pub fn compute_groups(
+    stats: &[CommitStats],
+    cache: &Cache,
+    path_prefix: Option<&str>,
+    filter: &CommitFilter,
+    excludes: &[String],
+    git_ignore: Option<&GitIgnoreMatcher>,
+    group_by: GroupBy,
+) -> Result<Vec<GroupEntry>> {
+    let mut groups: HashMap<String, (GroupEntry, HashSet<String>)> = HashMap::new();
+    for cs in stats {
+        let mut info = cache
+            .get_commit_info(&cs.commit_id)?
+            .ok_or_else(|| GmapError::Cache("Commit info not found".to_string()))?;
+        if !filter.matches(&info) {
+            continue;
+        }
+        filter.resolve_author(&mut info);
+        let files: Vec<_> = cs
+            .files
+            .iter()
+            .filter(|f| !f.is_summary())
+            .filter(|f| path_prefix.is_none_or(|prefix| f.path.starts_with(prefix)))
+            .filter(|f| !path_excluded(&f.path, excludes, filter.case_sensitive()))
+            .filter(|f| git_ignore.is_none_or(|gi| !gi.is_ignored(&f.path)))
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+
+        // A commit counts once per group it touches, so per-file groupings
+        // may count one commit in several groups.
+        let mut touched = HashSet::new();
+        for f in files {
+            let key = group_by.key(&info, f);
+            let (entry, authors) = groups.entry(key.clone()).or_insert_with(|| {
+                (
+                    GroupEntry {
+                        key: key.clone(),
+                        commits: 0,
+                        authors: 0,
+                        added_lines: 0,
+                        deleted_lines: 0,
+                        total_lines: 0,
+                    },
+                    HashSet::new(),
+                )
+            });
+            if touched.insert(key) {
+                entry.commits += 1;
+            }
+            entry.added_lines += f.added_lines as u64;
+            entry.deleted_lines += f.deleted_lines as u64;
+            entry.total_lines += (f.added_lines + f.deleted_lines) as u64;
+            authors.insert(info.author_email.to_lowercase());
+            entry.authors = authors.len() as u32;
+        }
+    }
+
+    let mut entries: Vec<GroupEntry> = groups.into_values().map(|(e, _)| e).collect();
+    entries.sort_by(|a, b| b.total_lines.cmp(&a.total_lines).then(a.key.cmp(&b.key)));
+    Ok(entries)
+}
+
+pub fn write_groups_json(
+    out: &mut dyn Write,
+    ctx: &OutputContext<'_>,
+    group_by: GroupBy,
+    groups: &[GroupEntry],
+) -> anyhow::Result<()> {
+    let output = GroupOutput {
+        version: SCHEMA_VERSION,
+        generated_at: Utc::now(),
+        repository_path: ctx.repository_path.clone(),
+        since: ctx.since.map(str::to_string),
+        until: ctx.until.map(str::to_string),
+        group_by: group_by.label().to_string(),
+        groups: groups.to_vec(),
+    };
+    writeln!(out, "{}", to_json(&output, ctx.compact)?)?;
+    Ok(())
+}
+
+pub fn write_groups_table(
+    out: &mut dyn Write,
+    group_by: GroupBy,
+    groups: &[GroupEntry],
+) -> anyhow::Result<()> {
+    let mut heading = group_by.label().to_string();
+    heading[..1].make_ascii_uppercase();
+    writeln!(
+        out,
+        "{:<40} {:>8} {:>8} {:>8} {:>8} {:>8}",
+        style(heading).bold(),
+        style("Commits").bold(),
+        style("Authors").bold(),
+        style("Added").bold(),
+        style("Deleted").bold(),
+        style("Total").bold()
+    )?;
+    writeln!(out, "{}", "─".repeat(85))?;
+    for g in groups {
+        writeln!(
+            out,
+            "{:<40} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            g.key, g.commits, g.authors, g.added_lines, g.deleted_lines, g.total_lines
+        )?;
+    }
+    Ok(())
+}
@@ -1,10 +1,16 @@
 use super::FileExtensionStats;
 use crate::cache::Cache;
 use crate::error::{GmapError, Result};
+use crate::filter::CommitFilter;
 use crate::model::CommitStats;
-use crate::model::HeatBucket;
-use crate::tui::WeekStats;
-use crate::util::{files_matching, path_excluded, period_key, GitIgnoreMatcher};
+use crate::model::{GrowthPoint, HeatBucket};
+use crate::tui::{FileActivity, WeekStats, TREEMAP_FILES};
+use crate::util::{
+    cumulative, files_matching, hour_histogram, next_period_key, path_excluded, period_key,
+    GitIgnoreMatcher, PeriodSpec,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -12,52 +18,44 @@ struct WeekAccum {
     commits: usize,
     added: usize,
     deleted: usize,
+    binary_files: usize,
     authors: HashMap<String, usize>,
     file_extensions: HashMap<String, FileExtensionStats>,
     file_changes: HashMap<String, usize>,
+    file_lines: HashMap<String, (usize, DateTime<Utc>)>,
+    timestamps: Vec<DateTime<Utc>>,
 }
 
 pub fn aggregate_weeks(
     stats: &[CommitStats],
     cache: &Cache,
     path_prefix: Option<&str>,
-    author: Option<&str>,
-    author_email: Option<&str>,
-    monthly: bool,
+    filter: &CommitFilter,
+    period: PeriodSpec,
     excludes: &[String],
-    git_ignore: Option<&std::cell::RefCell<GitIgnoreMatcher>>,
+    git_ignore: Option<&GitIgnoreMatcher>,
 ) -> Vec<WeekStats> {
+    let _phase = crate::timing::phase("aggregation");
     let mut week_map: HashMap<String, WeekAccum> = HashMap::new();
 
     for commit_stats in stats {
-        let commit_info = match cache.get_commit_info(&commit_stats.commit_id) {
+        let mut commit_info = match cache.get_commit_info(&commit_stats.commit_id) {
             Ok(Some(info)) => info,
             _ => continue,
         };
 
-        if let Some(a) = author {
-            if !commit_info
-                .author_name
-                .to_lowercase()
-                .contains(&a.to_lowercase())
-            {
-                continue;
-            }
-        }
-        if let Some(ae) = author_email {
-            if !commit_info
-                .author_email
-                .to_lowercase()
-                .contains(&ae.to_lowercase())
-            {
-                continue;
-            }
+        if !filter.matches(&commit_info) {
+            continue;
         }
+        filter.resolve_author(&mut commit_info);
 
-        let week_key = period_key(&commit_info.timestamp, monthly);
+        let week_key = period_key(&commit_info.timestamp, period);
 
         let filtered_files: Vec<&crate::model::FileStats> =
-            files_matching(&commit_stats.files, path_prefix).collect();
+            files_matching(&commit_stats.files, path_prefix)
+                .filter(|f| !path_excluded(&f.path, excludes, filter.case_sensitive()))
+                .filter(|f| git_ignore.is_none_or(|gi| !gi.is_ignored(&f.path)))
+                .collect();
 
         if filtered_files.is_empty() && path_prefix.is_some() {
             continue;
@@ -66,46 +64,37 @@ pub fn aggregate_weeks(
         let mut added = 0;
         let mut deleted = 0;
         for file_stats in &filtered_files {
-            if path_excluded(&file_stats.path, excludes) {
-                continue;
-            }
-            if let Some(gi) = git_ignore {
-                if gi.borrow_mut().is_ignored(&file_stats.path) {
-                    continue;
-                }
-            }
             added += file_stats.added_lines as usize;
             deleted += file_stats.deleted_lines as usize;
         }
 
+        let binary_files = binary_changes(commit_stats, path_prefix, filter, excludes, git_ignore);
+
         let entry = week_map
             .entry(week_key.clone())
             .or_insert_with(|| WeekAccum {
                 commits: 0,
                 added: 0,
                 deleted: 0,
+                binary_files: 0,
                 authors: HashMap::new(),
                 file_extensions: HashMap::new(),
                 file_changes: HashMap::new(),
+                file_lines: HashMap::new(),
+                timestamps: Vec::new(),
             });
 
         entry.commits += 1;
         entry.added += added;
         entry.deleted += deleted;
+        entry.binary_files += binary_files;
+        entry.timestamps.push(commit_info.timestamp);
         *entry
             .authors
             .entry(commit_info.author_name.clone())
             .or_insert(0) += 1;
 
-        for file_stats in &filtered_files {
-            if path_excluded(&file_stats.path, excludes) {
-                continue;
-            }
-            if let Some(gi) = git_ignore {
-                if gi.borrow_mut().is_ignored(&file_stats.path) {
-                    continue;
-                }
-            }
+        for file_stats in filtered_files.iter().filter(|f| !f.is_summary()) {
             let extension = Path::new(&file_stats.path)
                 .extension()
                 .and_then(|s| s.to_str())
@@ -130,6 +119,12 @@ pub fn aggregate_weeks(
                 .file_changes
                 .entry(file_stats.path.clone())
                 .or_insert(0) += 1;
+            let lines = entry
+                .file_lines
+                .entry(file_stats.path.clone())
+                .or_insert((0, commit_info.timestamp));
+            lines.0 += file_stats.added_lines as usize + file_stats.deleted_lines as usize;
+            lines.1 = lines.1.max(commit_info.timestamp);
         }
     }
 
@@ -142,31 +137,45 @@ pub fn aggregate_weeks(
                     commits,
                     added,
                     deleted,
+                    binary_files,
                     authors,
                     file_extensions,
                     file_changes,
+                    file_lines,
+                    timestamps,
                 },
             )| {
                 let mut top_authors: Vec<_> = authors.into_iter().collect();
-                top_authors.sort_by(|a, b| b.1.cmp(&a.1));
-                let top_authors = top_authors
-                    .into_iter()
-                    .map(|(name, _)| name)
-                    .take(3)
-                    .collect();
+                top_authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                top_authors.truncate(3);
 
                 let mut top_files: Vec<_> = file_changes.into_iter().collect();
-                top_files.sort_by(|a, b| b.1.cmp(&a.1));
+                top_files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
                 let top_files = top_files.into_iter().take(10).collect();
 
+                let mut file_activity: Vec<_> = file_lines
+                    .into_iter()
+                    .map(|(path, (lines, last_touched))| FileActivity {
+                        path,
+                        lines,
+                        last_touched,
+                    })
+                    .collect();
+                file_activity
+                    .sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.path.cmp(&b.path)));
+                file_activity.truncate(TREEMAP_FILES);
+
                 WeekStats {
                     week,
                     commits,
                     lines_added: added,
                     lines_deleted: deleted,
+                    binary_files_changed: binary_files,
                     top_authors,
                     file_extensions,
                     top_files,
+                    file_activity,
+                    commits_by_hour: hour_histogram(&timestamps),
                 }
             },
         )
@@ -176,76 +185,372 @@ pub fn aggregate_weeks(
     weeks
 }
 
+/// [`aggregate_weeks`], reusing the result stored in `cache` when the same
+/// parameters were last aggregated over exactly the same commit stats.
+/// Anything else recomputes and replaces the stored result. The key covers
+/// every ignore file that applies to the commits' paths, so editing any of
+/// them, nested ones included, misses.
+pub fn aggregate_weeks_cached(
+    stats: &[CommitStats],
+    cache: &Cache,
+    path_prefix: Option<&str>,
+    filter: &CommitFilter,
+    period: PeriodSpec,
+    excludes: &[String],
+    git_ignore: Option<&GitIgnoreMatcher>,
+) -> Vec<WeekStats> {
+    let ignore_rules = git_ignore.map(|gi| {
+        gi.rules_for(stats.iter().flat_map(|cs| {
+            let files = cs.files.iter().map(|f| f.path.as_str());
+            files.chain(cs.binary_paths.iter().map(String::as_str))
+        }))
+    });
+    let key = hex_hash(&(
+        env!("CARGO_PKG_VERSION"),
+        path_prefix,
+        filter,
+        period,
+        excludes,
+        ignore_rules,
+    ));
+    let fingerprint = format!("{}:{}", stats.len(), stats_hash(stats));
+
+    let cached = cache
+        .get_week_aggregate(&key, &fingerprint)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok());
+    crate::timing::log(crate::timing::COUNTS, || {
+        format!(
+            "weekly aggregate: {}",
+            if cached.is_some() {
+                "cache hit"
+            } else {
+                "cache miss"
+            }
+        )
+    });
+    if let Some(weeks) = cached {
+        return weeks;
+    }
+
+    let weeks = aggregate_weeks(
+        stats,
+        cache,
+        path_prefix,
+        filter,
+        period,
+        excludes,
+        git_ignore,
+    );
+    // A failed store only costs the next run a recomputation.
+    if let Ok(json) = serde_json::to_string(&weeks) {
+        let _ = cache.store_week_aggregate(&key, &fingerprint, &json);
+    }
+    weeks
+}
+
+/// 64-bit FNV-1a over the JSON encoding of `value`. Unlike `DefaultHasher`,
+/// its output never changes between Rust releases, so stored keys survive a
+/// toolchain upgrade.
+fn hex_hash(value: &impl Serialize) -> String {
+    struct Fnv1a(u64);
+    impl std::io::Write for Fnv1a {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            for &byte in buf {
+                self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+            }
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    // Writing to the hasher cannot fail, and neither can encoding these values.
+    let _ = serde_json::to_writer(&mut hasher, value);
+    format!("{:016x}", hasher.0)
+}
+
+/// Identifies the commits and per-file counts an aggregate was built from,
+/// whatever order they arrived in: freshly analyzed commits come in walk
+/// order, cached ones by timestamp.
+fn stats_hash(stats: &[CommitStats]) -> String {
+    let mut commits: Vec<_> = stats
+        .iter()
+        .map(|cs| {
+            let files: Vec<_> = cs
+                .files
+                .iter()
+                .map(|f| (&f.path, f.added_lines, f.deleted_lines, f.is_binary))
+                .collect();
+            (&cs.commit_id, files, &cs.binary_paths)
+        })
+        .collect();
+    commits.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    hex_hash(&commits)
+}
+
 pub fn compute_heat(
     stats: &[CommitStats],
     cache: &Cache,
     path_prefix: Option<&str>,
-    author: Option<&str>,
-    author_email: Option<&str>,
-    monthly: bool,
+    filter: &CommitFilter,
+    period: PeriodSpec,
     excludes: &[String],
-    git_ignore: Option<&std::cell::RefCell<GitIgnoreMatcher>>,
+    git_ignore: Option<&GitIgnoreMatcher>,
 ) -> Result<Vec<HeatBucket>> {
-    let mut week_map: HashMap<String, (u32, u64)> = HashMap::new();
+    let _phase = crate::timing::phase("aggregation");
+    let mut week_map: HashMap<String, HeatBucket> = HashMap::new();
 
     for commit_stats in stats {
         let commit_info = cache
             .get_commit_info(&commit_stats.commit_id)?
             .ok_or_else(|| GmapError::Cache("Commit info not found".to_string()))?;
 
-        if let Some(a) = author {
-            if !commit_info
-                .author_name
-                .to_lowercase()
-                .contains(&a.to_lowercase())
-            {
-                continue;
-            }
-        }
-        if let Some(ae) = author_email {
-            if !commit_info
-                .author_email
-                .to_lowercase()
-                .contains(&ae.to_lowercase())
-            {
-                continue;
-            }
+        if !filter.matches(&commit_info) {
+            continue;
         }
 
-        let week_key = period_key(&commit_info.timestamp, monthly);
+        let week_key = period_key(&commit_info.timestamp, period);
 
-        let mut lines_changed = 0u64;
+        let mut added = 0u64;
+        let mut deleted = 0u64;
+        let mut bytes_added = 0u64;
+        let mut bytes_deleted = 0u64;
         let mut has_matching_files = false;
 
         for file_stats in files_matching(&commit_stats.files, path_prefix) {
-            if path_excluded(&file_stats.path, excludes) {
+            if path_excluded(&file_stats.path, excludes, filter.case_sensitive()) {
                 continue;
             }
             if let Some(gi) = git_ignore {
-                if gi.borrow_mut().is_ignored(&file_stats.path) {
+                if gi.is_ignored(&file_stats.path) {
                     continue;
                 }
             }
             has_matching_files = true;
-            lines_changed += (file_stats.added_lines + file_stats.deleted_lines) as u64;
+            added += file_stats.added_lines as u64;
+            deleted += file_stats.deleted_lines as u64;
+            bytes_added += file_stats.bytes_added;
+            bytes_deleted += file_stats.bytes_deleted;
         }
 
         if has_matching_files || path_prefix.is_none() {
-            let entry = week_map.entry(week_key).or_insert((0, 0));
-            entry.0 += 1;
-            entry.1 += lines_changed;
+            let binary_files =
+                binary_changes(commit_stats, path_prefix, filter, excludes, git_ignore);
+            let bucket = week_map
+                .entry(week_key)
+                .or_insert_with_key(|week| HeatBucket {
+                    week: week.clone(),
+                    ..Default::default()
+                });
+            bucket.commit_count += 1;
+            bucket.lines_added += added;
+            bucket.lines_deleted += deleted;
+            bucket.lines_changed += added + deleted;
+            bucket.binary_files_changed += binary_files as u64;
+            bucket.bytes_added += bytes_added;
+            bucket.bytes_deleted += bytes_deleted;
         }
     }
 
-    let mut buckets: Vec<_> = week_map
-        .into_iter()
-        .map(|(week, (commit_count, lines_changed))| HeatBucket {
-            week,
-            commit_count,
-            lines_changed,
-        })
-        .collect();
-
+    let mut buckets: Vec<_> = week_map.into_values().collect();
     buckets.sort_by(|a, b| a.week.cmp(&b.week));
     Ok(buckets)
 }
+
+/// Binary files `commit_stats` changed that pass the path prefix, exclusions
+/// and ignore rules, whether kept in its files or set aside.
+fn binary_changes(
+    commit_stats: &CommitStats,
+    path_prefix: Option<&str>,
+    filter: &CommitFilter,
+    excludes: &[String],
+    git_ignore: Option<&GitIgnoreMatcher>,
+) -> usize {
+    commit_stats
+        .binary_files()
+        .filter(|path| path_prefix.is_none_or(|prefix| path.starts_with(prefix)))
+        .filter(|path| !path_excluded(path, excludes, filter.case_sensitive()))
+        .filter(|path| git_ignore.is_none_or(|gi| !gi.is_ignored(path)))
+        .count()
+}
+
+/// Insert zero buckets for periods without commits between the first and
+/// last bucket, so the series has one entry per period.
+pub fn fill_period_gaps(buckets: &mut Vec<HeatBucket>) {
+    let (Some(first), Some(last)) = (buckets.first(), buckets.last()) else {
+        return;
+    };
+    let (mut key, last) = (first.week.clone(), last.week.clone());
+    let mut by_key: HashMap<String, HeatBucket> =
+        buckets.drain(..).map(|b| (b.week.clone(), b)).collect();
+    loop {
+        let bucket = by_key.remove(&key).unwrap_or_else(|| HeatBucket {
+            week: key.clone(),
+            ..Default::default()
+        });
+        buckets.push(bucket);
+        match next_period_key(&key) {
+            Some(next) if key < last => key = next,
+            _ => break,
+        }
+    }
+    // Keys the walk could not step through stay, in order, at the end.
+    let mut rest: Vec<_> = by_key.into_values().collect();
+    rest.sort_by(|a, b| a.week.cmp(&b.week));
+    buckets.extend(rest);
+}
+
+/// Net lines (added − deleted) per bucket with a running total, in bucket order.
+pub fn growth_series(buckets: &[HeatBucket]) -> Vec<GrowthPoint> {
+    let totals = cumulative(
+        buckets
+            .iter()
+            .map(|b| b.lines_added as i64 - b.lines_deleted as i64),
+    );
+    buckets
+        .iter()
+        .zip(totals)
+        .map(|(b, cumulative_lines)| GrowthPoint {
+            week: b.week.clone(),
+            net_lines: b.lines_added as i64 - b.lines_deleted as i64,
+            cumulative_lines,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CommitInfo, DateKind, FileStats};
+    use chrono::TimeZone;
+
+    #[test]
+    fn hex_hash_is_fixed_fnv1a_over_json() {
+        // Pinned so a stored key never changes meaning between builds.
+        assert_eq!(hex_hash(&("gmap", 1)), "88c1688fc4712d4d");
+        assert_ne!(hex_hash(&("gmap", 1)), hex_hash(&("gmap", 2)));
+    }
+
+    fn bucket(week: &str, added: u64, deleted: u64) -> HeatBucket {
+        HeatBucket {
+            week: week.to_string(),
+            commit_count: 1,
+            lines_changed: added + deleted,
+            lines_added: added,
+            lines_deleted: deleted,
+            binary_files_changed: 0,
+            bytes_added: 0,
+            bytes_deleted: 0,
+            tags: Vec::new(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn gaps_between_periods_are_filled_with_zero_buckets() {
+        let mut buckets = vec![
+            bucket("2024-W51", 1, 0),
+            bucket("2024-W52", 2, 0),
+            bucket("2025-W02", 3, 0),
+        ];
+        fill_period_gaps(&mut buckets);
+
+        let weeks: Vec<_> = buckets
+            .iter()
+            .map(|b| (b.week.as_str(), b.commit_count))
+            .collect();
+        assert_eq!(
+            weeks,
+            [
+                ("2024-W51", 1),
+                ("2024-W52", 1),
+                ("2025-W01", 0),
+                ("2025-W02", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn growth_series_accumulates_net_lines() {
+        let buckets = vec![
+            bucket("2024-W01", 100, 0),
+            bucket("2024-W02", 20, 50),
+            bucket("2024-W03", 5, 5),
+            bucket("2024-W04", 40, 10),
+        ];
+
+        let growth = growth_series(&buckets);
+
+        let net: Vec<i64> = growth.iter().map(|g| g.net_lines).collect();
+        let total: Vec<i64> = growth.iter().map(|g| g.cumulative_lines).collect();
+        assert_eq!(net, vec![100, -30, 0, 30]);
+        assert_eq!(total, vec![100, 70, 70, 100]);
+        assert_eq!(growth[1].week, "2024-W02");
+    }
+
+    #[test]
+    fn tied_authors_and_files_rank_alphabetically() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(Some(dir.path()), dir.path(), DateKind::Committer).unwrap();
+        let timestamp = Utc.with_ymd_and_hms(2024, 1, 3, 12, 0, 0).unwrap();
+        let mut stats = Vec::new();
+        let mut infos = HashMap::new();
+        for (id, author, path) in [
+            ("cccc", "Carol", "c.rs"),
+            ("aaaa", "Alice", "a.rs"),
+            ("bbbb", "Bob", "b.rs"),
+        ] {
+            stats.push(CommitStats {
+                commit_id: id.to_string(),
+                files: vec![FileStats {
+                    path: path.to_string(),
+                    added_lines: 1,
+                    deleted_lines: 0,
+                    is_binary: false,
+                    size: None,
+                    bytes_added: 0,
+                    bytes_deleted: 0,
+                    hunks: Vec::new(),
+                    summarized_files: None,
+                }],
+                binary_paths: Vec::new(),
+            });
+            infos.insert(
+                id.to_string(),
+                CommitInfo {
+                    id: id.to_string(),
+                    author_name: author.to_string(),
+                    author_email: format!("{}@example.com", author.to_lowercase()),
+                    message: "change".to_string(),
+                    timestamp,
+                    parent_ids: Vec::new(),
+                    signature: None,
+                },
+            );
+        }
+        cache.store_commit_stats(&stats, &infos).unwrap();
+
+        let weeks = aggregate_weeks(
+            &stats,
+            &cache,
+            None,
+            &CommitFilter::default(),
+            PeriodSpec::default(),
+            &[],
+            None,
+        );
+
+        assert_eq!(weeks.len(), 1);
+        let authors: Vec<_> = weeks[0]
+            .top_authors
+            .iter()
+            .map(|(a, _)| a.as_str())
+            .collect();
+        assert_eq!(authors, ["Alice", "Bob", "Carol"]);
+        let files: Vec<_> = weeks[0].top_files.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(files, ["a.rs", "b.rs", "c.rs"]);
+    }
+}
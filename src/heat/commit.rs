@@ -1,19 +1,34 @@
 use crate::cache::Cache;
-use crate::model::CommitStats;
+use crate::filter::CommitFilter;
+use crate::model::{CommitInfo, CommitStats};
 use crate::tui::{CommitDetail, TuiState, WeekStats};
-use crate::util::{files_matching, period_key};
+use crate::util::{contains_text, files_matching, period_key, unique_abbrev_len, PeriodSpec};
 use std::io;
 
+/// Commits the TUI lists: those in `stats` that `filter` keeps, grouped into
+/// periods by `period_spec`, restricted to files under `path_prefix`.
+#[derive(Clone, Copy)]
+pub struct CommitScope<'a> {
+    pub stats: &'a [CommitStats],
+    pub cache: &'a Cache,
+    pub path_prefix: Option<&'a str>,
+    pub filter: &'a CommitFilter,
+    pub period_spec: PeriodSpec,
+}
+
 pub fn get_commits_for_period(
-    stats: &[CommitStats],
-    cache: &Cache,
+    scope: &CommitScope,
     period: &str,
-    path_prefix: Option<&str>,
-    author: Option<&str>,
-    author_email: Option<&str>,
-    monthly: bool,
 ) -> crate::error::Result<Vec<CommitDetail>> {
+    let CommitScope {
+        stats,
+        cache,
+        path_prefix,
+        filter,
+        period_spec,
+    } = *scope;
     let mut commits = Vec::new();
+    let abbrev = unique_abbrev_len(stats.iter().map(|cs| cs.commit_id.as_str()));
 
     for commit_stats in stats {
         let commit_info = match cache.get_commit_info(&commit_stats.commit_id) {
@@ -21,26 +36,11 @@ pub fn get_commits_for_period(
             _ => continue,
         };
 
-        if let Some(a) = author {
-            if !commit_info
-                .author_name
-                .to_lowercase()
-                .contains(&a.to_lowercase())
-            {
-                continue;
-            }
-        }
-        if let Some(ae) = author_email {
-            if !commit_info
-                .author_email
-                .to_lowercase()
-                .contains(&ae.to_lowercase())
-            {
-                continue;
-            }
+        if !filter.matches(&commit_info) {
+            continue;
         }
 
-        let commit_period = period_key(&commit_info.timestamp, monthly);
+        let commit_period = period_key(&commit_info.timestamp, period_spec);
         if commit_period != period {
             continue;
         }
@@ -52,7 +52,9 @@ pub fn get_commits_for_period(
 
         for file_stats in files_matching(&commit_stats.files, path_prefix) {
             has_matching_files = true;
-            files_changed.push(file_stats.path.clone());
+            if !file_stats.is_summary() {
+                files_changed.push(file_stats.path.clone());
+            }
             lines_added += file_stats.added_lines;
             lines_deleted += file_stats.deleted_lines;
         }
@@ -60,7 +62,7 @@ pub fn get_commits_for_period(
         if has_matching_files || path_prefix.is_none() {
             commits.push(CommitDetail {
                 hash: commit_info.id.clone(),
-                short_hash: commit_info.id.chars().take(8).collect(),
+                short_hash: commit_info.id.chars().take(abbrev).collect(),
                 message: commit_info.message.lines().next().unwrap_or("").to_string(),
                 author_name: commit_info.author_name.clone(),
                 author_email: commit_info.author_email.clone(),
@@ -68,23 +70,73 @@ pub fn get_commits_for_period(
                 files_changed,
                 lines_added,
                 lines_deleted,
+                signature: commit_info.signature,
             });
         }
     }
 
-    commits.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    commits.sort_by(|a, b| {
+        b.timestamp
+            .cmp(&a.timestamp)
+            .then_with(|| a.hash.cmp(&b.hash))
+    });
     Ok(commits)
 }
 
+/// Find the newest commit in any listed period whose message, author or hash
+/// contains `query` (ignoring case unless `case_sensitive`), returning the index of its period in
+/// `weeks` and its full hash. Commits outside the listed periods, or outside
+/// `scope`, are skipped, as in [`get_commits_for_period`].
+pub fn find_commit_global(
+    scope: &CommitScope,
+    weeks: &[WeekStats],
+    query: &str,
+    case_sensitive: bool,
+) -> Option<(usize, String)> {
+    let CommitScope {
+        stats,
+        cache,
+        path_prefix,
+        filter,
+        period_spec,
+    } = *scope;
+    let mut best: Option<(usize, CommitInfo)> = None;
+
+    for commit_stats in stats {
+        let Ok(Some(info)) = cache.get_commit_info(&commit_stats.commit_id) else {
+            continue;
+        };
+        if !filter.matches(&info) {
+            continue;
+        }
+        if ![&info.message, &info.author_name, &info.id]
+            .iter()
+            .any(|field| contains_text(field, query, case_sensitive))
+        {
+            continue;
+        }
+        if path_prefix.is_some() && files_matching(&commit_stats.files, path_prefix).count() == 0 {
+            continue;
+        }
+        let period = period_key(&info.timestamp, period_spec);
+        let Some(index) = weeks.iter().position(|w| w.week == period) else {
+            continue;
+        };
+        if best
+            .as_ref()
+            .is_none_or(|(_, seen)| info.timestamp > seen.timestamp)
+        {
+            best = Some((index, info));
+        }
+    }
+
+    best.map(|(index, info)| (index, info.id))
+}
+
 pub fn load_commit_details(
     state: &mut TuiState,
     weeks: &[WeekStats],
-    stats: &[CommitStats],
-    cache: &Cache,
-    path_prefix: Option<&str>,
-    author: Option<&str>,
-    author_email: Option<&str>,
-    monthly: bool,
+    scope: &CommitScope,
 ) -> io::Result<()> {
     if state.selected >= weeks.len() {
         return Ok(());
@@ -93,15 +145,7 @@ pub fn load_commit_details(
     state.loading_commits = true;
     let selected_week = &weeks[state.selected];
 
-    match get_commits_for_period(
-        stats,
-        cache,
-        &selected_week.week,
-        path_prefix,
-        author,
-        author_email,
-        monthly,
-    ) {
+    match get_commits_for_period(scope, &selected_week.week) {
         Ok(commits) => {
             state.commit_details = commits;
             state.commit_selected = 0;
@@ -116,3 +160,139 @@ pub fn load_commit_details(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DateKind, FileStats};
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn week(name: &str) -> WeekStats {
+        WeekStats {
+            week: name.to_string(),
+            commits: 1,
+            lines_added: 1,
+            lines_deleted: 0,
+            binary_files_changed: 0,
+            top_authors: Vec::new(),
+            file_extensions: HashMap::new(),
+            top_files: Vec::new(),
+            file_activity: Vec::new(),
+            commits_by_hour: [0; 24],
+        }
+    }
+
+    #[test]
+    fn global_search_resolves_the_matching_commits_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(Some(dir.path()), dir.path(), DateKind::Committer).unwrap();
+        let commits = [
+            (
+                "aaaa1111",
+                "initial import",
+                Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0),
+            ),
+            (
+                "bbbb2222",
+                "fix login redirect",
+                Utc.with_ymd_and_hms(2024, 1, 10, 12, 0, 0),
+            ),
+            (
+                "cccc3333",
+                "docs",
+                Utc.with_ymd_and_hms(2024, 1, 11, 12, 0, 0),
+            ),
+        ];
+        let mut stats = Vec::new();
+        let mut infos = HashMap::new();
+        for (id, message, timestamp) in commits {
+            stats.push(CommitStats {
+                commit_id: id.to_string(),
+                files: vec![FileStats {
+                    path: "src/lib.rs".to_string(),
+                    added_lines: 1,
+                    deleted_lines: 0,
+                    is_binary: false,
+                    size: None,
+                    bytes_added: 0,
+                    bytes_deleted: 0,
+                    hunks: Vec::new(),
+                    summarized_files: None,
+                }],
+                binary_paths: Vec::new(),
+            });
+            let info = CommitInfo {
+                id: id.to_string(),
+                author_name: "Alice".to_string(),
+                author_email: "alice@example.com".to_string(),
+                message: message.to_string(),
+                timestamp: timestamp.unwrap(),
+                parent_ids: Vec::new(),
+                signature: None,
+            };
+            infos.insert(id.to_string(), info);
+        }
+        cache.store_commit_stats(&stats, &infos).unwrap();
+
+        let weeks = vec![week("2024-W01"), week("2024-W02")];
+        let filter = CommitFilter::default();
+        let scope = CommitScope {
+            stats: &stats,
+            cache: &cache,
+            path_prefix: None,
+            filter: &filter,
+            period_spec: PeriodSpec::default(),
+        };
+        let find = |query: &str| find_commit_global(&scope, &weeks, query, false);
+
+        assert_eq!(find("LOGIN"), Some((1, "bbbb2222".to_string())));
+        assert_eq!(find("aaaa"), Some((0, "aaaa1111".to_string())));
+        // the newest match wins
+        assert_eq!(find("alice"), Some((1, "cccc3333".to_string())));
+        assert_eq!(find("nothing"), None);
+    }
+
+    #[test]
+    fn short_hashes_lengthen_to_stay_unique() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(Some(dir.path()), dir.path(), DateKind::Committer).unwrap();
+        let timestamp = Utc.with_ymd_and_hms(2024, 1, 3, 12, 0, 0).unwrap();
+        let mut stats = Vec::new();
+        let mut infos = HashMap::new();
+        for id in ["abcdef1234", "abcdef1999"] {
+            stats.push(CommitStats {
+                commit_id: id.to_string(),
+                files: Vec::new(),
+                binary_paths: Vec::new(),
+            });
+            infos.insert(
+                id.to_string(),
+                CommitInfo {
+                    id: id.to_string(),
+                    author_name: "Alice".to_string(),
+                    author_email: "alice@example.com".to_string(),
+                    message: "change".to_string(),
+                    timestamp,
+                    parent_ids: Vec::new(),
+                    signature: None,
+                },
+            );
+        }
+        cache.store_commit_stats(&stats, &infos).unwrap();
+
+        let filter = CommitFilter::default();
+        let scope = CommitScope {
+            stats: &stats,
+            cache: &cache,
+            path_prefix: None,
+            filter: &filter,
+            period_spec: PeriodSpec::default(),
+        };
+        let commits = get_commits_for_period(&scope, "2024-W01").unwrap();
+
+        let mut short: Vec<_> = commits.iter().map(|c| c.short_hash.as_str()).collect();
+        short.sort_unstable();
+        assert_eq!(short, ["abcdef12", "abcdef19"]);
+    }
+}
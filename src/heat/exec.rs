@@ -1,57 +1,169 @@
-use super::{
-    compute_heat, fetch_commit_stats_with_progress, output_heatmap, output_json, output_ndjson,
-};
-use crate::cache::Cache;
-use crate::cli::CommonArgs;
-use crate::git::GitRepo;
+use super::image::render_png;
+use super::{compute_heat, fetch_commit_stats_reported, fill_period_gaps, growth_series};
+use crate::cli::{CommonArgs, OutputArgs};
+use crate::output::{to_stdout, FormatRegistry, OutputContext};
+use crate::tui::draw::IntensityScale;
+use crate::util::{smoothed_by_period, tag_periods, threshold_exit, week_label, WeekFormat};
 use anyhow::Context;
-use std::cell::RefCell;
-
-pub fn exec(
-    common: CommonArgs,
-    json: bool,
-    ndjson: bool,
-    path: Option<String>,
-    monthly: bool,
-) -> anyhow::Result<()> {
-    let repo = GitRepo::open(common.repo.as_ref()).context("Failed to open git repository")?;
-    let mut cache =
-        Cache::new(common.cache.as_deref(), repo.path()).context("Failed to initialize cache")?;
-
-    let range = repo
-        .resolve_range(common.since.as_deref(), common.until.as_deref())
+use console::style;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Options for non-interactive `gmap heat`, on top of [`CommonArgs`].
+#[derive(Clone, Debug)]
+pub struct HeatOptions {
+    pub output: OutputArgs,
+    pub path: Option<String>,
+    pub monthly: bool,
+    pub growth: bool,
+    /// Exit with a failure code when a period has more commits than this
+    pub max_week_commits: Option<u32>,
+    /// Glob selecting the tags to mark on their periods
+    pub tag_pattern: Option<String>,
+    pub scale: IntensityScale,
+    /// Moving-average window, in periods
+    pub smooth: Option<u32>,
+    pub fill_gaps: bool,
+    pub hide_empty: bool,
+    /// Render a PNG here instead of writing to stdout
+    pub export_image: Option<PathBuf>,
+}
+
+pub fn exec(common: CommonArgs, options: HeatOptions) -> anyhow::Result<ExitCode> {
+    let HeatOptions {
+        output,
+        path,
+        monthly,
+        growth,
+        max_week_commits,
+        tag_pattern,
+        scale,
+        smooth,
+        fill_gaps,
+        hide_empty,
+        export_image,
+    } = options;
+    let registry = FormatRegistry::builtin();
+    let format = registry.get(output.format_name()?)?;
+
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let mut cache = common
+        .open_cache(&repo)
+        .context("Failed to initialize cache")?;
+
+    let range = common
+        .resolve_range(&repo)
         .context("Failed to resolve date range")?;
 
-    // Disable progress indicators in CLI to keep output clean in JSON/NDJSON
-    let all_stats = fetch_commit_stats_with_progress(
+    // The spinner draws on stderr only when it is a terminal, so piped
+    // output stays clean
+    let (all_stats, report) = fetch_commit_stats_reported(
         &repo,
         &mut cache,
         &range,
-        common.include_merges,
-        common.binary,
-        false,
+        common.merge_policy(),
+        common.binary_policy(),
+        common.recompute,
+        true,
     )?;
 
-    let gi = RefCell::new(crate::util::GitIgnoreMatcher::new(repo.path()));
-    let heat_data = compute_heat(
+    let gi = common.git_ignore(&repo);
+    let mut heat_data = compute_heat(
         &all_stats,
         &cache,
         path.as_deref(),
-        common.author.as_deref(),
-        common.author_email.as_deref(),
-        monthly,
+        &common.commit_filter(),
+        common.period_spec(monthly),
         &common.exclude,
-        Some(&gi),
+        gi.as_ref(),
     )
     .context("Failed to compute heat statistics")?;
+    if fill_gaps {
+        fill_period_gaps(&mut heat_data);
+    }
+
+    if let Some(pattern) = tag_pattern.as_deref() {
+        let tags = repo.tags(Some(pattern)).context("Failed to read tags")?;
+        let mut by_period = tag_periods(&tags, common.period_spec(monthly));
+        for bucket in &mut heat_data {
+            bucket.tags = by_period.remove(&bucket.week).unwrap_or_default();
+        }
+    }
+
+    if common.week_format != WeekFormat::Iso || common.sprints().is_some() {
+        for bucket in &mut heat_data {
+            let label = week_label(&bucket.week, common.week_format, common.week_start);
+            bucket.label = (label != bucket.week).then_some(label);
+        }
+    }
+
+    let growth = growth.then(|| growth_series(&heat_data));
+    let growth = growth.as_deref();
+    let smoothed = smooth.map(|window| {
+        let series: Vec<_> = heat_data
+            .iter()
+            .map(|b| (b.week.as_str(), b.commit_count as f64))
+            .collect();
+        smoothed_by_period(&series, window as usize)
+    });
+    let smoothed = smoothed.as_deref();
 
-    if json {
-        output_json(&heat_data, &repo, &common, path.as_deref())?;
-    } else if ndjson {
-        output_ndjson(&heat_data)?;
-    } else {
-        output_heatmap(&heat_data, &common)?;
+    let ctx = OutputContext {
+        repository_path: repo.path().display().to_string(),
+        since: common.since_arg(),
+        until: common.until_arg(),
+        path_prefix: path.as_deref(),
+        compact: output.compact,
+        growth,
+        smoothed,
+        scale,
+        hide_empty,
+        ndjson_header: output.ndjson_header,
+        bytes: common.only_binary,
+        ..Default::default()
+    };
+    match export_image {
+        Some(file) => {
+            let (shown, values): (Vec<_>, Vec<_>) = heat_data
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| !hide_empty || b.commit_count > 0)
+                .map(|(i, b)| {
+                    let value = smoothed.map_or(b.commit_count as f64, |s| s[i]);
+                    (b.clone(), value)
+                })
+                .unzip();
+            // A quarter of weeks or a year of months per row.
+            let per_row = if monthly { 12 } else { 13 };
+            let png =
+                render_png(&shown, &values, scale, per_row).context("Failed to render heatmap")?;
+            std::fs::write(&file, png)
+                .with_context(|| format!("Failed to write {}", file.display()))?;
+            eprintln!(
+                "Wrote {} periods to {}",
+                shown.len(),
+                style(file.display()).cyan()
+            );
+        }
+        None => to_stdout(|out| format.write_heat(out, &ctx, &heat_data))?,
     }
+    report.announce();
 
-    Ok(())
+    let breaches: Vec<String> = max_week_commits
+        .map(|limit| {
+            heat_data
+                .iter()
+                .filter(|b| b.commit_count > limit)
+                .map(|b| {
+                    format!(
+                        "{}: {} commits (--max-week-commits {limit})",
+                        b.week, b.commit_count
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(threshold_exit(&breaches))
 }
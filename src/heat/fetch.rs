@@ -1,61 +1,456 @@
 use crate::cache::Cache;
+use crate::error::GmapError;
 use crate::git::GitRepo;
-use crate::model::{CommitStats, DateRange};
+use crate::model::{BinaryPolicy, CommitStats, DateRange, MergePolicy};
+use crate::timing;
+use crate::util::analyzing_message;
 use anyhow::Context;
+use indicatif::{ProgressBar, ProgressStyle};
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Newly analyzed commits written to the cache per transaction.
+const STORE_BATCH: usize = 500;
+
+/// Minimum time between spinner message updates, to avoid flicker.
+const MESSAGE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set by the Ctrl-C handler; commit analysis stops at the next commit.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Make Ctrl-C stop commit analysis after the current commit, storing what
+/// was computed so far before exiting. A second Ctrl-C exits immediately.
+pub fn install_interrupt_handler() {
+    // fails only if a handler is already installed, which is just as good
+    let _ = ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+    });
+}
+
+/// Whether Ctrl-C has been pressed since the handler was installed.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
 
 pub fn fetch_commit_stats(
     repo: &GitRepo,
     cache: &mut Cache,
     range: &DateRange,
-    include_merges: bool,
-    binary: bool,
+    merges: MergePolicy,
+    binary: BinaryPolicy,
 ) -> anyhow::Result<Vec<CommitStats>> {
-    fetch_commit_stats_with_progress(repo, cache, range, include_merges, binary, true)
+    fetch_commit_stats_with_progress(repo, cache, range, merges, binary, true)
+}
+
+/// How many commits in range came from the cache versus were analyzed fresh.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FetchReport {
+    pub cached: usize,
+    pub computed: usize,
+}
+
+impl FetchReport {
+    /// Print the summary to stderr when someone is likely watching: stderr is
+    /// a terminal, or `-v` was given. Scripts reading stdout never see it.
+    pub fn announce(&self) {
+        if console::Term::stderr().is_term() || timing::enabled(timing::TIMINGS) {
+            eprintln!("{self}");
+        }
+    }
+}
+
+impl std::ops::AddAssign for FetchReport {
+    fn add_assign(&mut self, other: Self) {
+        self.cached += other.cached;
+        self.computed += other.computed;
+    }
+}
+
+impl std::fmt::Display for FetchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} commits from cache, {} newly analyzed",
+            self.cached, self.computed
+        )
+    }
 }
 
 pub fn fetch_commit_stats_with_progress(
     repo: &GitRepo,
     cache: &mut Cache,
     range: &DateRange,
-    include_merges: bool,
-    binary: bool,
-    _progress: bool,
+    merges: MergePolicy,
+    binary: BinaryPolicy,
+    progress: bool,
 ) -> anyhow::Result<Vec<CommitStats>> {
-    let mut cached_stats = cache
-        .get_commit_stats(range)
-        .context("Failed to get cached commit stats")?;
+    fetch_commit_stats_reported(repo, cache, range, merges, binary, false, progress)
+        .map(|(stats, _)| stats)
+}
+
+/// [`fetch_commit_stats_with_progress`], also reporting cache effectiveness.
+/// With `recompute`, cached rows are ignored and every commit in range is
+/// analyzed again and written back over them. With `progress`, a spinner on
+/// stderr names the commit being analyzed; it is only drawn on a terminal.
+pub fn fetch_commit_stats_reported(
+    repo: &GitRepo,
+    cache: &mut Cache,
+    range: &DateRange,
+    merges: MergePolicy,
+    binary: BinaryPolicy,
+    recompute: bool,
+    progress: bool,
+) -> anyhow::Result<(Vec<CommitStats>, FetchReport)> {
+    let settings = FetchSettings {
+        merges,
+        binary,
+        recompute,
+        progress,
+        ..FetchSettings::default()
+    };
+    fetch_until(
+        repo,
+        cache,
+        range,
+        settings,
+        &|_| INTERRUPTED.load(Ordering::SeqCst),
+        &mut |_| {},
+    )
+}
+
+/// Fetch like [`fetch_commit_stats_with_progress`], handing stats to `sink` as
+/// they become available: the cached commits first, then each batch of newly
+/// analyzed ones once it is stored. `stop` can end the fetch early, as with
+/// Ctrl-C, given how many commits have been analyzed so far.
+pub fn fetch_commit_stats_streaming(
+    repo: &GitRepo,
+    cache: &mut Cache,
+    range: &DateRange,
+    merges: MergePolicy,
+    binary: BinaryPolicy,
+    stop: &dyn Fn(usize) -> bool,
+    sink: &mut dyn FnMut(&[CommitStats]),
+) -> anyhow::Result<(Vec<CommitStats>, FetchReport)> {
+    let settings = FetchSettings {
+        merges,
+        binary,
+        ..FetchSettings::default()
+    };
+    fetch_until(repo, cache, range, settings, stop, sink)
+}
+
+/// Which commits [`fetch_until`] covers and how it analyzes them.
+#[derive(Clone, Copy)]
+struct FetchSettings {
+    merges: MergePolicy,
+    binary: BinaryPolicy,
+    /// Reanalyze commits that are already cached
+    recompute: bool,
+    /// Show the analysis spinner
+    progress: bool,
+    /// Newly analyzed commits stored per transaction
+    batch: usize,
+}
+
+impl Default for FetchSettings {
+    fn default() -> Self {
+        Self {
+            merges: MergePolicy::default(),
+            binary: BinaryPolicy::default(),
+            recompute: false,
+            progress: false,
+            batch: STORE_BATCH,
+        }
+    }
+}
 
-    let existing_ids: HashSet<&str> = cached_stats.iter().map(|c| c.commit_id.as_str()).collect();
+/// The fetch itself. Analyzed commits are stored every `batch` commits, so a
+/// crash midway through a long first run keeps the work done so far. `stop`
+/// is asked before each commit is analyzed, given how many have been analyzed
+/// so far; once it says yes, those are stored and [`GmapError::Interrupted`]
+/// is returned. `sink` sees the cached stats, then every stored batch.
+fn fetch_until(
+    repo: &GitRepo,
+    cache: &mut Cache,
+    range: &DateRange,
+    settings: FetchSettings,
+    stop: &dyn Fn(usize) -> bool,
+    sink: &mut dyn FnMut(&[CommitStats]),
+) -> anyhow::Result<(Vec<CommitStats>, FetchReport)> {
+    let FetchSettings {
+        merges,
+        binary,
+        recompute,
+        progress,
+        batch,
+    } = settings;
+    let repo_ids: Vec<gix::ObjectId> = {
+        let _phase = timing::phase("commit listing");
+        repo.list_commit_ids(range, merges)
+            .context("Failed to list commits from repository")?
+    };
+    let walked = repo_ids.len();
 
-    let repo_ids: Vec<gix::ObjectId> = repo
-        .list_commit_ids(range, include_merges)
-        .context("Failed to list commits from repository")?;
+    let mut cached_stats = if recompute {
+        Vec::new()
+    } else {
+        cache
+            .get_commit_stats(range)
+            .context("Failed to get cached commit stats")?
+    };
+    // The cache holds every commit ever analyzed; keep only those selected now
+    // (merge policy, branch, revision range).
+    let listed: HashSet<String> = repo_ids.iter().map(|id| id.to_string()).collect();
+    cached_stats.retain(|c| listed.contains(&c.commit_id));
+    // A commit summarized under a lower --max-files-per-commit, or when none
+    // applies any more, is analyzed again; one cached in full is summarized.
+    cached_stats.retain(|c| c.summarized_files().is_none_or(|n| repo.over_file_limit(n)));
+    for stats in &mut cached_stats {
+        if repo.over_file_limit(stats.files.len()) {
+            stats.summarize();
+        }
+    }
+    // Taken before the binary policy can drop commits, which are still cached.
+    let existing_ids: HashSet<String> = cached_stats.iter().map(|c| c.commit_id.clone()).collect();
+    binary.apply(&mut cached_stats);
+    sink(&cached_stats);
 
+    let mut computed = 0;
+    let mut pending: Vec<CommitStats> = Vec::new();
     let mut missing_stats: Vec<CommitStats> = Vec::new();
-    for oid in repo_ids {
-        let id_str = oid.to_string();
-        if existing_ids.contains(id_str.as_str()) {
-            continue;
-        }
-        let stats = repo
-            .compute_commit_stats_for(oid, binary)
-            .context("Failed to compute commit stats for missing commit")?;
-        missing_stats.push(stats);
-    }
-
-    if !missing_stats.is_empty() {
-        let mut commit_infos = std::collections::HashMap::new();
-        for stats in &missing_stats {
-            if let Ok(info) = repo.get_commit_info(&stats.commit_id) {
-                commit_infos.insert(stats.commit_id.clone(), info);
+    let mut interrupted = false;
+    let spinner = analysis_spinner(progress);
+    let mut last_message: Option<Instant> = None;
+    {
+        let _phase = timing::phase("stat computation");
+        for oid in repo_ids {
+            let id_str = oid.to_string();
+            if existing_ids.contains(&id_str) {
+                continue;
+            }
+            if stop(computed) {
+                interrupted = true;
+                break;
+            }
+            if progress && last_message.is_none_or(|at| at.elapsed() >= MESSAGE_INTERVAL) {
+                if let Ok(info) = repo.get_commit_info(&id_str) {
+                    spinner.set_message(analyzing_message(&id_str, &info.timestamp));
+                }
+                last_message = Some(Instant::now());
+            }
+            spinner.tick();
+            // Binary files are always recorded, so they can be counted
+            // whatever the binary policy.
+            let stats = repo
+                .compute_commit_stats_for(oid, true)
+                .context("Failed to compute commit stats for missing commit")?;
+            pending.push(stats);
+            computed += 1;
+            if pending.len() >= batch {
+                flush(repo, cache, binary, &mut pending, &mut missing_stats, sink)?;
             }
         }
-        cache
-            .store_commit_stats(&missing_stats, &commit_infos)
-            .context("Failed to store commit stats in cache")?;
-        cached_stats.extend(missing_stats);
+    }
+    spinner.finish_and_clear();
+    let report = FetchReport {
+        cached: existing_ids.len(),
+        computed,
+    };
+    timing::record_fetch(report.cached, report.computed);
+    timing::log(timing::COUNTS, || {
+        format!(
+            "commits walked: {walked}, cache hits: {}, cache misses: {}",
+            report.cached, report.computed
+        )
+    });
+    timing::log(timing::COUNTS, || {
+        format!("blobs read: {}", repo.blob_reads())
+    });
+
+    flush(repo, cache, binary, &mut pending, &mut missing_stats, sink)?;
+    if interrupted {
+        return Err(GmapError::Interrupted { saved: computed }.into());
+    }
+    cached_stats.extend(missing_stats);
+    warn_summarized(repo, &cached_stats);
+
+    Ok((cached_stats, report))
+}
+
+/// Store the `pending` batch, then shape it to the binary policy and hand it
+/// to `sink` before adding it to `analyzed`.
+fn flush(
+    repo: &GitRepo,
+    cache: &mut Cache,
+    binary: BinaryPolicy,
+    pending: &mut Vec<CommitStats>,
+    analyzed: &mut Vec<CommitStats>,
+    sink: &mut dyn FnMut(&[CommitStats]),
+) -> anyhow::Result<()> {
+    if pending.is_empty() {
+        return Ok(());
+    }
+    store_batch(repo, cache, pending)?;
+    let mut batch = std::mem::take(pending);
+    binary.apply(&mut batch);
+    sink(&batch);
+    analyzed.append(&mut batch);
+    Ok(())
+}
+
+/// Spinner for commit analysis, hidden unless `progress` is set.
+fn analysis_spinner(progress: bool) -> ProgressBar {
+    if !progress {
+        return ProgressBar::hidden();
+    }
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+    );
+    spinner
+}
+
+/// Name each commit recorded as a single summary row on stderr.
+fn warn_summarized(repo: &GitRepo, stats: &[CommitStats]) {
+    let Some(max) = repo.max_files_per_commit() else {
+        return;
+    };
+    for c in stats {
+        if let Some(files) = c.summarized_files() {
+            eprintln!(
+                "warning: commit {} changes {files} files, over --max-files-per-commit {max}; counted as one summary entry",
+                &c.commit_id[..c.commit_id.len().min(12)]
+            );
+        }
+    }
+}
+
+/// Store freshly analyzed commits in one transaction.
+fn store_batch(repo: &GitRepo, cache: &mut Cache, batch: &[CommitStats]) -> anyhow::Result<()> {
+    let _phase = timing::phase("cache store");
+    let mut commit_infos = std::collections::HashMap::new();
+    for stats in batch {
+        if let Ok(info) = repo.get_commit_info(&stats.commit_id) {
+            commit_infos.insert(stats.commit_id.clone(), info);
+        }
+    }
+    cache
+        .store_commit_stats(batch, &commit_infos)
+        .context("Failed to store commit stats in cache")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::DateKind;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .status()
+            .unwrap();
+        assert!(status.success());
     }
 
-    Ok(cached_stats)
+    fn repo_with_commits(dir: &Path, count: usize) -> GitRepo {
+        git(dir, &["init", "-q"]);
+        for i in 0..count {
+            std::fs::write(dir.join("file.txt"), "line\n".repeat(i + 1)).unwrap();
+            git(dir, &["add", "."]);
+            git(dir, &["commit", "-q", "-m", &format!("commit {i}")]);
+        }
+        GitRepo::open(Some(dir)).unwrap()
+    }
+
+    #[test]
+    fn interrupted_fetch_keeps_analyzed_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo_with_commits(dir.path(), 4);
+        let cache_dir = dir.path().join("cache");
+        let mut cache = Cache::new(Some(&cache_dir), dir.path(), DateKind::Committer).unwrap();
+        let range = DateRange::new();
+
+        let err = fetch_until(
+            &repo,
+            &mut cache,
+            &range,
+            FetchSettings::default(),
+            &|analyzed| analyzed == 2,
+            &mut |_| {},
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(GmapError::Interrupted { saved: 2 })
+        ));
+        assert_eq!(cache.get_commit_stats(&range).unwrap().len(), 2);
+
+        // the next run only analyzes what is left
+        let (stats, report) = fetch_until(
+            &repo,
+            &mut cache,
+            &range,
+            FetchSettings::default(),
+            &|_| false,
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(stats.len(), 4);
+        assert_eq!(
+            report,
+            FetchReport {
+                cached: 2,
+                computed: 2
+            }
+        );
+    }
+
+    #[test]
+    fn analyzed_commits_are_stored_in_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo_with_commits(dir.path(), 5);
+        let cache_dir = dir.path().join("cache");
+        let mut cache = Cache::new(Some(&cache_dir), dir.path(), DateKind::Committer).unwrap();
+        let range = DateRange::new();
+        let cached_now = || {
+            Cache::new(Some(&cache_dir), dir.path(), DateKind::Committer)
+                .unwrap()
+                .get_commit_stats(&range)
+                .unwrap()
+                .len()
+        };
+
+        let seen = std::cell::RefCell::new(Vec::new());
+        let (stats, _) = fetch_until(
+            &repo,
+            &mut cache,
+            &range,
+            FetchSettings {
+                batch: 2,
+                ..FetchSettings::default()
+            },
+            &|analyzed| {
+                seen.borrow_mut().push((analyzed, cached_now()));
+                false
+            },
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(stats.len(), 5);
+        assert_eq!(*seen.borrow(), vec![(0, 0), (1, 0), (2, 2), (3, 2), (4, 4)]);
+        assert_eq!(cached_now(), 5);
+    }
 }
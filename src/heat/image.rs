@@ -0,0 +1,127 @@
+use crate::error::{GmapError, Result};
+use crate::model::HeatBucket;
+use crate::tui::draw::{intensity_color, Intensity, IntensityScale};
+use ratatui::style::Color;
+
+const CELL: u32 = 14;
+const GAP: u32 = 2;
+const MARGIN: u32 = 8;
+const BACKGROUND: [u8; 3] = [24, 24, 24];
+/// Periods without commits, like the TUI's empty `░` bar.
+const EMPTY: [u8; 3] = [56, 56, 56];
+
+/// Encode `buckets` as a PNG grid, one square per period in order,
+/// `per_row` to a row, colored by `values` (commit counts, or a smoothed
+/// series of them, one per bucket) as in the TUI heatmap.
+pub fn render_png(
+    buckets: &[HeatBucket],
+    values: &[f64],
+    scale: IntensityScale,
+    per_row: usize,
+) -> Result<Vec<u8>> {
+    if buckets.is_empty() {
+        return Err(GmapError::Other("no periods to draw".to_string()));
+    }
+    let per_row = per_row.clamp(1, buckets.len());
+    let rows = buckets.len().div_ceil(per_row);
+    let span = |cells: usize| 2 * MARGIN + cells as u32 * CELL + (cells as u32 - 1) * GAP;
+    let (width, height) = (span(per_row), span(rows));
+
+    let mut pixels = BACKGROUND.repeat((width * height) as usize);
+    let intensity = Intensity::new(scale, values.iter().copied());
+    for (i, &value) in values.iter().enumerate().take(buckets.len()) {
+        let color = if value <= 0.0 {
+            EMPTY
+        } else {
+            rgb(intensity_color(intensity.ratio(value)))
+        };
+        let left = MARGIN + (i % per_row) as u32 * (CELL + GAP);
+        let top = MARGIN + (i / per_row) as u32 * (CELL + GAP);
+        for y in top..top + CELL {
+            let start = ((y * width + left) * 3) as usize;
+            for pixel in pixels[start..start + (CELL * 3) as usize].chunks_exact_mut(3) {
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| GmapError::Other(format!("PNG encoding failed: {e}")))?;
+    Ok(png)
+}
+
+/// RGB for the terminal colors the intensity bands use, from a common dark
+/// theme palette.
+fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Red => [205, 49, 49],
+        Color::Yellow => [229, 229, 16],
+        Color::Green => [13, 188, 121],
+        Color::Cyan => [17, 168, 205],
+        Color::Blue => [36, 114, 200],
+        Color::Rgb(r, g, b) => [r, g, b],
+        _ => [204, 204, 204],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(week: &str, commit_count: u32) -> HeatBucket {
+        HeatBucket {
+            week: week.to_string(),
+            commit_count,
+            lines_changed: 0,
+            lines_added: 0,
+            lines_deleted: 0,
+            binary_files_changed: 0,
+            bytes_added: 0,
+            bytes_deleted: 0,
+            tags: Vec::new(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn writes_a_decodable_grid_in_the_tui_colors() {
+        let buckets = [
+            bucket("2024-W01", 10),
+            bucket("2024-W02", 0),
+            bucket("2024-W03", 1),
+        ];
+        let counts: Vec<_> = buckets.iter().map(|b| b.commit_count as f64).collect();
+        let png = render_png(&buckets, &counts, IntensityScale::Linear, 2).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        // Two columns and two rows of cells.
+        assert_eq!(
+            (info.width, info.height),
+            (2 * 8 + 2 * 14 + 2, 2 * 8 + 2 * 14 + 2)
+        );
+
+        let at = |x: u32, y: u32| {
+            let i = ((y * info.width + x) * 3) as usize;
+            [pixels[i], pixels[i + 1], pixels[i + 2]]
+        };
+        assert_eq!(at(0, 0), BACKGROUND);
+        assert_eq!(at(8, 8), rgb(intensity_color(1.0)));
+        assert_eq!(at(8 + 16, 8), EMPTY);
+        assert_eq!(at(8, 8 + 16), rgb(intensity_color(0.1)));
+        assert_eq!(at(8 + 16, 8 + 16), BACKGROUND);
+    }
+
+    #[test]
+    fn no_periods_is_an_error() {
+        assert!(render_png(&[], &[], IntensityScale::Linear, 13).is_err());
+    }
+}
@@ -2,15 +2,19 @@ pub mod aggregate;
 pub mod commit;
 pub mod exec;
 pub mod fetch;
-pub mod output;
+pub mod image;
 
-pub use aggregate::{aggregate_weeks, compute_heat};
-pub use commit::{get_commits_for_period, load_commit_details};
-pub use exec::exec;
-pub use fetch::{fetch_commit_stats, fetch_commit_stats_with_progress};
-pub use output::{output_heatmap, output_json, output_ndjson};
+pub use aggregate::{
+    aggregate_weeks, aggregate_weeks_cached, compute_heat, fill_period_gaps, growth_series,
+};
+pub use commit::{find_commit_global, get_commits_for_period, load_commit_details, CommitScope};
+pub use exec::{exec, HeatOptions};
+pub use fetch::{
+    fetch_commit_stats, fetch_commit_stats_reported, fetch_commit_stats_streaming,
+    fetch_commit_stats_with_progress, FetchReport,
+};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct FileExtensionStats {
     pub commits: usize,
     pub lines_added: usize,
@@ -0,0 +1,86 @@
+use crate::cli::CommonArgs;
+use crate::model::{ExportEntry, FileStats};
+use crate::util::{path_excluded, to_json};
+use anyhow::Context;
+use console::style;
+
+/// Show what HEAD changed, without walking history or touching the cache.
+pub fn exec(common: CommonArgs, json: bool, compact: bool, full_hash: bool) -> anyhow::Result<()> {
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let head = repo.head_commit_id().context("Failed to resolve HEAD")?;
+    // Binary files are recorded and then shaped to the policy, as fetches do;
+    // with --only-binary a text-only HEAD lists no files.
+    let mut stats = vec![repo
+        .compute_commit_stats_for(head, true)
+        .context("Failed to compute stats for HEAD")?];
+    common.binary_policy().apply(&mut stats);
+    let (summary, mut files): (Vec<_>, Vec<_>) = stats
+        .pop()
+        .map(|s| s.files)
+        .unwrap_or_default()
+        .into_iter()
+        .partition(FileStats::is_summary);
+    files.retain(|f| !path_excluded(&f.path, &common.exclude, common.case_sensitive));
+    let info = repo
+        .get_commit_info(&head.to_string())
+        .context("Failed to read HEAD commit")?;
+
+    let entry = ExportEntry {
+        commit_id: info.id,
+        author_name: info.author_name,
+        author_email: info.author_email,
+        timestamp: info.timestamp,
+        message: info.message.trim_end().to_string(),
+        files,
+        summarized_files: summary.first().and_then(|f| f.summarized_files),
+        is_outlier: false,
+        signature: None,
+    };
+    if json {
+        println!("{}", to_json(&entry, compact)?);
+    } else {
+        output_table(&entry, full_hash);
+    }
+    Ok(())
+}
+
+fn output_table(entry: &ExportEntry, full_hash: bool) {
+    let shown = if full_hash { usize::MAX } else { 7 };
+    let hash: String = entry.commit_id.chars().take(shown).collect();
+    println!("{} {}", style(hash).yellow(), entry.message);
+    println!(
+        "{} <{}>, {}",
+        entry.author_name,
+        entry.author_email,
+        entry.timestamp.format("%Y-%m-%d %H:%M")
+    );
+    println!();
+    for f in &entry.files {
+        println!("{}", file_line(f));
+    }
+    let added: u64 = entry.files.iter().map(|f| f.added_lines as u64).sum();
+    let deleted: u64 = entry.files.iter().map(|f| f.deleted_lines as u64).sum();
+    let n = entry.summarized_files.unwrap_or(entry.files.len());
+    println!("{}", "─".repeat(40));
+    println!(
+        "{:>8} {:>8}  {} file{} changed",
+        style(format!("+{added}")).green(),
+        style(format!("-{deleted}")).red(),
+        n,
+        if n == 1 { "" } else { "s" }
+    );
+}
+
+fn file_line(f: &FileStats) -> String {
+    if f.is_binary {
+        return format!("{:>17}  {}", "binary", f.path);
+    }
+    format!(
+        "{:>8} {:>8}  {}",
+        format!("+{}", f.added_lines),
+        format!("-{}", f.deleted_lines),
+        f.path
+    )
+}
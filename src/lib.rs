@@ -1,10 +1,24 @@
+pub mod alias;
+pub mod authors;
 pub mod cache;
 pub mod churn;
+pub mod clean;
 pub mod cli;
+pub mod compare;
+pub mod coupling;
 pub mod error;
 pub mod export;
+pub mod filter;
 pub mod git;
+pub mod group;
 pub mod heat;
+pub mod last;
+pub mod matrix;
 pub mod model;
+pub mod output;
+pub mod size;
+pub mod stats;
+pub mod timing;
 pub mod tui;
 pub mod util;
+pub mod watch;
@@ -1,8 +1,16 @@
 use anyhow::Result;
 use clap::Parser;
 use gmap::cli::Cli;
+use gmap::error::GmapError;
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
-    cli.execute()
+    match cli.execute() {
+        Err(err) if matches!(err.downcast_ref(), Some(GmapError::Interrupted { .. })) => {
+            eprintln!("{err}");
+            Ok(ExitCode::from(130))
+        }
+        result => result,
+    }
 }
@@ -0,0 +1,212 @@
+use crate::cache::Cache;
+use crate::churn::aggregate_path;
+use crate::cli::CommonArgs;
+use crate::error::Result;
+use crate::filter::CommitFilter;
+use crate::heat::fetch_commit_stats_reported;
+use crate::model::{ChurnMatrix, CommitStats, MatrixOutput, Normalize, SCHEMA_VERSION};
+use crate::util::{path_excluded, period_key, to_json, PeriodSpec};
+use anyhow::Context;
+use chrono::Utc;
+use clap::ValueEnum;
+use console::style;
+use std::collections::{BTreeSet, HashMap};
+
+/// Shades from no activity to the scale maximum.
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+pub fn exec(
+    common: CommonArgs,
+    depth: u32,
+    monthly: bool,
+    normalize: Normalize,
+    json: bool,
+    compact: bool,
+    path: Option<String>,
+) -> anyhow::Result<()> {
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let mut cache = common
+        .open_cache(&repo)
+        .context("Failed to initialize cache")?;
+
+    let range = common
+        .resolve_range(&repo)
+        .context("Failed to resolve date range")?;
+
+    let (stats, report) = fetch_commit_stats_reported(
+        &repo,
+        &mut cache,
+        &range,
+        common.merge_policy(),
+        common.binary_policy(),
+        common.recompute,
+        true,
+    )?;
+
+    let matrix = compute_matrix(
+        &stats,
+        &cache,
+        depth,
+        path.as_deref(),
+        &common.commit_filter(),
+        &common.exclude,
+        common.period_spec(monthly),
+    )
+    .context("Failed to compute churn matrix")?;
+    let scaled = matrix.scaled(normalize);
+
+    if json {
+        let output = MatrixOutput {
+            version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            repository_path: repo.path().display().to_string(),
+            path_prefix: path.unwrap_or_default(),
+            since: common.since_arg().map(str::to_string),
+            until: common.until_arg().map(str::to_string),
+            depth,
+            normalize: normalize
+                .to_possible_value()
+                .map(|v| v.get_name().to_string())
+                .unwrap_or_default(),
+            matrix,
+            scaled,
+        };
+        println!("{}", to_json(&output, compact)?);
+    } else {
+        output_grid(&matrix, &scaled);
+    }
+    report.announce();
+    Ok(())
+}
+
+/// Sum lines changed per `(directory at depth, period)`. Rows are ordered by
+/// total churn, then name; columns are the periods with any activity, oldest first.
+pub fn compute_matrix(
+    stats: &[CommitStats],
+    cache: &Cache,
+    depth: u32,
+    path_prefix: Option<&str>,
+    filter: &CommitFilter,
+    excludes: &[String],
+    period: PeriodSpec,
+) -> Result<ChurnMatrix> {
+    let _phase = crate::timing::phase("aggregation");
+    let mut totals: HashMap<(String, String), u64> = HashMap::new();
+    for cs in stats {
+        let info = cache
+            .get_commit_info(&cs.commit_id)?
+            .ok_or_else(|| crate::error::GmapError::Cache("Commit info not found".to_string()))?;
+        if !filter.matches(&info) {
+            continue;
+        }
+        let key = period_key(&info.timestamp, period);
+        for f in cs.files.iter().filter(|f| !f.is_summary()) {
+            if path_prefix.is_some_and(|prefix| !f.path.starts_with(prefix))
+                || path_excluded(&f.path, excludes, filter.case_sensitive())
+            {
+                continue;
+            }
+            let lines = (f.added_lines + f.deleted_lines) as u64;
+            *totals
+                .entry((aggregate_path(&f.path, depth), key.clone()))
+                .or_default() += lines;
+        }
+    }
+
+    let periods: Vec<String> = totals
+        .keys()
+        .map(|(_, p)| p.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let mut row_totals: HashMap<&str, u64> = HashMap::new();
+    for ((dir, _), lines) in &totals {
+        *row_totals.entry(dir.as_str()).or_default() += lines;
+    }
+    let mut rows: Vec<String> = row_totals.keys().map(|d| d.to_string()).collect();
+    rows.sort_by(|a, b| {
+        row_totals[b.as_str()]
+            .cmp(&row_totals[a.as_str()])
+            .then_with(|| a.cmp(b))
+    });
+
+    let cells = rows
+        .iter()
+        .map(|dir| {
+            periods
+                .iter()
+                .map(|p| totals.get(&(dir.clone(), p.clone())).copied().unwrap_or(0))
+                .collect()
+        })
+        .collect();
+    Ok(ChurnMatrix {
+        rows,
+        periods,
+        cells,
+    })
+}
+
+fn shade(fraction: f64) -> char {
+    if fraction <= 0.0 {
+        return SHADES[0];
+    }
+    let steps = (SHADES.len() - 1) as f64;
+    SHADES[((fraction * steps).ceil() as usize).clamp(1, SHADES.len() - 1)]
+}
+
+fn output_grid(matrix: &ChurnMatrix, scaled: &[Vec<f64>]) {
+    if matrix.rows.is_empty() {
+        println!("No changes in range");
+        return;
+    }
+    let width = matrix
+        .rows
+        .iter()
+        .map(|r| r.len())
+        .max()
+        .unwrap_or(0)
+        .min(40);
+    for ((row, fractions), cells) in matrix.rows.iter().zip(scaled).zip(&matrix.cells) {
+        let shades: String = fractions.iter().map(|&f| shade(f)).collect();
+        let total: u64 = cells.iter().sum();
+        println!("{row:<width$} │{shades}│ {}", style(total).cyan());
+    }
+    println!(
+        "{:<width$}  {} … {} ({} periods)",
+        "",
+        matrix.periods.first().map(String::as_str).unwrap_or(""),
+        matrix.periods.last().map(String::as_str).unwrap_or(""),
+        matrix.periods.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaling_divides_by_row_column_or_global_max() {
+        let matrix = ChurnMatrix {
+            rows: vec!["src".into(), "docs".into()],
+            periods: vec!["2024-W01".into(), "2024-W02".into()],
+            cells: vec![vec![10, 40], vec![5, 0]],
+        };
+        assert_eq!(
+            matrix.scaled(Normalize::Global),
+            vec![vec![0.25, 1.0], vec![0.125, 0.0]]
+        );
+        assert_eq!(
+            matrix.scaled(Normalize::Row),
+            vec![vec![0.25, 1.0], vec![1.0, 0.0]]
+        );
+        assert_eq!(
+            matrix.scaled(Normalize::Col),
+            vec![vec![1.0, 1.0], vec![0.5, 0.0]]
+        );
+        assert_eq!(shade(0.0), ' ');
+        assert_eq!(shade(0.01), '░');
+        assert_eq!(shade(1.0), '█');
+    }
+}
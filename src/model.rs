@@ -2,7 +2,149 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-pub const SCHEMA_VERSION: u32 = 1;
+/// Version of the JSON output documents. 2 added `lines_added` / `lines_deleted`
+/// to heat buckets.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Which commit timestamp drives bucketing and date ranges.
+///
+/// The author date records when a change was originally written; the committer
+/// date records when it was last applied (rebase, cherry-pick, amend).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum DateKind {
+    Author,
+    #[default]
+    Committer,
+}
+
+/// How a `--since`/`--until` bound is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoundKind {
+    /// A date when it parses as one, otherwise a commit
+    #[default]
+    Auto,
+    /// A commit (hash, branch or tag), standing for its timestamp
+    Commit,
+    /// A date only
+    Date,
+}
+
+/// Which commits to analyze by number of parents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// Regular commits only
+    #[default]
+    Exclude,
+    /// Regular and merge commits
+    Include,
+    /// Merge commits only
+    Only,
+}
+
+impl MergePolicy {
+    pub fn keeps(self, parent_count: usize) -> bool {
+        match self {
+            MergePolicy::Exclude => parent_count <= 1,
+            MergePolicy::Include => true,
+            MergePolicy::Only => parent_count > 1,
+        }
+    }
+
+    /// The TUI merge toggle: switch merges in or out; merges-only turns them off.
+    pub fn toggled(self) -> Self {
+        match self {
+            MergePolicy::Exclude => MergePolicy::Include,
+            MergePolicy::Include | MergePolicy::Only => MergePolicy::Exclude,
+        }
+    }
+}
+
+/// Which files to analyze by content: text, text and binary, or binary only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinaryPolicy {
+    /// Text files only; binary files are set aside, to be counted but not charted
+    #[default]
+    Exclude,
+    /// Text and binary files
+    Include,
+    /// Binary files only; commits changing none are dropped
+    Only,
+}
+
+impl BinaryPolicy {
+    /// Shape freshly fetched `stats` to the policy.
+    pub fn apply(self, stats: &mut Vec<CommitStats>) {
+        match self {
+            BinaryPolicy::Exclude => stats.iter_mut().for_each(CommitStats::set_aside_binaries),
+            BinaryPolicy::Include => {}
+            BinaryPolicy::Only => {
+                for c in stats.iter_mut() {
+                    c.files.retain(|f| f.is_binary);
+                }
+                stats.retain(|c| !c.files.is_empty());
+            }
+        }
+    }
+}
+
+/// How a merge commit's changes are measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum MergeDiff {
+    /// Against the first parent only, like `git log -m --first-parent`
+    #[default]
+    FirstParent,
+    /// Against every parent, keeping each path's largest change
+    Combined,
+}
+
+/// How much of a merge commit's diff counts as its churn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum MergeChurn {
+    /// Everything the merge brought in, per `MergeDiff`
+    #[default]
+    Full,
+    /// Only what differs from the automatic merge of the parents: conflict
+    /// resolutions and manual edits
+    Resolution,
+}
+
+/// Whether a commit carries a signature, and in which format. Only presence is
+/// detected; signatures are not verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureStatus {
+    Unsigned,
+    Gpg,
+    Ssh,
+    X509,
+}
+
+impl SignatureStatus {
+    /// Classify a commit's `gpgsig` header by its armor line.
+    pub fn from_header(signature: Option<&[u8]>) -> Self {
+        match signature {
+            None => Self::Unsigned,
+            Some(sig) if sig.starts_with(b"-----BEGIN SSH SIGNATURE-----") => Self::Ssh,
+            Some(sig) if sig.starts_with(b"-----BEGIN SIGNED MESSAGE-----") => Self::X509,
+            Some(_) => Self::Gpg,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unsigned => "unsigned",
+            Self::Gpg => "gpg",
+            Self::Ssh => "ssh",
+            Self::X509 => "x509",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        [Self::Unsigned, Self::Gpg, Self::Ssh, Self::X509]
+            .into_iter()
+            .find(|status| status.as_str() == s)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitInfo {
@@ -12,6 +154,9 @@ pub struct CommitInfo {
     pub message: String,
     pub timestamp: DateTime<Utc>,
     pub parent_ids: Vec<String>,
+    /// `None` for commits cached before signatures were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureStatus>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,12 +165,119 @@ pub struct FileStats {
     pub added_lines: u32,
     pub deleted_lines: u32,
     pub is_binary: bool,
+    /// Blob size in bytes after the change (before it, for deletions);
+    /// `None` for rows cached before sizes were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Growth or shrinkage of the blob in bytes; zero for rows cached
+    /// before byte deltas were recorded
+    #[serde(default)]
+    pub bytes_added: u64,
+    #[serde(default)]
+    pub bytes_deleted: u64,
+    /// Changed line ranges; only filled by `export --hunks`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hunks: Vec<Hunk>,
+    /// Set only on a [`FileStats::summary`] row: how many files it stands for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summarized_files: Option<usize>,
+}
+
+impl FileStats {
+    /// The single row recorded for a commit changing more files than
+    /// `--max-files-per-commit` allows. It has no path, so per-path reports
+    /// leave it out, and no line counts, which would take reading every blob;
+    /// only the byte totals of all `files` are kept.
+    pub fn summary(files: usize, size: u64, bytes_added: u64, bytes_deleted: u64) -> Self {
+        Self {
+            path: String::new(),
+            added_lines: 0,
+            deleted_lines: 0,
+            is_binary: false,
+            size: Some(size),
+            bytes_added,
+            bytes_deleted,
+            hunks: Vec::new(),
+            summarized_files: Some(files),
+        }
+    }
+
+    /// Whether this is a [`FileStats::summary`] row rather than a real file.
+    pub fn is_summary(&self) -> bool {
+        self.summarized_files.is_some()
+    }
+}
+
+/// One changed region of a file, as in a `git diff -U0` hunk header: starts
+/// are 1-based, and a zero-length side starts at the line before the change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitStats {
     pub commit_id: String,
     pub files: Vec<FileStats>,
+    /// Binary files changed, moved out of `files` when `--binary` is off so
+    /// they can still be counted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binary_paths: Vec<String>,
+}
+
+impl CommitStats {
+    /// Replace the per-file rows with one [`FileStats::summary`] of them all,
+    /// folding in any summary rows already there. Line counts are dropped, as
+    /// for a commit summarized when it was analyzed.
+    pub fn summarize(&mut self) {
+        self.files = vec![FileStats::summary(
+            self.files
+                .iter()
+                .map(|f| f.summarized_files.unwrap_or(1))
+                .sum(),
+            self.files.iter().filter_map(|f| f.size).sum(),
+            self.files.iter().map(|f| f.bytes_added).sum(),
+            self.files.iter().map(|f| f.bytes_deleted).sum(),
+        )];
+    }
+
+    /// Move binary rows out of `files` into `binary_paths`.
+    pub fn set_aside_binaries(&mut self) {
+        let (binary, text) = std::mem::take(&mut self.files)
+            .into_iter()
+            .partition(|f| f.is_binary);
+        self.files = text;
+        self.binary_paths
+            .extend(binary.into_iter().map(|f: FileStats| f.path));
+    }
+
+    /// Paths of every binary file changed, whether in `files` or set aside.
+    pub fn binary_files(&self) -> impl Iterator<Item = &str> {
+        self.files
+            .iter()
+            .filter(|f| f.is_binary)
+            .map(|f| f.path.as_str())
+            .chain(self.binary_paths.iter().map(String::as_str))
+    }
+
+    /// How many files the commit changed, if it was recorded as one summary row.
+    pub fn summarized_files(&self) -> Option<usize> {
+        match self.files.as_slice() {
+            [only] => only.summarized_files,
+            _ => None,
+        }
+    }
+}
+
+/// Two files changed together in `count` commits; `a` sorts before `b`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CouplingEdge {
+    pub a: String,
+    pub b: String,
+    pub count: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +288,162 @@ pub struct ChurnEntry {
     pub total_lines: u64,
     pub commit_count: u32,
     pub authors: HashSet<String>,
+    /// Lines changed, each commit weighted by its recency (equals `total_lines` without decay)
+    #[serde(default)]
+    pub weighted_score: f64,
+    /// Earliest and latest change in range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_change: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_change: Option<DateTime<Utc>>,
+    /// Lines changed per day of active lifespan (`churn --velocity`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub velocity: Option<f64>,
+    /// Bytes binary files grew and shrank by, their churn in place of lines
+    #[serde(default)]
+    pub bytes_added: u64,
+    #[serde(default)]
+    pub bytes_deleted: u64,
+}
+
+/// Activity of one churn entry per period, oldest first (`churn --spark`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeriodSeries {
+    pub commits: Vec<u64>,
+    pub lines: Vec<u64>,
+}
+
+/// Ordering of churn entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ChurnSort {
+    /// Most lines changed first
+    #[default]
+    Total,
+    /// Highest recency-weighted score first
+    Weighted,
+    /// Most lines changed per day of active lifespan first
+    Velocity,
+}
+
+/// What a churn matrix cell is scaled against for shading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Normalize {
+    /// The largest cell in the same directory
+    Row,
+    /// The largest cell in the same period
+    Col,
+    /// The largest cell overall
+    #[default]
+    Global,
+}
+
+/// Lines changed per directory (rows) and period (columns).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChurnMatrix {
+    pub rows: Vec<String>,
+    pub periods: Vec<String>,
+    /// `cells[row][period]`
+    pub cells: Vec<Vec<u64>>,
+}
+
+impl ChurnMatrix {
+    /// Cells as fractions of their row, column or global maximum.
+    pub fn scaled(&self, normalize: Normalize) -> Vec<Vec<f64>> {
+        let max_of = |values: &mut dyn Iterator<Item = u64>| values.max().unwrap_or(0);
+        let global = max_of(&mut self.cells.iter().flatten().copied());
+        let col_max: Vec<u64> = (0..self.periods.len())
+            .map(|c| max_of(&mut self.cells.iter().map(|row| row[c])))
+            .collect();
+        self.cells
+            .iter()
+            .map(|row| {
+                let row_max = max_of(&mut row.iter().copied());
+                row.iter()
+                    .enumerate()
+                    .map(|(c, &value)| {
+                        let max = match normalize {
+                            Normalize::Row => row_max,
+                            Normalize::Col => col_max[c],
+                            Normalize::Global => global,
+                        };
+                        if max == 0 {
+                            0.0
+                        } else {
+                            value as f64 / max as f64
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatrixOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub path_prefix: String,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub depth: u32,
+    pub normalize: String,
+    #[serde(flatten)]
+    pub matrix: ChurnMatrix,
+    /// `cells` scaled per `normalize`, each in `0.0..=1.0`
+    pub scaled: Vec<Vec<f64>>,
+}
+
+/// Commit attribute churn can be grouped by instead of path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Domain part of the author email
+    Domain,
+    /// Conventional Commits type of the message (`feat`, `fix`, ...)
+    Type,
+    /// Size bucket of each changed file's blob (`small`, `medium`, `large`)
+    Size,
+}
+
+impl GroupBy {
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupBy::Domain => "domain",
+            GroupBy::Type => "type",
+            GroupBy::Size => "size",
+        }
+    }
+
+    /// Group of one file change; commit-level groupings ignore `file`.
+    pub fn key(self, info: &CommitInfo, file: &FileStats) -> String {
+        match self {
+            GroupBy::Domain => crate::util::email_domain(&info.author_email),
+            GroupBy::Type => crate::util::commit_type(&info.message).to_string(),
+            GroupBy::Size => crate::util::size_bucket(file.size).to_string(),
+        }
+    }
+}
+
+/// Totals for one group of commits, e.g. one author email domain or commit type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupEntry {
+    pub key: String,
+    pub commits: u32,
+    pub authors: u32,
+    pub added_lines: u64,
+    pub deleted_lines: u64,
+    pub total_lines: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub group_by: String,
+    pub groups: Vec<GroupEntry>,
 }
 
 impl ChurnEntry {
@@ -47,17 +455,54 @@ impl ChurnEntry {
             total_lines: 0,
             commit_count: 0,
             authors: HashSet::new(),
+            weighted_score: 0.0,
+            first_change: None,
+            last_change: None,
+            velocity: None,
+            bytes_added: 0,
+            bytes_deleted: 0,
         }
     }
 
-    pub fn add_stats(&mut self, stats: &FileStats, author: &str) {
+    /// Fold in one file change made at `timestamp`; `weight` scales its
+    /// contribution to `weighted_score`.
+    pub fn add_stats(
+        &mut self,
+        stats: &FileStats,
+        author: &str,
+        weight: f64,
+        timestamp: DateTime<Utc>,
+    ) {
+        let lines = (stats.added_lines + stats.deleted_lines) as u64;
         self.added_lines += stats.added_lines as u64;
         self.deleted_lines += stats.deleted_lines as u64;
-        self.total_lines += (stats.added_lines + stats.deleted_lines) as u64;
+        self.total_lines += lines;
+        self.weighted_score += lines as f64 * weight;
+        if stats.is_binary {
+            self.bytes_added += stats.bytes_added;
+            self.bytes_deleted += stats.bytes_deleted;
+        }
         self.commit_count += 1;
         if self.authors.len() < 100 {
             self.authors.insert(author.to_string());
         }
+        self.first_change = Some(self.first_change.map_or(timestamp, |t| t.min(timestamp)));
+        self.last_change = Some(self.last_change.map_or(timestamp, |t| t.max(timestamp)));
+    }
+
+    /// Bytes added plus deleted.
+    pub fn bytes_changed(&self) -> u64 {
+        self.bytes_added + self.bytes_deleted
+    }
+
+    /// Total lines over the days between the first and last change, counting
+    /// a lifespan shorter than a day as one day.
+    pub fn lines_per_day(&self) -> f64 {
+        let days = match (self.first_change, self.last_change) {
+            (Some(first), Some(last)) => (last - first).num_seconds() as f64 / 86_400.0,
+            _ => 0.0,
+        };
+        self.total_lines as f64 / days.max(1.0)
     }
 }
 
@@ -72,11 +517,119 @@ pub struct ChurnOutput {
     pub entries: Vec<ChurnEntry>,
 }
 
+/// One directory or file of `churn --tree`. A directory totals every file
+/// beneath it; `commit_count` and `authors` count each commit or author once
+/// per node, so they are not the sums of the children's counts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChurnTreeNode {
+    /// Last path component; `.` for the root
+    pub name: String,
+    /// Full path from the repository root; empty for the root
+    pub path: String,
+    pub added_lines: u64,
+    pub deleted_lines: u64,
+    pub total_lines: u64,
+    pub commit_count: u32,
+    pub authors: u32,
+    /// Busiest first; empty (and omitted) for files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ChurnTreeNode>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChurnTreeOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub tree: ChurnTreeNode,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HeatBucket {
     pub week: String,
     pub commit_count: u32,
     pub lines_changed: u64,
+    #[serde(default)]
+    pub lines_added: u64,
+    #[serde(default)]
+    pub lines_deleted: u64,
+    /// Binary file changes by the period's commits, with or without `--binary`
+    #[serde(default)]
+    pub binary_files_changed: u64,
+    /// Bytes the period's files grew and shrank by
+    #[serde(default)]
+    pub bytes_added: u64,
+    #[serde(default)]
+    pub bytes_deleted: u64,
+    /// Release tags whose commit falls in this period (`heat --tag-pattern`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Display label when `--week-format` differs from the `week` key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl HeatBucket {
+    /// Bytes added plus deleted, the size-based counterpart of `lines_changed`.
+    pub fn bytes_changed(&self) -> u64 {
+        self.bytes_added + self.bytes_deleted
+    }
+
+    /// How the period is shown to people: the `--week-format` label, else the key.
+    pub fn display_label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.week)
+    }
+}
+
+/// A tag and the timestamp of the commit it points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseTag {
+    pub name: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Net line delta for one period plus the running total up to and including it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrowthPoint {
+    pub week: String,
+    pub net_lines: i64,
+    pub cumulative_lines: i64,
+}
+
+/// First NDJSON line with `--ndjson-header`: the run metadata a JSON document
+/// carries, tagged `"type": "meta"` so it is told apart from the records.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NdjsonHeader {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    /// Command whose records follow
+    pub command: String,
+    pub repository_path: String,
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+impl NdjsonHeader {
+    pub fn new(
+        command: &str,
+        repository_path: String,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Self {
+        Self {
+            kind: "meta".to_string(),
+            version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            command: command.to_string(),
+            repository_path,
+            since: since.map(str::to_string),
+            until: until.map(str::to_string),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,6 +641,102 @@ pub struct HeatOutput {
     pub since: Option<String>,
     pub until: Option<String>,
     pub buckets: Vec<HeatBucket>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub growth: Option<Vec<GrowthPoint>>,
+}
+
+/// Activity totals for one date window of `compare`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodSummary {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub commits: u64,
+    pub lines_added: u64,
+    pub lines_deleted: u64,
+    pub lines_changed: u64,
+    pub active_files: u64,
+}
+
+/// Current minus baseline, per metric.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareDelta {
+    pub commits: i64,
+    pub lines_added: i64,
+    pub lines_deleted: i64,
+    pub lines_changed: i64,
+    pub active_files: i64,
+}
+
+/// A window against the equal-length one just before it
+/// (`stats --vs-previous-period`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodComparison {
+    pub baseline: PeriodSummary,
+    pub current: PeriodSummary,
+    pub delta: CompareDelta,
+}
+
+/// One author identity and how many commits it made in range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorEntry {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorsOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub authors: Vec<AuthorEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub path_prefix: String,
+    pub baseline: PeriodSummary,
+    pub current: PeriodSummary,
+    pub delta: CompareDelta,
+}
+
+/// Aggregate figures over all periods, shared by `stats` and the TUI Stats view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSummary {
+    pub periods: usize,
+    pub total_commits: usize,
+    pub lines_added: usize,
+    pub lines_deleted: usize,
+    pub net_change: i64,
+    pub avg_commits: f64,
+    pub max_commits: usize,
+    pub min_commits: usize,
+    pub commits_by_hour: [u64; 24],
+    pub busiest_hour: u32,
+    /// Commits per period, oldest first
+    pub trend: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub path_prefix: String,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub summary: StatsSummary,
+    /// Root commits in range left out by `--exclude-root`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_commits_excluded: Option<usize>,
+    /// Totals against the preceding window of the same length
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_period: Option<PeriodComparison>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,6 +747,15 @@ pub struct ExportEntry {
     pub timestamp: DateTime<Utc>,
     pub message: String,
     pub files: Vec<FileStats>,
+    /// Files changed by a commit over `--max-files-per-commit`, which lists
+    /// none of them in `files`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summarized_files: Option<usize>,
+    #[serde(default)]
+    pub is_outlier: bool,
+    /// Only filled by `export --show-signatures`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureStatus>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,10 +768,132 @@ pub struct ExportOutput {
     pub entries: Vec<ExportEntry>,
 }
 
+/// An export entry from `export --name-only`: changed paths, no line counts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameOnlyEntry {
+    pub commit_id: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+    pub files: Vec<String>,
+    /// Only filled by `export --show-signatures`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameOnlyOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub entries: Vec<NameOnlyEntry>,
+}
+
+/// An export entry from `export --summary-entries`: per-commit totals in place
+/// of the file list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitSummary {
+    pub commit_id: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+    pub files_changed: u64,
+    pub added: u64,
+    pub deleted: u64,
+    #[serde(default)]
+    pub is_outlier: bool,
+    /// Only filled by `export --show-signatures`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureStatus>,
+}
+
+impl From<ExportEntry> for CommitSummary {
+    fn from(entry: ExportEntry) -> Self {
+        Self {
+            files_changed: entry
+                .summarized_files
+                .map_or(entry.files.len() as u64, |files| files as u64),
+            added: entry.files.iter().map(|f| f.added_lines as u64).sum(),
+            deleted: entry.files.iter().map(|f| f.deleted_lines as u64).sum(),
+            commit_id: entry.commit_id,
+            author_name: entry.author_name,
+            author_email: entry.author_email,
+            timestamp: entry.timestamp,
+            message: entry.message,
+            is_outlier: entry.is_outlier,
+            signature: entry.signature,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitSummaryOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub entries: Vec<CommitSummary>,
+}
+
+/// `export --group-by-week`: the per-period aggregates behind the TUI.
+#[derive(Debug, Clone, Serialize)]
+pub struct WeekStatsOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub weeks: Vec<crate::tui::WeekStats>,
+}
+
+/// A file in a commit's tree, as counted by `size`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeFile {
+    pub path: String,
+    /// Line count; 0 for binaries
+    pub lines: u64,
+    pub bytes: u64,
+    pub is_binary: bool,
+}
+
+/// Files, lines and bytes of one extension in a `size` snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeEntry {
+    /// Lowercased extension without the dot; empty for files without one
+    pub extension: String,
+    pub files: u64,
+    pub lines: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeOutput {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repository_path: String,
+    pub path_prefix: String,
+    pub commit_id: String,
+    pub total_files: u64,
+    pub total_lines: u64,
+    pub total_bytes: u64,
+    /// Binary files, left out of the totals and `extensions`
+    pub binary_files: u64,
+    pub extensions: Vec<SizeEntry>,
+}
+
 #[derive(Debug, Clone)]
 pub struct DateRange {
     pub since: Option<DateTime<Utc>>,
     pub until: Option<DateTime<Utc>>,
+    /// Commit IDs selected by a revision range; `None` means no restriction.
+    pub commits: Option<HashSet<String>>,
+    /// Generations of history walked from the tip (the tip is generation 1).
+    pub max_depth: Option<u32>,
 }
 
 impl DateRange {
@@ -121,6 +901,8 @@ impl DateRange {
         Self {
             since: None,
             until: None,
+            commits: None,
+            max_depth: None,
         }
     }
 
@@ -134,6 +916,23 @@ impl DateRange {
         self
     }
 
+    pub fn with_commits(mut self, commits: HashSet<String>) -> Self {
+        self.commits = Some(commits);
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Whether `commit_id` is part of the revision range, if one was given.
+    pub fn contains_commit(&self, commit_id: &str) -> bool {
+        self.commits
+            .as_ref()
+            .is_none_or(|commits| commits.contains(commit_id))
+    }
+
     pub fn contains(&self, timestamp: &DateTime<Utc>) -> bool {
         if let Some(since) = self.since {
             if timestamp < &since {
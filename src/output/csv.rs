@@ -0,0 +1,127 @@
+use super::{OutputContext, OutputFormat};
+use crate::model::{ChurnEntry, ExportEntry, HeatBucket};
+use anyhow::Result;
+use std::borrow::Cow;
+use std::io::Write;
+
+/// Comma-separated values with a header row. Export emits one row per file change.
+pub struct CsvFormat;
+
+/// Quote a field when it contains a delimiter, quote or line break (RFC 4180).
+fn field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+impl OutputFormat for CsvFormat {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn write_heat(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        buckets: &[HeatBucket],
+    ) -> Result<()> {
+        write!(
+            out,
+            "week,commit_count,lines_changed,lines_added,lines_deleted,binary_files_changed"
+        )?;
+        if ctx.growth.is_some() {
+            write!(out, ",net_lines,cumulative_lines")?;
+        }
+        writeln!(out)?;
+        for (i, b) in buckets.iter().enumerate() {
+            write!(
+                out,
+                "{},{},{},{},{},{}",
+                field(b.display_label()),
+                b.commit_count,
+                b.lines_changed,
+                b.lines_added,
+                b.lines_deleted,
+                b.binary_files_changed
+            )?;
+            if let Some(point) = ctx.growth.and_then(|g| g.get(i)) {
+                write!(out, ",{},{}", point.net_lines, point.cumulative_lines)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    fn write_churn(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ChurnEntry],
+    ) -> Result<()> {
+        write!(
+            out,
+            "path,added_lines,deleted_lines,total_lines,commit_count,authors,weighted_score"
+        )?;
+        if ctx.velocity {
+            write!(out, ",lines_per_day")?;
+        }
+        if ctx.bytes {
+            write!(out, ",bytes_added,bytes_deleted")?;
+        }
+        writeln!(out)?;
+        for e in entries {
+            write!(
+                out,
+                "{},{},{},{},{},{},{}",
+                field(&e.path),
+                e.added_lines,
+                e.deleted_lines,
+                e.total_lines,
+                e.commit_count,
+                e.authors.len(),
+                e.weighted_score
+            )?;
+            if ctx.velocity {
+                write!(out, ",{}", e.lines_per_day())?;
+            }
+            if ctx.bytes {
+                write!(out, ",{},{}", e.bytes_added, e.bytes_deleted)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    fn write_export(
+        &self,
+        out: &mut dyn Write,
+        _ctx: &OutputContext<'_>,
+        entries: &[ExportEntry],
+    ) -> Result<()> {
+        writeln!(
+            out,
+            "commit_id,author_name,author_email,timestamp,message,path,added_lines,deleted_lines,is_binary,is_outlier"
+        )?;
+        for e in entries {
+            for f in &e.files {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{},{},{}",
+                    e.commit_id,
+                    field(&e.author_name),
+                    field(&e.author_email),
+                    e.timestamp.to_rfc3339(),
+                    field(e.message.trim_end()),
+                    field(&f.path),
+                    f.added_lines,
+                    f.deleted_lines,
+                    f.is_binary,
+                    e.is_outlier
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
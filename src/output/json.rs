@@ -0,0 +1,171 @@
+use super::{OutputContext, OutputFormat};
+use crate::model::{ChurnEntry, ChurnOutput, ExportEntry, HeatBucket, HeatOutput, SCHEMA_VERSION};
+use crate::util::to_json;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::ser::{SerializeMap, Serializer};
+use std::io::Write;
+
+/// A single JSON document with run metadata; `compact` minifies it.
+pub struct JsonFormat;
+
+impl OutputFormat for JsonFormat {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn write_heat(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        buckets: &[HeatBucket],
+    ) -> Result<()> {
+        let output = HeatOutput {
+            version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            repository_path: ctx.repository_path.clone(),
+            path_prefix: ctx.path_prefix.unwrap_or_default().to_string(),
+            since: ctx.since.map(str::to_string),
+            until: ctx.until.map(str::to_string),
+            buckets: buckets.to_vec(),
+            growth: ctx.growth.map(<[_]>::to_vec),
+        };
+        writeln!(out, "{}", to_json(&output, ctx.compact)?)?;
+        Ok(())
+    }
+
+    fn write_churn(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ChurnEntry],
+    ) -> Result<()> {
+        let output = ChurnOutput {
+            version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            repository_path: ctx.repository_path.clone(),
+            since: ctx.since.map(str::to_string),
+            until: ctx.until.map(str::to_string),
+            depth: ctx.depth,
+            entries: entries.to_vec(),
+        };
+        writeln!(out, "{}", to_json(&output, ctx.compact)?)?;
+        Ok(())
+    }
+
+    fn write_export(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ExportEntry],
+    ) -> Result<()> {
+        let header = ExportHeader {
+            generated_at: Utc::now(),
+            repository_path: &ctx.repository_path,
+            since: ctx.since,
+            until: ctx.until,
+        };
+        write_export_json(&mut *out, &header, entries, ctx.compact)?;
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
+/// Top-level fields of an export document, written ahead of the streamed entries.
+struct ExportHeader<'a> {
+    generated_at: DateTime<Utc>,
+    repository_path: &'a str,
+    since: Option<&'a str>,
+    until: Option<&'a str>,
+}
+
+/// Stream an export document to `writer` with the same layout as `ExportOutput`,
+/// serializing entries one at a time instead of building the whole document.
+fn write_export_json<W: Write>(
+    writer: W,
+    header: &ExportHeader<'_>,
+    entries: &[ExportEntry],
+    compact: bool,
+) -> serde_json::Result<()> {
+    if compact {
+        serialize_export(&mut serde_json::Serializer::new(writer), header, entries)
+    } else {
+        serialize_export(&mut serde_json::Serializer::pretty(writer), header, entries)
+    }
+}
+
+fn serialize_export<S: Serializer>(
+    serializer: S,
+    header: &ExportHeader<'_>,
+    entries: &[ExportEntry],
+) -> std::result::Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(6))?;
+    map.serialize_entry("version", &SCHEMA_VERSION)?;
+    map.serialize_entry("generated_at", &header.generated_at)?;
+    map.serialize_entry("repository_path", &header.repository_path)?;
+    map.serialize_entry("since", &header.since)?;
+    map.serialize_entry("until", &header.until)?;
+    map.serialize_entry("entries", entries)?;
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ExportOutput, FileStats};
+    use chrono::TimeZone;
+
+    fn entry(id: &str, day: u32, path: &str, added: u32) -> ExportEntry {
+        ExportEntry {
+            commit_id: id.to_string(),
+            author_name: "Alice".to_string(),
+            author_email: "alice@example.com".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 1, day, 9, 0, 0).unwrap(),
+            message: format!("touch {path}"),
+            files: vec![FileStats {
+                path: path.to_string(),
+                added_lines: added,
+                deleted_lines: 1,
+                is_binary: false,
+                size: None,
+                bytes_added: 0,
+                bytes_deleted: 0,
+                hunks: Vec::new(),
+                summarized_files: None,
+            }],
+            summarized_files: None,
+            is_outlier: false,
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn streamed_export_matches_buffered_output() {
+        let entries = vec![entry("a1", 2, "src/a.rs", 3), entry("b2", 3, "src/b.rs", 7)];
+        let generated_at = Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap();
+        let header = ExportHeader {
+            generated_at,
+            repository_path: "/tmp/repo",
+            since: Some("2024-01-01"),
+            until: None,
+        };
+        let buffered = ExportOutput {
+            version: SCHEMA_VERSION,
+            generated_at,
+            repository_path: "/tmp/repo".to_string(),
+            since: Some("2024-01-01".to_string()),
+            until: None,
+            entries: entries.clone(),
+        };
+
+        for compact in [true, false] {
+            let mut streamed = Vec::new();
+            write_export_json(&mut streamed, &header, &entries, compact).unwrap();
+            assert_eq!(
+                String::from_utf8(streamed).unwrap(),
+                to_json(&buffered, compact).unwrap(),
+                "compact = {compact}"
+            );
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Pluggable writers for heat, churn and export results.
+//!
+//! Each format implements [`OutputFormat`] once; commands look it up by name in
+//! a [`FormatRegistry`] instead of branching on output flags.
+
+mod csv;
+mod json;
+mod ndjson;
+mod prometheus;
+mod table;
+
+pub use self::csv::CsvFormat;
+pub use self::json::JsonFormat;
+pub use self::ndjson::NdjsonFormat;
+pub use self::prometheus::PrometheusFormat;
+pub use self::table::TableFormat;
+
+use crate::model::{ChurnEntry, ExportEntry, GrowthPoint, HeatBucket, PeriodSeries};
+use crate::tui::draw::IntensityScale;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Run `write` against a buffered, locked stdout and flush it.
+pub fn to_stdout(write: impl FnOnce(&mut dyn Write) -> Result<()>) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    write(&mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Run-level details a writer may include alongside the data.
+#[derive(Debug, Clone, Default)]
+pub struct OutputContext<'a> {
+    pub repository_path: String,
+    pub since: Option<&'a str>,
+    pub until: Option<&'a str>,
+    pub path_prefix: Option<&'a str>,
+    /// Directory depth churn was aggregated at
+    pub depth: Option<u32>,
+    /// Minified rather than pretty-printed, where the format has both
+    pub compact: bool,
+    /// Net-growth series for heat, when requested
+    pub growth: Option<&'a [GrowthPoint]>,
+    /// Moving average of commits per heat bucket, charted instead of the
+    /// raw counts when requested
+    pub smoothed: Option<&'a [f64]>,
+    /// Whether churn carries a meaningful recency-weighted score
+    pub weighted: bool,
+    /// Whether churn entries carry lines per active day
+    pub velocity: bool,
+    /// Per-period activity by churn path, drawn as sparklines
+    pub churn_series: Option<&'a HashMap<String, PeriodSeries>>,
+    /// How heat intensity levels are scaled
+    pub scale: IntensityScale,
+    /// Leave zero-commit periods out of textual heat listings
+    pub hide_empty: bool,
+    /// Lead NDJSON streams with a metadata line
+    pub ndjson_header: bool,
+    /// Chart bytes changed instead of lines (`heat --only-binary`), or add
+    /// byte columns to churn when binaries are analyzed
+    pub bytes: bool,
+}
+
+pub trait OutputFormat {
+    /// Name selected with `--format`.
+    fn name(&self) -> &'static str;
+
+    fn write_heat(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        buckets: &[HeatBucket],
+    ) -> Result<()>;
+
+    fn write_churn(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ChurnEntry],
+    ) -> Result<()>;
+
+    fn write_export(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ExportEntry],
+    ) -> Result<()>;
+}
+
+/// Output formats available by name.
+#[derive(Default)]
+pub struct FormatRegistry {
+    formats: Vec<Box<dyn OutputFormat>>,
+}
+
+impl FormatRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The formats gmap ships with: table, json, ndjson, csv and prometheus.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(TableFormat));
+        registry.register(Box::new(JsonFormat));
+        registry.register(Box::new(NdjsonFormat));
+        registry.register(Box::new(CsvFormat));
+        registry.register(Box::new(PrometheusFormat));
+        registry
+    }
+
+    /// Add a format, replacing any existing one with the same name.
+    pub fn register(&mut self, format: Box<dyn OutputFormat>) {
+        self.formats.retain(|f| f.name() != format.name());
+        self.formats.push(format);
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.formats.iter().map(|f| f.name()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn OutputFormat> {
+        self.formats
+            .iter()
+            .find(|f| f.name() == name)
+            .map(|f| f.as_ref())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown output format '{name}' (available: {})",
+                    self.names().join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct CountFormat;
+
+    impl OutputFormat for CountFormat {
+        fn name(&self) -> &'static str {
+            "count"
+        }
+
+        fn write_heat(
+            &self,
+            out: &mut dyn Write,
+            _ctx: &OutputContext<'_>,
+            buckets: &[HeatBucket],
+        ) -> Result<()> {
+            writeln!(out, "heat {}", buckets.len())?;
+            Ok(())
+        }
+
+        fn write_churn(
+            &self,
+            out: &mut dyn Write,
+            _ctx: &OutputContext<'_>,
+            entries: &[ChurnEntry],
+        ) -> Result<()> {
+            writeln!(out, "churn {}", entries.len())?;
+            Ok(())
+        }
+
+        fn write_export(
+            &self,
+            out: &mut dyn Write,
+            _ctx: &OutputContext<'_>,
+            entries: &[ExportEntry],
+        ) -> Result<()> {
+            writeln!(out, "export {}", entries.len())?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn registered_format_is_dispatched_by_name() {
+        let mut registry = FormatRegistry::builtin();
+        registry.register(Box::new(CountFormat));
+        assert_eq!(
+            registry.names(),
+            ["table", "json", "ndjson", "csv", "prometheus", "count"]
+        );
+
+        let bucket = HeatBucket {
+            week: "2024-W01".to_string(),
+            commit_count: 2,
+            lines_changed: 3,
+            lines_added: 2,
+            lines_deleted: 1,
+            binary_files_changed: 0,
+            bytes_added: 0,
+            bytes_deleted: 0,
+            tags: Vec::new(),
+            label: None,
+        };
+        let mut out = Vec::new();
+        registry
+            .get("count")
+            .unwrap()
+            .write_heat(
+                &mut out,
+                &OutputContext::default(),
+                &[bucket.clone(), bucket],
+            )
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "heat 2\n");
+
+        let err = registry.get("yaml").err().unwrap().to_string();
+        assert!(
+            err.contains("available: table, json, ndjson, csv, prometheus, count"),
+            "{err}"
+        );
+    }
+}
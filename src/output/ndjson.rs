@@ -0,0 +1,63 @@
+use super::{OutputContext, OutputFormat};
+use crate::model::{ChurnEntry, ExportEntry, HeatBucket, NdjsonHeader};
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+/// One JSON object per line, with run metadata only as an opt-in first line.
+pub struct NdjsonFormat;
+
+/// The `--ndjson-header` line for `command`, if it was asked for.
+fn write_header(out: &mut dyn Write, ctx: &OutputContext<'_>, command: &str) -> Result<()> {
+    if ctx.ndjson_header {
+        let header = NdjsonHeader::new(command, ctx.repository_path.clone(), ctx.since, ctx.until);
+        write_lines(out, &[header])?;
+    }
+    Ok(())
+}
+
+fn write_lines<T: Serialize>(out: &mut dyn Write, items: &[T]) -> Result<()> {
+    for item in items {
+        serde_json::to_writer(&mut *out, item)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+impl OutputFormat for NdjsonFormat {
+    fn name(&self) -> &'static str {
+        "ndjson"
+    }
+
+    /// Buckets first, then growth points when requested.
+    fn write_heat(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        buckets: &[HeatBucket],
+    ) -> Result<()> {
+        write_header(out, ctx, "heat")?;
+        write_lines(out, buckets)?;
+        write_lines(out, ctx.growth.unwrap_or_default())
+    }
+
+    fn write_churn(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ChurnEntry],
+    ) -> Result<()> {
+        write_header(out, ctx, "churn")?;
+        write_lines(out, entries)
+    }
+
+    fn write_export(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ExportEntry],
+    ) -> Result<()> {
+        write_header(out, ctx, "export")?;
+        write_lines(out, entries)
+    }
+}
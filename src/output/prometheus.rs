@@ -0,0 +1,255 @@
+use super::{OutputContext, OutputFormat};
+use crate::model::{ChurnEntry, ExportEntry, HeatBucket};
+use anyhow::Result;
+use std::io::Write;
+
+/// Prometheus text exposition, for the node_exporter textfile collector.
+pub struct PrometheusFormat;
+
+/// Escape a label value: backslash, double quote and line feed.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Write one gauge family: `# HELP`, `# TYPE`, then a sample per item.
+fn write_gauge<T>(
+    out: &mut dyn Write,
+    name: &str,
+    help: &str,
+    items: &[T],
+    labels: impl Fn(&T) -> Vec<(&'static str, String)>,
+    value: impl Fn(&T) -> f64,
+) -> Result<()> {
+    writeln!(out, "# HELP {name} {help}")?;
+    writeln!(out, "# TYPE {name} gauge")?;
+    for item in items {
+        let labels: Vec<String> = labels(item)
+            .into_iter()
+            .map(|(k, v)| format!("{k}=\"{}\"", escape_label(&v)))
+            .collect();
+        writeln!(out, "{name}{{{}}} {}", labels.join(","), value(item))?;
+    }
+    Ok(())
+}
+
+impl OutputFormat for PrometheusFormat {
+    fn name(&self) -> &'static str {
+        "prometheus"
+    }
+
+    fn write_heat(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        buckets: &[HeatBucket],
+    ) -> Result<()> {
+        let labels = |b: &HeatBucket| {
+            vec![
+                ("repository", ctx.repository_path.clone()),
+                ("week", b.week.clone()),
+            ]
+        };
+        write_gauge(
+            out,
+            "gmap_commits",
+            "Commits in the period.",
+            buckets,
+            labels,
+            |b| b.commit_count as f64,
+        )?;
+        write_gauge(
+            out,
+            "gmap_lines_changed",
+            "Lines added plus deleted in the period.",
+            buckets,
+            labels,
+            |b| b.lines_changed as f64,
+        )?;
+        write_gauge(
+            out,
+            "gmap_lines_added",
+            "Lines added in the period.",
+            buckets,
+            labels,
+            |b| b.lines_added as f64,
+        )?;
+        write_gauge(
+            out,
+            "gmap_lines_deleted",
+            "Lines deleted in the period.",
+            buckets,
+            labels,
+            |b| b.lines_deleted as f64,
+        )?;
+        write_gauge(
+            out,
+            "gmap_binary_files_changed",
+            "Binary file changes in the period.",
+            buckets,
+            labels,
+            |b| b.binary_files_changed as f64,
+        )
+    }
+
+    fn write_churn(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ChurnEntry],
+    ) -> Result<()> {
+        let labels = |e: &ChurnEntry| {
+            vec![
+                ("repository", ctx.repository_path.clone()),
+                ("path", e.path.clone()),
+            ]
+        };
+        write_gauge(
+            out,
+            "gmap_churn_lines",
+            "Lines added plus deleted for the path.",
+            entries,
+            labels,
+            |e| e.total_lines as f64,
+        )?;
+        write_gauge(
+            out,
+            "gmap_churn_commits",
+            "Commits touching the path.",
+            entries,
+            labels,
+            |e| e.commit_count as f64,
+        )
+    }
+
+    /// Export is per commit, which does not map onto time series; emit totals.
+    fn write_export(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ExportEntry],
+    ) -> Result<()> {
+        let totals = [(
+            entries.len() as f64,
+            entries
+                .iter()
+                .flat_map(|e| &e.files)
+                .map(|f| (f.added_lines + f.deleted_lines) as f64)
+                .sum::<f64>(),
+        )];
+        let labels = |_: &(f64, f64)| vec![("repository", ctx.repository_path.clone())];
+        write_gauge(
+            out,
+            "gmap_export_commits",
+            "Commits in the export.",
+            &totals,
+            labels,
+            |t| t.0,
+        )?;
+        write_gauge(
+            out,
+            "gmap_export_lines_changed",
+            "Lines added plus deleted across the export.",
+            &totals,
+            labels,
+            |t| t.1,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Sample = (String, Vec<(String, String)>, f64);
+
+    /// Minimal exposition-format check: comments are HELP/TYPE, samples are
+    /// `name{k="v",...} number` with properly escaped label values.
+    fn parse_samples(text: &str) -> Vec<Sample> {
+        let mut samples = Vec::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                assert!(
+                    comment.starts_with("HELP gmap_") || comment.starts_with("TYPE gmap_"),
+                    "unexpected comment: {line}"
+                );
+                continue;
+            }
+            let (name, rest) = line.split_once('{').expect("labels");
+            assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+            let (labels, value) = rest.rsplit_once("} ").expect("value");
+
+            let mut parsed = Vec::new();
+            let mut chars = labels.chars().peekable();
+            while chars.peek().is_some() {
+                let key: String = chars.by_ref().take_while(|&c| c != '=').collect();
+                assert_eq!(chars.next(), Some('"'), "label values are quoted");
+                let mut val = String::new();
+                loop {
+                    match chars.next().expect("unterminated label") {
+                        '\\' => match chars.next() {
+                            Some('n') => val.push('\n'),
+                            Some(c @ ('\\' | '"')) => val.push(c),
+                            other => panic!("bad escape {other:?}"),
+                        },
+                        '"' => break,
+                        c => val.push(c),
+                    }
+                }
+                parsed.push((key, val));
+                if chars.peek() == Some(&',') {
+                    chars.next();
+                }
+            }
+            samples.push((name.to_string(), parsed, value.parse().expect("number")));
+        }
+        samples
+    }
+
+    #[test]
+    fn heat_output_is_valid_exposition() {
+        let buckets = vec![HeatBucket {
+            week: "2024-W05".to_string(),
+            commit_count: 12,
+            lines_changed: 30,
+            lines_added: 20,
+            lines_deleted: 10,
+            binary_files_changed: 2,
+            bytes_added: 0,
+            bytes_deleted: 0,
+            tags: Vec::new(),
+            label: None,
+        }];
+        let ctx = OutputContext {
+            repository_path: "C:\\repos\\\"odd\"\nname".to_string(),
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        PrometheusFormat
+            .write_heat(&mut out, &ctx, &buckets)
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("# HELP gmap_commits "));
+        assert!(text.contains("# TYPE gmap_commits gauge\n"));
+
+        let samples = parse_samples(&text);
+        assert_eq!(samples.len(), 5);
+        let (name, labels, value) = &samples[0];
+        assert_eq!(name, "gmap_commits");
+        assert_eq!(*value, 12.0);
+        assert_eq!(
+            labels,
+            &vec![
+                ("repository".to_string(), ctx.repository_path.clone()),
+                ("week".to_string(), "2024-W05".to_string()),
+            ]
+        );
+        assert_eq!(samples[3].0, "gmap_lines_deleted");
+        assert_eq!(samples[3].2, 10.0);
+        assert_eq!(samples[4].0, "gmap_binary_files_changed");
+        assert_eq!(samples[4].2, 2.0);
+    }
+}
@@ -0,0 +1,357 @@
+use super::{OutputContext, OutputFormat};
+use crate::model::{ChurnEntry, ExportEntry, HeatBucket};
+use crate::tui::draw::Intensity;
+use crate::util::{anomalies, Anomaly, ANOMALY_SIGMAS};
+use anyhow::Result;
+use console::style;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::io::Write;
+
+/// Human-readable terminal output; the default when no format is chosen.
+pub struct TableFormat;
+
+const COMMIT_RAMP: [&str; 6] = [" ", "▁", "▃", "▅", "▇", "█"];
+const LINES_RAMP: [&str; 6] = [" ", "░", "▒", "▓", "█", "█"];
+/// Characters per churn sparkline; longer series are summed down to fit.
+const SPARK_WIDTH: usize = 12;
+
+fn intensity_char<'a>(ratio: f64, symbols: &'a [&str]) -> &'a str {
+    let levels = (symbols.len() - 1) as f64;
+    let level = ((ratio * levels).round() as usize).min(symbols.len() - 1);
+    symbols[level]
+}
+
+/// `values` as at most `width` characters of `symbols`, relative to the
+/// largest; each character sums an equal share of consecutive values.
+fn sparkline(values: &[u64], width: usize, symbols: &[&str]) -> String {
+    let slots = values.len().min(width);
+    let sums: Vec<u64> = (0..slots)
+        .map(|j| {
+            let (start, end) = (j * values.len() / slots, (j + 1) * values.len() / slots);
+            values[start..end].iter().sum()
+        })
+        .collect();
+    let max = sums.iter().copied().max().unwrap_or(0).max(1) as f64;
+    sums.iter()
+        .map(|&sum| intensity_char(sum as f64 / max, symbols))
+        .collect()
+}
+
+/// `↑`, `↓` or `→` per bucket for commits against the bucket before it; the
+/// first bucket has nothing to compare with and gets a blank.
+fn trend_arrows(buckets: &[HeatBucket]) -> Vec<&'static str> {
+    let mut previous = None;
+    buckets
+        .iter()
+        .map(|bucket| {
+            let arrow = match previous.map(|p: u32| bucket.commit_count.cmp(&p)) {
+                None => " ",
+                Some(Ordering::Greater) => "↑",
+                Some(Ordering::Less) => "↓",
+                Some(Ordering::Equal) => "→",
+            };
+            previous = Some(bucket.commit_count);
+            arrow
+        })
+        .collect()
+}
+
+impl OutputFormat for TableFormat {
+    fn name(&self) -> &'static str {
+        "table"
+    }
+
+    fn write_heat(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        buckets: &[HeatBucket],
+    ) -> Result<()> {
+        if buckets.is_empty() {
+            writeln!(out, "No data to display")?;
+            return Ok(());
+        }
+
+        match (ctx.since, ctx.until) {
+            (Some(since), Some(until)) => {
+                writeln!(out, "Filtering commits from {since} to {until}")?;
+            }
+            (Some(since), None) => {
+                writeln!(out, "Filtering commits since {since}")?;
+            }
+            (None, Some(until)) => {
+                writeln!(out, "Filtering commits until {until}")?;
+            }
+            _ => {}
+        }
+
+        let charted: Vec<f64> = match ctx.smoothed {
+            Some(smoothed) => smoothed.to_vec(),
+            None => buckets.iter().map(|b| b.commit_count as f64).collect(),
+        };
+        let commits = Intensity::new(ctx.scale, charted.iter().copied());
+        let changed = |b: &HeatBucket| {
+            if ctx.bytes {
+                b.bytes_changed()
+            } else {
+                b.lines_changed
+            }
+        };
+        let unit = if ctx.bytes { "bytes" } else { "lines" };
+        let lines = Intensity::new(ctx.scale, buckets.iter().map(|b| changed(b) as f64));
+
+        writeln!(out, "{}", style("Commit Activity Heatmap").bold())?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let trends = trend_arrows(buckets);
+        let counts: Vec<f64> = buckets.iter().map(|b| b.commit_count as f64).collect();
+        let anomalies = anomalies(&counts, ANOMALY_SIGMAS);
+        for (i, bucket) in buckets.iter().enumerate() {
+            if ctx.hide_empty && bucket.commit_count == 0 {
+                continue;
+            }
+            let commit_char = intensity_char(commits.ratio(charted[i]), &COMMIT_RAMP);
+            let lines_char = intensity_char(lines.ratio(changed(bucket) as f64), &LINES_RAMP);
+
+            let trend = match trends[i] {
+                "↑" => style(trends[i]).green(),
+                "↓" => style(trends[i]).red(),
+                arrow => style(arrow).dim(),
+            };
+            write!(
+                out,
+                "{} {} {} {} commits: {:>3}, {unit}: {:>6}",
+                bucket.display_label(),
+                style(commit_char).green(),
+                style(lines_char).blue(),
+                trend,
+                bucket.commit_count,
+                changed(bucket)
+            )?;
+            if ctx.smoothed.is_some() {
+                write!(out, ", avg: {:>5.1}", charted[i])?;
+            }
+            if bucket.binary_files_changed > 0 {
+                write!(out, ", binary: {}", bucket.binary_files_changed)?;
+            }
+            if let Some(point) = ctx.growth.and_then(|g| g.get(i)) {
+                write!(
+                    out,
+                    ", net: {:>+7}, total: {:>8}",
+                    point.net_lines, point.cumulative_lines
+                )?;
+            }
+            match anomalies.get(&i) {
+                Some(Anomaly::Busy) => write!(out, "  {}", style("▲ busy").red())?,
+                Some(Anomaly::Quiet) => write!(out, "  {}", style("▼ quiet").cyan())?,
+                None => {}
+            }
+            if !bucket.tags.is_empty() {
+                write!(out, "  {} {}", style("◆").yellow(), bucket.tags.join(", "))?;
+            }
+            writeln!(out)?;
+        }
+
+        if ctx.hide_empty {
+            let hidden = buckets.iter().filter(|b| b.commit_count == 0).count();
+            let commits: u64 = buckets.iter().map(|b| b.commit_count as u64).sum();
+            writeln!(
+                out,
+                "Total: {commits} commits over {} periods ({hidden} empty hidden)",
+                buckets.len()
+            )?;
+        }
+
+        writeln!(out, "\n{}", style("Legend").bold())?;
+        match ctx.smoothed {
+            Some(_) => writeln!(
+                out,
+                "  {} commits intensity (moving average)",
+                style("▁▃▅▇█").green()
+            )?,
+            None => writeln!(out, "  {} commits intensity", style("▁▃▅▇█").green())?,
+        }
+        writeln!(out, "  {} {unit} intensity", style("░▒▓█").blue())?;
+        writeln!(out, "  ↑↓→ commits vs the previous period")?;
+        if !anomalies.is_empty() {
+            writeln!(
+                out,
+                "  ▲ busy / ▼ quiet: commits more than {ANOMALY_SIGMAS}σ from the mean period"
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_churn(
+        &self,
+        out: &mut dyn Write,
+        ctx: &OutputContext<'_>,
+        entries: &[ChurnEntry],
+    ) -> Result<()> {
+        write!(
+            out,
+            "{:<50} {:>8} {:>8} {:>8} {:>6} {:>8}",
+            style("Path").bold(),
+            style("Added").bold(),
+            style("Deleted").bold(),
+            style("Total").bold(),
+            style("Commits").bold(),
+            style("Authors").bold()
+        )?;
+        if ctx.weighted {
+            write!(out, " {:>10}", style("Weighted").bold())?;
+        }
+        if ctx.velocity {
+            write!(out, " {:>10}", style("Lines/day").bold())?;
+        }
+        if ctx.bytes {
+            write!(
+                out,
+                " {:>10} {:>10}",
+                style("Bytes +").bold(),
+                style("Bytes -").bold()
+            )?;
+        }
+        if ctx.churn_series.is_some() {
+            write!(
+                out,
+                "  {:<w$} {:<w$}",
+                style("Commit trend").bold(),
+                style("Line trend").bold(),
+                w = SPARK_WIDTH
+            )?;
+        }
+        writeln!(out)?;
+        let width = 98
+            + if ctx.weighted { 11 } else { 0 }
+            + if ctx.velocity { 11 } else { 0 }
+            + if ctx.bytes { 22 } else { 0 }
+            + if ctx.churn_series.is_some() {
+                2 * SPARK_WIDTH + 3
+            } else {
+                0
+            };
+        writeln!(out, "{}", "─".repeat(width))?;
+        for e in entries.iter().take(50) {
+            write!(
+                out,
+                "{:<50} {:>8} {:>8} {:>8} {:>6} {:>8}",
+                e.path,
+                e.added_lines,
+                e.deleted_lines,
+                e.total_lines,
+                e.commit_count,
+                e.authors.len()
+            )?;
+            if ctx.weighted {
+                write!(out, " {:>10.1}", e.weighted_score)?;
+            }
+            if ctx.velocity {
+                write!(out, " {:>10.1}", e.lines_per_day())?;
+            }
+            if ctx.bytes {
+                write!(out, " {:>10} {:>10}", e.bytes_added, e.bytes_deleted)?;
+            }
+            if let Some(series) = ctx.churn_series.and_then(|s| s.get(&e.path)) {
+                write!(
+                    out,
+                    "  {:<w$} {:<w$}",
+                    style(sparkline(&series.commits, SPARK_WIDTH, &COMMIT_RAMP)).green(),
+                    style(sparkline(&series.lines, SPARK_WIDTH, &LINES_RAMP)).blue(),
+                    w = SPARK_WIDTH
+                )?;
+            }
+            writeln!(out)?;
+        }
+        if entries.len() > 50 {
+            writeln!(out, "\n... and {} more entries", entries.len() - 50)?;
+        }
+        Ok(())
+    }
+
+    fn write_export(
+        &self,
+        out: &mut dyn Write,
+        _ctx: &OutputContext<'_>,
+        entries: &[ExportEntry],
+    ) -> Result<()> {
+        writeln!(out, "{}", style("Export Summary").bold())?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let total_commits = entries.len();
+        let total_files: usize = entries
+            .iter()
+            .map(|e| e.summarized_files.unwrap_or(e.files.len()))
+            .sum();
+        let total_added: u64 = entries
+            .iter()
+            .flat_map(|e| &e.files)
+            .map(|f| f.added_lines as u64)
+            .sum();
+        let total_deleted: u64 = entries
+            .iter()
+            .flat_map(|e| &e.files)
+            .map(|f| f.deleted_lines as u64)
+            .sum();
+
+        let unique_authors: HashSet<_> = entries.iter().map(|e| &e.author_name).collect();
+
+        writeln!(out, "Total commits: {}", style(total_commits).cyan())?;
+        writeln!(out, "Total files changed: {}", style(total_files).cyan())?;
+        writeln!(out, "Total lines added: {}", style(total_added).green())?;
+        writeln!(out, "Total lines deleted: {}", style(total_deleted).red())?;
+        writeln!(
+            out,
+            "Unique authors: {}",
+            style(unique_authors.len()).yellow()
+        )?;
+        let outliers = entries.iter().filter(|e| e.is_outlier).count();
+        if outliers > 0 {
+            writeln!(out, "Outlier commits: {}", style(outliers).magenta())?;
+        }
+
+        if let (Some(first_commit), Some(last_commit)) = (entries.first(), entries.last()) {
+            writeln!(
+                out,
+                "Date range: {} to {}",
+                style(first_commit.timestamp.format("%Y-%m-%d")).dim(),
+                style(last_commit.timestamp.format("%Y-%m-%d")).dim()
+            )?;
+        }
+
+        writeln!(
+            out,
+            "\nUse --json, --ndjson or --format <name> to export the raw data."
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trend_follows_commits_against_the_previous_period() {
+        let buckets: Vec<_> = [1, 3, 5, 5, 2, 0]
+            .into_iter()
+            .enumerate()
+            .map(|(i, commit_count)| HeatBucket {
+                week: format!("2024-W{:02}", i + 1),
+                commit_count,
+                lines_changed: 0,
+                lines_added: 0,
+                lines_deleted: 0,
+                binary_files_changed: 0,
+                bytes_added: 0,
+                bytes_deleted: 0,
+                tags: Vec::new(),
+                label: None,
+            })
+            .collect();
+        assert_eq!(trend_arrows(&buckets), [" ", "↑", "↑", "→", "↓", "↓"]);
+        assert!(trend_arrows(&[]).is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::cli::CommonArgs;
+use crate::model::{SizeEntry, SizeOutput, TreeFile, SCHEMA_VERSION};
+use crate::util::{path_excluded, to_json};
+use anyhow::Context;
+use chrono::Utc;
+use console::style;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Count files, lines and bytes in HEAD's tree per extension: a snapshot of
+/// the codebase as it is, rather than how it changed.
+pub fn exec(
+    common: CommonArgs,
+    json: bool,
+    compact: bool,
+    path: Option<String>,
+) -> anyhow::Result<()> {
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let head = repo.head_commit_id().context("Failed to resolve HEAD")?;
+    let files = {
+        let _phase = crate::timing::phase("tree walk");
+        repo.tree_files(head)
+            .context("Failed to read HEAD's tree")?
+    };
+    let size = compute_size(
+        &files,
+        path.as_deref(),
+        &common.exclude,
+        common.case_sensitive,
+    );
+
+    if json {
+        let output = SizeOutput {
+            version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            repository_path: repo.path().display().to_string(),
+            path_prefix: path.unwrap_or_default(),
+            commit_id: head.to_string(),
+            total_files: size.extensions.iter().map(|e| e.files).sum(),
+            total_lines: size.extensions.iter().map(|e| e.lines).sum(),
+            total_bytes: size.extensions.iter().map(|e| e.bytes).sum(),
+            binary_files: size.binary_files,
+            extensions: size.extensions,
+        };
+        println!("{}", to_json(&output, compact)?);
+    } else {
+        output_table(&size);
+    }
+    Ok(())
+}
+
+/// Per-extension totals of a tree, largest by lines first.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SizeSummary {
+    pub extensions: Vec<SizeEntry>,
+    pub binary_files: u64,
+}
+
+/// Sum text files per extension under `path_prefix`, skipping excluded paths.
+/// Binaries are only counted.
+pub fn compute_size(
+    files: &[TreeFile],
+    path_prefix: Option<&str>,
+    excludes: &[String],
+    case_sensitive: bool,
+) -> SizeSummary {
+    let mut by_ext: HashMap<String, SizeEntry> = HashMap::new();
+    let mut binary_files = 0;
+    for f in files {
+        if path_prefix.is_some_and(|prefix| !f.path.starts_with(prefix))
+            || path_excluded(&f.path, excludes, case_sensitive)
+        {
+            continue;
+        }
+        if f.is_binary {
+            binary_files += 1;
+            continue;
+        }
+        let extension = Path::new(&f.path)
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let entry = by_ext
+            .entry(extension.clone())
+            .or_insert_with(|| SizeEntry {
+                extension,
+                files: 0,
+                lines: 0,
+                bytes: 0,
+            });
+        entry.files += 1;
+        entry.lines += f.lines;
+        entry.bytes += f.bytes;
+    }
+    let mut extensions: Vec<SizeEntry> = by_ext.into_values().collect();
+    extensions.sort_by(|a, b| {
+        b.lines
+            .cmp(&a.lines)
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
+    SizeSummary {
+        extensions,
+        binary_files,
+    }
+}
+
+fn output_table(size: &SizeSummary) {
+    println!(
+        "{:<12} {:>8} {:>10} {:>12}",
+        style("Extension").bold(),
+        style("Files").bold(),
+        style("Lines").bold(),
+        style("Bytes").bold()
+    );
+    for e in &size.extensions {
+        let name = if e.extension.is_empty() {
+            "(none)".to_string()
+        } else {
+            format!(".{}", e.extension)
+        };
+        println!(
+            "{:<12} {:>8} {:>10} {:>12}",
+            style(name).cyan(),
+            e.files,
+            e.lines,
+            e.bytes
+        );
+    }
+    println!("{}", "─".repeat(45));
+    println!(
+        "{:<12} {:>8} {:>10} {:>12}",
+        "Total",
+        size.extensions.iter().map(|e| e.files).sum::<u64>(),
+        style(size.extensions.iter().map(|e| e.lines).sum::<u64>()).green(),
+        size.extensions.iter().map(|e| e.bytes).sum::<u64>()
+    );
+    if size.binary_files > 0 {
+        println!("{} binary files not counted", size.binary_files);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, lines: u64, bytes: u64, is_binary: bool) -> TreeFile {
+        TreeFile {
+            path: path.to_string(),
+            lines,
+            bytes,
+            is_binary,
+        }
+    }
+
+    #[test]
+    fn sums_files_and_lines_per_extension() {
+        let files = vec![
+            file("src/main.rs", 120, 3000, false),
+            file("src/lib.RS", 30, 800, false),
+            file("README.md", 40, 1200, false),
+            file("Makefile", 10, 200, false),
+            file("assets/logo.png", 0, 9000, true),
+            file("vendor/dep.rs", 500, 12000, false),
+        ];
+
+        let size = compute_size(&files, None, &["vendor/".to_string()], false);
+
+        assert_eq!(
+            size.extensions,
+            vec![
+                SizeEntry {
+                    extension: "rs".into(),
+                    files: 2,
+                    lines: 150,
+                    bytes: 3800
+                },
+                SizeEntry {
+                    extension: "md".into(),
+                    files: 1,
+                    lines: 40,
+                    bytes: 1200
+                },
+                SizeEntry {
+                    extension: "".into(),
+                    files: 1,
+                    lines: 10,
+                    bytes: 200
+                },
+            ]
+        );
+        assert_eq!(size.binary_files, 1);
+
+        let src = compute_size(&files, Some("src/"), &[], false);
+        assert_eq!(src.extensions.len(), 1);
+        assert_eq!(src.extensions[0].files, 2);
+    }
+}
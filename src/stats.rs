@@ -0,0 +1,153 @@
+use crate::cli::CommonArgs;
+use crate::heat::{aggregate_weeks_cached, fetch_commit_stats_reported};
+use crate::model::{StatsOutput, StatsSummary, SCHEMA_VERSION};
+use crate::tui::WeekStats;
+use crate::util::to_json;
+use anyhow::Context;
+use chrono::Utc;
+use console::style;
+
+pub fn exec(
+    common: CommonArgs,
+    json: bool,
+    compact: bool,
+    path: Option<String>,
+    monthly: bool,
+    vs_previous_period: bool,
+) -> anyhow::Result<()> {
+    let repo = common
+        .open_repo()
+        .context("Failed to open git repository")?;
+    let mut cache = common
+        .open_cache(&repo)
+        .context("Failed to initialize cache")?;
+
+    let range = common
+        .resolve_range(&repo)
+        .context("Failed to resolve date range")?;
+
+    let (all_stats, mut report) = fetch_commit_stats_reported(
+        &repo,
+        &mut cache,
+        &range,
+        common.merge_policy(),
+        common.binary_policy(),
+        common.recompute,
+        true,
+    )?;
+
+    let gi = common.git_ignore(&repo);
+    let weeks = aggregate_weeks_cached(
+        &all_stats,
+        &cache,
+        path.as_deref(),
+        &common.commit_filter(),
+        common.period_spec(monthly),
+        &common.exclude,
+        gi.as_ref(),
+    );
+    let summary = summarize_weeks(&weeks);
+    let root_commits_excluded = common.exclude_root.then(|| {
+        all_stats
+            .iter()
+            .filter_map(|s| cache.get_commit_info(&s.commit_id).ok().flatten())
+            .filter(|info| info.parent_ids.is_empty())
+            .count()
+    });
+    let previous_period = if vs_previous_period {
+        let (comparison, comparison_report) = crate::compare::vs_previous_period(
+            &repo,
+            &mut cache,
+            &common,
+            &range,
+            &all_stats,
+            path.as_deref(),
+        )?;
+        report += comparison_report;
+        Some(comparison)
+    } else {
+        None
+    };
+
+    if json {
+        let output = StatsOutput {
+            version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            repository_path: repo.path().display().to_string(),
+            path_prefix: path.unwrap_or_default(),
+            since: common.since_arg().map(str::to_string),
+            until: common.until_arg().map(str::to_string),
+            summary,
+            root_commits_excluded,
+            previous_period,
+        };
+        println!("{}", to_json(&output, compact)?);
+    } else {
+        output_table(&summary, root_commits_excluded);
+        if let Some(p) = &previous_period {
+            println!();
+            crate::compare::output_table(&p.baseline, &p.current, &p.delta);
+        }
+    }
+    report.announce();
+
+    Ok(())
+}
+
+/// Repository-wide figures over `weeks`, as shown in the TUI Stats view.
+pub fn summarize_weeks(weeks: &[WeekStats]) -> StatsSummary {
+    let total_commits: usize = weeks.iter().map(|w| w.commits).sum();
+    let lines_added: usize = weeks.iter().map(|w| w.lines_added).sum();
+    let lines_deleted: usize = weeks.iter().map(|w| w.lines_deleted).sum();
+    let avg_commits = if weeks.is_empty() {
+        0.0
+    } else {
+        total_commits as f64 / weeks.len() as f64
+    };
+
+    let mut commits_by_hour = [0u64; 24];
+    for w in weeks {
+        for (total, count) in commits_by_hour.iter_mut().zip(w.commits_by_hour) {
+            *total += count;
+        }
+    }
+    let busiest_hour = (0..24).max_by_key(|&h| commits_by_hour[h]).unwrap_or(0) as u32;
+
+    StatsSummary {
+        periods: weeks.len(),
+        total_commits,
+        lines_added,
+        lines_deleted,
+        net_change: lines_added as i64 - lines_deleted as i64,
+        avg_commits,
+        max_commits: weeks.iter().map(|w| w.commits).max().unwrap_or(0),
+        min_commits: weeks.iter().map(|w| w.commits).min().unwrap_or(0),
+        commits_by_hour,
+        busiest_hour,
+        trend: weeks.iter().map(|w| w.commits as u64).collect(),
+    }
+}
+
+fn output_table(summary: &StatsSummary, root_commits_excluded: Option<usize>) {
+    println!("{}", style("Repository Statistics").bold());
+    println!("{}", "─".repeat(50));
+    println!("Periods: {}", style(summary.periods).cyan());
+    println!("Total commits: {}", style(summary.total_commits).green());
+    println!(
+        "Lines added: {}",
+        style(format!("+{}", summary.lines_added)).green()
+    );
+    println!(
+        "Lines deleted: {}",
+        style(format!("-{}", summary.lines_deleted)).red()
+    );
+    println!("Net change: {:+}", summary.net_change);
+    if let Some(roots) = root_commits_excluded {
+        println!("Root commits excluded: {}", style(roots).yellow());
+    }
+    println!();
+    println!("Average commits per period: {:.1}", summary.avg_commits);
+    println!("Maximum period: {}", summary.max_commits);
+    println!("Minimum period: {}", summary.min_commits);
+    println!("Busiest hour (UTC): {:02}:00", summary.busiest_hour);
+}
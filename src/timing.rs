@@ -0,0 +1,103 @@
+//! Opt-in diagnostics on stderr: phase timings at `-v`, counts at `-vv`/`--debug`.
+//!
+//! Silent by default, and never written to stdout, so machine-readable output
+//! is unaffected.
+
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+static CACHE_HITS: AtomicUsize = AtomicUsize::new(0);
+static ANALYZED: AtomicUsize = AtomicUsize::new(0);
+
+/// Level at which phase timings are logged.
+pub const TIMINGS: u8 = 1;
+/// Level at which counts (commits walked, cache hits/misses) are logged.
+pub const COUNTS: u8 = 2;
+
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+pub fn enabled(level: u8) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= level
+}
+
+/// Log `message` to stderr when verbosity is at least `level`.
+pub fn log(level: u8, message: impl FnOnce() -> String) {
+    if enabled(level) {
+        eprintln!("[gmap] {}", message());
+    }
+}
+
+/// Times a phase from creation until drop.
+#[must_use = "the phase is timed until this guard is dropped"]
+pub struct Phase {
+    name: &'static str,
+    start: Instant,
+}
+
+/// Start timing `name`; the elapsed time is logged when the guard drops.
+pub fn phase(name: &'static str) -> Phase {
+    Phase {
+        name,
+        start: Instant::now(),
+    }
+}
+
+impl Drop for Phase {
+    fn drop(&mut self) {
+        log(TIMINGS, || {
+            format!(
+                "{}: {:.1} ms",
+                self.name,
+                self.start.elapsed().as_secs_f64() * 1000.0
+            )
+        });
+    }
+}
+
+/// Count one fetch's cache hits and newly analyzed commits toward the run
+/// summary.
+pub fn record_fetch(cached: usize, computed: usize) {
+    CACHE_HITS.fetch_add(cached, Ordering::Relaxed);
+    ANALYZED.fetch_add(computed, Ordering::Relaxed);
+}
+
+/// What a run did, for `--summary-json-stderr`.
+#[derive(Debug, serde::Serialize)]
+pub struct RunSummary<'a> {
+    pub command: &'a str,
+    pub exit_code: u8,
+    pub since: Option<&'a str>,
+    pub until: Option<&'a str>,
+    pub duration_ms: u64,
+    /// Commits read, from the cache or analyzed
+    pub commits: usize,
+    pub cache_hits: usize,
+    pub analyzed: usize,
+}
+
+impl<'a> RunSummary<'a> {
+    /// Summarize a run with the fetch counts recorded so far.
+    pub fn new(
+        command: &'a str,
+        exit_code: u8,
+        since: Option<&'a str>,
+        until: Option<&'a str>,
+        elapsed: Duration,
+    ) -> Self {
+        let cache_hits = CACHE_HITS.load(Ordering::Relaxed);
+        let analyzed = ANALYZED.load(Ordering::Relaxed);
+        Self {
+            command,
+            exit_code,
+            since,
+            until,
+            duration_ms: elapsed.as_millis() as u64,
+            commits: cache_hits + analyzed,
+            cache_hits,
+            analyzed,
+        }
+    }
+}
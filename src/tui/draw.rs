@@ -1,15 +1,67 @@
 use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
 
 const LEVELS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
 
+/// How activity is mapped onto intensity levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum IntensityScale {
+    /// Proportional to the busiest period
+    #[default]
+    Linear,
+    /// Logarithmic, so one outlier doesn't flatten every other period
+    Log,
+    /// By rank among all periods
+    Percentile,
+}
+
+/// Maps values onto `0.0..=1.0` for a series, per an `IntensityScale`.
+pub struct Intensity {
+    scale: IntensityScale,
+    max: f64,
+    sorted: Vec<f64>,
+}
+
+impl Intensity {
+    pub fn new(scale: IntensityScale, values: impl IntoIterator<Item = f64>) -> Self {
+        let mut sorted: Vec<f64> = values.into_iter().collect();
+        sorted.sort_by(f64::total_cmp);
+        let max = sorted.last().copied().unwrap_or(0.0);
+        Self { scale, max, sorted }
+    }
+
+    /// Where `value` falls between none and the series' top intensity.
+    pub fn ratio(&self, value: f64) -> f64 {
+        if self.max <= 0.0 {
+            return 0.0;
+        }
+        let ratio = match self.scale {
+            IntensityScale::Linear => value / self.max,
+            IntensityScale::Log => value.max(0.0).ln_1p() / self.max.ln_1p(),
+            IntensityScale::Percentile => {
+                if self.sorted.len() < 2 {
+                    value / self.max
+                } else {
+                    let at_or_below = self.sorted.partition_point(|&v| v <= value);
+                    at_or_below.saturating_sub(1) as f64 / (self.sorted.len() - 1) as f64
+                }
+            }
+        };
+        ratio.clamp(0.0, 1.0)
+    }
+}
+
 /// Returns a compact intensity bar of fixed width (3) based on commits/max.
 pub fn enhanced_intensity_bar(commits: usize, max: usize) -> String {
-    const WIDTH: usize = 3;
     if max == 0 {
         return "▁▁▁".to_string();
     }
+    intensity_bar(commits as f64 / max as f64)
+}
 
-    let ratio = commits as f64 / max as f64;
+/// Fixed-width (3) bar for an intensity `ratio` in `0.0..=1.0`.
+pub fn intensity_bar(ratio: f64) -> String {
+    const WIDTH: usize = 3;
     let filled = ((ratio * WIDTH as f64).round() as usize).min(WIDTH);
     let intensity_idx =
         ((ratio * (LEVELS.len() - 1) as f64).round() as usize).min(LEVELS.len() - 1);
@@ -18,24 +70,120 @@ pub fn enhanced_intensity_bar(commits: usize, max: usize) -> String {
     bar_char.repeat(filled) + &"░".repeat(WIDTH - filled)
 }
 
-/// Chooses a style/color based on relative intensity of commit activity.
-pub fn get_intensity_color(commits: usize, max: usize) -> Style {
-    if max == 0 {
-        return Style::default().fg(Color::White);
-    }
-
-    let ratio = commits as f64 / max as f64;
-    if ratio > 0.8 {
-        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
-    } else if ratio > 0.6 {
-        Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD)
-    } else if ratio > 0.4 {
-        Style::default().fg(Color::Green)
-    } else if ratio > 0.2 {
-        Style::default().fg(Color::Cyan)
+/// Intensity color bands, busiest first: a ratio above the threshold gets
+/// that color. The last band catches everything else.
+const INTENSITY_BANDS: [(f64, Color); 5] = [
+    (0.8, Color::Red),
+    (0.6, Color::Yellow),
+    (0.4, Color::Green),
+    (0.2, Color::Cyan),
+    (0.0, Color::Blue),
+];
+
+fn band_style(band: usize) -> Style {
+    let style = Style::default().fg(INTENSITY_BANDS[band].1);
+    if band < 2 {
+        style.add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::Blue)
+        style
+    }
+}
+
+fn intensity_band(ratio: f64) -> usize {
+    INTENSITY_BANDS
+        .iter()
+        .position(|&(threshold, _)| ratio > threshold)
+        .unwrap_or(INTENSITY_BANDS.len() - 1)
+}
+
+/// Color for an intensity `ratio` in `0.0..=1.0`.
+pub fn intensity_style(ratio: f64) -> Style {
+    band_style(intensity_band(ratio))
+}
+
+/// The bare color of [`intensity_style`], for renderers without text styles.
+pub fn intensity_color(ratio: f64) -> Color {
+    INTENSITY_BANDS[intensity_band(ratio)].1
+}
+
+/// One-line key for the intensity colors, built from `INTENSITY_BANDS` so it
+/// always matches `intensity_style`.
+pub fn intensity_legend(scale: IntensityScale) -> Line<'static> {
+    let basis = match scale {
+        IntensityScale::Percentile => "percentile",
+        IntensityScale::Linear | IntensityScale::Log => "of busiest",
+    };
+    let mut spans = vec![Span::raw(format!("Intensity ({basis}): "))];
+    let mut upper = 100;
+    for (band, &(threshold, _)) in INTENSITY_BANDS.iter().enumerate() {
+        let lower = (threshold * 100.0).round() as u32;
+        let label = if band == 0 {
+            format!(">{lower}%")
+        } else if band == INTENSITY_BANDS.len() - 1 {
+            format!("≤{upper}%")
+        } else {
+            format!("{lower}-{upper}%")
+        };
+        spans.push(Span::styled(format!("■ {label}  "), band_style(band)));
+        upper = lower;
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series() -> Vec<f64> {
+        let mut values: Vec<f64> = (1..=10).map(f64::from).collect();
+        values.push(100.0);
+        values
+    }
+
+    #[test]
+    fn log_scale_lifts_mid_range_values_under_an_outlier() {
+        let linear = Intensity::new(IntensityScale::Linear, series());
+        let log = Intensity::new(IntensityScale::Log, series());
+
+        assert!((linear.ratio(10.0) - 0.1).abs() < 1e-9);
+        assert!(log.ratio(10.0) > 0.5, "got {}", log.ratio(10.0));
+        assert!(log.ratio(5.0) < log.ratio(10.0));
+        assert_eq!(linear.ratio(100.0), 1.0);
+        assert_eq!(log.ratio(100.0), 1.0);
+        assert_ne!(
+            intensity_bar(linear.ratio(10.0)),
+            intensity_bar(log.ratio(10.0))
+        );
+    }
+
+    #[test]
+    fn percentile_scale_ranks_values() {
+        let percentile = Intensity::new(IntensityScale::Percentile, series());
+
+        assert_eq!(percentile.ratio(1.0), 0.0);
+        assert!((percentile.ratio(6.0) - 0.5).abs() < 1e-9);
+        assert_eq!(percentile.ratio(100.0), 1.0);
+    }
+
+    #[test]
+    fn legend_follows_the_color_thresholds() {
+        let legend = intensity_legend(IntensityScale::Linear);
+        let bands: Vec<_> = legend.spans[1..]
+            .iter()
+            .map(|span| (span.content.trim().to_string(), span.style))
+            .collect();
+
+        assert_eq!(
+            bands,
+            [
+                ("■ >80%".to_string(), intensity_style(0.81)),
+                ("■ 60-80%".to_string(), intensity_style(0.8)),
+                ("■ 40-60%".to_string(), intensity_style(0.6)),
+                ("■ 20-40%".to_string(), intensity_style(0.4)),
+                ("■ ≤20%".to_string(), intensity_style(0.2)),
+            ]
+        );
+        assert_ne!(intensity_style(0.81), intensity_style(0.8));
+        assert_ne!(intensity_style(0.21), intensity_style(0.2));
     }
 }
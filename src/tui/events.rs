@@ -1,17 +1,17 @@
-use std::cell::RefCell;
 use std::io;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
 
 use crate::cache::Cache;
 use crate::cli::CommonArgs;
+use crate::filter::CommitFilter;
 use crate::git::GitRepo;
-use crate::heat::load_commit_details;
-use crate::model::{CommitStats, DateRange};
+use crate::heat::{load_commit_details, CommitScope};
+use crate::model::{CommitStats, MergePolicy};
 use crate::util::GitIgnoreMatcher;
 
 use super::input::ensure_selection_in_filtered;
-use super::state::{TuiState, ViewMode, WeekStats};
+use super::state::{FileSortColumn, TuiState, ViewMode, WeekStats};
 
 mod actions;
 mod input_modes;
@@ -19,20 +19,43 @@ mod input_modes;
 use actions::*;
 use input_modes::*;
 
+/// What the run loop shares with every event handler, fixed for the session.
+#[derive(Clone, Copy)]
+pub struct EventContext<'a> {
+    pub stats: &'a [CommitStats],
+    pub cache: &'a Cache,
+    /// Path prefix given on the command line; the `p` prompt overrides it
+    pub path: Option<&'a str>,
+    pub common: &'a CommonArgs,
+    pub filter: &'a CommitFilter,
+    pub repo: &'a GitRepo,
+    pub gi: Option<&'a GitIgnoreMatcher>,
+}
+
+impl<'a> EventContext<'a> {
+    /// The commits listed under `path_prefix` with periods `monthly` or weekly.
+    pub fn commit_scope<'b>(&self, path_prefix: Option<&'b str>, monthly: bool) -> CommitScope<'b>
+    where
+        'a: 'b,
+    {
+        CommitScope {
+            stats: self.stats,
+            cache: self.cache,
+            path_prefix,
+            filter: self.filter,
+            period_spec: self.common.period_spec(monthly),
+        }
+    }
+}
+
 /// Handle a keyboard event, mutating TUI state and returning `true` if the loop should exit.
 pub fn handle_key_events(
     key_event: KeyEvent,
     state: &mut TuiState,
     weeks: &mut Vec<WeekStats>,
-    stats: &mut Vec<CommitStats>,
-    cache: &mut Cache,
-    path: Option<&str>,
-    common: &CommonArgs,
-    repo: &GitRepo,
-    range: &DateRange,
-    gi: &RefCell<GitIgnoreMatcher>,
+    ctx: &EventContext,
     monthly_state: &mut bool,
-    include_merges_state: &mut bool,
+    merge_policy: &mut MergePolicy,
 ) -> io::Result<bool> {
     if key_event.kind != KeyEventKind::Press {
         return Ok(false);
@@ -55,18 +78,18 @@ pub fn handle_key_events(
         return Ok(false);
     }
 
+    if state.global_search_mode {
+        handle_global_search_input(key_event.code, state, weeks, ctx, *monthly_state);
+        return Ok(false);
+    }
+
     if state.path_mode {
-        handle_path_input(
-            key_event.code,
-            state,
-            weeks,
-            stats,
-            cache,
-            path,
-            common,
-            gi,
-            *monthly_state,
-        )?;
+        handle_path_input(key_event.code, state, weeks, ctx, *monthly_state)?;
+        return Ok(false);
+    }
+
+    if state.loading && key_event.code == KeyCode::Esc {
+        state.loading = false;
         return Ok(false);
     }
 
@@ -81,8 +104,14 @@ pub fn handle_key_events(
             state.commit_search_mode = true;
             state.commit_search_query.clear();
         }
+        KeyCode::Char('z') => toggle_fuzzy_search(state, weeks),
+        KeyCode::Char('i') => toggle_case_sensitive_search(state, weeks),
+        KeyCode::Char('f') => {
+            state.global_search_mode = true;
+            state.global_search_query.clear();
+        }
         KeyCode::Enter => {
-            try_load_commit_details(state, weeks, stats, cache, path, common, *monthly_state);
+            try_load_commit_details(state, weeks, ctx, *monthly_state);
         }
         KeyCode::Char('p') => {
             state.path_mode = true;
@@ -90,32 +119,30 @@ pub fn handle_key_events(
         }
         KeyCode::Char('c') => copy_full_hash(state),
         KeyCode::Char('y') => copy_short_hash(state),
-        KeyCode::Char('o') => open_commit_in_pager(state, repo),
+        KeyCode::Char('o') => open_commit_in_pager(state, ctx.repo),
+        KeyCode::Char('e') => open_file_in_editor(state, ctx.repo),
+        KeyCode::Char('[') => move_file_selection(state, false),
+        KeyCode::Char(']') => move_file_selection(state, true),
         KeyCode::Char('m') => {
-            toggle_monthly(state, weeks, stats, cache, path, common, gi, monthly_state)?;
+            toggle_monthly(state, weeks, ctx, monthly_state)?;
         }
         KeyCode::Char('M') => {
-            toggle_merges(
-                state,
-                weeks,
-                stats,
-                cache,
-                path,
-                common,
-                repo,
-                range,
-                gi,
-                include_merges_state,
-                *monthly_state,
-            )?;
-        }
-        KeyCode::Char('A') => {
-            toggle_show_all(state, weeks, stats, cache, path, common, gi, *monthly_state)?
-        }
-        KeyCode::Tab => state.tab_index = (state.tab_index + 1) % 4,
+            toggle_merges(state, merge_policy);
+        }
+        KeyCode::Char('A') => toggle_show_all(state, weeks, ctx, *monthly_state)?,
+        KeyCode::Char('>') => state.show_side_panel = !state.show_side_panel,
+        KeyCode::Char('d') => state.dense = !state.dense,
+        KeyCode::Char('H') => state.full_hash = !state.full_hash,
+        KeyCode::Char('s') => {
+            state.file_sort = Some(state.file_sort.map_or(FileSortColumn::Ext, |c| c.next()));
+        }
+        KeyCode::Char('S') => state.file_sort_desc = !state.file_sort_desc,
+        KeyCode::Char('w') => state.treemap_all_periods = !state.treemap_all_periods,
+        KeyCode::Char('a') => state.smooth = !state.smooth,
+        KeyCode::Tab => state.tab_index = (state.tab_index + 1) % 5,
         KeyCode::BackTab => {
             state.tab_index = if state.tab_index == 0 {
-                3
+                4
             } else {
                 state.tab_index - 1
             };
@@ -148,10 +175,8 @@ pub fn handle_mouse_event(
     mouse_event: MouseEvent,
     state: &mut TuiState,
     weeks: &[WeekStats],
-    stats: &[CommitStats],
-    cache: &Cache,
-    path_prefix: Option<&str>,
-    monthly: bool,
+    ctx: &EventContext,
+    monthly_state: bool,
 ) -> io::Result<()> {
     match mouse_event.kind {
         MouseEventKind::ScrollUp => {
@@ -172,26 +197,17 @@ pub fn handle_mouse_event(
                 ensure_selection_in_filtered(state);
             }
         }
-        MouseEventKind::Down(MouseButton::Left) => {
+        MouseEventKind::Down(MouseButton::Left)
             if state.view_mode != ViewMode::CommitDetails
                 && !weeks.is_empty()
-                && state.selected < weeks.len()
-            {
-                if let Err(e) = load_commit_details(
-                    state,
-                    weeks,
-                    stats,
-                    cache,
-                    path_prefix,
-                    None,
-                    None,
-                    monthly,
-                ) {
-                    eprintln!("Error loading commit details: {e}");
-                } else {
-                    state.view_mode = ViewMode::CommitDetails;
-                    state.tab_index = 3;
-                }
+                && state.selected < weeks.len() =>
+        {
+            let scope = ctx.commit_scope(ctx.path, monthly_state);
+            if let Err(e) = load_commit_details(state, weeks, &scope) {
+                eprintln!("Error loading commit details: {e}");
+            } else {
+                state.view_mode = ViewMode::CommitDetails;
+                state.tab_index = 3;
             }
         }
         _ => {}
@@ -1,24 +1,21 @@
-use std::cell::RefCell;
 use std::io;
 
-use crate::cache::Cache;
-use crate::cli::CommonArgs;
 use crate::git::GitRepo;
-use crate::heat::{aggregate_weeks, load_commit_details};
-use crate::model::{CommitStats, DateRange};
-use crate::util::GitIgnoreMatcher;
+use crate::heat::{aggregate_weeks, find_commit_global, load_commit_details};
+use crate::model::MergePolicy;
 
-use super::super::input::{apply_search_filter, copy_to_clipboard, ensure_selection_in_filtered};
+use super::super::input::{
+    apply_commit_search_filter, apply_search_filter, copy_to_clipboard, editor_command,
+    ensure_selection_in_filtered, git_show_args, page,
+};
 use super::super::state::{TuiState, ViewMode, WeekStats};
+use super::EventContext;
 
 /// Load commit details for the currently selected period and switch into the details view.
 pub(super) fn try_load_commit_details(
     state: &mut TuiState,
     weeks: &[WeekStats],
-    stats: &[CommitStats],
-    cache: &Cache,
-    path: Option<&str>,
-    common: &CommonArgs,
+    ctx: &EventContext,
     monthly_state: bool,
 ) {
     if state.view_mode == ViewMode::CommitDetails
@@ -31,18 +28,10 @@ pub(super) fn try_load_commit_details(
     let active_path_owned = state
         .path_filter
         .clone()
-        .or_else(|| path.map(|p| p.to_string()));
+        .or_else(|| ctx.path.map(|p| p.to_string()));
     let active_path = active_path_owned.as_deref();
-    match load_commit_details(
-        state,
-        weeks,
-        stats,
-        cache,
-        active_path,
-        common.author.as_deref(),
-        common.author_email.as_deref(),
-        monthly_state,
-    ) {
+    let scope = ctx.commit_scope(active_path, monthly_state);
+    match load_commit_details(state, weeks, &scope) {
         Ok(_) => {
             state.commit_filtered_indices = (0..state.commit_details.len()).collect();
             state.view_mode = ViewMode::CommitDetails;
@@ -54,6 +43,83 @@ pub(super) fn try_load_commit_details(
     }
 }
 
+/// Jump to the newest commit in any period matching the global search query,
+/// loading that period's commits and selecting it in the details view.
+pub(super) fn jump_to_global_match(
+    state: &mut TuiState,
+    weeks: &[WeekStats],
+    ctx: &EventContext,
+    monthly_state: bool,
+) {
+    let active_path_owned = state
+        .path_filter
+        .clone()
+        .or_else(|| ctx.path.map(|p| p.to_string()));
+    let active_path = active_path_owned.as_deref();
+    let scope = ctx.commit_scope(active_path, monthly_state);
+    let Some((index, hash)) = find_commit_global(
+        &scope,
+        weeks,
+        &state.global_search_query,
+        state.case_sensitive_search,
+    ) else {
+        state.status_message = Some((
+            format!("No commit matches '{}'", state.global_search_query),
+            std::time::Instant::now(),
+        ));
+        return;
+    };
+
+    state.selected = index;
+    state.search_query.clear();
+    apply_search_filter(weeks, state);
+    if let Err(e) = load_commit_details(state, weeks, &scope) {
+        state.status_message = Some((format!("Load error: {e}"), std::time::Instant::now()));
+        return;
+    }
+    state.commit_search_query.clear();
+    state.commit_filtered_indices = (0..state.commit_details.len()).collect();
+    state.commit_selected = state
+        .commit_details
+        .iter()
+        .position(|c| c.hash == hash)
+        .unwrap_or(0);
+    state.view_mode = ViewMode::CommitDetails;
+    state.tab_index = 3;
+}
+
+/// Switch search filters between ignoring and respecting case, re-applying both.
+pub(super) fn toggle_case_sensitive_search(state: &mut TuiState, weeks: &[WeekStats]) {
+    state.case_sensitive_search = !state.case_sensitive_search;
+    apply_search_filter(weeks, state);
+    apply_commit_search_filter(state);
+    let mode = if state.case_sensitive_search {
+        "case-sensitive"
+    } else {
+        "ignoring case"
+    };
+    state.status_message = Some((
+        format!("Search matching: {mode}"),
+        std::time::Instant::now(),
+    ));
+}
+
+/// Switch search filters between substring and fuzzy matching, re-applying both.
+pub(super) fn toggle_fuzzy_search(state: &mut TuiState, weeks: &[WeekStats]) {
+    state.fuzzy_search = !state.fuzzy_search;
+    apply_search_filter(weeks, state);
+    apply_commit_search_filter(state);
+    let mode = if state.fuzzy_search {
+        "fuzzy"
+    } else {
+        "substring"
+    };
+    state.status_message = Some((
+        format!("Search matching: {mode}"),
+        std::time::Instant::now(),
+    ));
+}
+
 /// Copy the full commit hash of the selected commit, surfacing clipboard errors in status.
 pub(super) fn copy_full_hash(state: &mut TuiState) {
     if let Some(commit) = state.commit_details.get(state.commit_selected) {
@@ -83,31 +149,137 @@ pub(super) fn copy_short_hash(state: &mut TuiState) {
     }
 }
 
-/// Open the selected commit in the user's pager by spawning `git show` temporarily outside raw mode.
+/// Open the selected commit's `git show --stat` in the configured pager, temporarily outside raw mode.
 pub(super) fn open_commit_in_pager(state: &mut TuiState, repo: &GitRepo) {
-    if let Some(commit) = state.commit_details.get(state.commit_selected) {
-        let _ = crossterm::terminal::disable_raw_mode();
-        let _ = std::process::Command::new("sh")
-            .arg("-c")
-            .arg(format!(
-                "git -C '{}' show --stat {} | ${{PAGER:-less -R}}",
-                repo.path().display(),
-                commit.hash
-            ))
-            .status();
-        let _ = crossterm::terminal::enable_raw_mode();
+    let Some(commit) = state.commit_details.get(state.commit_selected) else {
+        return;
+    };
+    let Some(args) = git_show_args(repo.path(), &commit.hash) else {
+        return;
+    };
+    let output = match std::process::Command::new("git").args(args).output() {
+        Ok(output) if output.status.success() => output.stdout,
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            state.status_message = Some((
+                format!("git show failed: {}", stderr.trim()),
+                std::time::Instant::now(),
+            ));
+            return;
+        }
+        Err(e) => {
+            state.status_message =
+                Some((format!("git show failed: {e}"), std::time::Instant::now()));
+            return;
+        }
+    };
+
+    let _ = crossterm::terminal::disable_raw_mode();
+    let result = page(&output, &state.pager);
+    let _ = crossterm::terminal::enable_raw_mode();
+    if let Err(e) = result {
+        state.status_message = Some((format!("Pager error: {e}"), std::time::Instant::now()));
     }
 }
 
+/// Open the highlighted file of the selected commit in `$VISUAL`/`$EDITOR`,
+/// temporarily outside raw mode like the pager. Files still in the working
+/// tree open there; others open as a temp copy of the blob at that commit,
+/// removed once the editor exits.
+pub(super) fn open_file_in_editor(state: &mut TuiState, repo: &GitRepo) {
+    let Some(commit) = state.commit_details.get(state.commit_selected) else {
+        return;
+    };
+    let Some(file) = commit.files_changed.get(
+        state
+            .file_selected
+            .min(commit.files_changed.len().saturating_sub(1)),
+    ) else {
+        return;
+    };
+    let Some(editor) = state.editor.clone() else {
+        state.status_message = Some((
+            "Set $VISUAL or $EDITOR to open files".to_string(),
+            std::time::Instant::now(),
+        ));
+        return;
+    };
+
+    let working_copy = repo.path().join(file);
+    let snapshot = if working_copy.is_file() {
+        None
+    } else {
+        match write_snapshot(repo, &commit.hash, &commit.short_hash, file) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                state.status_message = Some((
+                    format!("Could not read {file} at {}: {e}", commit.short_hash),
+                    std::time::Instant::now(),
+                ));
+                return;
+            }
+        }
+    };
+    let target = snapshot
+        .as_ref()
+        .map_or(working_copy.as_path(), |snapshot| snapshot.path());
+    let Some((program, args)) = editor_command(&editor, target) else {
+        return;
+    };
+
+    let _ = crossterm::terminal::disable_raw_mode();
+    let status = std::process::Command::new(program).args(args).status();
+    let _ = crossterm::terminal::enable_raw_mode();
+
+    let message = match status {
+        Ok(status) if status.success() => return,
+        Ok(_) => format!("Could not open {file} in the editor"),
+        Err(e) => format!("Editor error: {e}"),
+    };
+    state.status_message = Some((message, std::time::Instant::now()));
+}
+
+/// Write `file` as of commit `hash` to a new temp file whose name ends with
+/// the file's own, so editors still pick its syntax. The file is created
+/// fresh, never reusing or following an existing path.
+fn write_snapshot(
+    repo: &GitRepo,
+    hash: &str,
+    short_hash: &str,
+    file: &str,
+) -> crate::error::Result<tempfile::NamedTempFile> {
+    let contents = repo.file_at(hash, file)?;
+    let name = std::path::Path::new(file)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut snapshot = tempfile::Builder::new()
+        .prefix(&format!("gmap-{short_hash}-"))
+        .suffix(&format!("-{name}"))
+        .tempfile()?;
+    io::Write::write_all(&mut snapshot, &contents)?;
+    Ok(snapshot)
+}
+
+/// Move the file highlight within the selected commit's changed files.
+pub(super) fn move_file_selection(state: &mut TuiState, forward: bool) {
+    let Some(commit) = state.commit_details.get(state.commit_selected) else {
+        return;
+    };
+    let last = commit.files_changed.len().saturating_sub(1);
+    let current = state.file_selected.min(last);
+    state.file_selected = if forward {
+        (current + 1).min(last)
+    } else {
+        current.saturating_sub(1)
+    };
+}
+
 /// Toggle weekly/monthly aggregation, re-aggregate data, and refresh commit filters.
 pub(super) fn toggle_monthly(
     state: &mut TuiState,
     weeks: &mut Vec<WeekStats>,
-    stats: &mut Vec<CommitStats>,
-    cache: &mut Cache,
-    path: Option<&str>,
-    common: &CommonArgs,
-    gi: &RefCell<GitIgnoreMatcher>,
+    ctx: &EventContext,
     monthly_state: &mut bool,
 ) -> io::Result<()> {
     if should_throttle_refresh(state) {
@@ -115,14 +287,13 @@ pub(super) fn toggle_monthly(
     }
     *monthly_state = !*monthly_state;
     *weeks = aggregate_weeks(
-        stats,
-        cache,
-        state.path_filter.as_deref().or(path),
-        common.author.as_deref(),
-        common.author_email.as_deref(),
-        *monthly_state,
-        &common.exclude,
-        Some(gi),
+        ctx.stats,
+        ctx.cache,
+        state.path_filter.as_deref().or(ctx.path),
+        ctx.filter,
+        ctx.common.period_spec(*monthly_state),
+        &ctx.common.exclude,
+        ctx.gi,
     );
     if !state.show_all {
         let limit = if *monthly_state { 12 } else { 52 };
@@ -135,92 +306,41 @@ pub(super) fn toggle_monthly(
         let active_path_owned = state
             .path_filter
             .clone()
-            .or_else(|| path.map(|p| p.to_string()));
+            .or_else(|| ctx.path.map(|p| p.to_string()));
         let active_path = active_path_owned.as_deref();
-        let _ = load_commit_details(
-            state,
-            weeks,
-            stats,
-            cache,
-            active_path,
-            common.author.as_deref(),
-            common.author_email.as_deref(),
-            *monthly_state,
-        );
+        let scope = ctx.commit_scope(active_path, *monthly_state);
+        let _ = load_commit_details(state, weeks, &scope);
         state.commit_filtered_indices = (0..state.commit_details.len()).collect();
     }
     Ok(())
 }
 
-/// Toggle inclusion of merge commits, refetch stats, and rebuild the current aggregation.
-pub(super) fn toggle_merges(
-    state: &mut TuiState,
-    weeks: &mut Vec<WeekStats>,
-    stats: &mut Vec<CommitStats>,
-    cache: &mut Cache,
-    path: Option<&str>,
-    common: &CommonArgs,
-    repo: &GitRepo,
-    range: &DateRange,
-    gi: &RefCell<GitIgnoreMatcher>,
-    include_merges_state: &mut bool,
-    monthly_state: bool,
-) -> io::Result<()> {
+/// Toggle inclusion of merge commits; the run loop refetches stats in the
+/// background for the new policy.
+pub(super) fn toggle_merges(state: &mut TuiState, merge_policy: &mut MergePolicy) {
     if should_throttle_refresh(state) {
-        return Ok(());
-    }
-    *include_merges_state = !*include_merges_state;
-    *stats = crate::heat::fetch_commit_stats_with_progress(
-        repo,
-        cache,
-        range,
-        *include_merges_state,
-        common.binary,
-        false,
-    )
-    .map_err(io::Error::other)?;
-    *weeks = aggregate_weeks(
-        stats,
-        cache,
-        state.path_filter.as_deref().or(path),
-        common.author.as_deref(),
-        common.author_email.as_deref(),
-        monthly_state,
-        &common.exclude,
-        Some(gi),
-    );
-    if !state.show_all {
-        let limit = if monthly_state { 12 } else { 52 };
-        if weeks.len() > limit {
-            *weeks = weeks.split_off(weeks.len() - limit);
-        }
+        return;
     }
-    apply_search_filter(weeks, state);
-    Ok(())
+    *merge_policy = merge_policy.toggled();
 }
 
 /// Toggle between showing all periods or the recent subset and refresh derived state.
 pub(super) fn toggle_show_all(
     state: &mut TuiState,
     weeks: &mut Vec<WeekStats>,
-    stats: &mut Vec<CommitStats>,
-    cache: &mut Cache,
-    path: Option<&str>,
-    common: &CommonArgs,
-    gi: &RefCell<GitIgnoreMatcher>,
+    ctx: &EventContext,
     monthly_state: bool,
 ) -> io::Result<()> {
     state.show_all = !state.show_all;
     if state.show_all {
         *weeks = aggregate_weeks(
-            stats,
-            cache,
-            state.path_filter.as_deref().or(path),
-            common.author.as_deref(),
-            common.author_email.as_deref(),
-            monthly_state,
-            &common.exclude,
-            Some(gi),
+            ctx.stats,
+            ctx.cache,
+            state.path_filter.as_deref().or(ctx.path),
+            ctx.filter,
+            ctx.common.period_spec(monthly_state),
+            &ctx.common.exclude,
+            ctx.gi,
         );
     } else {
         let limit = if monthly_state { 12 } else { 52 };
@@ -233,18 +353,10 @@ pub(super) fn toggle_show_all(
         let active_path_owned = state
             .path_filter
             .clone()
-            .or_else(|| path.map(|p| p.to_string()));
+            .or_else(|| ctx.path.map(|p| p.to_string()));
         let active_path = active_path_owned.as_deref();
-        let _ = load_commit_details(
-            state,
-            weeks,
-            stats,
-            cache,
-            active_path,
-            common.author.as_deref(),
-            common.author_email.as_deref(),
-            monthly_state,
-        );
+        let scope = ctx.commit_scope(active_path, monthly_state);
+        let _ = load_commit_details(state, weeks, &scope);
         state.commit_filtered_indices = (0..state.commit_details.len()).collect();
     }
     Ok(())
@@ -253,6 +365,7 @@ pub(super) fn toggle_show_all(
 /// Move selection upward respecting the current view and filtered commit indices.
 pub(super) fn move_up(state: &mut TuiState) {
     if state.view_mode == ViewMode::CommitDetails {
+        state.file_selected = 0;
         if !state.commit_details.is_empty() {
             if !state.commit_search_query.is_empty() && !state.commit_filtered_indices.is_empty() {
                 if let Some(pos) = state
@@ -277,6 +390,7 @@ pub(super) fn move_up(state: &mut TuiState) {
 /// Move selection downward respecting filtered commit indices and list bounds.
 pub(super) fn move_down(state: &mut TuiState, weeks_len: usize) {
     if state.view_mode == ViewMode::CommitDetails {
+        state.file_selected = 0;
         if !state.commit_details.is_empty() {
             if !state.commit_search_query.is_empty() && !state.commit_filtered_indices.is_empty() {
                 if let Some(pos) = state
@@ -1,16 +1,13 @@
-use std::cell::RefCell;
 use std::io;
 
 use crossterm::event::KeyCode;
 
-use crate::cache::Cache;
-use crate::cli::CommonArgs;
 use crate::heat::aggregate_weeks;
-use crate::model::CommitStats;
-use crate::util::GitIgnoreMatcher;
 
 use super::super::input::{apply_commit_search_filter, apply_search_filter};
 use super::super::state::{TuiState, WeekStats};
+use super::actions::jump_to_global_match;
+use super::EventContext;
 
 /// Handle period search keystrokes, applying filters on every change.
 pub(super) fn handle_search_input(code: KeyCode, state: &mut TuiState, weeks: &[WeekStats]) {
@@ -18,6 +15,7 @@ pub(super) fn handle_search_input(code: KeyCode, state: &mut TuiState, weeks: &[
         KeyCode::Esc => {
             state.search_mode = false;
             state.search_query.clear();
+            state.search_error = None;
             state.filtered_indices = (0..weeks.len()).collect();
         }
         KeyCode::Enter => {
@@ -42,6 +40,7 @@ pub(super) fn handle_commit_search_input(code: KeyCode, state: &mut TuiState) {
         KeyCode::Esc => {
             state.commit_search_mode = false;
             state.commit_search_query.clear();
+            state.search_error = None;
             state.commit_filtered_indices = (0..state.commit_details.len()).collect();
         }
         KeyCode::Enter => {
@@ -60,16 +59,41 @@ pub(super) fn handle_commit_search_input(code: KeyCode, state: &mut TuiState) {
     }
 }
 
+/// Handle global commit search keystrokes; Enter jumps to the best match.
+pub(super) fn handle_global_search_input(
+    code: KeyCode,
+    state: &mut TuiState,
+    weeks: &[WeekStats],
+    ctx: &EventContext,
+    monthly_state: bool,
+) {
+    match code {
+        KeyCode::Esc => {
+            state.global_search_mode = false;
+            state.global_search_query.clear();
+        }
+        KeyCode::Enter => {
+            state.global_search_mode = false;
+            if !state.global_search_query.is_empty() {
+                jump_to_global_match(state, weeks, ctx, monthly_state);
+            }
+        }
+        KeyCode::Backspace => {
+            state.global_search_query.pop();
+        }
+        KeyCode::Char(c) => {
+            state.global_search_query.push(c);
+        }
+        _ => {}
+    }
+}
+
 /// Handle path prefix input and re-aggregate data when the user submits a new path.
 pub(super) fn handle_path_input(
     code: KeyCode,
     state: &mut TuiState,
     weeks: &mut Vec<WeekStats>,
-    stats: &mut Vec<CommitStats>,
-    cache: &mut Cache,
-    path: Option<&str>,
-    common: &CommonArgs,
-    gi: &RefCell<GitIgnoreMatcher>,
+    ctx: &EventContext,
     monthly_state: bool,
 ) -> io::Result<()> {
     match code {
@@ -86,14 +110,13 @@ pub(super) fn handle_path_input(
                 Some(trimmed.to_string())
             };
             *weeks = aggregate_weeks(
-                stats,
-                cache,
-                state.path_filter.as_deref().or(path),
-                common.author.as_deref(),
-                common.author_email.as_deref(),
-                monthly_state,
-                &common.exclude,
-                Some(gi),
+                ctx.stats,
+                ctx.cache,
+                state.path_filter.as_deref().or(ctx.path),
+                ctx.filter,
+                ctx.common.period_spec(monthly_state),
+                &ctx.common.exclude,
+                ctx.gi,
             );
             if !state.show_all {
                 let limit = if monthly_state { 12 } else { 52 };
@@ -1,27 +1,102 @@
 use super::{TuiState, WeekStats};
+use crate::util::contains_text;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use regex::{Regex, RegexBuilder};
+use std::ffi::OsString;
 use std::io::Write;
+use std::path::Path;
 use std::process::{Command, Stdio};
 
+/// Matches search queries: substring by default, fuzzy (query chars in order,
+/// gaps allowed) with a score to rank results by, or a regex when the query
+/// starts with `/`. All three ignore case unless asked not to.
+enum QueryMatcher {
+    Substring(String, bool),
+    Fuzzy(String, Box<SkimMatcherV2>),
+    Regex(Regex),
+}
+
+impl QueryMatcher {
+    fn new(query: &str, fuzzy: bool, case_sensitive: bool) -> Result<Self, regex::Error> {
+        if let Some(pattern) = query.strip_prefix('/') {
+            return RegexBuilder::new(pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map(Self::Regex);
+        }
+        Ok(if fuzzy {
+            let matcher = SkimMatcherV2::default();
+            let matcher = if case_sensitive {
+                matcher.respect_case()
+            } else {
+                matcher.ignore_case()
+            };
+            Self::Fuzzy(query.to_string(), Box::new(matcher))
+        } else {
+            Self::Substring(query.to_string(), case_sensitive)
+        })
+    }
+
+    /// Best score among `fields`, or `None` when none matches. Substring and
+    /// regex hits all score 0, so they keep their original order.
+    fn score(&self, fields: &[&str]) -> Option<i64> {
+        match self {
+            Self::Fuzzy(query, matcher) => fields
+                .iter()
+                .filter_map(|field| matcher.fuzzy_match(field, query))
+                .max(),
+            Self::Substring(query, case_sensitive) => fields
+                .iter()
+                .any(|field| contains_text(field, query, *case_sensitive))
+                .then_some(0),
+            Self::Regex(regex) => fields
+                .iter()
+                .any(|field| regex.is_match(field))
+                .then_some(0),
+        }
+    }
+}
+
+/// Build the matcher for `query`. An invalid regex is reported in the status
+/// line and `search_error`, and yields `None` so the caller keeps its current
+/// filter.
+fn query_matcher(query: &str, state: &mut TuiState) -> Option<QueryMatcher> {
+    match QueryMatcher::new(query, state.fuzzy_search, state.case_sensitive_search) {
+        Ok(matcher) => {
+            state.search_error = None;
+            Some(matcher)
+        }
+        Err(err) => {
+            let reason = match err {
+                regex::Error::Syntax(msg) => msg.lines().last().unwrap_or_default().to_string(),
+                other => other.to_string(),
+            };
+            let message = format!("Invalid regex: {reason}");
+            state.search_error = Some(message.clone());
+            state.status_message = Some((message, std::time::Instant::now()));
+            None
+        }
+    }
+}
+
 /// Update `filtered_indices` based on `search_query`, and ensure selection stays valid.
 pub fn apply_search_filter(weeks: &[WeekStats], state: &mut TuiState) {
     if state.search_query.is_empty() {
         state.filtered_indices = (0..weeks.len()).collect();
+        state.search_error = None;
     } else {
-        let query = state.search_query.to_lowercase();
+        let query = state.search_query.clone();
+        let Some(matcher) = query_matcher(&query, state) else {
+            return;
+        };
         state.filtered_indices = weeks
             .iter()
             .enumerate()
             .filter_map(|(i, week)| {
-                if week.week.to_lowercase().contains(&query)
-                    || week
-                        .top_authors
-                        .iter()
-                        .any(|author| author.to_lowercase().contains(&query))
-                {
-                    Some(i)
-                } else {
-                    None
-                }
+                let mut fields = vec![week.week.as_str()];
+                fields.extend(week.top_authors.iter().map(|(name, _)| name.as_str()));
+                matcher.score(&fields).map(|_| i)
             })
             .collect();
     }
@@ -118,27 +193,120 @@ pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
     Err("Clipboard copy failed. Install one of: pbcopy (macOS), wl-copy (Wayland), xclip (X11), or use Windows clip.".into())
 }
 
-/// Update commit_filtered_indices based on commit_search_query.
+/// Pager used when neither `--pager` nor `$PAGER` is set.
+pub const DEFAULT_PAGER: &str = if cfg!(windows) { "more" } else { "less -R" };
+
+/// Program and arguments that run `pager`. It is split on whitespace and
+/// spawned directly, except on Windows where `cmd /C` runs it so built-ins and
+/// `PATHEXT` lookup work. `None` for an empty pager.
+pub fn pager_command(pager: &str) -> Option<(String, Vec<String>)> {
+    let pager = pager.trim();
+    if pager.is_empty() {
+        return None;
+    }
+    if cfg!(windows) {
+        return Some(("cmd".to_string(), vec!["/C".to_string(), pager.to_string()]));
+    }
+    let mut words = pager.split_whitespace().map(String::from);
+    let program = words.next()?;
+    Some((program, words.collect()))
+}
+
+/// Show `text` in `pager`, or print it to stdout when there is no pager or it
+/// cannot be started.
+pub fn page(text: &[u8], pager: &str) -> std::io::Result<()> {
+    if let Some((program, args)) = pager_command(pager) {
+        if let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .spawn()
+        {
+            if let Some(mut stdin) = child.stdin.take() {
+                // the pager may quit before reading everything
+                let _ = stdin.write_all(text);
+            }
+            child.wait()?;
+            return Ok(());
+        }
+    }
+    let mut stdout = std::io::stdout();
+    stdout.write_all(text)?;
+    write!(stdout, "\n(no pager available; press Enter to return)")?;
+    stdout.flush()?;
+    std::io::stdin().read_line(&mut String::new())?;
+    Ok(())
+}
+
+/// Arguments for `git show --stat` of commit `hash` in `repo`, each passed to
+/// git as-is with no shell in between. `None` unless `hash` is a full or
+/// abbreviated hex id, so it can never be read as an option.
+pub fn git_show_args(repo: &Path, hash: &str) -> Option<Vec<OsString>> {
+    if hash.is_empty() || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(vec![
+        "-C".into(),
+        repo.as_os_str().to_owned(),
+        "show".into(),
+        "--stat".into(),
+        hash.into(),
+    ])
+}
+
+/// Program and arguments that open `file` in `editor`, split like
+/// [`pager_command`] with the path passed as one more argument, never through
+/// a shell. `None` for an empty editor.
+pub fn editor_command(editor: &str, file: &Path) -> Option<(String, Vec<OsString>)> {
+    let (program, args) = pager_command(editor)?;
+    let mut args: Vec<OsString> = args.into_iter().map(OsString::from).collect();
+    args.push(file.as_os_str().to_owned());
+    Some((program, args))
+}
+
+/// Whether `query` is an abbreviation of `hash`: at least 4 hex digits (git's
+/// shortest), compared ignoring case whatever the search case setting.
+fn is_hash_prefix(hash: &str, query: &str) -> bool {
+    query.len() >= 4
+        && query.len() <= hash.len()
+        && query.chars().all(|c| c.is_ascii_hexdigit())
+        && hash[..query.len()].eq_ignore_ascii_case(query)
+}
+
+/// Update commit_filtered_indices based on commit_search_query. Fuzzy matches
+/// are ranked best first, with their scores kept in `commit_match_scores`; a
+/// `/` prefix filters by regex instead. A hash prefix longer than the short
+/// hash still finds its commit.
 pub fn apply_commit_search_filter(state: &mut TuiState) {
     if state.commit_search_query.is_empty() {
         state.commit_filtered_indices = (0..state.commit_details.len()).collect();
+        state.commit_match_scores.clear();
+        state.search_error = None;
     } else {
-        let q = state.commit_search_query.to_lowercase();
-        state.commit_filtered_indices = state
+        let query = state.commit_search_query.clone();
+        let Some(matcher) = query_matcher(&query, state) else {
+            return;
+        };
+        let mut scored: Vec<(usize, i64)> = state
             .commit_details
             .iter()
             .enumerate()
             .filter_map(|(i, c)| {
-                if c.message.to_lowercase().contains(&q)
-                    || c.author_name.to_lowercase().contains(&q)
-                    || c.short_hash.to_lowercase().contains(&q)
-                {
-                    Some(i)
-                } else {
-                    None
-                }
+                matcher
+                    .score(&[&c.message, &c.author_name, &c.short_hash])
+                    .or_else(|| is_hash_prefix(&c.hash, &query).then_some(0))
+                    .map(|score| (i, score))
             })
             .collect();
+        // stable, so equal scores stay newest first
+        scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        state.commit_filtered_indices = scored.iter().map(|&(i, _)| i).collect();
+        state.commit_match_scores = scored.iter().map(|&(_, score)| score).collect();
+        if state.fuzzy_search {
+            if let Some(&best) = state.commit_filtered_indices.first() {
+                state.commit_selected = best;
+                return;
+            }
+        }
     }
     if state.commit_selected >= state.commit_filtered_indices.len() {
         state.commit_selected = state.commit_filtered_indices.len().saturating_sub(1);
@@ -158,9 +326,12 @@ mod tests {
             commits: 1,
             lines_added: 1,
             lines_deleted: 0,
-            top_authors: authors.iter().map(|a| a.to_string()).collect(),
+            binary_files_changed: 0,
+            top_authors: authors.iter().map(|a| (a.to_string(), 1)).collect(),
             file_extensions: HashMap::new(),
             top_files: Vec::new(),
+            file_activity: Vec::new(),
+            commits_by_hour: [0; 24],
         }
     }
 
@@ -171,9 +342,11 @@ mod tests {
             week("2024-W02", &["bob"]),
             week("2024-W03", &["carol"]),
         ];
-        let mut state = TuiState::default();
-        state.selected = 2;
-        state.search_query = "w02".into();
+        let mut state = TuiState {
+            selected: 2,
+            search_query: "w02".into(),
+            ..Default::default()
+        };
 
         apply_search_filter(&weeks, &mut state);
 
@@ -197,18 +370,21 @@ mod tests {
             files_changed: vec![],
             lines_added: 1,
             lines_deleted: 0,
+            signature: None,
         }
     }
 
     #[test]
     fn commit_search_filters_and_trims_selection() {
-        let mut state = TuiState::default();
-        state.commit_details = vec![
-            commit_detail("a1", "Alice", "initial commit"),
-            commit_detail("b2", "Bob", "feature work"),
-        ];
-        state.commit_selected = 5;
-        state.commit_search_query = "bob".into();
+        let mut state = TuiState {
+            commit_details: vec![
+                commit_detail("a1", "Alice", "initial commit"),
+                commit_detail("b2", "Bob", "feature work"),
+            ],
+            commit_selected: 5,
+            commit_search_query: "bob".into(),
+            ..Default::default()
+        };
 
         apply_commit_search_filter(&mut state);
 
@@ -222,4 +398,167 @@ mod tests {
         apply_commit_search_filter(&mut state);
         assert_eq!(state.commit_filtered_indices, vec![1]);
     }
+
+    #[test]
+    fn commit_search_matches_hash_prefixes_beyond_the_short_hash() {
+        let mut first = commit_detail("1a2b3c4d", "Alice", "initial commit");
+        first.hash = "1a2b3c4d5e6f7a8b9c0d1a2b3c4d5e6f7a8b9c0d".to_string();
+        let mut second = commit_detail("1a2b3c4d", "Bob", "feature work");
+        second.hash = "1a2b3c4dffffffffffffffffffffffffffffffff".to_string();
+        let mut state = TuiState {
+            commit_details: vec![first, second],
+            commit_search_query: "1A2B3C4D5E".into(),
+            ..Default::default()
+        };
+
+        apply_commit_search_filter(&mut state);
+        assert_eq!(state.commit_filtered_indices, vec![0]);
+
+        state.commit_search_query = "1a2b3c4dff".into();
+        apply_commit_search_filter(&mut state);
+        assert_eq!(state.commit_filtered_indices, vec![1]);
+    }
+
+    #[test]
+    fn fuzzy_commit_search_ranks_closer_matches_first() {
+        let mut state = TuiState {
+            commit_details: vec![
+                commit_detail("a1", "Alice", "fix: refresh stale authentication tokens"),
+                commit_detail("b2", "Bob", "fix authentication"),
+                commit_detail("c3", "Carol", "update changelog"),
+            ],
+            commit_search_query: "fix athntctn".into(),
+            ..Default::default()
+        };
+
+        apply_commit_search_filter(&mut state);
+        assert!(
+            state.commit_filtered_indices.is_empty(),
+            "substring mode needs the exact text"
+        );
+
+        state.fuzzy_search = true;
+        apply_commit_search_filter(&mut state);
+        assert_eq!(state.commit_filtered_indices, vec![1, 0]);
+        assert!(state.commit_match_scores[0] > state.commit_match_scores[1]);
+        assert_eq!(state.commit_selected, 1, "best match is selected");
+    }
+
+    #[test]
+    fn regex_commit_search_matches_patterns() {
+        let mut state = TuiState {
+            commit_details: vec![
+                commit_detail("a1", "Alice", "fix(parser): handle empty input"),
+                commit_detail("b2", "Bob", "docs: mention the fix"),
+                commit_detail("c3", "Carol", "Fix(cli): typo"),
+            ],
+            commit_search_query: r"/^fix\(.*\):".into(),
+            ..Default::default()
+        };
+
+        apply_commit_search_filter(&mut state);
+
+        assert_eq!(state.commit_filtered_indices, vec![0, 2]);
+        assert!(state.status_message.is_none());
+    }
+
+    #[test]
+    fn case_sensitive_search_respects_case() {
+        let mut state = TuiState {
+            commit_details: vec![
+                commit_detail("a1", "Alice", "Fix parser"),
+                commit_detail("b2", "Bob", "fix docs"),
+            ],
+            commit_search_query: "Fix".into(),
+            ..Default::default()
+        };
+
+        apply_commit_search_filter(&mut state);
+        assert_eq!(state.commit_filtered_indices, vec![0, 1]);
+
+        state.case_sensitive_search = true;
+        apply_commit_search_filter(&mut state);
+        assert_eq!(state.commit_filtered_indices, vec![0]);
+
+        state.commit_search_query = "/^fix".into();
+        apply_commit_search_filter(&mut state);
+        assert_eq!(state.commit_filtered_indices, vec![1]);
+    }
+
+    #[test]
+    fn invalid_regex_keeps_filter_and_reports() {
+        let mut state = TuiState {
+            commit_details: vec![
+                commit_detail("a1", "Alice", "fix(parser): handle empty input"),
+                commit_detail("b2", "Bob", "docs: mention the fix"),
+            ],
+            commit_search_query: "/^fix".into(),
+            ..Default::default()
+        };
+        apply_commit_search_filter(&mut state);
+        assert_eq!(state.commit_filtered_indices, vec![0]);
+
+        state.commit_search_query.push('(');
+        apply_commit_search_filter(&mut state);
+
+        assert_eq!(
+            state.commit_filtered_indices,
+            vec![0],
+            "filter is unchanged"
+        );
+        let (message, _) = state.status_message.as_ref().expect("status message");
+        assert!(message.starts_with("Invalid regex"), "{message}");
+    }
+
+    #[test]
+    fn editor_command_passes_the_path_as_one_argument() {
+        assert_eq!(editor_command(" ", Path::new("a.rs")), None);
+        let file = Path::new("/tmp/it's here; rm -rf ~/main.rs");
+        let (program, args) = editor_command("code --wait", file).unwrap();
+        if cfg!(windows) {
+            assert_eq!(program, "cmd");
+            assert_eq!(
+                args,
+                ["/C", "code --wait", "/tmp/it's here; rm -rf ~/main.rs"]
+            );
+        } else {
+            assert_eq!(program, "code");
+            assert_eq!(args, ["--wait", "/tmp/it's here; rm -rf ~/main.rs"]);
+        }
+    }
+
+    #[test]
+    fn pager_command_matches_platform() {
+        assert_eq!(pager_command("  "), None);
+        let (program, args) = pager_command("less -R -F").unwrap();
+        if cfg!(windows) {
+            assert_eq!(program, "cmd");
+            assert_eq!(args, vec!["/C", "less -R -F"]);
+        } else {
+            assert_eq!(program, "less");
+            assert_eq!(args, vec!["-R", "-F"]);
+        }
+    }
+
+    #[test]
+    fn git_show_args_keep_paths_whole_and_reject_option_like_hashes() {
+        let repo = Path::new("/work/it's here; rm -rf ~");
+        let args = git_show_args(repo, "0a1b2c3d").unwrap();
+        assert_eq!(
+            args,
+            [
+                "-C",
+                "/work/it's here; rm -rf ~",
+                "show",
+                "--stat",
+                "0a1b2c3d"
+            ]
+            .map(OsString::from)
+            .to_vec()
+        );
+
+        assert_eq!(git_show_args(repo, "--output=/tmp/owned"), None);
+        assert_eq!(git_show_args(repo, "HEAD; echo hi"), None);
+        assert_eq!(git_show_args(repo, ""), None);
+    }
 }
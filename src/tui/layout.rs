@@ -33,8 +33,16 @@ pub fn get_visible_weeks<'a>(
         return Vec::new();
     }
 
+    // Tabs and the prompt line take 4 rows; the normal table adds borders,
+    // a header, and the intensity legend.
     const VERTICAL_PADDING: usize = 8;
-    let view_height = height.saturating_sub(VERTICAL_PADDING).max(1);
+    const DENSE_VERTICAL_PADDING: usize = 4;
+    let padding = if state.dense {
+        DENSE_VERTICAL_PADDING
+    } else {
+        VERTICAL_PADDING
+    };
+    let view_height = height.saturating_sub(padding).max(1);
 
     let indices: Vec<usize> = if state.filtered_indices.is_empty() {
         (0..weeks.len()).collect()
@@ -78,18 +86,23 @@ mod tests {
             commits: 1,
             lines_added: 1,
             lines_deleted: 0,
+            binary_files_changed: 0,
             top_authors: vec![],
             file_extensions: HashMap::new(),
             top_files: vec![],
+            file_activity: vec![],
+            commits_by_hour: [0; 24],
         }
     }
 
     #[test]
     fn visible_weeks_center_on_selection() {
         let weeks: Vec<_> = (0..10).map(week).collect();
-        let mut state = TuiState::default();
-        state.selected = 5;
-        state.filtered_indices = (0..weeks.len()).collect();
+        let state = TuiState {
+            selected: 5,
+            filtered_indices: (0..weeks.len()).collect(),
+            ..Default::default()
+        };
 
         let visible = get_visible_weeks(&weeks, &state, 12);
 
@@ -101,13 +114,32 @@ mod tests {
     #[test]
     fn visible_weeks_respect_filtered_indices() {
         let weeks: Vec<_> = (0..8).map(week).collect();
-        let mut state = TuiState::default();
-        state.selected = 3;
-        state.filtered_indices = vec![1, 3, 5, 7];
+        let state = TuiState {
+            selected: 3,
+            filtered_indices: vec![1, 3, 5, 7],
+            ..Default::default()
+        };
 
         let visible = get_visible_weeks(&weeks, &state, 40);
         let weeks_only: Vec<_> = visible.into_iter().map(|(w, _)| w.week.clone()).collect();
 
         assert_eq!(weeks_only, vec!["W01", "W03", "W05", "W07"]);
     }
+
+    #[test]
+    fn dense_mode_shows_more_weeks() {
+        let weeks: Vec<_> = (0..40).map(week).collect();
+        let mut state = TuiState {
+            selected: 20,
+            filtered_indices: (0..weeks.len()).collect(),
+            ..Default::default()
+        };
+
+        let normal = get_visible_weeks(&weeks, &state, 20).len();
+        state.dense = true;
+        let dense = get_visible_weeks(&weeks, &state, 20).len();
+
+        assert_eq!(normal, 12);
+        assert_eq!(dense, 16);
+    }
 }
@@ -0,0 +1,178 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use crate::cli::CommonArgs;
+use crate::model::{CommitStats, DateRange, MergePolicy};
+
+/// What the background fetch sends the TUI.
+#[derive(Debug)]
+pub enum LoadEvent {
+    /// More commits, already stored in the cache.
+    Stats(Vec<CommitStats>),
+    /// The fetch ended; on success, with every commit in range.
+    Done(Result<Vec<CommitStats>, String>),
+}
+
+/// Commit stats being fetched on a background thread, so the TUI can draw
+/// and respond while a large repository is analyzed. Dropping the loader
+/// stops the fetch after the current commit.
+pub struct Loader {
+    events: Receiver<LoadEvent>,
+    cancel: Arc<AtomicBool>,
+    /// Commits received so far.
+    pub loaded: usize,
+    /// Set once the fetch has ended, successfully or not.
+    pub done: bool,
+    /// Why the fetch failed, if it did.
+    pub error: Option<String>,
+}
+
+impl Loader {
+    /// Start fetching `range` with its own repository handle and cache connection.
+    pub fn spawn(common: &CommonArgs, range: &DateRange, merges: MergePolicy) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&cancel);
+        let common = common.clone();
+        let range = range.clone();
+        thread::spawn(move || {
+            let result = (|| -> anyhow::Result<Vec<CommitStats>> {
+                let repo = common.open_repo()?;
+                let mut cache = common.open_cache(&repo)?;
+                crate::heat::fetch_commit_stats_streaming(
+                    &repo,
+                    &mut cache,
+                    &range,
+                    merges,
+                    common.binary_policy(),
+                    &|_| stop.load(Ordering::Relaxed),
+                    &mut |batch| {
+                        let _ = tx.send(LoadEvent::Stats(batch.to_vec()));
+                    },
+                )
+                .map(|(stats, _)| stats)
+            })();
+            let _ = tx.send(LoadEvent::Done(result.map_err(|e| format!("{e:#}"))));
+        });
+        Self::from_channel(rx, cancel)
+    }
+
+    fn from_channel(events: Receiver<LoadEvent>, cancel: Arc<AtomicBool>) -> Self {
+        Self {
+            events,
+            cancel,
+            loaded: 0,
+            done: false,
+            error: None,
+        }
+    }
+
+    /// Move everything received so far into `stats`, returning whether it
+    /// changed. Batches are appended; the final event replaces `stats` with
+    /// the complete set.
+    pub fn drain(&mut self, stats: &mut Vec<CommitStats>) -> bool {
+        let mut changed = false;
+        while !self.done {
+            match self.events.try_recv() {
+                Ok(LoadEvent::Stats(batch)) => {
+                    self.loaded += batch.len();
+                    changed |= !batch.is_empty();
+                    stats.extend(batch);
+                }
+                Ok(LoadEvent::Done(Ok(all))) => {
+                    self.loaded = all.len();
+                    *stats = all;
+                    self.done = true;
+                    changed = true;
+                }
+                Ok(LoadEvent::Done(Err(err))) => {
+                    self.error = Some(err);
+                    self.done = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.error = Some("background load stopped unexpectedly".to_string());
+                    self.done = true;
+                }
+            }
+        }
+        changed
+    }
+}
+
+impl Drop for Loader {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(id: &str) -> CommitStats {
+        CommitStats {
+            commit_id: id.to_string(),
+            files: Vec::new(),
+            binary_paths: Vec::new(),
+        }
+    }
+
+    fn ids(stats: &[CommitStats]) -> Vec<&str> {
+        stats.iter().map(|c| c.commit_id.as_str()).collect()
+    }
+
+    #[test]
+    fn drain_appends_batches_then_takes_the_complete_set() {
+        let (tx, rx) = mpsc::channel();
+        let mut loader = Loader::from_channel(rx, Arc::new(AtomicBool::new(false)));
+        let mut stats = Vec::new();
+
+        assert!(!loader.drain(&mut stats), "nothing sent yet");
+
+        tx.send(LoadEvent::Stats(vec![commit("a"), commit("b")]))
+            .unwrap();
+        tx.send(LoadEvent::Stats(vec![commit("c")])).unwrap();
+        assert!(loader.drain(&mut stats));
+        assert_eq!(ids(&stats), ["a", "b", "c"]);
+        assert_eq!(loader.loaded, 3);
+        assert!(!loader.done);
+
+        tx.send(LoadEvent::Stats(vec![commit("d")])).unwrap();
+        tx.send(LoadEvent::Done(Ok(vec![
+            commit("a"),
+            commit("b"),
+            commit("c"),
+            commit("d"),
+        ])))
+        .unwrap();
+        assert!(loader.drain(&mut stats));
+        assert_eq!(ids(&stats), ["a", "b", "c", "d"]);
+        assert!(loader.done);
+        assert_eq!(loader.error, None);
+
+        drop(tx);
+        assert!(
+            !loader.drain(&mut stats),
+            "a finished loader ignores the closed channel"
+        );
+    }
+
+    #[test]
+    fn drain_keeps_partial_stats_on_failure() {
+        let (tx, rx) = mpsc::channel();
+        let mut loader = Loader::from_channel(rx, Arc::new(AtomicBool::new(false)));
+        let mut stats = Vec::new();
+
+        tx.send(LoadEvent::Stats(vec![commit("a")])).unwrap();
+        tx.send(LoadEvent::Done(Err("disk full".to_string())))
+            .unwrap();
+        loader.drain(&mut stats);
+
+        assert_eq!(ids(&stats), ["a"]);
+        assert!(loader.done);
+        assert_eq!(loader.error.as_deref(), Some("disk full"));
+    }
+}
@@ -2,7 +2,9 @@ pub mod draw;
 pub mod events;
 pub mod input;
 pub mod layout;
+pub mod loader;
 pub mod run;
+pub mod squarify;
 pub mod state;
 pub mod views;
 
@@ -10,6 +12,6 @@ pub use draw::*;
 pub use events::*;
 pub use input::*;
 pub use layout::*;
-pub use run::run;
+pub use run::{run, TuiOptions};
 pub use state::*;
 pub use views::*;
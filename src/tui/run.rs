@@ -1,6 +1,5 @@
-use std::cell::RefCell;
 use std::io;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crossterm::event::{poll, read, DisableMouseCapture, EnableMouseCapture, Event};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
@@ -13,68 +12,143 @@ use ratatui::{
 
 use crate::cache::Cache;
 use crate::cli::CommonArgs;
-use crate::git::GitRepo;
-use crate::heat::aggregate_weeks;
+use crate::heat::{aggregate_weeks, aggregate_weeks_cached};
+use crate::model::CommitStats;
+use crate::util::{tag_periods, GitIgnoreMatcher};
 
-use super::events::{handle_key_events, handle_mouse_event};
-use super::state::{TuiState, ViewMode};
+use super::draw::IntensityScale;
+use super::events::{handle_key_events, handle_mouse_event, EventContext};
+use super::input::apply_search_filter;
+use super::loader::Loader;
+use super::state::{TuiState, ViewMode, WeekStats, DEFAULT_SMOOTH_WINDOW};
 use super::views::{
     draw_commit_details_view, draw_file_modal, draw_heatmap_view, draw_help_overlay,
-    draw_statistics_view, draw_timeline_view,
+    draw_loading_overlay, draw_statistics_view, draw_timeline_view, draw_treemap_view,
 };
 
+/// Minimum time between re-aggregations while stats are still loading.
+const REBUILD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Options for `gmap heat --interactive`, on top of [`CommonArgs`].
+#[derive(Clone, Debug)]
+pub struct TuiOptions {
+    pub path: Option<String>,
+    pub monthly: bool,
+    pub growth: bool,
+    /// Glob selecting the tags to mark on the timeline
+    pub tag_pattern: Option<String>,
+    pub message_width: Option<usize>,
+    pub pager: Option<String>,
+    pub editor: Option<String>,
+    pub dense: bool,
+    pub full_hash: bool,
+    pub show_signatures: bool,
+    pub scale: IntensityScale,
+    /// Moving-average window, in periods
+    pub smooth: Option<u32>,
+}
+
 /// Launch the interactive TUI, handling setup, draw loop, and event dispatch.
-pub fn run(common: &CommonArgs, path: Option<String>, monthly: bool) -> io::Result<()> {
-    let repo = GitRepo::open(common.repo.as_ref()).map_err(io::Error::other)?;
-    let mut cache = Cache::new(common.cache.as_deref(), repo.path()).map_err(io::Error::other)?;
-    let range = repo
-        .resolve_range(common.since.as_deref(), common.until.as_deref())
-        .map_err(io::Error::other)?;
-    let mut include_merges_state = common.include_merges;
+pub fn run(common: &CommonArgs, options: TuiOptions) -> io::Result<()> {
+    let TuiOptions {
+        path,
+        monthly,
+        growth,
+        tag_pattern,
+        message_width,
+        pager,
+        editor,
+        dense,
+        full_hash,
+        show_signatures,
+        scale,
+        smooth,
+    } = options;
+    let repo = common.open_repo().map_err(io::Error::other)?;
+    let cache = common.open_cache(&repo).map_err(io::Error::other)?;
+    let range = common.resolve_range(&repo).map_err(io::Error::other)?;
+    let tags = match tag_pattern.as_deref() {
+        Some(pattern) => repo.tags(Some(pattern)).map_err(io::Error::other)?,
+        None => Vec::new(),
+    };
+    let mut merge_policy = common.merge_policy();
     let mut monthly_state = monthly;
 
-    let mut stats = crate::heat::fetch_commit_stats_with_progress(
-        &repo,
-        &mut cache,
-        &range,
-        include_merges_state,
-        common.binary,
-        false,
-    )
-    .map_err(io::Error::other)?;
-    let gi = RefCell::new(crate::util::GitIgnoreMatcher::new(repo.path()));
-    let mut weeks = aggregate_weeks(
-        &stats,
-        &cache,
-        path.as_deref(),
-        common.author.as_deref(),
-        common.author_email.as_deref(),
-        monthly_state,
-        &common.exclude,
-        Some(&gi),
-    );
+    let gi = common.git_ignore(&repo);
+    let filter = common.commit_filter();
+    let mut stats = Vec::new();
+    let mut weeks = Vec::new();
+    // Stats arrive in the background; the UI works with what has loaded so far.
+    let mut loader = Some(Loader::spawn(common, &range, merge_policy));
+    let mut last_rebuild: Option<Instant> = None;
+    let mut stale = false;
 
     enable_raw_mode()?;
     crossterm::execute!(io::stdout(), EnableMouseCapture)?;
 
     let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
-    let mut state = TuiState::default();
-    if !state.show_all {
-        let limit = if monthly_state { 12 } else { 52 };
-        if weeks.len() > limit {
-            weeks = weeks.split_off(weeks.len() - limit);
-        }
-    }
+    let mut state = TuiState {
+        show_growth: growth,
+        smooth: smooth.is_some(),
+        smooth_window: smooth.map_or(DEFAULT_SMOOTH_WINDOW, |window| window as usize),
+        message_width,
+        pager: pager.unwrap_or_else(|| super::input::DEFAULT_PAGER.to_string()),
+        editor,
+        loading: true,
+        dense,
+        full_hash,
+        show_signatures,
+        intensity_scale: scale,
+        case_sensitive_search: common.case_sensitive,
+        ..Default::default()
+    };
     state.filtered_indices = (0..weeks.len()).collect();
     terminal.clear()?;
 
     loop {
+        if loader.is_some() && !state.loading {
+            // Esc dropped the load; keep whatever arrived before it.
+            loader = None;
+            state.status_message = Some((
+                format!("Loading stopped; showing {} commits", stats.len()),
+                Instant::now(),
+            ));
+        }
+        if let Some(active) = loader.as_mut() {
+            stale |= active.drain(&mut stats);
+            state.loaded_commits = active.loaded;
+            state.loading_tick = state.loading_tick.wrapping_add(1);
+            if active.done {
+                if let Some(err) = &active.error {
+                    state.status_message = Some((format!("Load error: {err}"), Instant::now()));
+                }
+                loader = None;
+                state.loading = false;
+            }
+        }
+        let due = last_rebuild.is_none_or(|t| t.elapsed() >= REBUILD_INTERVAL);
+        if stale && (due || loader.is_none()) {
+            weeks = rebuild_weeks(
+                &stats,
+                &cache,
+                path.as_deref(),
+                common,
+                gi.as_ref(),
+                &state,
+                monthly_state,
+            );
+            apply_search_filter(&weeks, &mut state);
+            last_rebuild = Some(Instant::now());
+            stale = false;
+        }
+
         if let Some((_, t)) = &state.status_message {
             if t.elapsed().as_secs() >= 3 {
                 state.status_message = None;
             }
         }
 
+        let tag_marks = tag_periods(&tags, common.period_spec(monthly_state));
         if let Err(e) = terminal.draw(|f| {
             let size = f.size();
 
@@ -83,7 +157,7 @@ pub fn run(common: &CommonArgs, path: Option<String>, monthly: bool) -> io::Resu
                 return;
             }
 
-            if state.show_file_modal {
+            if state.show_file_modal && state.selected < weeks.len() {
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints([Constraint::Length(3), Constraint::Min(0)])
@@ -92,10 +166,13 @@ pub fn run(common: &CommonArgs, path: Option<String>, monthly: bool) -> io::Resu
                 match state.view_mode {
                     ViewMode::Heatmap => draw_heatmap_view(f, chunks[1], &weeks, &state),
                     ViewMode::Statistics => draw_statistics_view(f, chunks[1], &weeks, &state),
-                    ViewMode::Timeline => draw_timeline_view(f, chunks[1], &weeks, &state),
+                    ViewMode::Timeline => {
+                        draw_timeline_view(f, chunks[1], &weeks, &state, &tag_marks)
+                    }
                     ViewMode::CommitDetails => {
                         draw_commit_details_view(f, chunks[1], &weeks, &mut state)
                     }
+                    ViewMode::Treemap => draw_treemap_view(f, chunks[1], &weeks, &state),
                 }
                 draw_file_modal(f, size, &weeks[state.selected]);
                 return;
@@ -117,54 +194,63 @@ pub fn run(common: &CommonArgs, path: Option<String>, monthly: bool) -> io::Resu
                 1 => ViewMode::Statistics,
                 2 => ViewMode::Timeline,
                 3 => ViewMode::CommitDetails,
+                4 => ViewMode::Treemap,
                 _ => ViewMode::Heatmap,
             };
 
             match state.view_mode {
                 ViewMode::Heatmap => draw_heatmap_view(f, chunks[1], &weeks, &state),
                 ViewMode::Statistics => draw_statistics_view(f, chunks[1], &weeks, &state),
-                ViewMode::Timeline => draw_timeline_view(f, chunks[1], &weeks, &state),
+                ViewMode::Timeline => draw_timeline_view(f, chunks[1], &weeks, &state, &tag_marks),
                 ViewMode::CommitDetails => {
                     draw_commit_details_view(f, chunks[1], &weeks, &mut state)
                 }
+                ViewMode::Treemap => draw_treemap_view(f, chunks[1], &weeks, &state),
             }
 
             draw_prompt(f, &state, chunks[2]);
+            draw_loading_overlay(f, size, &state);
         }) {
             eprintln!("TUI draw error: {e}");
         }
 
         if poll(Duration::from_millis(200))? {
+            let ctx = EventContext {
+                stats: &stats,
+                cache: &cache,
+                path: path.as_deref(),
+                common,
+                filter: &filter,
+                repo: &repo,
+                gi: gi.as_ref(),
+            };
             match read()? {
                 Event::Mouse(mouse_event) => {
-                    handle_mouse_event(
-                        mouse_event,
-                        &mut state,
-                        &weeks,
-                        &stats,
-                        &cache,
-                        path.as_deref(),
-                        monthly_state,
-                    )?;
+                    handle_mouse_event(mouse_event, &mut state, &weeks, &ctx, monthly_state)?;
                 }
                 Event::Key(key_event) => {
+                    let policy_before = merge_policy;
                     let quit = handle_key_events(
                         key_event,
                         &mut state,
                         &mut weeks,
-                        &mut stats,
-                        &mut cache,
-                        path.as_deref(),
-                        common,
-                        &repo,
-                        &range,
-                        &gi,
+                        &ctx,
                         &mut monthly_state,
-                        &mut include_merges_state,
+                        &mut merge_policy,
                     )?;
                     if quit {
                         break;
                     }
+                    if merge_policy != policy_before {
+                        // Refetch for the new policy; replacing the loader
+                        // stops the old one.
+                        stats.clear();
+                        loader = Some(Loader::spawn(common, &range, merge_policy));
+                        state.loading = true;
+                        state.loaded_commits = 0;
+                        stale = true;
+                        last_rebuild = Some(Instant::now());
+                    }
                 }
                 _ => {}
             }
@@ -177,9 +263,45 @@ pub fn run(common: &CommonArgs, path: Option<String>, monthly: bool) -> io::Resu
     Ok(())
 }
 
+/// Aggregate `stats` into periods for the current filters, keeping only the
+/// recent ones unless everything is shown.
+fn rebuild_weeks(
+    stats: &[CommitStats],
+    cache: &Cache,
+    path: Option<&str>,
+    common: &CommonArgs,
+    gi: Option<&GitIgnoreMatcher>,
+    state: &TuiState,
+    monthly: bool,
+) -> Vec<WeekStats> {
+    // Partial stats change every rebuild while loading; only the full set is
+    // worth storing.
+    let aggregate = if state.loading {
+        aggregate_weeks
+    } else {
+        aggregate_weeks_cached
+    };
+    let mut weeks = aggregate(
+        stats,
+        cache,
+        state.path_filter.as_deref().or(path),
+        &common.commit_filter(),
+        common.period_spec(monthly),
+        &common.exclude,
+        gi,
+    );
+    if !state.show_all {
+        let limit = if monthly { 12 } else { 52 };
+        if weeks.len() > limit {
+            weeks = weeks.split_off(weeks.len() - limit);
+        }
+    }
+    weeks
+}
+
 /// Render the view-mode tabs for the active layout.
 fn render_tabs(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
-    let titles = ["Heatmap", "Stats", "Timeline", "Commits"];
+    let titles = ["Heatmap", "Stats", "Timeline", "Commits", "Treemap"];
     let tab_items: Vec<String> = titles.iter().map(|t| t.to_string()).collect();
     let tabs = Tabs::new(tab_items)
         .block(Block::default().borders(Borders::ALL).title("View Mode"))
@@ -190,28 +312,45 @@ fn render_tabs(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
 /// Draw the bottom prompt/status line depending on active input modes.
 fn draw_prompt(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
     use ratatui::widgets::Paragraph;
+    let fuzzy = match (state.fuzzy_search, state.case_sensitive_search) {
+        (false, false) => "",
+        (true, false) => " (fuzzy)",
+        (false, true) => " (case-sensitive)",
+        (true, true) => " (fuzzy, case-sensitive)",
+    };
+    let hint = state
+        .search_error
+        .as_deref()
+        .unwrap_or("Enter to apply, Esc to cancel; /regex");
     if state.search_mode {
         let p = Paragraph::new(format!(
-            "Period filter: {} (Enter to apply, Esc to cancel)",
+            "Period filter{fuzzy}: {} ({hint})",
             state.search_query
         ));
         f.render_widget(p, area);
     } else if state.commit_search_mode {
         let p = Paragraph::new(format!(
-            "Commit filter: {} (Enter to apply, Esc to cancel)",
+            "Commit filter{fuzzy}: {} ({hint})",
             state.commit_search_query
         ));
         f.render_widget(p, area);
+    } else if state.global_search_mode {
+        let p = Paragraph::new(format!(
+            "Find commit in all periods: {} (Enter to jump, Esc to cancel)",
+            state.global_search_query
+        ));
+        f.render_widget(p, area);
     } else if state.path_mode {
         let p = Paragraph::new(format!(
             "Path prefix: {} (Enter to apply, Esc to cancel)",
             state.path_input
         ));
         f.render_widget(p, area);
-    } else if let Some((message, ts)) = &state.status_message {
-        if ts.elapsed().as_millis() < 2500 {
-            let p = Paragraph::new(message.clone());
-            f.render_widget(p, area);
-        }
+    } else if let Some((message, _)) = state
+        .status_message
+        .as_ref()
+        .filter(|(_, ts)| ts.elapsed().as_millis() < 2500)
+    {
+        f.render_widget(Paragraph::new(message.clone()), area);
     }
 }
@@ -0,0 +1,168 @@
+//! Squarified treemap layout (Bruls, Huizing and van Wijk): tiles whose areas
+//! are proportional to their weights, kept as close to square as possible.
+
+use ratatui::layout::Rect;
+
+/// A rectangle in fractional cell coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Tile {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Tile {
+    pub fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    /// Snap to whole cells inside `origin`. Edges are rounded rather than
+    /// sizes, so neighbouring tiles still meet without gaps or overlaps.
+    pub fn to_cells(self, origin: Rect) -> Rect {
+        let (left, right) = (self.x.round(), (self.x + self.width).round());
+        let (top, bottom) = (self.y.round(), (self.y + self.height).round());
+        Rect {
+            x: origin.x + left as u16,
+            y: origin.y + top as u16,
+            width: (right - left).max(0.0) as u16,
+            height: (bottom - top).max(0.0) as u16,
+        }
+    }
+}
+
+/// Lay out `weights` over a `width` x `height` area, returning one tile per
+/// weight in input order. Zero and negative weights get empty tiles.
+pub fn squarify(weights: &[f64], width: f64, height: f64) -> Vec<Tile> {
+    let mut tiles = vec![Tile::default(); weights.len()];
+    let total: f64 = weights.iter().filter(|w| **w > 0.0).sum();
+    if total <= 0.0 || width <= 0.0 || height <= 0.0 {
+        return tiles;
+    }
+
+    // Largest first, with weights scaled to the area they will cover.
+    let scale = width * height / total;
+    let mut order: Vec<usize> = (0..weights.len()).filter(|&i| weights[i] > 0.0).collect();
+    order.sort_by(|&a, &b| weights[b].total_cmp(&weights[a]).then(a.cmp(&b)));
+
+    let mut free = Tile {
+        x: 0.0,
+        y: 0.0,
+        width,
+        height,
+    };
+    let mut row: Vec<(usize, f64)> = Vec::new();
+    for index in order {
+        let area = weights[index] * scale;
+        let side = free.width.min(free.height);
+        if !row.is_empty() {
+            let current = worst(&row, side);
+            row.push((index, area));
+            if worst(&row, side) <= current {
+                continue;
+            }
+            row.pop();
+            free = lay_row(&row, free, &mut tiles);
+            row.clear();
+        }
+        row.push((index, area));
+    }
+    if !row.is_empty() {
+        lay_row(&row, free, &mut tiles);
+    }
+    tiles
+}
+
+/// The worst aspect ratio in `row` when laid along a side of length `side`.
+fn worst(row: &[(usize, f64)], side: f64) -> f64 {
+    let sum: f64 = row.iter().map(|(_, area)| area).sum();
+    let (side2, sum2) = (side * side, sum * sum);
+    row.iter()
+        .map(|(_, area)| (side2 * area / sum2).max(sum2 / (side2 * area)))
+        .fold(0.0, f64::max)
+}
+
+/// Place `row` along the shorter side of `free`, returning what is left.
+fn lay_row(row: &[(usize, f64)], free: Tile, tiles: &mut [Tile]) -> Tile {
+    let sum: f64 = row.iter().map(|(_, area)| area).sum();
+    if free.width >= free.height {
+        // A column on the left.
+        let column = sum / free.height;
+        let mut y = free.y;
+        for &(index, area) in row {
+            let height = area / column;
+            tiles[index] = Tile {
+                x: free.x,
+                y,
+                width: column,
+                height,
+            };
+            y += height;
+        }
+        Tile {
+            x: free.x + column,
+            width: (free.width - column).max(0.0),
+            ..free
+        }
+    } else {
+        // A row along the top.
+        let band = sum / free.width;
+        let mut x = free.x;
+        for &(index, area) in row {
+            let width = area / band;
+            tiles[index] = Tile {
+                x,
+                y: free.y,
+                width,
+                height: band,
+            };
+            x += width;
+        }
+        Tile {
+            y: free.y + band,
+            height: (free.height - band).max(0.0),
+            ..free
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_areas_are_proportional_to_weights() {
+        let weights = [6.0, 6.0, 4.0, 3.0, 2.0, 2.0, 1.0];
+        let (width, height) = (80.0, 24.0);
+        let total: f64 = weights.iter().sum();
+        let tiles = squarify(&weights, width, height);
+        let origin = Rect::new(0, 0, 80, 24);
+
+        let mut cells = 0;
+        for (weight, tile) in weights.iter().zip(&tiles) {
+            let expected = weight / total * width * height;
+            assert!((tile.area() - expected).abs() < 1e-9);
+            assert!(tile.x + tile.width <= width + 1e-9);
+            assert!(tile.y + tile.height <= height + 1e-9);
+
+            // Snapping moves each edge by at most half a cell.
+            let snapped = tile.to_cells(origin);
+            let area = snapped.width as f64 * snapped.height as f64;
+            assert!(
+                (area - expected).abs() <= tile.width + tile.height + 1.0,
+                "{weight}: {area} cells vs {expected}"
+            );
+            cells += snapped.width as u32 * snapped.height as u32;
+        }
+        // Snapped tiles still cover the area exactly once.
+        assert_eq!(cells, 80 * 24);
+    }
+
+    #[test]
+    fn empty_weights_get_empty_tiles() {
+        let tiles = squarify(&[0.0, 3.0, 1.0], 10.0, 4.0);
+        assert_eq!(tiles[0].area(), 0.0);
+        assert!((tiles[1].area() - 30.0).abs() < 1e-9);
+        assert!(squarify(&[0.0], 10.0, 4.0).iter().all(|t| t.area() == 0.0));
+    }
+}
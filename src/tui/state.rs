@@ -1,18 +1,46 @@
+use super::draw::IntensityScale;
 use crate::heat::FileExtensionStats;
+use crate::model::SignatureStatus;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub type TopFile = (String, usize);
+/// An author and their commit count in a period.
+pub type TopAuthor = (String, usize);
 
-#[derive(Clone, Debug)]
+/// Files kept per period for the treemap; smaller ones would not get a
+/// readable tile anyway.
+pub const TREEMAP_FILES: usize = 50;
+
+/// Moving-average window for the timeline when `a` turns smoothing on
+/// without `--smooth`.
+pub const DEFAULT_SMOOTH_WINDOW: usize = 4;
+
+/// Lines changed in one file over a period, for the treemap view.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FileActivity {
+    pub path: String,
+    pub lines: usize,
+    pub last_touched: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct WeekStats {
     pub week: String,
     pub commits: usize,
     pub lines_added: usize,
     pub lines_deleted: usize,
-    pub top_authors: Vec<String>,
+    /// Binary file changes by the period's commits, with or without `--binary`
+    #[serde(default)]
+    pub binary_files_changed: usize,
+    pub top_authors: Vec<TopAuthor>,
+    #[serde(serialize_with = "crate::util::sorted_map")]
     pub file_extensions: HashMap<String, FileExtensionStats>,
     pub top_files: Vec<TopFile>,
+    /// The period's most changed files by lines, largest first.
+    pub file_activity: Vec<FileActivity>,
+    pub commits_by_hour: [u64; 24],
 }
 
 #[derive(Default, Clone, Debug)]
@@ -26,6 +54,8 @@ pub struct CommitDetail {
     pub files_changed: Vec<String>,
     pub lines_added: u32,
     pub lines_deleted: u32,
+    /// `None` when the commit was cached before signatures were recorded.
+    pub signature: Option<SignatureStatus>,
 }
 
 impl CommitDetail {
@@ -38,6 +68,7 @@ pub enum ViewMode {
     Statistics,
     Timeline,
     CommitDetails,
+    Treemap,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -46,6 +77,39 @@ pub enum FocusPane {
     Commits,
 }
 
+/// Column the files view tables are sorted by.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FileSortColumn {
+    Ext,
+    Commits,
+    Files,
+    Added,
+    Deleted,
+}
+
+impl FileSortColumn {
+    /// The column after this one, wrapping back to the first.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Ext => Self::Commits,
+            Self::Commits => Self::Files,
+            Self::Files => Self::Added,
+            Self::Added => Self::Deleted,
+            Self::Deleted => Self::Ext,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ext => "ext",
+            Self::Commits => "commits",
+            Self::Files => "files",
+            Self::Added => "added",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TuiState {
     pub selected: usize,
@@ -61,14 +125,58 @@ pub struct TuiState {
     pub commit_search_query: String,
     pub commit_search_mode: bool,
     pub commit_filtered_indices: Vec<usize>,
+    /// Match score of each entry in `commit_filtered_indices`, best first.
+    pub commit_match_scores: Vec<i64>,
+    /// Match search queries fuzzily instead of as substrings.
+    pub fuzzy_search: bool,
+    /// Match search queries with exact case; starts from `--case-sensitive`.
+    pub case_sensitive_search: bool,
+    /// Why the search query being typed cannot be applied (an invalid regex).
+    pub search_error: Option<String>,
+    pub global_search_query: String,
+    pub global_search_mode: bool,
     pub path_filter: Option<String>,
     pub path_mode: bool,
     pub path_input: String,
     pub commit_details: Vec<CommitDetail>,
     pub commit_selected: usize,
+    /// Highlighted entry in the selected commit's changed files.
+    pub file_selected: usize,
+    /// Whether the heatmap view shows its right-hand summary panel.
+    pub show_side_panel: bool,
+    /// Heatmap as one borderless line per period, fitting more on screen.
+    pub dense: bool,
+    /// Show full commit hashes instead of their abbreviations.
+    pub full_hash: bool,
+    /// Treemap covers every listed period instead of the selected one.
+    pub treemap_all_periods: bool,
+    /// Show whether the selected commit is signed in its details.
+    pub show_signatures: bool,
+    /// How period activity maps onto heatmap bars and colors.
+    pub intensity_scale: IntensityScale,
+    /// Sort column for both files view tables; `None` keeps the overall
+    /// table by deleted lines and the week table by added lines.
+    pub file_sort: Option<FileSortColumn>,
+    pub file_sort_desc: bool,
     pub loading_commits: bool,
     pub status_message: Option<(String, std::time::Instant)>,
     pub last_refresh: Option<std::time::Instant>,
+    pub show_growth: bool,
+    /// Plot the timeline as a moving average over `smooth_window` periods.
+    pub smooth: bool,
+    pub smooth_window: usize,
+    /// Cap on commit message chars in tables; `None` fills the column.
+    pub message_width: Option<usize>,
+    /// Set while commit stats are being fetched in the background.
+    pub loading: bool,
+    /// Commits received so far by the current fetch.
+    pub loaded_commits: usize,
+    /// Advances every frame while loading, to animate the spinner.
+    pub loading_tick: usize,
+    /// Command `o` pipes `git show` into.
+    pub pager: String,
+    /// Command `e` opens files in, from `$VISUAL` or `$EDITOR`.
+    pub editor: Option<String>,
 }
 
 impl Default for TuiState {
@@ -87,14 +195,91 @@ impl Default for TuiState {
             commit_search_query: String::new(),
             commit_search_mode: false,
             commit_filtered_indices: Vec::new(),
+            commit_match_scores: Vec::new(),
+            fuzzy_search: false,
+            case_sensitive_search: false,
+            search_error: None,
+            global_search_query: String::new(),
+            global_search_mode: false,
             path_filter: None,
             path_mode: false,
             path_input: String::new(),
             commit_details: Vec::new(),
             commit_selected: 0,
+            file_selected: 0,
+            show_side_panel: true,
+            dense: false,
+            full_hash: false,
+            treemap_all_periods: false,
+            show_signatures: false,
+            intensity_scale: IntensityScale::Linear,
+            file_sort: None,
+            file_sort_desc: true,
             loading_commits: false,
             status_message: None,
             last_refresh: None,
+            show_growth: false,
+            smooth: false,
+            smooth_window: DEFAULT_SMOOTH_WINDOW,
+            message_width: None,
+            loading: false,
+            loaded_commits: 0,
+            loading_tick: 0,
+            pager: super::input::DEFAULT_PAGER.to_string(),
+            editor: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn week_stats_round_trip_through_json() {
+        let week = WeekStats {
+            week: "2024-W05".to_string(),
+            commits: 3,
+            lines_added: 12,
+            lines_deleted: 4,
+            binary_files_changed: 0,
+            top_authors: vec![("Alice".to_string(), 2), ("Bob".to_string(), 1)],
+            file_extensions: HashMap::from([
+                (
+                    "rs".to_string(),
+                    FileExtensionStats {
+                        commits: 2,
+                        lines_added: 10,
+                        lines_deleted: 4,
+                        files_changed: 2,
+                    },
+                ),
+                (
+                    String::new(),
+                    FileExtensionStats {
+                        commits: 1,
+                        lines_added: 2,
+                        lines_deleted: 0,
+                        files_changed: 1,
+                    },
+                ),
+            ]),
+            top_files: vec![("src/lib.rs".to_string(), 2), ("Makefile".to_string(), 1)],
+            file_activity: vec![FileActivity {
+                path: "src/lib.rs".to_string(),
+                lines: 14,
+                last_touched: Utc.with_ymd_and_hms(2024, 2, 1, 9, 30, 0).unwrap(),
+            }],
+            commits_by_hour: std::array::from_fn(|hour| hour as u64 % 3),
+        };
+
+        let json = serde_json::to_string(&week).unwrap();
+        assert!(json.contains(r#""top_files":[["src/lib.rs",2],["Makefile",1]]"#));
+        // Extensions are written in key order, not hash order.
+        assert!(json.find(r#""":{"#).unwrap() < json.find(r#""rs":{"#).unwrap());
+
+        let back: WeekStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, week);
+    }
+}
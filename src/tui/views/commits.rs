@@ -5,7 +5,7 @@ use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
 use ratatui::Frame;
 
 use super::super::state::{TuiState, WeekStats};
-use super::{header_cell, truncate};
+use super::{column_width, display_hash, fit_message, hash_column, header_cell, signature_line};
 
 /// Render the commit details view, including the commit list and the selected commit summary.
 pub fn draw_commit_details_view(
@@ -71,6 +71,14 @@ pub fn draw_commit_details_view(
         state.commit_selected = indices[0];
     }
 
+    let widths = [
+        hash_column(state),
+        Constraint::Percentage(50),
+        Constraint::Percentage(25),
+        Constraint::Length(15),
+    ];
+    let message_column = column_width(inner_chunks[0], &widths, 1);
+
     let commit_rows: Vec<Row> = indices
         .iter()
         .map(|&i| {
@@ -78,22 +86,24 @@ pub fn draw_commit_details_view(
             let is_selected = i == state.commit_selected;
 
             let hash_cell = if is_selected {
-                Cell::from(format!("{} ◄", commit.short_hash)).style(
+                Cell::from(format!("{} ◄", display_hash(commit, state))).style(
                     Style::default()
                         .fg(Color::Yellow)
                         .add_modifier(Modifier::BOLD),
                 )
             } else {
-                Cell::from(commit.short_hash.clone()).style(Style::default().fg(Color::Cyan))
+                Cell::from(display_hash(commit, state).to_string())
+                    .style(Style::default().fg(Color::Cyan))
             };
 
-            let message_cell = Cell::from(truncate(&commit.message, 50)).style(if is_selected {
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(Color::White)
-            });
+            let message_cell = Cell::from(fit_message(&commit.message, message_column, state))
+                .style(if is_selected {
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                });
 
             let author_cell =
                 Cell::from(commit.author_name.clone()).style(Style::default().fg(Color::Magenta));
@@ -113,40 +123,35 @@ pub fn draw_commit_details_view(
         .unwrap_or(0);
     table_state.select(Some(pos_in_filtered));
 
-    let commits_table = Table::new(
-        commit_rows,
-        [
-            Constraint::Length(10),
-            Constraint::Percentage(50),
-            Constraint::Percentage(25),
-            Constraint::Length(15),
-        ],
-    )
-    .header(Row::new([
-        header_cell("Hash", Color::Yellow),
-        header_cell("Message", Color::Yellow),
-        header_cell("Author", Color::Yellow),
-        header_cell("Changes", Color::Yellow),
-    ]))
-    .block(
-        Block::default()
-            .title(format!(
-                "Commits - Week {} ({} commits)",
-                selected_week.week,
-                state.commit_details.len()
-            ))
-            .borders(Borders::ALL),
-    );
+    let commits_table = Table::new(commit_rows, widths)
+        .header(Row::new([
+            header_cell("Hash", Color::Yellow),
+            header_cell("Message", Color::Yellow),
+            header_cell("Author", Color::Yellow),
+            header_cell("Changes", Color::Yellow),
+        ]))
+        .block(
+            Block::default()
+                .title(format!(
+                    "Commits - Week {} ({} commits)",
+                    selected_week.week,
+                    state.commit_details.len()
+                ))
+                .borders(Borders::ALL),
+        );
 
     f.render_stateful_widget(commits_table, inner_chunks[0], &mut table_state);
 
     if let Some(selected_commit) = state.commit_details.get(state.commit_selected) {
         let details_chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(8), Constraint::Min(0)])
+            .constraints([
+                Constraint::Length(8 + u16::from(state.show_signatures)),
+                Constraint::Min(0),
+            ])
             .split(inner_chunks[1]);
 
-        let commit_info = vec![
+        let mut commit_info = vec![
             Line::from(vec![Span::styled(
                 "Commit Details",
                 Style::default()
@@ -157,7 +162,7 @@ pub fn draw_commit_details_view(
             Line::from(vec![
                 Span::styled("Hash: ", Style::default().fg(Color::White)),
                 Span::styled(
-                    selected_commit.short_hash.clone(),
+                    display_hash(selected_commit, state).to_string(),
                     Style::default().fg(Color::Cyan),
                 ),
             ]),
@@ -189,6 +194,9 @@ pub fn draw_commit_details_view(
                 ),
             ]),
         ];
+        if state.show_signatures {
+            commit_info.push(signature_line(selected_commit));
+        }
 
         let info_panel = Paragraph::new(commit_info).block(
             Block::default()
@@ -198,6 +206,9 @@ pub fn draw_commit_details_view(
         );
         f.render_widget(info_panel, details_chunks[0]);
 
+        let highlighted = state
+            .file_selected
+            .min(selected_commit.files_changed.len().saturating_sub(1));
         let files_text: Vec<Line> = std::iter::once(Line::from(vec![Span::styled(
             "Files Changed",
             Style::default()
@@ -205,17 +216,37 @@ pub fn draw_commit_details_view(
                 .add_modifier(Modifier::BOLD),
         )]))
         .chain(std::iter::once(Line::from("")))
-        .chain(selected_commit.files_changed.iter().take(20).map(|file| {
-            let display_path = if file.len() > 40 {
-                format!("...{}", &file[file.len() - 37..])
-            } else {
-                file.clone()
-            };
-            Line::from(vec![
-                Span::raw("  "),
-                Span::styled(display_path, Style::default().fg(Color::Cyan)),
-            ])
-        }))
+        .chain(
+            selected_commit
+                .files_changed
+                .iter()
+                .enumerate()
+                .skip(highlighted.saturating_sub(19))
+                .take(20)
+                .map(|(i, file)| {
+                    let display_path = if file.len() > 40 {
+                        format!("...{}", &file[file.len() - 37..])
+                    } else {
+                        file.clone()
+                    };
+                    if i == highlighted {
+                        Line::from(vec![
+                            Span::styled("▸ ", Style::default().fg(Color::Yellow)),
+                            Span::styled(
+                                display_path,
+                                Style::default()
+                                    .fg(Color::Yellow)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                        ])
+                    } else {
+                        Line::from(vec![
+                            Span::raw("  "),
+                            Span::styled(display_path, Style::default().fg(Color::Cyan)),
+                        ])
+                    }
+                }),
+        )
         .collect();
 
         let files_panel = Paragraph::new(files_text).block(
@@ -227,3 +258,62 @@ pub fn draw_commit_details_view(
         f.render_widget(files_panel, details_chunks[1]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::CommitDetail;
+    use chrono::Utc;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::collections::HashMap;
+
+    fn rendered(state: &mut TuiState) -> String {
+        let weeks = [WeekStats {
+            week: "2024-W01".to_string(),
+            commits: 1,
+            lines_added: 1,
+            lines_deleted: 0,
+            binary_files_changed: 0,
+            top_authors: Vec::new(),
+            file_extensions: HashMap::new(),
+            top_files: Vec::new(),
+            file_activity: Vec::new(),
+            commits_by_hour: [0; 24],
+        }];
+        let mut terminal = Terminal::new(TestBackend::new(200, 20)).unwrap();
+        terminal
+            .draw(|f| draw_commit_details_view(f, f.size(), &weeks, state))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn full_hash_toggle_renders_the_whole_hash() {
+        let hash = "0123456789abcdef0123456789abcdef01234567";
+        let mut state = TuiState {
+            commit_details: vec![CommitDetail {
+                hash: hash.to_string(),
+                short_hash: hash[..7].to_string(),
+                message: "fix parser".to_string(),
+                author_name: "Alice".to_string(),
+                author_email: "alice@example.com".to_string(),
+                timestamp: Utc::now(),
+                files_changed: Vec::new(),
+                lines_added: 1,
+                lines_deleted: 0,
+                signature: None,
+            }],
+            ..Default::default()
+        };
+
+        let screen = rendered(&mut state);
+        assert!(screen.contains("0123456 ◄"), "{screen}");
+        assert!(!screen.contains(hash));
+
+        state.full_hash = true;
+        let screen = rendered(&mut state);
+        assert!(screen.contains(&format!("{hash} ◄")));
+    }
+}
@@ -4,10 +4,10 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
 use ratatui::Frame;
 
-use super::super::draw::{enhanced_intensity_bar, get_intensity_color};
+use super::super::draw::{intensity_bar, intensity_style, Intensity};
 use super::super::layout::get_visible_weeks;
 use super::super::state::{TuiState, WeekStats};
-use super::{header_cell, truncate};
+use super::{column_width, display_hash, fit_message, hash_column, header_cell, signature_line};
 
 /// Render the composite dashboard view combining periods, commit list, and details.
 pub fn draw_dashboard(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &TuiState) {
@@ -21,12 +21,16 @@ pub fn draw_dashboard(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &Tu
         .split(area);
 
     let visible_weeks = get_visible_weeks(weeks, state, f.size().height as usize);
-    let max_commits = weeks.iter().map(|ws| ws.commits).max().unwrap_or(1);
+    let intensity = Intensity::new(
+        state.intensity_scale,
+        weeks.iter().map(|ws| ws.commits as f64),
+    );
 
     let rows: Vec<Row> = visible_weeks
         .iter()
         .map(|(week, is_selected)| {
-            let intensity_bar = enhanced_intensity_bar(week.commits, max_commits);
+            let ratio = intensity.ratio(week.commits as f64);
+            let intensity_bar = intensity_bar(ratio);
             let week_label = if *is_selected {
                 format!("{} ◄", week.week)
             } else {
@@ -41,7 +45,7 @@ pub fn draw_dashboard(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &Tu
             } else {
                 Cell::from(week_label).style(Style::default().fg(Color::White))
             };
-            let commits_style = get_intensity_color(week.commits, max_commits);
+            let commits_style = intensity_style(ratio);
             let commits_cell =
                 Cell::from(format!("{:>3} {}", week.commits, intensity_bar)).style(commits_style);
             let lines_delta = week.lines_added as i64 - week.lines_deleted as i64;
@@ -74,6 +78,12 @@ pub fn draw_dashboard(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &Tu
     .block(Block::default().title("Periods").borders(Borders::ALL));
     f.render_widget(periods, chunks[0]);
 
+    let widths = [
+        hash_column(state),
+        Constraint::Percentage(60),
+        Constraint::Percentage(30),
+    ];
+    let message_column = column_width(chunks[1], &widths, 1);
     let commit_rows: Vec<Row> = {
         let indices = if state.commit_filtered_indices.is_empty() {
             (0..state.commit_details.len()).collect::<Vec<_>>()
@@ -86,16 +96,17 @@ pub fn draw_dashboard(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &Tu
             .map(|(i, commit)| {
                 let is_selected = i == state.commit_selected;
                 let hash_cell = if is_selected {
-                    Cell::from(format!("{} ◄", commit.short_hash)).style(
+                    Cell::from(format!("{} ◄", display_hash(commit, state))).style(
                         Style::default()
                             .fg(Color::Yellow)
                             .add_modifier(Modifier::BOLD),
                     )
                 } else {
-                    Cell::from(commit.short_hash.clone()).style(Style::default().fg(Color::Cyan))
+                    Cell::from(display_hash(commit, state).to_string())
+                        .style(Style::default().fg(Color::Cyan))
                 };
-                let message_cell =
-                    Cell::from(truncate(&commit.message, 50)).style(if is_selected {
+                let message_cell = Cell::from(fit_message(&commit.message, message_column, state))
+                    .style(if is_selected {
                         Style::default()
                             .fg(Color::White)
                             .add_modifier(Modifier::BOLD)
@@ -110,29 +121,25 @@ pub fn draw_dashboard(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &Tu
     };
     let mut table_state = TableState::default();
     table_state.select(Some(state.commit_selected));
-    let commits_table = Table::new(
-        commit_rows,
-        [
-            Constraint::Length(10),
-            Constraint::Percentage(60),
-            Constraint::Percentage(30),
-        ],
-    )
-    .header(Row::new([
-        header_cell("Hash", Color::Yellow),
-        header_cell("Message", Color::Yellow),
-        header_cell("Author", Color::Yellow),
-    ]))
-    .block(Block::default().title("Commits").borders(Borders::ALL));
+    let commits_table = Table::new(commit_rows, widths)
+        .header(Row::new([
+            header_cell("Hash", Color::Yellow),
+            header_cell("Message", Color::Yellow),
+            header_cell("Author", Color::Yellow),
+        ]))
+        .block(Block::default().title("Commits").borders(Borders::ALL));
     f.render_stateful_widget(commits_table, chunks[1], &mut table_state);
 
     if let Some(selected_commit) = state.commit_details.get(state.commit_selected) {
         let details_chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(8), Constraint::Min(0)])
+            .constraints([
+                Constraint::Length(8 + u16::from(state.show_signatures)),
+                Constraint::Min(0),
+            ])
             .split(chunks[2]);
 
-        let commit_info = vec![
+        let mut commit_info = vec![
             Line::from(vec![Span::styled(
                 "Commit Details",
                 Style::default()
@@ -143,7 +150,7 @@ pub fn draw_dashboard(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &Tu
             Line::from(vec![
                 Span::styled("Hash: ", Style::default().fg(Color::White)),
                 Span::styled(
-                    selected_commit.short_hash.clone(),
+                    display_hash(selected_commit, state).to_string(),
                     Style::default().fg(Color::Cyan),
                 ),
             ]),
@@ -175,6 +182,9 @@ pub fn draw_dashboard(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &Tu
                 ),
             ]),
         ];
+        if state.show_signatures {
+            commit_info.push(signature_line(selected_commit));
+        }
         f.render_widget(
             Paragraph::new(commit_info).block(Block::default().title("Info").borders(Borders::ALL)),
             details_chunks[0],
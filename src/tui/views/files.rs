@@ -3,11 +3,12 @@ use ratatui::style::{Color, Style};
 use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
 use ratatui::Frame;
 
-use crate::heat::FileExtensionStats;
-
-use super::super::state::{TuiState, WeekStats};
+use super::super::state::{FileSortColumn, TuiState, WeekStats};
 use super::header_cell;
 
+/// One table row: extension, commits, files changed, lines added, lines deleted.
+type ExtRow = (String, usize, usize, usize, usize);
+
 /// Render the file-type breakdown for the repository and the currently selected week.
 pub fn draw_files_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &TuiState) {
     let chunks = Layout::default()
@@ -27,28 +28,13 @@ pub fn draw_files_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &T
         }
     }
 
-    let mut overall_vec: Vec<(String, usize, usize, usize, usize)> = overall
+    let mut overall_vec: Vec<ExtRow> = overall
         .into_iter()
         .map(|(ext, v)| (ext, v.0, v.1, v.2, v.3))
         .collect();
-    overall_vec.sort_by(|a, b| b.3.cmp(&a.3));
-
-    let overall_rows: Vec<Row> = overall_vec
-        .into_iter()
-        .map(|(ext, commits, files, added, deleted)| {
-            Row::new(vec![
-                Cell::from(if ext.is_empty() {
-                    "(none)".to_string()
-                } else {
-                    ext
-                }),
-                Cell::from(format!("{commits}")),
-                Cell::from(format!("{files}")),
-                Cell::from(format!("+{added}")).style(Style::default().fg(Color::Green)),
-                Cell::from(format!("-{deleted}")).style(Style::default().fg(Color::Red)),
-            ])
-        })
-        .collect();
+    let overall_sort = state.file_sort.unwrap_or(FileSortColumn::Deleted);
+    sort_ext_rows(&mut overall_vec, overall_sort, state.file_sort_desc);
+    let overall_rows: Vec<Row> = overall_vec.into_iter().map(ext_row).collect();
 
     let overall_table = Table::new(
         overall_rows,
@@ -69,7 +55,10 @@ pub fn draw_files_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &T
     ]))
     .block(
         Block::default()
-            .title("Overall File Types")
+            .title(format!(
+                "Overall File Types {}",
+                sort_hint(overall_sort, state.file_sort_desc)
+            ))
             .borders(Borders::ALL),
     );
 
@@ -86,24 +75,22 @@ pub fn draw_files_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &T
     }
 
     let w = &weeks[state.selected];
-    let mut week_vec: Vec<(&String, &FileExtensionStats)> = w.file_extensions.iter().collect();
-    week_vec.sort_by(|a, b| b.1.lines_added.cmp(&a.1.lines_added));
-    let week_rows: Vec<Row> = week_vec
-        .into_iter()
+    let mut week_vec: Vec<ExtRow> = w
+        .file_extensions
+        .iter()
         .map(|(ext, s)| {
-            Row::new(vec![
-                Cell::from(if ext.is_empty() {
-                    "(none)".to_string()
-                } else {
-                    ext.clone()
-                }),
-                Cell::from(format!("{}", s.commits)),
-                Cell::from(format!("{}", s.files_changed)),
-                Cell::from(format!("+{}", s.lines_added)).style(Style::default().fg(Color::Green)),
-                Cell::from(format!("-{}", s.lines_deleted)).style(Style::default().fg(Color::Red)),
-            ])
+            (
+                ext.clone(),
+                s.commits,
+                s.files_changed,
+                s.lines_added,
+                s.lines_deleted,
+            )
         })
         .collect();
+    let week_sort = state.file_sort.unwrap_or(FileSortColumn::Added);
+    sort_ext_rows(&mut week_vec, week_sort, state.file_sort_desc);
+    let week_rows: Vec<Row> = week_vec.into_iter().map(ext_row).collect();
 
     let week_table = Table::new(
         week_rows,
@@ -124,9 +111,81 @@ pub fn draw_files_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &T
     ]))
     .block(
         Block::default()
-            .title(format!("File Types - {}", w.week))
+            .title(format!(
+                "File Types - {} {}",
+                w.week,
+                sort_hint(week_sort, state.file_sort_desc)
+            ))
             .borders(Borders::ALL),
     );
 
     f.render_widget(week_table, chunks[0]);
 }
+
+/// Sort rows by `column`, breaking ties by extension so the order is stable.
+fn sort_ext_rows(rows: &mut [ExtRow], column: FileSortColumn, descending: bool) {
+    rows.sort_by(|a, b| {
+        let key = |r: &ExtRow| match column {
+            FileSortColumn::Ext => 0,
+            FileSortColumn::Commits => r.1,
+            FileSortColumn::Files => r.2,
+            FileSortColumn::Added => r.3,
+            FileSortColumn::Deleted => r.4,
+        };
+        let order = key(a).cmp(&key(b)).then_with(|| a.0.cmp(&b.0));
+        if descending {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+}
+
+fn ext_row((ext, commits, files, added, deleted): ExtRow) -> Row<'static> {
+    Row::new(vec![
+        Cell::from(if ext.is_empty() {
+            "(none)".to_string()
+        } else {
+            ext
+        }),
+        Cell::from(format!("{commits}")),
+        Cell::from(format!("{files}")),
+        Cell::from(format!("+{added}")).style(Style::default().fg(Color::Green)),
+        Cell::from(format!("-{deleted}")).style(Style::default().fg(Color::Red)),
+    ])
+}
+
+fn sort_hint(column: FileSortColumn, descending: bool) -> String {
+    let arrow = if descending { '↓' } else { '↑' };
+    format!("(by {} {arrow})", column.label())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<ExtRow> {
+        vec![
+            ("rs".to_string(), 5, 3, 10, 40),
+            ("md".to_string(), 9, 1, 30, 5),
+            ("toml".to_string(), 1, 7, 20, 20),
+        ]
+    }
+
+    fn order(column: FileSortColumn, descending: bool) -> Vec<String> {
+        let mut rows = rows();
+        sort_ext_rows(&mut rows, column, descending);
+        rows.into_iter().map(|r| r.0).collect()
+    }
+
+    #[test]
+    fn rows_sort_by_each_column() {
+        assert_eq!(order(FileSortColumn::Ext, false), ["md", "rs", "toml"]);
+        assert_eq!(order(FileSortColumn::Ext, true), ["toml", "rs", "md"]);
+        assert_eq!(order(FileSortColumn::Commits, true), ["md", "rs", "toml"]);
+        assert_eq!(order(FileSortColumn::Files, true), ["toml", "rs", "md"]);
+        assert_eq!(order(FileSortColumn::Added, true), ["md", "toml", "rs"]);
+        assert_eq!(order(FileSortColumn::Deleted, true), ["rs", "toml", "md"]);
+        assert_eq!(order(FileSortColumn::Deleted, false), ["md", "toml", "rs"]);
+    }
+}
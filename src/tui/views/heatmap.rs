@@ -3,44 +3,56 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
 use ratatui::Frame;
+use std::collections::HashMap;
 
 use super::super::{
-    draw::{enhanced_intensity_bar, get_intensity_color},
+    draw::{enhanced_intensity_bar, intensity_bar, intensity_legend, intensity_style, Intensity},
     layout::get_visible_weeks,
     state::{TuiState, WeekStats},
 };
 use super::header_cell;
+use crate::util::{anomalies, Anomaly, ANOMALY_SIGMAS};
 
 /// Render the heatmap view showing weekly activity and a side panel of details.
 pub fn draw_heatmap_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &TuiState) {
+    if state.dense {
+        draw_dense_heatmap(f, area, weeks, state);
+        return;
+    }
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+        .constraints(panel_constraints(state.show_side_panel))
         .split(area);
 
     let visible_weeks = get_visible_weeks(weeks, state, f.size().height as usize);
-    let max_commits = weeks.iter().map(|ws| ws.commits).max().unwrap_or(1);
+    let intensity = Intensity::new(
+        state.intensity_scale,
+        weeks.iter().map(|ws| ws.commits as f64),
+    );
+
+    let anomalies = period_anomalies(weeks);
 
     let rows: Vec<Row> = visible_weeks
         .iter()
         .map(|(week, is_selected)| {
-            let intensity_bar = enhanced_intensity_bar(week.commits, max_commits);
+            let ratio = intensity.ratio(week.commits as f64);
+            let intensity_bar = intensity_bar(ratio);
             let week_label = if *is_selected {
                 format!("{} ◄", week.week)
             } else {
                 week.week.clone()
             };
-            let week_cell = if *is_selected {
-                Cell::from(week_label).style(
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                )
+            let week_style = if *is_selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
             } else {
-                Cell::from(week_label).style(Style::default().fg(Color::White))
+                Style::default().fg(Color::White)
             };
+            let week_cell = Cell::from(week_label)
+                .style(week_style.patch(anomaly_style(anomalies.get(week.week.as_str()))));
 
-            let commits_style = get_intensity_color(week.commits, max_commits);
+            let commits_style = intensity_style(ratio);
             let commits_cell =
                 Cell::from(format!("{:>3} {}", week.commits, intensity_bar)).style(commits_style);
 
@@ -64,7 +76,7 @@ pub fn draw_heatmap_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state:
                 .top_authors
                 .iter()
                 .take(max_displayed)
-                .cloned()
+                .map(|(name, _)| name.clone())
                 .collect::<Vec<_>>();
             if author_count > max_displayed {
                 displayed.push(format!("… (+{} more)", author_count - max_displayed));
@@ -113,8 +125,92 @@ pub fn draw_heatmap_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state:
             .border_style(Style::default().fg(Color::Blue)),
     );
 
-    f.render_widget(table, chunks[0]);
-    draw_enhanced_side_panel(f, chunks[1], weeks, state);
+    let table_area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(chunks[0]);
+    f.render_widget(table, table_area[0]);
+    f.render_widget(
+        Paragraph::new(intensity_legend(state.intensity_scale)),
+        table_area[1],
+    );
+    if state.show_side_panel {
+        draw_enhanced_side_panel(f, chunks[1], weeks, state);
+    }
+}
+
+/// Render one borderless line per period across the full width: label,
+/// commit count, and a bar scaled to the busiest period.
+fn draw_dense_heatmap(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &TuiState) {
+    const LABEL_WIDTH: u16 = 12;
+    const COUNT_WIDTH: u16 = 5;
+    let visible_weeks = get_visible_weeks(weeks, state, f.size().height as usize);
+    let intensity = Intensity::new(
+        state.intensity_scale,
+        weeks.iter().map(|ws| ws.commits as f64),
+    );
+    let bar_width = area.width.saturating_sub(LABEL_WIDTH + COUNT_WIDTH + 2) as usize;
+    let anomalies = period_anomalies(weeks);
+
+    let rows: Vec<Row> = visible_weeks
+        .iter()
+        .map(|(week, is_selected)| {
+            let label_style = if *is_selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            }
+            .patch(anomaly_style(anomalies.get(week.week.as_str())));
+            let ratio = intensity.ratio(week.commits as f64);
+            let filled = (ratio * bar_width as f64).round() as usize;
+            let style = intensity_style(ratio);
+            Row::new(vec![
+                Cell::from(week.week.clone()).style(label_style),
+                Cell::from(format!("{:>4}", week.commits)).style(style),
+                Cell::from("■".repeat(filled)).style(style),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(LABEL_WIDTH),
+            Constraint::Length(COUNT_WIDTH),
+            Constraint::Min(0),
+        ],
+    );
+    f.render_widget(table, area);
+}
+
+/// Periods of `weeks` far busier or quieter than the rest, by key.
+fn period_anomalies(weeks: &[WeekStats]) -> HashMap<&str, Anomaly> {
+    let counts: Vec<f64> = weeks.iter().map(|w| w.commits as f64).collect();
+    anomalies(&counts, ANOMALY_SIGMAS)
+        .into_iter()
+        .map(|(i, anomaly)| (weeks[i].week.as_str(), anomaly))
+        .collect()
+}
+
+/// Background marking an anomalous period's label.
+fn anomaly_style(anomaly: Option<&Anomaly>) -> Style {
+    match anomaly {
+        Some(Anomaly::Busy) => Style::default().bg(Color::Red),
+        Some(Anomaly::Quiet) => Style::default().bg(Color::Blue),
+        None => Style::default(),
+    }
+}
+
+/// Split between the week table and the side panel; a hidden panel gives
+/// the table the full width.
+fn panel_constraints(show_side_panel: bool) -> [Constraint; 2] {
+    if show_side_panel {
+        [Constraint::Percentage(70), Constraint::Percentage(30)]
+    } else {
+        [Constraint::Percentage(100), Constraint::Percentage(0)]
+    }
 }
 
 /// Render the right-hand summary panel with week stats, comparisons, authors, and top files.
@@ -232,7 +328,7 @@ pub fn draw_enhanced_side_panel(f: &mut Frame, area: Rect, weeks: &[WeekStats],
         .iter()
         .take(max_displayed)
         .enumerate()
-        .map(|(i, author)| {
+        .map(|(i, (author, _))| {
             let icon = match i {
                 0 => "🥇",
                 1 => "🥈",
@@ -320,3 +416,28 @@ pub fn draw_enhanced_side_panel(f: &mut Frame, area: Rect, weeks: &[WeekStats],
     );
     f.render_widget(files_summary_panel, chunks[3]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_side_panel_gives_the_table_full_width() {
+        assert_eq!(
+            panel_constraints(true),
+            [Constraint::Percentage(70), Constraint::Percentage(30)]
+        );
+        assert_eq!(
+            panel_constraints(false),
+            [Constraint::Percentage(100), Constraint::Percentage(0)]
+        );
+
+        let area = Rect::new(0, 0, 100, 10);
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(panel_constraints(false))
+            .split(area);
+        assert_eq!(chunks[0].width, 100);
+        assert_eq!(chunks[1].width, 0);
+    }
+}
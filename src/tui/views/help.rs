@@ -38,8 +38,13 @@ pub fn draw_help_overlay(f: &mut Frame, area: Rect) {
                 .fg(Color::Green)
                 .add_modifier(Modifier::BOLD),
         )]),
-        Line::from("  Tab         Next view (Heatmap/Stats/Timeline/Commits)"),
+        Line::from("  Tab         Next view (Heatmap/Stats/Timeline/Commits/Treemap)"),
         Line::from("  Shift+Tab   Previous view"),
+        Line::from("  >           Hide/show the heatmap side panel"),
+        Line::from("  d           Dense heatmap: one line per period"),
+        Line::from("  s / S       Cycle file-type sort column / flip direction"),
+        Line::from("  w           Treemap of the selected period / all periods"),
+        Line::from("  a           Timeline as raw counts / moving average"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Actions:",
@@ -48,7 +53,10 @@ pub fn draw_help_overlay(f: &mut Frame, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from("  c / y       Copy full / short hash"),
+        Line::from("  H           Show full / short hashes"),
         Line::from("  o           Open commit in pager (git show)"),
+        Line::from("  [ / ]       Highlight previous / next changed file"),
+        Line::from("  e           Open highlighted file in $VISUAL/$EDITOR"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Search & Filter:",
@@ -58,10 +66,14 @@ pub fn draw_help_overlay(f: &mut Frame, area: Rect) {
         )]),
         Line::from("  /           Filter periods"),
         Line::from("  :           Filter commits (message/author/hash)"),
+        Line::from("  f           Find a commit in any period and jump to it"),
+        Line::from("  z           Toggle fuzzy matching for / and : (ranked)"),
+        Line::from("  i           Toggle case-sensitive matching for /, : and f"),
+        Line::from("  /pattern    Start a / or : query with / to match a regex"),
         Line::from("  p           Set path prefix filter"),
         Line::from("  m/M         Toggle monthly/include merges"),
         Line::from("  A           Toggle show-all vs last 12m/52w"),
-        Line::from("  Esc         Cancel input / close help"),
+        Line::from("  Esc         Cancel input / close help / stop loading"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "General:",
@@ -0,0 +1,72 @@
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::tui::centered_rect;
+use crate::tui::state::TuiState;
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Draw a small centered box with a spinner and the number of commits loaded
+/// so far, while a background fetch is running.
+pub fn draw_loading_overlay(f: &mut Frame, area: Rect, state: &TuiState) {
+    if !state.loading {
+        return;
+    }
+    let popup = centered_rect(40, 20, area);
+    f.render_widget(Clear, popup);
+
+    let spinner = SPINNER[state.loading_tick % SPINNER.len()];
+    let lines = vec![
+        Line::from(vec![Span::styled(
+            format!("{spinner} Loading commits"),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(format!("{} analyzed so far", state.loaded_commits)),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Esc: stop and use what has loaded",
+            Style::default().fg(Color::DarkGray),
+        )]),
+    ];
+    let p = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .block(Block::default().title("Loading").borders(Borders::ALL));
+    f.render_widget(p, popup);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn rendered(state: &TuiState) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        terminal
+            .draw(|f| draw_loading_overlay(f, f.size(), state))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn overlay_shows_only_while_loading() {
+        let mut state = TuiState {
+            loading: true,
+            loaded_commits: 1234,
+            ..Default::default()
+        };
+        let screen = rendered(&state);
+        assert!(screen.contains("Loading commits"));
+        assert!(screen.contains("1234 analyzed so far"));
+
+        state.loading = false;
+        let screen = rendered(&state);
+        assert!(!screen.contains("Loading"));
+    }
+}
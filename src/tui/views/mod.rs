@@ -1,14 +1,21 @@
+use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
 use ratatui::widgets::Cell;
 
+use super::state::{CommitDetail, TuiState};
+use crate::model::SignatureStatus;
+
 mod commits;
 mod dashboard;
 mod file_modal;
 mod files;
 mod heatmap;
 mod help;
+mod loading;
 mod statistics;
 mod timeline;
+mod treemap;
 
 pub use commits::draw_commit_details_view;
 pub use dashboard::draw_dashboard;
@@ -16,8 +23,10 @@ pub use file_modal::draw_file_modal;
 pub use files::draw_files_view;
 pub use heatmap::{draw_enhanced_side_panel, draw_heatmap_view};
 pub use help::draw_help_overlay;
+pub use loading::draw_loading_overlay;
 pub use statistics::draw_statistics_view;
 pub use timeline::draw_timeline_view;
+pub use treemap::draw_treemap_view;
 
 /// Convenience helper to build a styled table header cell.
 pub(crate) fn header_cell(text: &str, color: Color) -> Cell<'static> {
@@ -26,9 +35,91 @@ pub(crate) fn header_cell(text: &str, color: Color) -> Cell<'static> {
 
 /// Truncate a string to `max` chars with an ellipsis when necessary.
 pub(crate) fn truncate(s: &str, max: usize) -> String {
-    if s.len() > max {
-        format!("{}...", &s[..max.saturating_sub(3)])
+    if s.chars().count() > max {
+        let kept: String = s.chars().take(max.saturating_sub(3)).collect();
+        format!("{kept}...")
     } else {
         s.to_string()
     }
 }
+
+/// Width of column `index` when a bordered table with `widths` fills `area`.
+pub(crate) fn column_width(area: Rect, widths: &[Constraint], index: usize) -> usize {
+    let inner = Rect {
+        x: area.x + 1,
+        y: area.y + 1,
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(2),
+    };
+    Layout::horizontal(widths.iter().copied())
+        .spacing(1)
+        .split(inner)
+        .get(index)
+        .map_or(0, |column| column.width as usize)
+}
+
+/// Fit a commit message to a table column `column` chars wide, capped by
+/// `--message-width` when given.
+pub(crate) fn fit_message(message: &str, column: usize, state: &TuiState) -> String {
+    let width = state.message_width.map_or(column, |max| max.min(column));
+    truncate(message, width)
+}
+
+/// The hash shown for `commit`: the full hash when toggled on, else its
+/// abbreviation.
+pub(crate) fn display_hash<'a>(commit: &'a CommitDetail, state: &TuiState) -> &'a str {
+    if state.full_hash {
+        &commit.hash
+    } else {
+        &commit.short_hash
+    }
+}
+
+/// The `Signature:` line of the details pane; commits cached before
+/// signatures were recorded show as unknown.
+pub(crate) fn signature_line(commit: &CommitDetail) -> Line<'static> {
+    let (text, color) = match commit.signature {
+        Some(SignatureStatus::Unsigned) => ("unsigned", Color::DarkGray),
+        Some(status) => (status.as_str(), Color::Green),
+        None => ("unknown", Color::DarkGray),
+    };
+    Line::from(vec![
+        Span::styled("Signature: ", Style::default().fg(Color::White)),
+        Span::styled(text, Style::default().fg(color)),
+    ])
+}
+
+/// Hash column wide enough for the shown hashes plus the selection marker.
+pub(crate) fn hash_column(state: &TuiState) -> Constraint {
+    let widest = state
+        .commit_details
+        .iter()
+        .map(|c| display_hash(c, state).len())
+        .max()
+        .unwrap_or(8);
+    Constraint::Length(widest as u16 + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_counts_chars_not_bytes() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("fix the parser crash", 10), "fix the...");
+        assert_eq!(truncate("ändere größe überall", 10), "ändere ...");
+    }
+
+    #[test]
+    fn fit_message_uses_column_unless_capped() {
+        let message = "feat: add a configurable message width";
+        let mut state = TuiState::default();
+        assert_eq!(fit_message(message, 20, &state), "feat: add a confi...");
+        assert_eq!(fit_message(message, 80, &state), message);
+
+        state.message_width = Some(12);
+        assert_eq!(fit_message(message, 80, &state), "feat: add...");
+        assert_eq!(fit_message(message, 8, &state), "feat:...");
+    }
+}
@@ -5,6 +5,8 @@ use ratatui::widgets::{Block, Borders, Gauge, Paragraph, Sparkline};
 use ratatui::Frame;
 
 use super::super::state::{TuiState, WeekStats};
+use crate::model::StatsSummary;
+use crate::stats::summarize_weeks;
 
 /// Render the aggregate repository statistics view with gauges and a commit trend sparkline.
 pub fn draw_statistics_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &TuiState) {
@@ -17,18 +19,19 @@ pub fn draw_statistics_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], stat
         ])
         .split(area);
 
-    let total_commits: usize = weeks.iter().map(|w| w.commits).sum();
-    let total_added: usize = weeks.iter().map(|w| w.lines_added).sum();
-    let total_deleted: usize = weeks.iter().map(|w| w.lines_deleted).sum();
-    let avg_commits = if !weeks.is_empty() {
-        total_commits / weeks.len()
-    } else {
-        0
-    };
-    let max_commits = weeks.iter().map(|w| w.commits).max().unwrap_or(0);
-    let min_commits = weeks.iter().map(|w| w.commits).min().unwrap_or(0);
-
-    let net_change = total_added as i64 - total_deleted as i64;
+    let StatsSummary {
+        total_commits,
+        lines_added: total_added,
+        lines_deleted: total_deleted,
+        net_change,
+        avg_commits,
+        max_commits,
+        min_commits,
+        commits_by_hour: hourly,
+        busiest_hour,
+        trend: trend_data,
+        ..
+    } = summarize_weeks(weeks);
 
     let stats_text = vec![
         Line::from(vec![Span::styled(
@@ -77,7 +80,10 @@ pub fn draw_statistics_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], stat
         )]),
         Line::from(vec![
             Span::styled("Average per week: ", Style::default().fg(Color::White)),
-            Span::styled(format!("{avg_commits}"), Style::default().fg(Color::Cyan)),
+            Span::styled(
+                format!("{avg_commits:.1}"),
+                Style::default().fg(Color::Cyan),
+            ),
         ]),
         Line::from(vec![
             Span::styled("Maximum week: ", Style::default().fg(Color::White)),
@@ -119,12 +125,26 @@ pub fn draw_statistics_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], stat
         f.render_widget(gauge, chunks[1]);
     }
 
-    let trend_data: Vec<u64> = weeks.iter().map(|w| w.commits as u64).collect();
+    let bottom_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[2]);
+
     if trend_data.len() > 1 {
         let sparkline = Sparkline::default()
             .block(Block::default().title("Commit Trend").borders(Borders::ALL))
             .data(&trend_data)
             .style(Style::default().fg(Color::Yellow));
-        f.render_widget(sparkline, chunks[2]);
+        f.render_widget(sparkline, bottom_chunks[0]);
     }
+
+    let hourly_sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title(format!("Commits by Hour (UTC, peak {busiest_hour:02}:00)"))
+                .borders(Borders::ALL),
+        )
+        .data(&hourly)
+        .style(Style::default().fg(Color::Cyan));
+    f.render_widget(hourly_sparkline, bottom_chunks[1]);
 }
@@ -1,29 +1,76 @@
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Modifier, Style};
-use ratatui::widgets::{Block, Borders, Cell, Row, Sparkline, Table};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Sparkline, Table};
 use ratatui::Frame;
+use std::collections::BTreeMap;
 
 use super::super::state::{TuiState, WeekStats};
+use crate::util::{cumulative, smoothed_by_period};
 
 /// Render a simple commit timeline sparkline plus a table of recent weeks.
-pub fn draw_timeline_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], _state: &TuiState) {
+/// `tags` maps period keys to release tags, marked under the sparkline.
+pub fn draw_timeline_view(
+    f: &mut Frame,
+    area: Rect,
+    weeks: &[WeekStats],
+    state: &TuiState,
+    tags: &BTreeMap<String, Vec<String>>,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
         .split(area);
 
-    let commit_data: Vec<u64> = weeks.iter().map(|w| w.commits as u64).collect();
+    let spark_area = if state.show_growth {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[0]);
+        draw_growth_sparkline(f, split[1], weeks);
+        split[0]
+    } else {
+        chunks[0]
+    };
+
+    let spark_area = if weeks.iter().any(|w| tags.contains_key(&w.week)) {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(4)])
+            .split(spark_area);
+        draw_release_markers(f, split[1], weeks, tags);
+        split[0]
+    } else {
+        spark_area
+    };
+
+    let (title, commit_data) = if state.smooth {
+        let series: Vec<_> = weeks
+            .iter()
+            .map(|w| (w.week.as_str(), w.commits as f64))
+            .collect();
+        // Sparklines take integers; scaling keeps the averages' shape.
+        let averaged = smoothed_by_period(&series, state.smooth_window)
+            .into_iter()
+            .map(|avg| (avg * 100.0).round() as u64)
+            .collect();
+        (
+            format!(
+                "Commits Over Time ({}-period moving average)",
+                state.smooth_window
+            ),
+            averaged,
+        )
+    } else {
+        let raw: Vec<u64> = weeks.iter().map(|w| w.commits as u64).collect();
+        ("Commits Over Time".to_string(), raw)
+    };
 
     if !commit_data.is_empty() {
         let commits_sparkline = Sparkline::default()
-            .block(
-                Block::default()
-                    .title("Commits Over Time")
-                    .borders(Borders::ALL),
-            )
+            .block(Block::default().title(title).borders(Borders::ALL))
             .data(&commit_data)
             .style(Style::default().fg(ratatui::style::Color::Green));
-        f.render_widget(commits_sparkline, chunks[0]);
+        f.render_widget(commits_sparkline, spark_area);
     }
 
     let recent_weeks = weeks.iter().rev().take(10).collect::<Vec<_>>();
@@ -68,3 +115,63 @@ pub fn draw_timeline_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], _state
 
     f.render_widget(timeline_table, chunks[1]);
 }
+
+/// One marker per tagged period, in the same columns as the sparkline bars,
+/// with the tag names listed below.
+fn draw_release_markers(
+    f: &mut Frame,
+    area: Rect,
+    weeks: &[WeekStats],
+    tags: &BTreeMap<String, Vec<String>>,
+) {
+    let width = area.width.saturating_sub(2) as usize;
+    let markers: String = weeks
+        .iter()
+        .take(width)
+        .map(|w| {
+            if tags.contains_key(&w.week) {
+                '▲'
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let labels = weeks
+        .iter()
+        .filter_map(|w| {
+            tags.get(&w.week)
+                .map(|names| format!("{} ({})", names.join(", "), w.week))
+        })
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+    let paragraph = Paragraph::new(format!("{markers}\n{labels}"))
+        .block(Block::default().title("Releases").borders(Borders::ALL))
+        .style(Style::default().fg(ratatui::style::Color::Yellow));
+    f.render_widget(paragraph, area);
+}
+
+/// Plot the running net line total. Sparklines are unsigned, so values are
+/// shifted up by the lowest point; the title carries the real figures.
+fn draw_growth_sparkline(f: &mut Frame, area: Rect, weeks: &[WeekStats]) {
+    let totals = cumulative(
+        weeks
+            .iter()
+            .map(|w| w.lines_added as i64 - w.lines_deleted as i64),
+    );
+    let Some(&current) = totals.last() else {
+        return;
+    };
+    let floor = totals.iter().copied().min().unwrap_or(0).min(0);
+    let data: Vec<u64> = totals.iter().map(|&t| (t - floor) as u64).collect();
+
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title(format!("Net Line Growth (total {current:+})"))
+                .borders(Borders::ALL),
+        )
+        .data(&data)
+        .style(Style::default().fg(ratatui::style::Color::Cyan));
+    f.render_widget(sparkline, area);
+}
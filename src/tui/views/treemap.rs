@@ -0,0 +1,151 @@
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+use std::collections::HashMap;
+
+use super::super::squarify::squarify;
+use super::super::state::{FileActivity, TuiState, WeekStats, TREEMAP_FILES};
+use super::truncate;
+
+/// Render the selected period's files (or every listed period's, toggled with
+/// `w`) as a squarified treemap: area follows lines changed, color how
+/// recently the file was touched, from blue (oldest shown) to orange.
+pub fn draw_treemap_view(f: &mut Frame, area: Rect, weeks: &[WeekStats], state: &TuiState) {
+    let (scope, files) = if state.treemap_all_periods {
+        ("all periods".to_string(), merged_activity(weeks))
+    } else {
+        match weeks.get(state.selected) {
+            Some(week) => (week.week.clone(), week.file_activity.clone()),
+            None => (String::new(), Vec::new()),
+        }
+    };
+    let block = Block::default()
+        .title(format!(
+            "Treemap: {scope} (area = lines changed, color = recency; w: period/all)"
+        ))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if files.is_empty() {
+        f.render_widget(Paragraph::new("No file changes"), inner);
+        return;
+    }
+
+    let weights: Vec<f64> = files.iter().map(|file| file.lines.max(1) as f64).collect();
+    let tiles = squarify(&weights, inner.width as f64, inner.height as f64);
+    let oldest = files.iter().map(|file| file.last_touched).min();
+    let newest = files.iter().map(|file| file.last_touched).max();
+    let span = match (oldest, newest) {
+        (Some(oldest), Some(newest)) => (newest - oldest).num_seconds() as f64,
+        _ => 0.0,
+    };
+
+    for (file, tile) in files.iter().zip(tiles) {
+        let cells = tile.to_cells(inner);
+        if cells.width == 0 || cells.height == 0 {
+            continue;
+        }
+        let recency = match oldest {
+            Some(oldest) if span > 0.0 => (file.last_touched - oldest).num_seconds() as f64 / span,
+            _ => 1.0,
+        };
+        let name = file.path.rsplit('/').next().unwrap_or(&file.path);
+        let width = cells.width as usize;
+        let lines = vec![
+            Line::from(truncate(name, width)),
+            Line::from(truncate(&format!("{} lines", file.lines), width)),
+        ];
+        f.render_widget(
+            Paragraph::new(lines)
+                .style(Style::default().fg(Color::Black).bg(recency_color(recency))),
+            cells,
+        );
+    }
+}
+
+/// Sum each file's lines over `weeks`, keeping its latest touch, largest first.
+fn merged_activity(weeks: &[WeekStats]) -> Vec<FileActivity> {
+    let mut merged: HashMap<&str, FileActivity> = HashMap::new();
+    for file in weeks.iter().flat_map(|w| &w.file_activity) {
+        merged
+            .entry(&file.path)
+            .and_modify(|seen| {
+                seen.lines += file.lines;
+                seen.last_touched = seen.last_touched.max(file.last_touched);
+            })
+            .or_insert_with(|| file.clone());
+    }
+    let mut files: Vec<_> = merged.into_values().collect();
+    files.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.path.cmp(&b.path)));
+    files.truncate(TREEMAP_FILES);
+    files
+}
+
+/// Blend from blue (0.0, oldest) to orange (1.0, newest).
+fn recency_color(recency: f64) -> Color {
+    let blend = |old: u8, new: u8| (old as f64 + (new as f64 - old as f64) * recency) as u8;
+    Color::Rgb(blend(70, 235), blend(110, 140), blend(180, 50))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn all_periods_merges_lines_per_file() {
+        let at = |day| Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0).unwrap();
+        let file = |path: &str, lines, day| FileActivity {
+            path: path.to_string(),
+            lines,
+            last_touched: at(day),
+        };
+        let week = |name: &str, file_activity| WeekStats {
+            week: name.to_string(),
+            commits: 1,
+            lines_added: 1,
+            lines_deleted: 0,
+            binary_files_changed: 0,
+            top_authors: Vec::new(),
+            file_extensions: HashMap::new(),
+            top_files: Vec::new(),
+            file_activity,
+            commits_by_hour: [0; 24],
+        };
+        let weeks = [
+            week(
+                "2024-W01",
+                vec![file("src/big.rs", 40, 2), file("b.rs", 5, 3)],
+            ),
+            week("2024-W02", vec![file("b.rs", 50, 9)]),
+        ];
+
+        let merged = merged_activity(&weeks);
+        let summary: Vec<_> = merged.iter().map(|f| (f.path.as_str(), f.lines)).collect();
+        assert_eq!(summary, [("b.rs", 55), ("src/big.rs", 40)]);
+        assert_eq!(merged[0].last_touched, at(9));
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        let state = TuiState {
+            treemap_all_periods: true,
+            ..Default::default()
+        };
+        terminal
+            .draw(|f| draw_treemap_view(f, f.size(), &weeks, &state))
+            .unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("big.rs"), "{screen}");
+        assert!(screen.contains("55 lines"));
+    }
+}
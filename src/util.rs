@@ -1,24 +1,406 @@
-use crate::model::FileStats;
-use chrono::{DateTime, Datelike, Months, Utc};
-use std::collections::HashMap;
+use crate::model::{FileStats, ReleaseTag};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Timelike, Utc};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// First day of a weekly bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, clap::ValueEnum)]
+pub enum WeekStart {
+    /// ISO weeks, starting Monday
+    #[default]
+    #[value(name = "mon", alias = "monday")]
+    Monday,
+    /// Weeks starting Sunday
+    #[value(name = "sun", alias = "sunday")]
+    Sunday,
+}
+
+/// How weekly period keys are shown in output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum WeekFormat {
+    /// ISO year and week, e.g. `2024-W05`
+    #[default]
+    Iso,
+    /// Date of the week's first day, e.g. `2024-01-29`
+    Date,
+    /// Week then year, e.g. `W5 2024`
+    Short,
+}
+
+/// How commit timestamps are grouped into periods.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PeriodSpec {
+    pub monthly: bool,
+    pub week_start: WeekStart,
+    /// Fixed-length sprints, taking precedence over weeks and months
+    pub sprints: Option<Sprints>,
+    /// Weeks restarting at each month, in place of ISO weeks
+    pub month_weeks: bool,
+}
+
+impl PeriodSpec {
+    pub fn new(monthly: bool, week_start: WeekStart) -> Self {
+        Self {
+            monthly,
+            week_start,
+            sprints: None,
+            month_weeks: false,
+        }
+    }
+
+    pub fn with_month_weeks(mut self, month_weeks: bool) -> Self {
+        self.month_weeks = month_weeks;
+        self
+    }
+
+    pub fn with_sprints(mut self, sprints: Option<Sprints>) -> Self {
+        self.sprints = sprints;
+        self
+    }
+}
+
+/// Back-to-back sprint windows of `length_days`, the first starting on `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Sprints {
+    pub start: NaiveDate,
+    pub length_days: u32,
+}
+
+/// Digits sprint numbers are zero-padded to in keys, so keys sort in order.
+const SPRINT_KEY_DIGITS: usize = 4;
+
+/// Sprint key (`Sprint 0003`) for the window of `length_days` days, counted
+/// from 1 at `start`, that `timestamp`'s UTC date falls in. Commits before
+/// `start` share `Sprint 0000`.
+pub fn sprint_key(timestamp: &DateTime<Utc>, start: NaiveDate, length_days: u32) -> String {
+    let days = (timestamp.date_naive() - start).num_days();
+    let index = if days < 0 {
+        0
+    } else {
+        days / i64::from(length_days.max(1)) + 1
+    };
+    format!("Sprint {index:0SPRINT_KEY_DIGITS$}")
+}
+
+/// The sprint number of a [`sprint_key`], if `key` is one.
+fn sprint_number(key: &str) -> Option<u64> {
+    key.strip_prefix("Sprint ")?.parse().ok()
+}
 
 pub fn week_key(timestamp: &DateTime<Utc>) -> String {
-    format!("{}-W{:02}", timestamp.year(), timestamp.iso_week().week())
+    let iso = timestamp.iso_week();
+    format!("{}-W{:02}", iso.year(), iso.week())
+}
+
+/// Week key for the given week start. A Sunday-start week is labelled with the
+/// ISO week of the Monday that follows it.
+pub fn week_key_from(timestamp: &DateTime<Utc>, week_start: WeekStart) -> String {
+    match week_start {
+        WeekStart::Monday => week_key(timestamp),
+        WeekStart::Sunday => week_key(&(*timestamp + Duration::days(1))),
+    }
+}
+
+/// Render a week key (`2024-W05`) in `format`, and a sprint key without its
+/// padding (`Sprint 3`). Other keys, such as months, are returned unchanged.
+pub fn week_label(key: &str, format: WeekFormat, week_start: WeekStart) -> String {
+    if let Some(number) = sprint_number(key) {
+        return format!("Sprint {number}");
+    }
+    let parsed = key
+        .split_once("-W")
+        .and_then(|(year, week)| Some((year.parse::<i32>().ok()?, week.parse::<u32>().ok()?)));
+    let Some((year, week)) = parsed else {
+        return key.to_string();
+    };
+    match format {
+        WeekFormat::Iso => key.to_string(),
+        WeekFormat::Short => format!("W{week} {year}"),
+        WeekFormat::Date => {
+            match chrono::NaiveDate::from_isoywd_opt(year, week, chrono::Weekday::Mon) {
+                // A Sunday week carries the ISO week of the Monday after it.
+                Some(monday) => match week_start {
+                    WeekStart::Monday => monday,
+                    WeekStart::Sunday => monday - Duration::days(1),
+                }
+                .format("%Y-%m-%d")
+                .to_string(),
+                None => key.to_string(),
+            }
+        }
+    }
+}
+
+/// The period key after `key`, for week (`2024-W05`), month (`2024-02`),
+/// week of month (`2024-02-W4`) or sprint (`Sprint 0003`) keys.
+pub fn next_period_key(key: &str) -> Option<String> {
+    if let Some(number) = sprint_number(key) {
+        return Some(format!("Sprint {:0SPRINT_KEY_DIGITS$}", number + 1));
+    }
+    if let Some((year, month, week)) = parse_month_week(key) {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let next_month = first.checked_add_months(Months::new(1))?;
+        // Week 5 only exists in months longer than 28 days.
+        let weeks = ((next_month - first).num_days() as u32).div_ceil(7);
+        return Some(if week < weeks {
+            format!("{year}-{month:02}-W{}", week + 1)
+        } else {
+            format!("{}-{:02}-W1", next_month.year(), next_month.month())
+        });
+    }
+    if let Some((year, week)) = key.split_once("-W") {
+        let monday = chrono::NaiveDate::from_isoywd_opt(
+            year.parse().ok()?,
+            week.parse().ok()?,
+            chrono::Weekday::Mon,
+        )?;
+        let iso = (monday + Duration::days(7)).iso_week();
+        return Some(format!("{}-W{:02}", iso.year(), iso.week()));
+    }
+    let (year, month) = key.split_once('-')?;
+    let (year, month): (i32, u32) = (year.parse().ok()?, month.parse().ok()?);
+    Some(if month >= 12 {
+        format!("{}-01", year + 1)
+    } else {
+        format!("{year}-{:02}", month + 1)
+    })
 }
 
 pub fn month_key(timestamp: &DateTime<Utc>) -> String {
     format!("{}-{:02}", timestamp.year(), timestamp.month())
 }
 
-pub fn period_key(timestamp: &DateTime<Utc>, monthly: bool) -> String {
-    if monthly {
+/// Week of the month (`2024-01-W4`): days 1–7 are week 1, 8–14 week 2 and
+/// so on, so week 5 holds days 29–31 and every week rolls up into one month.
+pub fn month_week_key(timestamp: &DateTime<Utc>) -> String {
+    format!(
+        "{}-W{}",
+        month_key(timestamp),
+        (timestamp.day() - 1) / 7 + 1
+    )
+}
+
+/// Year, month and week of a [`month_week_key`], if `key` is one.
+fn parse_month_week(key: &str) -> Option<(i32, u32, u32)> {
+    let (month, week) = key.rsplit_once("-W")?;
+    let (year, month) = month.split_once('-')?;
+    Some((year.parse().ok()?, month.parse().ok()?, week.parse().ok()?))
+}
+
+pub fn period_key(timestamp: &DateTime<Utc>, period: PeriodSpec) -> String {
+    if let Some(sprints) = period.sprints {
+        sprint_key(timestamp, sprints.start, sprints.length_days)
+    } else if period.monthly {
         month_key(timestamp)
+    } else if period.month_weeks {
+        month_week_key(timestamp)
+    } else {
+        week_key_from(timestamp, period.week_start)
+    }
+}
+
+/// Count timestamps per hour of day (UTC), index 0 being midnight.
+pub fn hour_histogram<'a, I>(timestamps: I) -> [u64; 24]
+where
+    I: IntoIterator<Item = &'a DateTime<Utc>>,
+{
+    let mut hours = [0u64; 24];
+    for ts in timestamps {
+        hours[ts.hour() as usize] += 1;
+    }
+    hours
+}
+
+/// Serialize `value` as pretty-printed JSON, or minified when `compact` is set.
+pub fn to_json<T: serde::Serialize>(value: &T, compact: bool) -> serde_json::Result<String> {
+    if compact {
+        serde_json::to_string(value)
+    } else {
+        serde_json::to_string_pretty(value)
+    }
+}
+
+/// Serialize a map with its keys in order, for `serialize_with` on `HashMap`
+/// fields, so the output is the same from run to run.
+pub fn sorted_map<V: serde::Serialize, S: serde::Serializer>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
+
+/// Commit-size cutoff above which a commit is treated as an outlier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlierThreshold {
+    /// Absolute lines changed (added + deleted)
+    Lines(u64),
+    /// Multiple of the median commit size
+    MedianFactor(f64),
+}
+
+impl OutlierThreshold {
+    /// Flag each commit size that exceeds the threshold.
+    pub fn flag(&self, sizes: &[u64]) -> Vec<bool> {
+        let limit = match *self {
+            OutlierThreshold::Lines(n) => n as f64,
+            OutlierThreshold::MedianFactor(factor) => median(sizes) * factor,
+        };
+        sizes.iter().map(|&s| s as f64 > limit).collect()
+    }
+}
+
+fn median(values: &[u64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) as f64 / 2.0
+    } else {
+        sorted[mid] as f64
+    }
+}
+
+/// A period whose commit count lies far from the mean of its series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anomaly {
+    Busy,
+    Quiet,
+}
+
+/// Standard deviations from the mean past which a period is an anomaly.
+pub const ANOMALY_SIGMAS: f64 = 2.0;
+
+/// Indices of `values` more than `sigmas` standard deviations above or below
+/// their mean. A flat series has none.
+pub fn anomalies(values: &[f64], sigmas: f64) -> BTreeMap<usize, Anomaly> {
+    if values.is_empty() {
+        return BTreeMap::new();
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let sd = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+    if sd == 0.0 {
+        return BTreeMap::new();
+    }
+    values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, value)| {
+            let z = (value - mean) / sd;
+            if z > sigmas {
+                Some((i, Anomaly::Busy))
+            } else if z < -sigmas {
+                Some((i, Anomaly::Quiet))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Exponential decay factor for something `age_days` old: 1.0 now, 0.5 after
+/// one half-life. Future timestamps are treated as age zero.
+pub fn recency_weight(age_days: f64, half_life_days: f64) -> f64 {
+    (-age_days.max(0.0) * std::f64::consts::LN_2 / half_life_days).exp()
+}
+
+/// Running totals of `deltas`, one per input value.
+pub fn cumulative<I: IntoIterator<Item = i64>>(deltas: I) -> Vec<i64> {
+    deltas
+        .into_iter()
+        .scan(0i64, |total, d| {
+            *total += d;
+            Some(*total)
+        })
+        .collect()
+}
+
+/// Trailing moving average over `window` values; the first few average over
+/// what is available so far. A window of 0 or 1 keeps the values as they are.
+pub fn moving_average(values: &[f64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    let mut sum = 0.0;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            sum += value;
+            if i >= window {
+                sum -= values[i - window];
+            }
+            sum / (i + 1).min(window) as f64
+        })
+        .collect()
+}
+
+/// Every period key from the lowest to the highest of `keys`, in order. When
+/// some key cannot be reached by stepping periods, just the distinct keys.
+pub fn dense_period_keys<'a>(keys: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let given: std::collections::BTreeSet<&str> = keys.into_iter().collect();
+    let (Some(&first), Some(&last)) = (given.first(), given.last()) else {
+        return Vec::new();
+    };
+    let mut dense = vec![first.to_string()];
+    while let Some(next) = dense
+        .last()
+        .filter(|key| key.as_str() < last)
+        .and_then(|key| next_period_key(key))
+    {
+        dense.push(next);
+    }
+    if given
+        .iter()
+        .all(|key| dense.binary_search_by(|d| d.as_str().cmp(key)).is_ok())
+    {
+        dense
     } else {
-        week_key(timestamp)
+        given.into_iter().map(str::to_string).collect()
     }
 }
 
+/// [`moving_average`] of per-period values, with periods missing between the
+/// first and last key counting as zero, read back for each key in `series`
+/// (sorted by key). Keys that cannot be stepped through are averaged as given.
+pub fn smoothed_by_period(series: &[(&str, f64)], window: usize) -> Vec<f64> {
+    let given: HashMap<&str, f64> = series.iter().copied().collect();
+    let keys = dense_period_keys(series.iter().map(|&(key, _)| key));
+    let dense: HashMap<&str, usize> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| (key.as_str(), i))
+        .collect();
+    let values: Vec<f64> = keys
+        .iter()
+        .map(|key| given.get(key.as_str()).copied().unwrap_or(0.0))
+        .collect();
+    let averaged = moving_average(&values, window);
+    series.iter().map(|(key, _)| averaged[dense[key]]).collect()
+}
+
+/// Lines changed by the files of a commit that `--exclude` and the gitignore
+/// rules keep: the commit size outlier thresholds are measured against.
+pub fn commit_size(
+    files: &[FileStats],
+    excludes: &[String],
+    case_sensitive: bool,
+    git_ignore: Option<&GitIgnoreMatcher>,
+) -> u64 {
+    files
+        .iter()
+        .filter(|f| !path_excluded(&f.path, excludes, case_sensitive))
+        .filter(|f| git_ignore.is_none_or(|gi| !gi.is_ignored(&f.path)))
+        .map(|f| f.added_lines as u64 + f.deleted_lines as u64)
+        .sum()
+}
+
 pub fn files_matching<'a>(
     files: &'a [FileStats],
     path_prefix: Option<&'a str>,
@@ -32,12 +414,151 @@ pub fn files_matching<'a>(
     })
 }
 
-pub fn path_excluded(path: &str, excludes: &[String]) -> bool {
-    if excludes.is_empty() {
-        return false;
+/// Lowercased domain of an email address, or `unknown` when there is none.
+pub fn email_domain(email: &str) -> String {
+    match email.trim().rsplit_once('@') {
+        Some((_, domain)) if !domain.trim().is_empty() => {
+            domain.trim().trim_end_matches('>').to_lowercase()
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Conventional Commits types recognized by [`commit_type`].
+pub const COMMIT_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Conventional Commits type of a message (`feat(scope)!: ...` is `feat`), or
+/// `other` when the subject has no recognized `type(scope):` prefix.
+pub fn commit_type(message: &str) -> &'static str {
+    let subject = message.lines().next().unwrap_or_default();
+    let Some((head, _)) = subject.split_once(':') else {
+        return "other";
+    };
+    let head = head.strip_suffix('!').unwrap_or(head);
+    let kind = match head.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return "other",
+        None => head,
+    };
+    let kind = kind.to_ascii_lowercase();
+    COMMIT_TYPES
+        .iter()
+        .find(|t| **t == kind)
+        .copied()
+        .unwrap_or("other")
+}
+
+/// Blobs below this many bytes are `small`.
+pub const SMALL_BLOB_BYTES: u64 = 4 * 1024;
+/// Blobs below this many bytes (and not small) are `medium`; the rest `large`.
+pub const MEDIUM_BLOB_BYTES: u64 = 64 * 1024;
+
+/// Size bucket of a blob, or `unknown` when no size was recorded.
+pub fn size_bucket(size: Option<u64>) -> &'static str {
+    match size {
+        None => "unknown",
+        Some(n) if n < SMALL_BLOB_BYTES => "small",
+        Some(n) if n < MEDIUM_BLOB_BYTES => "medium",
+        Some(_) => "large",
+    }
+}
+
+/// Shell-style glob match supporting `*` (any run) and `?` (one character).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        match p.get(pi) {
+            Some('*') => {
+                backtrack = Some((pi, ti));
+                pi += 1;
+            }
+            Some(&c) if c == '?' || c == t[ti] => {
+                pi += 1;
+                ti += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    pi = star + 1;
+                    ti = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Tag names per period key, each list in tag name order.
+pub fn tag_periods(tags: &[ReleaseTag], period: PeriodSpec) -> BTreeMap<String, Vec<String>> {
+    let mut map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for tag in tags {
+        map.entry(period_key(&tag.timestamp, period))
+            .or_default()
+            .push(tag.name.clone());
+    }
+    for names in map.values_mut() {
+        names.sort();
+    }
+    map
+}
+
+/// Shortest abbreviation, at least git's default of 7, that keeps every one of
+/// `hashes` distinct.
+pub fn unique_abbrev_len<'a>(hashes: impl IntoIterator<Item = &'a str>) -> usize {
+    const MIN_ABBREV: usize = 7;
+    let mut sorted: Vec<&str> = hashes.into_iter().collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+    sorted
+        .windows(2)
+        .map(|pair| {
+            let shared = pair[0]
+                .bytes()
+                .zip(pair[1].bytes())
+                .take_while(|(a, b)| a == b)
+                .count();
+            (shared + 1).min(pair[0].len().max(pair[1].len()))
+        })
+        .fold(MIN_ABBREV, usize::max)
+}
+
+/// Spinner message naming the commit being analyzed, so a stall points at it.
+pub fn analyzing_message(commit_id: &str, timestamp: &DateTime<Utc>) -> String {
+    let short: String = commit_id.chars().take(7).collect();
+    format!("Analyzing {short} ({})", timestamp.format("%Y-%m-%d"))
+}
+
+/// Report each breached `--max-*` limit on stderr; failure when any breached.
+pub fn threshold_exit(breaches: &[String]) -> ExitCode {
+    for breach in breaches {
+        eprintln!("threshold exceeded: {breach}");
+    }
+    if breaches.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Substring test behind every filter: author, path exclusion and search.
+/// Case-insensitive unless `case_sensitive` (`--case-sensitive`).
+pub fn contains_text(haystack: &str, needle: &str, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        haystack.contains(needle)
+    } else {
+        haystack.to_lowercase().contains(&needle.to_lowercase())
     }
-    let p = path.to_lowercase();
-    excludes.iter().any(|ex| p.contains(&ex.to_lowercase()))
+}
+
+pub fn path_excluded(path: &str, excludes: &[String], case_sensitive: bool) -> bool {
+    excludes
+        .iter()
+        .any(|ex| contains_text(path, ex, case_sensitive))
 }
 
 pub fn cutoff_timestamp(months_back: u32) -> DateTime<Utc> {
@@ -47,64 +568,454 @@ pub fn cutoff_timestamp(months_back: u32) -> DateTime<Utc> {
         .unwrap_or(now)
 }
 
+/// Ignore rules of a worktree. The root `.gitignore` and `.git/info/exclude`
+/// are read once; every other directory's `.gitignore` is read the first time
+/// a path below it is checked. Lookups take `&self`, so one matcher can be
+/// shared across threads.
 pub struct GitIgnoreMatcher {
     root: PathBuf,
-    cache: HashMap<PathBuf, Option<ignore::gitignore::Gitignore>>,
+    root_rules: Option<Gitignore>,
+    /// Each directory's own `.gitignore`, keyed by path relative to the root.
+    dirs: RwLock<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+    files_read: AtomicUsize,
 }
 
 impl GitIgnoreMatcher {
     pub fn new<P: AsRef<Path>>(root: P) -> Self {
-        Self {
-            root: root.as_ref().to_path_buf(),
-            cache: HashMap::new(),
-        }
+        let root = root.as_ref().to_path_buf();
+        let mut matcher = Self {
+            root: root.clone(),
+            root_rules: None,
+            dirs: RwLock::new(HashMap::new()),
+            files_read: AtomicUsize::new(0),
+        };
+        matcher.root_rules = matcher.load(
+            &root,
+            &[
+                root.join(".gitignore"),
+                root.join(".git").join("info").join("exclude"),
+            ],
+        );
+        matcher
     }
 
-    pub fn is_ignored(&mut self, rel_path: &str) -> bool {
-        let abs = self.root.join(rel_path);
-        let dir_buf: PathBuf = abs
-            .parent()
-            .map(|p| p.to_path_buf())
-            .unwrap_or_else(|| self.root.clone());
-        let gi = self.get_or_build_for_dir(&dir_buf);
-        if let Some(gi) = gi {
-            let m = gi.matched(Path::new(rel_path), false);
-            m.is_ignore()
-        } else {
-            false
+    pub fn is_ignored(&self, rel_path: &str) -> bool {
+        let rel = Path::new(rel_path);
+        // As in git, the deepest `.gitignore` with a matching rule decides.
+        // Rules naming a directory cover every file below it.
+        for dir in rel.ancestors().skip(1) {
+            if dir.as_os_str().is_empty() {
+                break;
+            }
+            if let Some(gi) = self.dir_rules(dir) {
+                let m = gi.matched_path_or_any_parents(rel.strip_prefix(dir).unwrap_or(rel), false);
+                if !m.is_none() {
+                    return m.is_ignore();
+                }
+            }
         }
+        self.root_rules
+            .as_ref()
+            .is_some_and(|gi| gi.matched_path_or_any_parents(rel, false).is_ignore())
+    }
+
+    /// [`Self::is_ignored`] for each of `paths`, in order.
+    pub fn batch_ignored(&self, paths: &[&str]) -> Vec<bool> {
+        paths.iter().map(|path| self.is_ignored(path)).collect()
     }
 
-    fn get_or_build_for_dir(&mut self, dir: &Path) -> Option<&ignore::gitignore::Gitignore> {
-        if !self.cache.contains_key(dir) {
-            self.cache
-                .insert(dir.to_path_buf(), self.build_for_dir(dir));
+    /// Contents of every ignore file that can decide whether any of `paths`
+    /// is ignored, by path relative to the root: `.git/info/exclude` and the
+    /// `.gitignore` of the root and of each directory on the way to a path.
+    pub fn rules_for<'a>(
+        &self,
+        paths: impl IntoIterator<Item = &'a str>,
+    ) -> BTreeMap<String, String> {
+        let mut dirs = BTreeSet::new();
+        for path in paths {
+            dirs.extend(
+                Path::new(path)
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|dir| !dir.as_os_str().is_empty()),
+            );
         }
-        self.cache.get(dir).and_then(|o| o.as_ref())
+        let files = [
+            PathBuf::from(".git/info/exclude"),
+            PathBuf::from(".gitignore"),
+        ]
+        .into_iter()
+        .chain(dirs.into_iter().map(|dir| dir.join(".gitignore")));
+        files
+            .filter_map(|file| {
+                let text = std::fs::read_to_string(self.root.join(&file)).ok()?;
+                Some((file.to_string_lossy().replace('\\', "/"), text))
+            })
+            .collect()
     }
 
-    fn build_for_dir(&self, dir: &Path) -> Option<ignore::gitignore::Gitignore> {
-        use ignore::gitignore::GitignoreBuilder;
-        let mut builder = GitignoreBuilder::new(&self.root);
-        let root_gi = self.root.join(".gitignore");
-        if root_gi.exists() {
-            let _ = builder.add(&root_gi);
+    fn dir_rules(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        if let Some(rules) = self.dirs.read().unwrap_or_else(|e| e.into_inner()).get(dir) {
+            return rules.clone();
         }
-        let info_excl = self.root.join(".git").join("info").join("exclude");
-        if info_excl.exists() {
-            let _ = builder.add(&info_excl);
+        let abs = self.root.join(dir);
+        let loaded = self.load(&abs, &[abs.join(".gitignore")]).map(Arc::new);
+        // Another thread may have loaded it meanwhile; either copy will do.
+        self.dirs
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(dir.to_path_buf())
+            .or_insert(loaded)
+            .clone()
+    }
+
+    /// Rules from those of `files` that exist, relative to `base`.
+    fn load(&self, base: &Path, files: &[PathBuf]) -> Option<Gitignore> {
+        let mut builder = GitignoreBuilder::new(base);
+        let mut added = 0;
+        for file in files.iter().filter(|f| f.is_file()) {
+            // Lines that fail to parse are skipped; the rest still apply.
+            let _ = builder.add(file);
+            added += 1;
         }
-        // Add .gitignore files along the path from root to dir
-        if let Ok(rel) = dir.strip_prefix(&self.root) {
-            let mut cur = PathBuf::new();
-            for comp in rel.components() {
-                cur.push(comp);
-                let f = self.root.join(&cur).join(".gitignore");
-                if f.exists() {
-                    let _ = builder.add(f);
-                }
-            }
+        self.files_read.fetch_add(added, Ordering::Relaxed);
+        if added == 0 {
+            return None;
         }
         builder.build().ok()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn email_domain_falls_back_to_unknown() {
+        assert_eq!(email_domain("alice@Example.COM"), "example.com");
+        assert_eq!(email_domain("bob@corp.example.org"), "corp.example.org");
+        assert_eq!(email_domain("no-at-sign"), "unknown");
+        assert_eq!(email_domain("trailing@"), "unknown");
+        assert_eq!(email_domain(""), "unknown");
+    }
+
+    #[test]
+    fn commit_type_parses_conventional_prefixes() {
+        assert_eq!(commit_type("feat: add --by-type"), "feat");
+        assert_eq!(commit_type("fix(cache): handle corrupt db\n\nbody"), "fix");
+        assert_eq!(commit_type("refactor(cli)!: drop --legacy"), "refactor");
+        assert_eq!(commit_type("feat!: breaking"), "feat");
+        assert_eq!(commit_type("Docs: capitalized"), "docs");
+        assert_eq!(commit_type("wip: not a known type"), "other");
+        assert_eq!(commit_type("Merge branch 'main'"), "other");
+        assert_eq!(commit_type("fix(unclosed: nope"), "other");
+        assert_eq!(commit_type(""), "other");
+    }
+
+    #[test]
+    fn size_bucket_thresholds() {
+        assert_eq!(size_bucket(Some(0)), "small");
+        assert_eq!(size_bucket(Some(SMALL_BLOB_BYTES - 1)), "small");
+        assert_eq!(size_bucket(Some(SMALL_BLOB_BYTES)), "medium");
+        assert_eq!(size_bucket(Some(MEDIUM_BLOB_BYTES)), "large");
+        assert_eq!(size_bucket(None), "unknown");
+    }
+
+    #[test]
+    fn sunday_commit_moves_to_next_week_with_sunday_start() {
+        // 2024-01-07 is the Sunday closing ISO week 1
+        let sunday = Utc.with_ymd_and_hms(2024, 1, 7, 12, 0, 0).unwrap();
+        let monday = Utc.with_ymd_and_hms(2024, 1, 8, 12, 0, 0).unwrap();
+
+        assert_eq!(week_key_from(&sunday, WeekStart::Monday), "2024-W01");
+        assert_eq!(week_key_from(&sunday, WeekStart::Sunday), "2024-W02");
+        assert_eq!(
+            week_key_from(&monday, WeekStart::Sunday),
+            week_key_from(&sunday, WeekStart::Sunday),
+            "a Sunday-start week runs Sunday through Saturday"
+        );
+    }
+
+    #[test]
+    fn hour_histogram_buckets_by_hour() {
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 3, 4, h, m, 0).unwrap();
+        let stamps = [at(9, 0), at(9, 59), at(14, 30), at(23, 1), at(0, 0)];
+
+        let hours = hour_histogram(&stamps);
+
+        assert_eq!(hours[0], 1);
+        assert_eq!(hours[9], 2);
+        assert_eq!(hours[14], 1);
+        assert_eq!(hours[23], 1);
+        assert_eq!(hours.iter().sum::<u64>(), stamps.len() as u64);
+    }
+
+    #[test]
+    fn huge_commit_is_flagged_as_outlier() {
+        let sizes = [12, 8, 10_000, 15, 9, 11];
+
+        let by_median = OutlierThreshold::MedianFactor(10.0).flag(&sizes);
+        assert_eq!(by_median, vec![false, false, true, false, false, false]);
+
+        let absolute = OutlierThreshold::Lines(10).flag(&sizes);
+        assert_eq!(absolute, vec![true, false, true, true, false, true]);
+
+        assert!(OutlierThreshold::MedianFactor(10.0).flag(&[]).is_empty());
+    }
+
+    #[test]
+    fn tags_map_to_their_commit_period() {
+        assert!(glob_match("v*", "v1.2.0"));
+        assert!(glob_match("v?.*", "v1.2"));
+        assert!(!glob_match("v*", "release-1"));
+        assert!(glob_match("*-rc*", "v2-rc1"));
+
+        let tag = |name: &str, day: u32| ReleaseTag {
+            name: name.to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0).unwrap(),
+        };
+        let tags = [tag("v1.1", 10), tag("v1.0", 3), tag("v1.0.1", 9)];
+        let weekly = tag_periods(&tags, PeriodSpec::default());
+        assert_eq!(weekly["2024-W01"], ["v1.0"]);
+        assert_eq!(weekly["2024-W02"], ["v1.0.1", "v1.1"]);
+        let monthly = tag_periods(&tags, PeriodSpec::new(true, WeekStart::Monday));
+        assert_eq!(monthly["2024-01"], ["v1.0", "v1.0.1", "v1.1"]);
+    }
+
+    #[test]
+    fn analyzing_message_names_short_hash_and_date() {
+        let ts = Utc.with_ymd_and_hms(2024, 1, 2, 15, 30, 0).unwrap();
+        assert_eq!(
+            analyzing_message("abc1234def5678", &ts),
+            "Analyzing abc1234 (2024-01-02)"
+        );
+    }
+
+    #[test]
+    fn week_labels_render_the_same_week_each_way() {
+        let key = "2024-W05";
+        assert_eq!(
+            week_label(key, WeekFormat::Iso, WeekStart::Monday),
+            "2024-W05"
+        );
+        assert_eq!(
+            week_label(key, WeekFormat::Date, WeekStart::Monday),
+            "2024-01-29"
+        );
+        assert_eq!(
+            week_label(key, WeekFormat::Date, WeekStart::Sunday),
+            "2024-01-28"
+        );
+        assert_eq!(
+            week_label(key, WeekFormat::Short, WeekStart::Monday),
+            "W5 2024"
+        );
+        assert_eq!(
+            week_label("2024-02", WeekFormat::Short, WeekStart::Monday),
+            "2024-02",
+            "month keys are not weeks"
+        );
+    }
+
+    #[test]
+    fn anomalies_flag_only_periods_far_from_the_mean() {
+        let weeks = [5.0, 6.0, 5.0, 4.0, 5.0, 6.0, 5.0, 4.0, 5.0, 40.0];
+        assert_eq!(
+            anomalies(&weeks, ANOMALY_SIGMAS),
+            BTreeMap::from([(9, Anomaly::Busy)])
+        );
+
+        let mut quiet = [10.0; 10];
+        quiet[3] = 0.0;
+        assert_eq!(
+            anomalies(&quiet, ANOMALY_SIGMAS),
+            BTreeMap::from([(3, Anomaly::Quiet)])
+        );
+
+        assert!(anomalies(&[7.0; 5], ANOMALY_SIGMAS).is_empty());
+        assert!(anomalies(&[], ANOMALY_SIGMAS).is_empty());
+    }
+
+    #[test]
+    fn moving_average_over_a_known_window() {
+        let values = [2.0, 4.0, 6.0, 8.0, 0.0, 0.0];
+        assert_eq!(
+            moving_average(&values, 3),
+            [2.0, 3.0, 4.0, 6.0, 14.0 / 3.0, 8.0 / 3.0]
+        );
+        assert_eq!(moving_average(&values, 1), values);
+        assert_eq!(moving_average(&values, 0), values);
+        assert!(moving_average(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn smoothing_counts_missing_periods_as_zero() {
+        // W02 is missing, so W03 averages 3 and 0.
+        let series = [("2024-W01", 3.0), ("2024-W03", 6.0), ("2024-W04", 9.0)];
+        assert_eq!(smoothed_by_period(&series, 2), [3.0, 3.0, 7.5]);
+        let months = [("2024-11", 2.0), ("2025-01", 4.0)];
+        assert_eq!(smoothed_by_period(&months, 2), [2.0, 2.0]);
+        let custom = [("a", 2.0), ("b", 4.0)];
+        assert_eq!(smoothed_by_period(&custom, 2), [2.0, 3.0]);
+    }
+
+    #[test]
+    fn next_period_key_crosses_year_ends() {
+        assert_eq!(next_period_key("2024-W05").as_deref(), Some("2024-W06"));
+        assert_eq!(next_period_key("2020-W53").as_deref(), Some("2021-W01"));
+        assert_eq!(next_period_key("2024-W52").as_deref(), Some("2025-W01"));
+        assert_eq!(next_period_key("2024-12").as_deref(), Some("2025-01"));
+        assert_eq!(next_period_key("2024-09").as_deref(), Some("2024-10"));
+        assert_eq!(next_period_key("main"), None);
+    }
+
+    #[test]
+    fn sprints_count_from_the_start_date() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        let at = |month, day| Utc.with_ymd_and_hms(2024, month, day, 12, 0, 0).unwrap();
+        assert_eq!(sprint_key(&at(1, 8), start, 14), "Sprint 0001");
+        assert_eq!(sprint_key(&at(1, 21), start, 14), "Sprint 0001");
+        assert_eq!(sprint_key(&at(1, 22), start, 14), "Sprint 0002");
+        assert_eq!(sprint_key(&at(5, 1), start, 14), "Sprint 0009");
+        assert_eq!(sprint_key(&at(1, 7), start, 14), "Sprint 0000");
+
+        let period = PeriodSpec::new(true, WeekStart::Monday).with_sprints(Some(Sprints {
+            start,
+            length_days: 14,
+        }));
+        assert_eq!(period_key(&at(1, 22), period), "Sprint 0002");
+        assert_eq!(
+            next_period_key("Sprint 0009").as_deref(),
+            Some("Sprint 0010")
+        );
+        assert_eq!(
+            week_label("Sprint 0010", WeekFormat::Iso, WeekStart::Monday),
+            "Sprint 10"
+        );
+    }
+
+    #[test]
+    fn month_weeks_restart_each_month() {
+        let at = |month, day| Utc.with_ymd_and_hms(2024, month, day, 12, 0, 0).unwrap();
+        let period = PeriodSpec::default().with_month_weeks(true);
+        assert_eq!(period_key(&at(3, 1), period), "2024-03-W1");
+        assert_eq!(period_key(&at(3, 7), period), "2024-03-W1");
+        assert_eq!(period_key(&at(3, 8), period), "2024-03-W2");
+        assert_eq!(period_key(&at(3, 28), period), "2024-03-W4");
+        assert_eq!(period_key(&at(3, 31), period), "2024-03-W5");
+        // Monthly grouping still wins.
+        let monthly = PeriodSpec::new(true, WeekStart::Monday).with_month_weeks(true);
+        assert_eq!(period_key(&at(3, 28), monthly), "2024-03");
+
+        assert_eq!(next_period_key("2024-03-W4").as_deref(), Some("2024-03-W5"));
+        assert_eq!(next_period_key("2024-12-W5").as_deref(), Some("2025-01-W1"));
+        // February 2023 has no fifth week; 2024's has one day of it.
+        assert_eq!(next_period_key("2023-02-W4").as_deref(), Some("2023-03-W1"));
+        assert_eq!(next_period_key("2024-02-W4").as_deref(), Some("2024-02-W5"));
+        assert_eq!(
+            dense_period_keys(["2024-01-W5", "2024-02-W2"]),
+            ["2024-01-W5", "2024-02-W1", "2024-02-W2"]
+        );
+    }
+
+    #[test]
+    fn week_key_uses_iso_year() {
+        let at = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap();
+        // Late December in week 1 of the next ISO year (was "2024-W01").
+        assert_eq!(week_key(&at(2024, 12, 30)), "2025-W01");
+        assert_eq!(week_key(&at(2024, 12, 29)), "2024-W52");
+        // Early January in the last week of the previous ISO year (was
+        // "2021-W53", sorting after the rest of 2021).
+        assert_eq!(week_key(&at(2021, 1, 1)), "2020-W53");
+        assert_eq!(week_key(&at(2021, 1, 4)), "2021-W01");
+        // Both sides of the turn land in one contiguous bucket.
+        assert_eq!(week_key(&at(2020, 12, 31)), week_key(&at(2021, 1, 3)));
+    }
+
+    #[test]
+    fn abbreviations_lengthen_past_shared_prefixes() {
+        assert_eq!(unique_abbrev_len(["1234567aaa", "89abcdefff"]), 7);
+        assert_eq!(
+            unique_abbrev_len(["1234567aaa", "1234567bbb", "fedcba9876"]),
+            8
+        );
+        assert_eq!(unique_abbrev_len(["123456789a", "123456789b"]), 10);
+        assert_eq!(unique_abbrev_len([]), 7);
+    }
+
+    #[test]
+    fn gitignore_files_are_read_once_however_many_paths_are_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::create_dir_all(root.join(".git/info")).unwrap();
+        std::fs::write(root.join(".git/info/exclude"), "secret.txt\n").unwrap();
+        std::fs::create_dir_all(root.join("src/gen/deep")).unwrap();
+        std::fs::write(root.join("src/.gitignore"), "*.tmp\n!keep.log\n").unwrap();
+
+        let gi = GitIgnoreMatcher::new(root);
+        for _ in 0..3 {
+            assert!(gi.is_ignored("build.log"));
+            assert!(gi.is_ignored("secret.txt"));
+            assert!(gi.is_ignored("src/gen/deep/out.log"));
+            assert!(gi.is_ignored("src/gen/x.tmp"));
+            assert!(!gi.is_ignored("src/keep.log"));
+            assert!(!gi.is_ignored("x.tmp"));
+            assert!(!gi.is_ignored("src/gen/deep/lib.rs"));
+        }
+        // Root .gitignore, info/exclude and src/.gitignore, once each.
+        assert_eq!(gi.files_read.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn directory_rules_cover_the_files_below_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join(".gitignore"), "dist/\n*.log\n").unwrap();
+        std::fs::create_dir_all(root.join("web/src")).unwrap();
+        std::fs::write(root.join("web/.gitignore"), "gen/\n").unwrap();
+
+        let gi = GitIgnoreMatcher::new(root);
+        assert!(gi.is_ignored("dist/a.js"));
+        assert!(gi.is_ignored("dist/assets/logo.svg"));
+        assert!(gi.is_ignored("web/dist/a.js"));
+        assert!(gi.is_ignored("web/gen/api.rs"));
+        assert!(gi.is_ignored("web/src/gen/api.rs"));
+        assert!(!gi.is_ignored("distro/a.js"));
+        assert!(!gi.is_ignored("gen/api.rs"));
+        assert!(!gi.is_ignored("web/src/main.rs"));
+    }
+
+    #[test]
+    fn batch_matches_single_lookups_across_threads() {
+        fn assert_sync<T: Sync + Send>() {}
+        assert_sync::<GitIgnoreMatcher>();
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::fs::write(root.join("a/.gitignore"), "gen/\n*.rs\n!keep.rs\n").unwrap();
+        let paths = [
+            "x.log",
+            "x.rs",
+            "a/x.rs",
+            "a/keep.rs",
+            "a/b/y.rs",
+            "a/b/z.log",
+            "a/b/z.txt",
+        ];
+
+        let gi = GitIgnoreMatcher::new(root);
+        let batches: Vec<Vec<bool>> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| gi.batch_ignored(&paths)))
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        let single: Vec<bool> = paths.iter().map(|p| gi.is_ignored(p)).collect();
+        assert_eq!(single, [true, false, true, false, true, true, false]);
+        assert!(batches.iter().all(|batch| *batch == single));
+    }
+}
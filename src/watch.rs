@@ -0,0 +1,74 @@
+use crate::cli::CommonArgs;
+use crate::heat::fetch::interrupted;
+use anyhow::Context;
+use console::{style, Term};
+use gix::ObjectId;
+use std::process::ExitCode;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// How often the wait between checks looks for Ctrl-C.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Whether a cycle has to recompute: on the first one, and whenever HEAD is
+/// no longer the commit the shown output was computed at.
+pub fn needs_refresh(shown: Option<&ObjectId>, head: &ObjectId) -> bool {
+    shown != Some(head)
+}
+
+/// Check HEAD every `interval`, clearing the screen and rerunning `cycle`
+/// when it has moved, until Ctrl-C. Each rerun reads the warm cache, so only
+/// new commits are analyzed. Threshold exit codes don't stop the loop.
+pub fn run(
+    common: &CommonArgs,
+    interval: Duration,
+    mut cycle: impl FnMut() -> anyhow::Result<ExitCode>,
+) -> anyhow::Result<ExitCode> {
+    let mut shown = None;
+    loop {
+        // Reopened each time so moved refs are seen.
+        let head = common
+            .open_repo()
+            .context("Failed to open git repository")?
+            .head_commit_id()
+            .context("Failed to resolve HEAD")?;
+        if needs_refresh(shown.as_ref(), &head) {
+            Term::stdout().clear_screen()?;
+            cycle()?;
+            eprintln!(
+                "{}",
+                style(format!(
+                    "Checking HEAD every {}s; Ctrl-C to stop",
+                    interval.as_secs()
+                ))
+                .dim()
+            );
+            shown = Some(head);
+        }
+
+        let start = Instant::now();
+        while start.elapsed() < interval {
+            if interrupted() {
+                return Ok(ExitCode::SUCCESS);
+            }
+            sleep(TICK.min(interval.saturating_sub(start.elapsed())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oid(hex: &str) -> ObjectId {
+        ObjectId::from_hex(hex.repeat(40).as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn refreshes_only_when_head_moves() {
+        let (a, b) = (oid("a"), oid("b"));
+        assert!(needs_refresh(None, &a));
+        assert!(!needs_refresh(Some(&a), &a));
+        assert!(needs_refresh(Some(&a), &b));
+    }
+}
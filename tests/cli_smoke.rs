@@ -53,6 +53,20 @@ fn ensure_clean(dir: &Path) {
 }
 
 fn commit_file(dir: &Path, name: &str, content: &str) {
+    commit_file_with_env(dir, name, content, &[]);
+}
+
+/// Commit with both author and committer dates pinned to `date` (RFC 2822 or ISO 8601).
+fn commit_file_at(dir: &Path, name: &str, content: &str, date: &str) {
+    commit_file_with_env(
+        dir,
+        name,
+        content,
+        &[("GIT_AUTHOR_DATE", date), ("GIT_COMMITTER_DATE", date)],
+    );
+}
+
+fn commit_file_with_env(dir: &Path, name: &str, content: &str, envs: &[(&str, &str)]) {
     let path = dir.join(name);
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).unwrap();
@@ -68,6 +82,7 @@ fn commit_file(dir: &Path, name: &str, content: &str) {
         .success());
     assert!(Command::new("git")
         .args(["commit", "-m", &format!("add {name}")])
+        .envs(envs.iter().copied())
         .current_dir(dir)
         .status()
         .unwrap()
@@ -194,3 +209,2861 @@ fn include_merges_flag_affects_counts() {
 
     assert!(sum2 >= sum1);
 }
+
+fn heat_buckets(dir: &Path, extra: &[&str]) -> Vec<(String, u64)> {
+    let mut cmd = Command::cargo_bin("gmap").unwrap();
+    cmd.current_dir(dir).arg("--repo").arg(dir).args(extra);
+    cmd.args(["heat", "--json"]);
+    let out = cmd.assert().success().get_output().stdout.clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    v["buckets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| {
+            (
+                b["week"].as_str().unwrap().to_string(),
+                b["commit_count"].as_u64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn week_start_moves_sunday_commit_into_next_week() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-07T12:00:00Z");
+    commit_file_at(dir.path(), "b.txt", "b\n", "2024-01-08T12:00:00Z");
+
+    let monday = heat_buckets(dir.path(), &[]);
+    assert_eq!(
+        monday,
+        vec![("2024-W01".to_string(), 1), ("2024-W02".to_string(), 1)]
+    );
+
+    let sunday = heat_buckets(dir.path(), &["--week-start", "sun"]);
+    assert_eq!(sunday, vec![("2024-W02".to_string(), 2)]);
+}
+
+#[test]
+fn hide_empty_drops_filled_gaps_from_the_listing_only() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-03T12:00:00Z");
+    commit_file_at(dir.path(), "b.txt", "b\n", "2024-01-17T12:00:00Z");
+
+    let sparse = heat_buckets(dir.path(), &[]);
+    assert_eq!(sparse.len(), 2, "no gap filling by default");
+    let mut cmd = Command::cargo_bin("gmap").unwrap();
+    cmd.current_dir(dir.path())
+        .arg("--repo")
+        .arg(dir.path())
+        .args(["heat", "--json", "--fill-gaps", "--hide-empty"]);
+    let out = cmd.assert().success().get_output().stdout.clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let weeks: Vec<_> = v["buckets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| {
+            (
+                b["week"].as_str().unwrap(),
+                b["commit_count"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(weeks, [("2024-W01", 1), ("2024-W02", 0), ("2024-W03", 1)]);
+
+    let table = String::from_utf8(gmap_stdout(
+        dir.path(),
+        &["heat", "--fill-gaps", "--hide-empty"],
+    ))
+    .unwrap();
+    assert!(
+        table.contains("2024-W01") && table.contains("2024-W03"),
+        "{table}"
+    );
+    assert!(!table.contains("2024-W02"), "{table}");
+    assert!(
+        table.contains("Total: 2 commits over 3 periods (1 empty hidden)"),
+        "{table}"
+    );
+}
+
+#[test]
+fn week_format_relabels_buckets_but_keeps_keys() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-31T12:00:00Z");
+
+    let json = gmap_stdout(dir.path(), &["--week-format", "date", "heat", "--json"]);
+    let v: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(v["buckets"][0]["week"], "2024-W05");
+    assert_eq!(v["buckets"][0]["label"], "2024-01-29");
+
+    let table =
+        String::from_utf8(gmap_stdout(dir.path(), &["--week-format", "short", "heat"])).unwrap();
+    assert!(table.contains("W5 2024"), "{table}");
+    assert!(!table.contains("2024-W05"), "{table}");
+}
+
+#[test]
+fn date_flag_selects_author_or_committer_timestamp() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_with_env(
+        dir.path(),
+        "a.txt",
+        "a\n",
+        &[
+            ("GIT_AUTHOR_DATE", "2024-01-03T12:00:00Z"),
+            ("GIT_COMMITTER_DATE", "2024-03-06T12:00:00Z"),
+        ],
+    );
+
+    let committer = heat_buckets(dir.path(), &[]);
+    assert_eq!(committer, vec![("2024-W10".to_string(), 1)]);
+
+    let author = heat_buckets(dir.path(), &["--date", "author"]);
+    assert_eq!(author, vec![("2024-W01".to_string(), 1)]);
+}
+
+fn gmap_stdout(dir: &Path, args: &[&str]) -> Vec<u8> {
+    let mut cmd = Command::cargo_bin("gmap").unwrap();
+    cmd.current_dir(dir).arg("--repo").arg(dir).args(args);
+    cmd.assert().success().get_output().stdout.clone()
+}
+
+#[test]
+fn compact_json_is_minified_and_equivalent() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "src/a.rs", "fn a(){}\n", "2024-01-02T10:00:00Z");
+    commit_file_at(
+        dir.path(),
+        "src/a.rs",
+        "fn a(){}\nfn b(){}\n",
+        "2024-01-03T10:00:00Z",
+    );
+
+    let pretty = gmap_stdout(dir.path(), &["export", "--json"]);
+    let compact = gmap_stdout(dir.path(), &["export", "--json", "--compact"]);
+
+    let compact_text = String::from_utf8(compact.clone()).unwrap();
+    assert_eq!(compact_text.trim_end().lines().count(), 1);
+    assert!(!compact_text.contains("  "), "no indentation expected");
+
+    let mut pretty_v: serde_json::Value = serde_json::from_slice(&pretty).unwrap();
+    let mut compact_v: serde_json::Value = serde_json::from_slice(&compact).unwrap();
+    pretty_v.as_object_mut().unwrap().remove("generated_at");
+    compact_v.as_object_mut().unwrap().remove("generated_at");
+    assert_eq!(pretty_v, compact_v);
+}
+
+#[test]
+fn compact_requires_json() {
+    let dir = tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("gmap").unwrap();
+    cmd.current_dir(dir.path())
+        .args(["heat", "--compact"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn flag_outliers_marks_huge_commit() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    for (i, day) in ["02", "03", "04", "05"].iter().enumerate() {
+        let content: String = (0..=i).map(|n| format!("fn f{n}(){{}}\n")).collect();
+        let date = format!("2024-01-{day}T10:00:00Z");
+        commit_file_at(dir.path(), "src/small.rs", &content, &date);
+    }
+    let huge: String = (0..10_000)
+        .map(|n| format!("const C{n}: u32 = {n};\n"))
+        .collect();
+    commit_file_at(dir.path(), "src/huge.rs", &huge, "2024-01-06T10:00:00Z");
+
+    let out = gmap_stdout(dir.path(), &["export", "--json", "--flag-outliers"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let flagged: Vec<bool> = v["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["is_outlier"].as_bool().unwrap())
+        .collect();
+    assert_eq!(flagged, vec![false, false, false, false, true]);
+
+    let out = gmap_stdout(dir.path(), &["churn", "--json", "--exclude-outliers"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let paths: Vec<&str> = v["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, vec!["src/small.rs"]);
+}
+
+#[test]
+fn outliers_are_ranked_among_the_filtered_commits() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    let bob = [("GIT_AUTHOR_NAME", "Bob")];
+    let alice = [("GIT_AUTHOR_NAME", "Alice")];
+    for i in 0..4 {
+        let content = "x\n".repeat(500);
+        commit_file_with_env(dir.path(), &format!("bob{i}.rs"), &content, &bob);
+    }
+    for i in 1..=4 {
+        commit_file_with_env(dir.path(), "alice.rs", &"a\n".repeat(i), &alice);
+    }
+    commit_file_with_env(dir.path(), "big.rs", &"b\n".repeat(50), &alice);
+
+    // Among all commits big.rs is ordinary; among Alice's it stands out.
+    let export: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &["--author", "alice", "export", "--json", "--flag-outliers"],
+    ))
+    .unwrap();
+    let flagged: Vec<&str> = export["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["is_outlier"].as_bool().unwrap())
+        .map(|e| e["message"].as_str().unwrap().trim())
+        .collect();
+    assert_eq!(flagged, ["add big.rs"]);
+
+    let churn: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &["--author", "alice", "churn", "--json", "--exclude-outliers"],
+    ))
+    .unwrap();
+    let paths: Vec<&str> = churn["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["alice.rs"]);
+
+    for factor in ["0", "-1", "NaN", "inf"] {
+        let out = Command::cargo_bin("gmap")
+            .unwrap()
+            .args(["--repo", dir.path().to_str().unwrap()])
+            .args(["churn", "--exclude-outliers"])
+            .arg(format!("--outlier-factor={factor}"))
+            .output()
+            .unwrap();
+        assert!(!out.status.success());
+        let stderr = String::from_utf8(out.stderr).unwrap();
+        assert!(stderr.contains("--outlier-factor"), "{stderr}");
+    }
+}
+
+#[test]
+fn heat_buckets_split_added_and_deleted() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(
+        dir.path(),
+        "src/a.rs",
+        "fn a(){}\nfn b(){}\n",
+        "2024-01-02T10:00:00Z",
+    );
+    commit_file_at(
+        dir.path(),
+        "src/a.rs",
+        "fn a(){}\nfn c(){}\n",
+        "2024-01-03T10:00:00Z",
+    );
+
+    let out = gmap_stdout(dir.path(), &["heat", "--json"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(v["version"], 2);
+    let buckets = v["buckets"].as_array().unwrap();
+    assert_eq!(buckets.len(), 1);
+    let b = &buckets[0];
+    assert_eq!(b["lines_added"], 3);
+    assert_eq!(b["lines_deleted"], 1);
+    assert_eq!(
+        b["lines_changed"].as_u64().unwrap(),
+        b["lines_added"].as_u64().unwrap() + b["lines_deleted"].as_u64().unwrap()
+    );
+}
+
+#[test]
+fn exclude_bots_drops_bot_commits() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-03T12:00:00Z");
+    commit_file_with_env(
+        dir.path(),
+        "Cargo.lock",
+        "bump\n",
+        &[
+            ("GIT_AUTHOR_NAME", "dependabot[bot]"),
+            (
+                "GIT_AUTHOR_EMAIL",
+                "49699333+dependabot[bot]@users.noreply.github.com",
+            ),
+            ("GIT_AUTHOR_DATE", "2024-01-10T12:00:00Z"),
+            ("GIT_COMMITTER_DATE", "2024-01-10T12:00:00Z"),
+        ],
+    );
+    commit_file_at(dir.path(), "b.txt", "b\n", "2024-01-17T12:00:00Z");
+
+    let all = heat_buckets(dir.path(), &[]);
+    assert_eq!(all.len(), 3);
+
+    let humans = vec![("2024-W01".to_string(), 1), ("2024-W03".to_string(), 1)];
+    assert_eq!(heat_buckets(dir.path(), &["--exclude-bots"]), humans);
+    assert_eq!(
+        heat_buckets(dir.path(), &["--exclude-author", "DEPENDABOT"]),
+        humans
+    );
+}
+
+#[test]
+fn compare_reports_delta_between_windows() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-10T12:00:00Z");
+    commit_file_at(dir.path(), "b.txt", "b\n", "2024-04-10T12:00:00Z");
+    commit_file_at(dir.path(), "c.txt", "c\nc\n", "2024-04-11T12:00:00Z");
+    commit_file_at(dir.path(), "a.txt", "a2\n", "2024-04-12T12:00:00Z");
+
+    let out = gmap_stdout(
+        dir.path(),
+        &[
+            "--since",
+            "2024-04-01",
+            "--until",
+            "2024-06-30",
+            "compare",
+            "--baseline-since",
+            "2024-01-01",
+            "--baseline-until",
+            "2024-03-31",
+            "--json",
+        ],
+    );
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(v["baseline"]["commits"], 1);
+    assert_eq!(v["current"]["commits"], 3);
+    assert_eq!(v["delta"]["commits"], 2);
+    assert_eq!(v["delta"]["lines_added"], 3);
+    assert_eq!(v["delta"]["lines_deleted"], 1);
+    assert_eq!(v["delta"]["active_files"], 2);
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    assert!(Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap()
+        .success());
+}
+
+fn export_messages(dir: &Path, range: &str) -> Vec<String> {
+    export_messages_with(dir, &["--range", range])
+}
+
+/// Sorted, trimmed commit messages from `export --json` under global `args`.
+fn export_messages_with(dir: &Path, args: &[&str]) -> Vec<String> {
+    let mut all = args.to_vec();
+    all.extend(["export", "--json"]);
+    let out = gmap_stdout(dir, &all);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let mut messages: Vec<String> = v["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["message"].as_str().unwrap().trim_end().to_string())
+        .collect();
+    messages.sort();
+    messages
+}
+
+/// base -- main.txt (HEAD), with `feature` branching off base: f1.txt, f2.txt
+fn init_branched_repo(dir: &Path) {
+    init_git_repo(dir);
+    commit_file_at(dir, "base.txt", "base\n", "2024-01-01T12:00:00Z");
+    git(dir, &["checkout", "-q", "-b", "feature"]);
+    commit_file_at(dir, "f1.txt", "f1\n", "2024-01-02T12:00:00Z");
+    commit_file_at(dir, "f2.txt", "f2\n", "2024-01-03T12:00:00Z");
+    git(dir, &["checkout", "-q", "-"]);
+    commit_file_at(dir, "main.txt", "main\n", "2024-01-04T12:00:00Z");
+}
+
+#[test]
+fn range_two_dot_selects_commits_only_on_feature() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_branched_repo(dir.path());
+
+    assert_eq!(
+        export_messages(dir.path(), "HEAD..feature"),
+        vec!["add f1.txt", "add f2.txt"]
+    );
+    assert_eq!(
+        export_messages(dir.path(), "feature.."),
+        vec!["add main.txt"]
+    );
+}
+
+#[test]
+fn range_three_dot_selects_symmetric_difference() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_branched_repo(dir.path());
+
+    assert_eq!(
+        export_messages(dir.path(), "HEAD...feature"),
+        vec!["add f1.txt", "add f2.txt", "add main.txt"]
+    );
+}
+
+#[test]
+fn range_rejects_invalid_revspec() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_branched_repo(dir.path());
+
+    let mut cmd = Command::cargo_bin("gmap").unwrap();
+    let out = cmd
+        .current_dir(dir.path())
+        .arg("--repo")
+        .arg(dir.path())
+        .args(["--range", "no-such-branch..feature", "export", "--json"])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8_lossy(&out);
+    assert!(
+        stderr.contains("Invalid revision range 'no-such-branch..feature'"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn stats_json_matches_hand_computed_figures() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "1\n2\n", "2024-01-02T09:00:00Z");
+    commit_file_at(dir.path(), "b.txt", "b\n", "2024-01-03T14:00:00Z");
+    commit_file_at(dir.path(), "a.txt", "1\nchanged\n", "2024-01-16T14:30:00Z");
+
+    let out = gmap_stdout(dir.path(), &["stats", "--json"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let s = &v["summary"];
+    assert_eq!(s["periods"], 2);
+    assert_eq!(s["total_commits"], 3);
+    assert_eq!(s["lines_added"], 4);
+    assert_eq!(s["lines_deleted"], 1);
+    assert_eq!(s["net_change"], 3);
+    assert_eq!(s["avg_commits"], 1.5);
+    assert_eq!(s["max_commits"], 2);
+    assert_eq!(s["min_commits"], 1);
+    assert_eq!(s["busiest_hour"], 14);
+    assert_eq!(s["commits_by_hour"][9], 1);
+    assert_eq!(s["trend"], serde_json::json!([2, 1]));
+}
+
+#[test]
+fn half_life_ranks_recent_small_edit_above_old_large_one() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    let large: String = (0..200).map(|n| format!("line {n}\n")).collect();
+    commit_file_at(dir.path(), "old.txt", &large, "2020-01-01T12:00:00Z");
+    let yesterday = (chrono::Utc::now() - chrono::Duration::days(1)).to_rfc3339();
+    commit_file_at(dir.path(), "new.txt", "a\nb\n", &yesterday);
+
+    let paths = |args: &[&str]| -> Vec<String> {
+        let out = gmap_stdout(dir.path(), args);
+        let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        v["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["path"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    assert_eq!(paths(&["churn", "--json"]), vec!["old.txt", "new.txt"]);
+    assert_eq!(
+        paths(&["churn", "--json", "--half-life", "30", "--sort", "weighted"]),
+        vec!["new.txt", "old.txt"]
+    );
+}
+
+#[test]
+fn format_csv_writes_header_and_rows() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a,b.txt", "x\n", "2024-01-02T10:00:00Z");
+
+    let out = String::from_utf8(gmap_stdout(dir.path(), &["churn", "--format", "csv"])).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "path,added_lines,deleted_lines,total_lines,commit_count,authors,weighted_score",
+            "\"a,b.txt\",1,0,1,1,1,1"
+        ]
+    );
+}
+
+#[test]
+fn max_thresholds_fail_the_run_when_exceeded() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(
+        dir.path(),
+        "big.txt",
+        "1\n2\n3\n4\n5\n",
+        "2024-01-02T10:00:00Z",
+    );
+    commit_file_at(dir.path(), "small.txt", "1\n", "2024-01-03T10:00:00Z");
+
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("gmap").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("--repo")
+            .arg(dir.path())
+            .args(args);
+        cmd.assert()
+    };
+
+    let out = run(&["churn", "--json", "--max-file-churn", "4"])
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("big.txt: 5 lines changed"), "{stderr}");
+    assert!(!stderr.contains("small.txt"), "{stderr}");
+    // The report is still printed in full.
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(v["entries"].as_array().unwrap().len(), 2);
+
+    run(&["churn", "--json", "--max-file-churn", "5"]).success();
+    let out = run(&["heat", "--json", "--max-week-commits", "1"])
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("2024-W01: 2 commits"), "{stderr}");
+    run(&["heat", "--json", "--max-week-commits", "2"]).success();
+}
+
+#[test]
+fn debug_logs_timings_to_stderr_only() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-02T10:00:00Z");
+
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("gmap").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("--repo")
+            .arg(dir.path())
+            .args(args);
+        cmd.assert().success().get_output().clone()
+    };
+
+    let quiet = run(&["churn", "--format", "csv"]);
+    assert!(quiet.stderr.is_empty(), "silent by default");
+
+    let debug = run(&["--debug", "churn", "--format", "csv"]);
+    let stderr = String::from_utf8(debug.stderr).unwrap();
+    for phase in ["range resolution", "commit listing", "aggregation"] {
+        assert!(stderr.contains(&format!("[gmap] {phase}: ")), "{stderr}");
+    }
+    assert!(stderr.contains("commits walked: 1"), "{stderr}");
+    assert_eq!(debug.stdout, quiet.stdout);
+}
+
+#[test]
+fn fetch_report_counts_cold_then_warm_run() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-02T10:00:00Z");
+    commit_file_at(dir.path(), "b.txt", "b\n", "2024-01-03T10:00:00Z");
+
+    let stderr = || {
+        let mut cmd = Command::cargo_bin("gmap").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("--repo")
+            .arg(dir.path())
+            .args(["-v", "heat", "--json"]);
+        String::from_utf8(cmd.assert().success().get_output().stderr.clone()).unwrap()
+    };
+
+    let cold = stderr();
+    assert!(
+        cold.contains("0 commits from cache, 2 newly analyzed"),
+        "{cold}"
+    );
+    let warm = stderr();
+    assert!(
+        warm.contains("2 commits from cache, 0 newly analyzed"),
+        "{warm}"
+    );
+}
+
+#[test]
+fn recompute_matches_cached_results_but_reanalyzes_every_commit() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-02T10:00:00Z");
+    commit_file_at(dir.path(), "a.txt", "a\nb\n", "2024-01-03T10:00:00Z");
+
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("gmap").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("--repo")
+            .arg(dir.path())
+            .args(args);
+        cmd.assert().success().get_output().clone()
+    };
+
+    let first = run(&["churn", "--format", "csv"]);
+    let warm = run(&["-v", "churn", "--format", "csv"]);
+    let recomputed = run(&["-v", "--recompute", "churn", "--format", "csv"]);
+
+    assert_eq!(recomputed.stdout, first.stdout);
+    let warm_err = String::from_utf8(warm.stderr).unwrap();
+    assert!(warm_err.contains("2 commits from cache, 0 newly analyzed"));
+    let recompute_err = String::from_utf8(recomputed.stderr).unwrap();
+    assert!(
+        recompute_err.contains("0 commits from cache, 2 newly analyzed"),
+        "{recompute_err}"
+    );
+}
+
+#[test]
+fn truncated_cache_is_backed_up_and_recreated() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-02T10:00:00Z");
+
+    let healthy = gmap_stdout(dir.path(), &["churn", "--format", "csv"]);
+    let db = dir.path().join(".gmap").join("cache.db");
+    let bytes = fs::read(&db).unwrap();
+    fs::write(&db, &bytes[..bytes.len().min(64)]).unwrap();
+
+    let mut strict = Command::cargo_bin("gmap").unwrap();
+    strict
+        .current_dir(dir.path())
+        .arg("--repo")
+        .arg(dir.path())
+        .args(["--reset-cache-on-error", "false", "churn"])
+        .assert()
+        .failure();
+
+    let mut cmd = Command::cargo_bin("gmap").unwrap();
+    cmd.current_dir(dir.path())
+        .arg("--repo")
+        .arg(dir.path())
+        .args(["churn", "--format", "csv"]);
+    let out = cmd.assert().success().get_output().clone();
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("warning: cache"), "{stderr}");
+    assert_eq!(out.stdout, healthy);
+    assert!(dir.path().join(".gmap").join("cache.db.corrupt").exists());
+}
+
+#[test]
+fn clean_removes_cache_database() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-02T10:00:00Z");
+
+    let cache_dir = dir.path().join("cache-dir");
+    let cache_arg = cache_dir.to_str().unwrap();
+    gmap_stdout(dir.path(), &["--cache", cache_arg, "churn"]);
+    let db = cache_dir.join("cache.db");
+    assert!(db.exists());
+
+    let dry = String::from_utf8(gmap_stdout(
+        dir.path(),
+        &["--cache", cache_arg, "clean", "--dry-run"],
+    ))
+    .unwrap();
+    assert!(dry.contains("Would remove"), "{dry}");
+    assert!(db.exists(), "dry run keeps the file");
+
+    let out = String::from_utf8(gmap_stdout(dir.path(), &["--cache", cache_arg, "clean"])).unwrap();
+    assert!(out.contains("Freed"), "{out}");
+    assert!(!db.exists());
+    assert!(!cache_dir.exists(), "empty cache directory is removed");
+}
+
+#[test]
+fn by_domain_groups_churn_by_author_email_domain() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    for (i, email) in ["a@corp.com", "b@Corp.com", "c@oss.org", "nobody"]
+        .iter()
+        .enumerate()
+    {
+        commit_file_with_env(
+            dir.path(),
+            &format!("f{i}.txt"),
+            "x\n",
+            &[("GIT_AUTHOR_EMAIL", email)],
+        );
+    }
+
+    let out = gmap_stdout(dir.path(), &["churn", "--by-domain", "--json"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(v["group_by"], "domain");
+    let groups: Vec<(String, u64, u64)> = v["groups"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|g| {
+            (
+                g["key"].as_str().unwrap().to_string(),
+                g["commits"].as_u64().unwrap(),
+                g["authors"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            ("corp.com".to_string(), 2, 2),
+            ("oss.org".to_string(), 1, 1),
+            ("unknown".to_string(), 1, 1),
+        ]
+    );
+}
+
+#[test]
+fn by_type_tallies_conventional_commit_types() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    let messages = [
+        "feat: first",
+        "feat(cli)!: second",
+        "fix(cache): third",
+        "update readme",
+    ];
+    for (i, message) in messages.iter().enumerate() {
+        let name = format!("f{i}.txt");
+        fs::write(dir.path().join(&name), "x\ny\n").unwrap();
+        git(dir.path(), &["add", &name]);
+        git(dir.path(), &["commit", "-q", "-m", message]);
+    }
+
+    let out = gmap_stdout(dir.path(), &["churn", "--by-type", "--json"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(v["group_by"], "type");
+    let tally: Vec<(String, u64, u64)> = v["groups"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|g| {
+            (
+                g["key"].as_str().unwrap().to_string(),
+                g["commits"].as_u64().unwrap(),
+                g["total_lines"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        tally,
+        vec![
+            ("feat".to_string(), 2, 4),
+            ("fix".to_string(), 1, 2),
+            ("other".to_string(), 1, 2),
+        ]
+    );
+}
+
+#[test]
+fn size_bucket_puts_large_file_in_large_bucket() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "small.toml", "a = 1\n");
+    // 2000 lines of 50 bytes each, about 100 KiB
+    let large: String = (0..2000).map(|i| format!("{i:049}\n")).collect();
+    commit_file(dir.path(), "large.rs", &large);
+
+    let out = gmap_stdout(dir.path(), &["churn", "--size-bucket", "--json"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(v["group_by"], "size");
+    let groups = v["groups"].as_array().unwrap();
+    let bucket = |key: &str| groups.iter().find(|g| g["key"] == key).cloned();
+    assert_eq!(bucket("large").unwrap()["total_lines"], 2000);
+    assert_eq!(bucket("small").unwrap()["total_lines"], 1);
+    assert!(bucket("medium").is_none());
+}
+
+#[test]
+fn merges_only_analyzes_just_the_merge_commit() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_branched_repo(dir.path());
+    git(
+        dir.path(),
+        &["merge", "-q", "--no-ff", "feature", "-m", "merge feature"],
+    );
+
+    // warm the cache with the regular commits first
+    assert_eq!(export_messages_with(dir.path(), &[]).len(), 4);
+    assert_eq!(
+        export_messages_with(dir.path(), &["--merges-only"]),
+        vec!["merge feature"]
+    );
+
+    let mut both = Command::cargo_bin("gmap").unwrap();
+    both.current_dir(dir.path())
+        .args(["--merges-only", "--include-merges", "export"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn combined_merge_diff_counts_every_octopus_parent() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "base.txt", "base\n", "2024-01-01T12:00:00Z");
+    for branch in ["one", "two"] {
+        git(dir.path(), &["checkout", "-q", "-b", branch, "master"]);
+        commit_file_at(
+            dir.path(),
+            &format!("{branch}.txt"),
+            "1\n2\n3\n",
+            "2024-01-02T12:00:00Z",
+        );
+    }
+    git(dir.path(), &["checkout", "-q", "master"]);
+    commit_file_at(dir.path(), "main.txt", "m\n", "2024-01-03T12:00:00Z");
+    git(
+        dir.path(),
+        &["merge", "-q", "--no-ff", "one", "two", "-m", "octopus"],
+    );
+
+    let merge_lines = |extra: &[&str]| -> u64 {
+        let mut args = vec!["--merges-only"];
+        args.extend(extra);
+        args.extend(["export", "--json"]);
+        let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(dir.path(), &args)).unwrap();
+        let entries = v["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1, "only the octopus merge");
+        entries[0]["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["added_lines"].as_u64().unwrap() + f["deleted_lines"].as_u64().unwrap())
+            .sum()
+    };
+
+    // first parent (master) sees one.txt and two.txt come in
+    let first_parent = merge_lines(&[]);
+    assert_eq!(first_parent, 6);
+    // against each branch parent, main.txt and the other branch's file arrive too
+    let combined = merge_lines(&["--merge-diff", "combined"]);
+    assert_eq!(combined, 7);
+    assert!(combined > first_parent);
+}
+
+#[test]
+fn resolution_merge_churn_counts_only_the_conflict_fix() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    let config: String = (0..20).map(|i| format!("key{i} = {i}\n")).collect();
+    commit_file_at(dir.path(), "config.txt", &config, "2024-01-01T12:00:00Z");
+    git(dir.path(), &["checkout", "-q", "-b", "feature", "master"]);
+    let module: String = (0..50).map(|i| format!("fn f{i}() {{}}\n")).collect();
+    commit_file_at(dir.path(), "module.rs", &module, "2024-01-02T12:00:00Z");
+    let feature_config = config.replace("key0 = 0", "key0 = feature");
+    commit_file_at(
+        dir.path(),
+        "config.txt",
+        &feature_config,
+        "2024-01-03T12:00:00Z",
+    );
+    git(dir.path(), &["checkout", "-q", "master"]);
+    let main_config = config.replace("key0 = 0", "key0 = main");
+    commit_file_at(
+        dir.path(),
+        "config.txt",
+        &main_config,
+        "2024-01-04T12:00:00Z",
+    );
+
+    // conflicts on key0; settle it with a value neither side had
+    let merge = Command::new("git")
+        .args(["merge", "-q", "--no-ff", "feature", "-m", "merge feature"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(!merge.status.success(), "the merge should conflict");
+    let resolved = config.replace("key0 = 0", "key0 = both");
+    fs::write(dir.path().join("config.txt"), resolved).unwrap();
+    git(dir.path(), &["add", "config.txt"]);
+    git(
+        dir.path(),
+        &["-c", "core.editor=true", "commit", "-q", "--no-edit"],
+    );
+
+    let merge_lines = |mode: &str| -> u64 {
+        let args = ["--merges-only", "--merge-churn", mode, "export", "--json"];
+        let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(dir.path(), &args)).unwrap();
+        let entries = v["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1, "only the merge");
+        entries[0]["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["added_lines"].as_u64().unwrap() + f["deleted_lines"].as_u64().unwrap())
+            .sum()
+    };
+
+    // the first-parent diff brings in module.rs and rewrites key0
+    let full = merge_lines("full");
+    assert_eq!(full, 52);
+    // against the conflicted automatic merge, the fix replaces a 5-line
+    // conflict block with one line
+    let resolution = merge_lines("resolution");
+    assert_eq!(resolution, 6);
+    assert!(resolution * 5 < full);
+}
+
+#[test]
+fn ignore_revs_drops_listed_commits_from_churn() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(
+        dir.path(),
+        "src/lib.rs",
+        "fn a() {}\n",
+        "2024-01-01T12:00:00Z",
+    );
+    let reformat: String = (0..300).map(|i| format!("line {i}\n")).collect();
+    commit_file_at(
+        dir.path(),
+        "vendor/big.rs",
+        &reformat,
+        "2024-01-02T12:00:00Z",
+    );
+    let out = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    let big = String::from_utf8(out.stdout).unwrap();
+    commit_file_at(
+        dir.path(),
+        "src/lib.rs",
+        "fn b() {}\n",
+        "2024-01-03T12:00:00Z",
+    );
+
+    let churn_paths = |args: &[&str]| -> Vec<String> {
+        let mut all = args.to_vec();
+        all.extend(["churn", "--json"]);
+        let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(dir.path(), &all)).unwrap();
+        v["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["path"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(churn_paths(&[])[0], "vendor/big.rs");
+
+    let list = dir.path().join("ignore-revs.txt");
+    fs::write(&list, format!("# repo-wide reformat\n\n{big}")).unwrap();
+    let ignored = churn_paths(&["--ignore-revs", list.to_str().unwrap()]);
+    assert_eq!(ignored, vec!["src/lib.rs"]);
+    // the inline form takes abbreviated hashes too
+    assert_eq!(churn_paths(&["--ignore-rev", &big[..10]]), ignored);
+}
+
+#[test]
+fn aliases_merge_author_names_in_churn() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    let as_author = |name: &str, email: &str, content: &str| {
+        commit_file_with_env(
+            dir.path(),
+            "lib.rs",
+            content,
+            &[("GIT_AUTHOR_NAME", name), ("GIT_AUTHOR_EMAIL", email)],
+        );
+    };
+    as_author("Alice", "alice@corp.example", "a\n");
+    as_author("alice-laptop", "alice@home.example", "a\nb\n");
+    as_author("Bob", "bob@corp.example", "a\nb\nc\n");
+
+    let authors = |args: &[&str]| -> Vec<String> {
+        let mut all = args.to_vec();
+        all.extend(["churn", "--json"]);
+        let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(dir.path(), &all)).unwrap();
+        let mut names: Vec<String> = v["entries"][0]["authors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a.as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(authors(&[]), vec!["Alice", "Bob", "alice-laptop"]);
+
+    let file = dir.path().join("aliases.ini");
+    fs::write(&file, "[aliases]\nAlice = alice-laptop\n").unwrap();
+    let merged = authors(&["--aliases", file.to_str().unwrap()]);
+    assert_eq!(merged, vec!["Alice", "Bob"]);
+}
+
+#[test]
+fn churn_tree_totals_match_flat_churn_per_directory() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "src/cli/args.rs", "a\nb\nc\n");
+    commit_file(dir.path(), "src/lib.rs", "x\n");
+    commit_file(dir.path(), "src/cli/args.rs", "a\nB\n");
+    commit_file(dir.path(), "docs/guide.md", "1\n2\n");
+
+    let flat: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["churn", "--json"])).unwrap();
+    let flat_total = |prefix: &str| -> u64 {
+        flat["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["path"].as_str().unwrap().starts_with(prefix))
+            .map(|e| e["total_lines"].as_u64().unwrap())
+            .sum()
+    };
+    let v: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["churn", "--tree", "--json"])).unwrap();
+    let root = &v["tree"];
+    assert_eq!(root["name"], ".");
+    assert_eq!(root["total_lines"].as_u64().unwrap(), flat_total(""));
+
+    fn check(node: &serde_json::Value, flat_total: &dyn Fn(&str) -> u64) {
+        let Some(children) = node["children"].as_array() else {
+            return;
+        };
+        let path = node["path"].as_str().unwrap();
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{path}/")
+        };
+        assert_eq!(node["total_lines"].as_u64().unwrap(), flat_total(&prefix));
+        let summed: u64 = children
+            .iter()
+            .map(|c| c["total_lines"].as_u64().unwrap())
+            .sum();
+        assert_eq!(node["total_lines"].as_u64().unwrap(), summed);
+        for child in children {
+            check(child, flat_total);
+        }
+    }
+    check(root, &flat_total);
+
+    let src = &root["children"][0];
+    assert_eq!(src["path"], "src");
+    // args.rs changed twice and lib.rs once
+    assert_eq!(src["commit_count"], 3);
+    assert_eq!(src["children"][0]["path"], "src/cli");
+}
+
+#[test]
+fn missing_blob_error_names_the_referring_commit() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.txt", "one\ntwo\n");
+
+    let rev_parse = |rev: &str| {
+        let out = Command::new("git")
+            .args(["rev-parse", rev])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        String::from_utf8(out.stdout).unwrap().trim().to_string()
+    };
+    let commit = rev_parse("HEAD");
+    let blob = rev_parse("HEAD:a.txt");
+    fs::remove_file(
+        dir.path()
+            .join(".git/objects")
+            .join(&blob[..2])
+            .join(&blob[2..]),
+    )
+    .unwrap();
+
+    let out = Command::cargo_bin("gmap")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("--repo")
+        .arg(dir.path())
+        .args(["export", "--json"])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(out).unwrap();
+    assert!(
+        stderr.contains(&format!(
+            "object {blob} referenced by commit {commit} could not be found"
+        )),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn missing_tree_error_names_the_operation() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.txt", "one\n");
+
+    let out = Command::new("git")
+        .args(["rev-parse", "HEAD", "HEAD^{tree}"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    let ids = String::from_utf8(out.stdout).unwrap();
+    let (commit, tree) = ids.trim().split_once('\n').unwrap();
+    fs::remove_file(
+        dir.path()
+            .join(".git/objects")
+            .join(&tree[..2])
+            .join(&tree[2..]),
+    )
+    .unwrap();
+
+    let out = Command::cargo_bin("gmap")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("--repo")
+        .arg(dir.path())
+        .args(["churn", "--json"])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(out).unwrap();
+    assert!(
+        stderr.contains(&format!("while reading tree for commit {commit}")),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn last_lists_head_files_without_creating_a_cache() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.txt", "one\ntwo\n");
+    commit_file(dir.path(), "a.txt", "one\n2\nthree\n");
+
+    let v: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["last", "--json"])).unwrap();
+    assert_eq!(v["message"], "add a.txt");
+    let files = v["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["path"], "a.txt");
+    assert_eq!(files[0]["added_lines"], 2);
+    assert_eq!(files[0]["deleted_lines"], 1);
+
+    let table = String::from_utf8(gmap_stdout(dir.path(), &["last"])).unwrap();
+    assert!(table.contains("+2       -1  a.txt"), "{table}");
+    assert!(table.contains("1 file changed"), "{table}");
+    assert!(!dir.path().join(".gmap").exists());
+}
+
+#[test]
+fn last_honors_the_binary_policy() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.txt", "one\n");
+    fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+    fs::write(dir.path().join("logo.bin"), [0u8; 64]).unwrap();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-m", "logo and text"]);
+
+    let paths = |args: &[&str]| -> Vec<String> {
+        let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(dir.path(), args)).unwrap();
+        v["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(paths(&["last", "--json"]), ["a.txt"]);
+    assert_eq!(
+        paths(&["--binary", "last", "--json"]),
+        ["a.txt", "logo.bin"]
+    );
+    assert_eq!(paths(&["--only-binary", "last", "--json"]), ["logo.bin"]);
+}
+
+#[test]
+fn export_hunks_locate_a_single_line_edit() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(
+        dir.path(),
+        "a.txt",
+        "one\ntwo\nthree\n",
+        "2024-01-01T12:00:00Z",
+    );
+    commit_file_at(
+        dir.path(),
+        "a.txt",
+        "one\nTWO\nthree\n",
+        "2024-01-02T12:00:00Z",
+    );
+
+    let plain: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["export", "--json"])).unwrap();
+    assert!(plain["entries"][1]["files"][0].get("hunks").is_none());
+
+    let v: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["export", "--json", "--hunks"])).unwrap();
+    let hunks = |entry: usize| v["entries"][entry]["files"][0]["hunks"].clone();
+    assert_eq!(
+        hunks(0),
+        serde_json::json!([{"old_start": 0, "old_lines": 0, "new_start": 1, "new_lines": 3}])
+    );
+    assert_eq!(
+        hunks(1),
+        serde_json::json!([{"old_start": 2, "old_lines": 1, "new_start": 2, "new_lines": 1}])
+    );
+}
+
+#[test]
+fn name_only_export_lists_paths_without_reading_blobs() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.txt", "one\n");
+    commit_file(dir.path(), "src/b.rs", "fn b() {}\n");
+
+    let out = Command::cargo_bin("gmap")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["--repo"])
+        .arg(dir.path())
+        .args(["--debug", "export", "--json", "--name-only"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let mut files: Vec<&serde_json::Value> = v["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|e| e["files"].as_array().unwrap())
+        .collect();
+    files.sort_by_key(|f| f.as_str());
+    assert_eq!(files, ["a.txt", "src/b.rs"]);
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("[gmap] blobs read: 0"), "{stderr}");
+    assert!(!dir.path().join(".gmap").exists());
+
+    let full = Command::cargo_bin("gmap")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["--repo"])
+        .arg(dir.path())
+        .args(["--debug", "export", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(full).unwrap();
+    assert!(stderr.contains("[gmap] blobs read: "), "{stderr}");
+    assert!(!stderr.contains("[gmap] blobs read: 0\n"), "{stderr}");
+}
+
+#[test]
+fn name_only_export_honors_path_excludes() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.txt", "one\n");
+    commit_file(dir.path(), "vendor/lib.rs", "fn lib() {}\n");
+    fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+    fs::write(dir.path().join("vendor/lib.rs"), "fn lib2() {}\n").unwrap();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-m", "both"]);
+
+    // Each export drops vendor paths and the commit left without files.
+    for extra in [&["--name-only"][..], &[]] {
+        let mut args = vec!["--exclude=vendor/", "export", "--json"];
+        args.extend_from_slice(extra);
+        let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(dir.path(), &args)).unwrap();
+        let files: Vec<Vec<&str>> = v["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                e["files"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|f| f.as_str().or(f["path"].as_str()).unwrap())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(files.len(), 2, "{extra:?}: {files:?}");
+        assert!(
+            files.iter().flatten().all(|path| *path == "a.txt"),
+            "{extra:?}: {files:?}"
+        );
+    }
+}
+
+#[test]
+fn matrix_cell_sums_churn_per_directory_and_period() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "src/a.rs", "1\n2\n", "2024-01-01T12:00:00Z");
+    commit_file_at(dir.path(), "src/b.rs", "1\n2\n3\n", "2024-01-03T12:00:00Z");
+    commit_file_at(dir.path(), "docs/x.md", "x\n", "2024-01-03T13:00:00Z");
+    commit_file_at(dir.path(), "src/a.rs", "1\n", "2024-01-09T12:00:00Z");
+
+    let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &["matrix", "--json", "--normalize", "row"],
+    ))
+    .unwrap();
+    assert_eq!(v["rows"], serde_json::json!(["src", "docs"]));
+    assert_eq!(v["periods"], serde_json::json!(["2024-W01", "2024-W02"]));
+    // src in W01: 2 + 3 added lines; in W02: one deletion
+    assert_eq!(v["cells"], serde_json::json!([[5, 1], [1, 0]]));
+    assert_eq!(v["normalize"], "row");
+    assert_eq!(v["scaled"][0][0], 1.0);
+    assert_eq!(v["scaled"][0][1], 0.2);
+
+    let grid = String::from_utf8(gmap_stdout(dir.path(), &["matrix"])).unwrap();
+    assert!(grid.contains("src  │█░│ 6"), "{grid}");
+    assert!(grid.contains("2024-W01 … 2024-W02 (2 periods)"), "{grid}");
+}
+
+#[test]
+fn tag_pattern_annotates_the_tagged_commit_period() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-02T12:00:00Z");
+    git(dir.path(), &["tag", "-a", "v1.0", "-m", "first release"]);
+    commit_file_at(dir.path(), "b.txt", "b\n", "2024-01-10T12:00:00Z");
+    git(dir.path(), &["tag", "nightly-10"]);
+    commit_file_at(dir.path(), "c.txt", "c\n", "2024-01-17T12:00:00Z");
+
+    let tags_by_week = |args: &[&str]| -> Vec<(String, serde_json::Value)> {
+        let mut all = vec!["heat", "--json"];
+        all.extend(args);
+        let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(dir.path(), &all)).unwrap();
+        v["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|b| b.get("tags").is_some())
+            .map(|b| (b["week"].as_str().unwrap().to_string(), b["tags"].clone()))
+            .collect()
+    };
+
+    assert!(tags_by_week(&[]).is_empty());
+    assert_eq!(
+        tags_by_week(&["--tag-pattern", "v*"]),
+        [("2024-W01".to_string(), serde_json::json!(["v1.0"]))]
+    );
+    assert_eq!(
+        tags_by_week(&["--tag-pattern"]),
+        [
+            ("2024-W01".to_string(), serde_json::json!(["v1.0"])),
+            ("2024-W02".to_string(), serde_json::json!(["nightly-10"])),
+        ]
+    );
+
+    let table =
+        String::from_utf8(gmap_stdout(dir.path(), &["heat", "--tag-pattern", "v*"])).unwrap();
+    let line = table.lines().find(|l| l.starts_with("2024-W01")).unwrap();
+    assert!(line.ends_with("◆ v1.0"), "{line}");
+}
+
+#[test]
+fn since_last_tag_analyzes_only_commits_after_the_tag() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-01T12:00:00Z");
+    git(dir.path(), &["tag", "v1.0"]);
+    commit_file_at(dir.path(), "b.txt", "b\n", "2024-01-02T12:00:00Z");
+    git(dir.path(), &["tag", "-a", "nightly", "-m", "nightly build"]);
+    commit_file_at(dir.path(), "c.txt", "c\n", "2024-01-03T12:00:00Z");
+
+    assert_eq!(
+        export_messages_with(dir.path(), &["--since-last-tag"]),
+        ["add c.txt"]
+    );
+    assert_eq!(
+        export_messages_with(dir.path(), &["--since-last-tag", "--tag-pattern", "v*"]),
+        ["add b.txt", "add c.txt"]
+    );
+
+    let out = Command::cargo_bin("gmap")
+        .unwrap()
+        .arg("--repo")
+        .arg(dir.path())
+        .args([
+            "--since-last-tag",
+            "--tag-pattern",
+            "release-*",
+            "export",
+            "--json",
+        ])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(out).unwrap();
+    assert!(
+        stderr.contains("No tag matching 'release-*' is reachable from HEAD"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn default_branch_walks_mainline_from_a_topic_checkout() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_branched_repo(dir.path());
+    git(dir.path(), &["checkout", "-q", "feature"]);
+
+    assert_eq!(
+        export_messages_with(dir.path(), &[]),
+        ["add base.txt", "add f1.txt", "add f2.txt"]
+    );
+    assert_eq!(
+        export_messages_with(dir.path(), &["--default-branch"]),
+        ["add base.txt", "add main.txt"]
+    );
+
+    // a detached HEAD, as CI checks out, still finds the mainline
+    git(dir.path(), &["checkout", "-q", "--detach", "feature"]);
+    assert_eq!(
+        export_messages_with(dir.path(), &["--default-branch"]),
+        ["add base.txt", "add main.txt"]
+    );
+}
+
+#[test]
+fn size_counts_head_tree_per_extension() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "src/main.rs", "fn main() {}\n");
+    commit_file(dir.path(), "src/lib.rs", "pub mod a;\npub mod b;\n");
+    commit_file(dir.path(), "notes.md", "# Notes\n\ntodo\n");
+    commit_file(dir.path(), "gen/big.rs", "// generated\n");
+
+    let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &["--exclude=gen/", "size", "--json"],
+    ))
+    .unwrap();
+    assert_eq!(v["total_files"], 3);
+    assert_eq!(v["total_lines"], 6);
+    let extensions = v["extensions"].as_array().unwrap();
+    assert_eq!(extensions[0]["extension"], "md");
+    assert_eq!(extensions[0]["lines"], 3);
+    assert_eq!(extensions[1]["extension"], "rs");
+    assert_eq!(extensions[1]["files"], 2);
+    assert_eq!(extensions[1]["lines"], 3);
+    assert!(!dir.path().join(".gmap").exists());
+}
+
+#[test]
+fn summary_json_stderr_reports_the_run_without_touching_stdout() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.txt", "a\n");
+    commit_file(dir.path(), "b.txt", "b\n");
+
+    let mut cmd = Command::cargo_bin("gmap").unwrap();
+    cmd.current_dir(dir.path())
+        .arg("--repo")
+        .arg(dir.path())
+        .args(["--summary-json-stderr", "heat", "--json"]);
+    let output = cmd.assert().success().get_output().clone();
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report["buckets"].is_array());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let line = stderr.lines().last().expect("summary line");
+    let summary: serde_json::Value = serde_json::from_str(line).unwrap();
+    assert_eq!(summary["command"], "heat");
+    assert_eq!(summary["exit_code"], 0);
+    assert_eq!(summary["commits"], 2);
+    assert_eq!(summary["analyzed"], 2);
+    assert_eq!(summary["cache_hits"], 0);
+    for key in ["since", "until", "duration_ms"] {
+        assert!(summary.get(key).is_some(), "missing {key}: {line}");
+    }
+}
+
+#[test]
+fn max_depth_limits_generations_walked() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    for i in 0..5 {
+        commit_file(dir.path(), &format!("f{i}.txt"), "x\n");
+    }
+
+    let total = |extra: &[&str]| -> u64 {
+        heat_buckets(dir.path(), extra)
+            .iter()
+            .map(|(_, commits)| commits)
+            .sum()
+    };
+    assert_eq!(total(&[]), 5);
+    assert_eq!(total(&["--max-depth", "3"]), 3);
+    assert_eq!(total(&["--max-depth", "1"]), 1);
+}
+
+#[test]
+fn churn_velocity_ranks_short_lived_churn_first() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    let lines = |n: usize| "x\n".repeat(n);
+    commit_file_at(dir.path(), "long.txt", &lines(250), "2022-01-03T12:00:00Z");
+    commit_file_at(dir.path(), "short.txt", &lines(250), "2024-01-01T12:00:00Z");
+    commit_file_at(dir.path(), "short.txt", &lines(500), "2024-01-05T12:00:00Z");
+    commit_file_at(dir.path(), "long.txt", &lines(500), "2024-01-08T12:00:00Z");
+
+    let out = gmap_stdout(dir.path(), &["churn", "--sort", "velocity", "--json"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let entries = v["entries"].as_array().unwrap();
+    let paths: Vec<_> = entries
+        .iter()
+        .map(|e| e["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["short.txt", "long.txt"]);
+    assert_eq!(entries[0]["total_lines"], entries[1]["total_lines"]);
+    assert_eq!(entries[0]["velocity"].as_f64().unwrap(), 125.0);
+    assert!(entries[1]["velocity"].as_f64().unwrap() < 1.0);
+}
+
+#[test]
+fn equal_churn_entries_sort_by_path() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    for name in ["zeta.txt", "alpha.txt", "mid.txt", "beta.txt"] {
+        commit_file(dir.path(), name, "one\n");
+    }
+
+    for _ in 0..3 {
+        let out = gmap_stdout(dir.path(), &["churn", "--json"]);
+        let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let paths: Vec<_> = v["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["path"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(paths, ["alpha.txt", "beta.txt", "mid.txt", "zeta.txt"]);
+    }
+}
+
+#[test]
+fn jsonl_is_an_alias_for_ndjson() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "src/a.rs", "fn a(){}\n", "2024-01-02T10:00:00Z");
+    commit_file_at(dir.path(), "b.md", "# b\n", "2024-01-09T10:00:00Z");
+
+    for cmd in ["churn", "heat", "export"] {
+        let ndjson = gmap_stdout(dir.path(), &[cmd, "--ndjson"]);
+        let jsonl = gmap_stdout(dir.path(), &[cmd, "--jsonl"]);
+        assert!(!ndjson.is_empty());
+        assert_eq!(ndjson, jsonl, "{cmd} --jsonl differs from --ndjson");
+    }
+
+    Command::cargo_bin("gmap")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["churn", "--json", "--jsonl"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn contradictory_format_flags_are_rejected() {
+    let dir = tempdir().unwrap();
+    for cmd in ["churn", "heat", "export"] {
+        for (first, second) in [
+            ("--json", "--ndjson"),
+            ("--ndjson", "--prometheus"),
+            ("--json", "--format"),
+        ] {
+            let mut args = vec![cmd, first, second];
+            if second == "--format" {
+                args.push("csv");
+            }
+            let out = Command::cargo_bin("gmap")
+                .unwrap()
+                .current_dir(dir.path())
+                .args(&args)
+                .assert()
+                .failure()
+                .get_output()
+                .stderr
+                .clone();
+            let stderr = String::from_utf8_lossy(&out);
+            assert!(stderr.contains("cannot be used with"), "{stderr}");
+            assert!(
+                stderr.contains(first) && stderr.contains(second),
+                "{stderr}"
+            );
+        }
+    }
+}
+
+#[test]
+fn authors_lists_identities_by_commit_count() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    let as_author = |name: &str, email: &str, content: &str| {
+        commit_file_with_env(
+            dir.path(),
+            "lib.rs",
+            content,
+            &[("GIT_AUTHOR_NAME", name), ("GIT_AUTHOR_EMAIL", email)],
+        );
+    };
+    as_author("Bob", "bob@example.com", "a\n");
+    as_author("Alice", "alice@example.com", "a\nb\n");
+    as_author("Alice", "alice@example.com", "a\nb\nc\n");
+
+    let out = gmap_stdout(dir.path(), &["authors", "--json"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let authors: Vec<_> = v["authors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| {
+            (
+                a["name"].as_str().unwrap().to_string(),
+                a["email"].as_str().unwrap().to_string(),
+                a["commits"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        authors,
+        [
+            ("Alice".to_string(), "alice@example.com".to_string(), 2),
+            ("Bob".to_string(), "bob@example.com".to_string(), 1),
+        ]
+    );
+
+    let table = String::from_utf8(gmap_stdout(dir.path(), &["authors"])).unwrap();
+    assert!(table.contains("alice@example.com"), "{table}");
+}
+
+#[test]
+fn case_sensitive_author_filter_requires_exact_case() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_with_env(
+        dir.path(),
+        "lib.rs",
+        "a\n",
+        &[
+            ("GIT_AUTHOR_NAME", "Alice"),
+            ("GIT_AUTHOR_EMAIL", "alice@example.com"),
+        ],
+    );
+
+    let commits = |args: &[&str]| -> u64 {
+        let mut all = args.to_vec();
+        all.extend(["heat", "--json"]);
+        let v: serde_json::Value = serde_json::from_slice(&gmap_stdout(dir.path(), &all)).unwrap();
+        v["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["commit_count"].as_u64().unwrap())
+            .sum()
+    };
+    assert_eq!(commits(&["--author", "alice"]), 1);
+    assert_eq!(commits(&["--author", "alice", "--case-sensitive"]), 0);
+    assert_eq!(commits(&["--author", "Alice", "--case-sensitive"]), 1);
+}
+
+#[test]
+fn explicit_commit_and_date_bounds_reject_the_other_kind() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-02T10:00:00Z");
+    commit_file_at(dir.path(), "b.txt", "b\n", "2024-02-10T10:00:00Z");
+
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("gmap").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("--repo")
+            .arg(dir.path())
+            .args(args);
+        cmd.output().unwrap()
+    };
+    let commits = |args: &[&str]| -> usize {
+        let mut all = args.to_vec();
+        all.extend(["export", "--json"]);
+        let out = run(&all);
+        assert!(out.status.success(), "{out:?}");
+        let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        v["entries"].as_array().unwrap().len()
+    };
+    let failure = |args: &[&str]| -> String {
+        let mut all = args.to_vec();
+        all.extend(["export", "--json"]);
+        let out = run(&all);
+        assert!(!out.status.success(), "{args:?} should fail");
+        String::from_utf8(out.stderr).unwrap()
+    };
+
+    assert_eq!(commits(&["--since-commit", "HEAD"]), 1);
+    assert_eq!(commits(&["--until-commit", "HEAD~1"]), 1);
+    assert_eq!(commits(&["--since-date", "2024-02-01"]), 1);
+    assert_eq!(commits(&["--until-date", "2024-02-01"]), 1);
+
+    let err = failure(&["--since-commit", "2024-01-01"]);
+    assert!(err.contains("Invalid commit '2024-01-01'"), "{err}");
+    let err = failure(&["--until-date", "HEAD"]);
+    assert!(err.contains("Not a date: 'HEAD'"), "{err}");
+    let err = failure(&["--since", "HEAD", "--since-date", "2024-01-01"]);
+    assert!(err.contains("cannot be used with"), "{err}");
+
+    let out = run(&[
+        "--debug",
+        "--since",
+        "HEAD~1",
+        "--until",
+        "2024-03-01",
+        "heat",
+    ]);
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("since 'HEAD~1' read as commit"), "{stderr}");
+    assert!(
+        stderr.contains("until '2024-03-01' read as date"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn show_signatures_reports_signed_and_unsigned_commits() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.txt", "a\n");
+    commit_file(dir.path(), "b.txt", "b\n");
+
+    // Splice a signature header into HEAD; presence is all gmap checks.
+    let raw = Command::new("git")
+        .args(["cat-file", "commit", "HEAD"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap()
+        .stdout;
+    let raw = String::from_utf8(raw).unwrap();
+    let (headers, message) = raw.split_once("\n\n").unwrap();
+    let signed = format!(
+        "{headers}\ngpgsig -----BEGIN PGP SIGNATURE-----\n \n iQEzBAABCAAdFiEE\n -----END PGP SIGNATURE-----\n\n{message}"
+    );
+    let mut child = Command::new("git")
+        .args(["hash-object", "-t", "commit", "-w", "--stdin"])
+        .current_dir(dir.path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(signed.as_bytes())
+        .unwrap();
+    let id = String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap();
+    git(dir.path(), &["update-ref", "HEAD", id.trim()]);
+
+    // Both commits share a timestamp, so compare regardless of order.
+    let signatures = |args: &[&str]| -> Vec<Option<String>> {
+        let out = gmap_stdout(dir.path(), args);
+        let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let mut found: Vec<_> = v["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["signature"].as_str().map(str::to_string))
+            .collect();
+        found.sort();
+        found
+    };
+    assert_eq!(signatures(&["export", "--json"]), [None, None]);
+    assert_eq!(
+        signatures(&["export", "--json", "--show-signatures"]),
+        [Some("gpg".to_string()), Some("unsigned".to_string())]
+    );
+}
+
+#[test]
+fn heat_export_image_writes_a_png() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.txt", "a\n", "2024-01-02T12:00:00Z");
+    commit_file_at(dir.path(), "a.txt", "a\nb\n", "2024-01-20T12:00:00Z");
+
+    let image = dir.path().join("heat.png");
+    let out = Command::cargo_bin("gmap")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("--repo")
+        .arg(dir.path())
+        .args(["heat", "--export-image"])
+        .arg(&image)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    assert!(out.stdout.is_empty());
+
+    let png = fs::read(&image).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert!(png.len() > 8);
+}
+
+#[test]
+fn summary_entries_total_the_detailed_files() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.txt", "1\n2\n3\n");
+    // b.txt is picked up by the next commit's `git add .`
+    fs::write(dir.path().join("b.txt"), "x\ny\n").unwrap();
+    commit_file(dir.path(), "a.txt", "1\nchanged\n");
+
+    let detailed: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["export", "--json"])).unwrap();
+    let summaries = gmap_stdout(dir.path(), &["export", "--ndjson", "--summary-entries"]);
+    let summaries: Vec<serde_json::Value> = summaries
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_slice(line).unwrap())
+        .collect();
+
+    let entries = detailed["entries"].as_array().unwrap();
+    assert_eq!(summaries.len(), entries.len());
+    for entry in entries {
+        let summary = summaries
+            .iter()
+            .find(|s| s["commit_id"] == entry["commit_id"])
+            .unwrap();
+        let files = entry["files"].as_array().unwrap();
+        let total = |key: &str| files.iter().map(|f| f[key].as_u64().unwrap()).sum::<u64>();
+        assert_eq!(summary["files_changed"], files.len() as u64);
+        assert_eq!(summary["added"], total("added_lines"));
+        assert_eq!(summary["deleted"], total("deleted_lines"));
+        assert!(summary.get("files").is_none());
+    }
+    let second = summaries.iter().find(|s| s["files_changed"] == 2).unwrap();
+    assert_eq!(
+        (&second["added"], &second["deleted"]),
+        (&3.into(), &2.into())
+    );
+}
+
+#[test]
+fn group_by_week_export_matches_the_tui_aggregation() {
+    use gmap::cache::Cache;
+    use gmap::filter::CommitFilter;
+    use gmap::heat::aggregate_weeks;
+    use gmap::model::{DateKind, DateRange};
+    use gmap::util::PeriodSpec;
+
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "src/a.rs", "1\n", "2024-01-02T12:00:00Z");
+    commit_file_at(dir.path(), "README.md", "hi\n", "2024-01-03T12:00:00Z");
+    commit_file_at(dir.path(), "src/a.rs", "1\n2\n", "2024-01-10T12:00:00Z");
+
+    let out = gmap_stdout(dir.path(), &["export", "--json", "--group-by-week"]);
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+    // The export filled the cache; aggregate straight from it.
+    let cache = Cache::new(None::<&Path>, dir.path(), DateKind::Committer).unwrap();
+    let stats = cache.get_commit_stats(&DateRange::default()).unwrap();
+    let weeks = aggregate_weeks(
+        &stats,
+        &cache,
+        None,
+        &CommitFilter::default(),
+        PeriodSpec::default(),
+        &[],
+        None,
+    );
+    assert_eq!(v["weeks"], serde_json::to_value(&weeks).unwrap());
+
+    assert_eq!(v["weeks"][0]["week"], "2024-W01");
+    assert_eq!(v["weeks"][0]["commits"], 2);
+    assert_eq!(
+        v["weeks"][0]["top_authors"],
+        serde_json::json!([["Your Name", 2]])
+    );
+    assert_eq!(v["weeks"][0]["file_extensions"]["rs"]["lines_added"], 1);
+}
+
+#[test]
+fn weekly_aggregate_is_reused_until_the_inputs_change() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.rs", "1\n", "2024-01-02T12:00:00Z");
+
+    let run = |args: &[&str]| {
+        let out = Command::cargo_bin("gmap")
+            .unwrap()
+            .current_dir(dir.path())
+            .arg("--repo")
+            .arg(dir.path())
+            .arg("--debug")
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .clone();
+        (out.stdout, String::from_utf8(out.stderr).unwrap())
+    };
+    let export = ["export", "--json", "--compact", "--group-by-week"];
+
+    let (first, stderr) = run(&export);
+    assert!(stderr.contains("weekly aggregate: cache miss"), "{stderr}");
+    let (second, stderr) = run(&export);
+    assert!(stderr.contains("weekly aggregate: cache hit"), "{stderr}");
+    let weeks =
+        |out: &[u8]| serde_json::from_slice::<serde_json::Value>(out).unwrap()["weeks"].clone();
+    assert_eq!(weeks(&first), weeks(&second));
+
+    // Other filters and new commits each miss.
+    let (_, stderr) = run(&["--author", "nobody", "export", "--json", "--group-by-week"]);
+    assert!(stderr.contains("weekly aggregate: cache miss"), "{stderr}");
+    commit_file_at(dir.path(), "a.rs", "1\n2\n", "2024-01-03T12:00:00Z");
+    let (third, stderr) = run(&export);
+    assert!(stderr.contains("weekly aggregate: cache miss"), "{stderr}");
+    assert_eq!(weeks(&third)[0]["commits"], 2);
+}
+
+#[test]
+fn weekly_aggregate_misses_after_a_nested_gitignore_changes() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.rs", "1\n", "2024-01-02T12:00:00Z");
+    commit_file_at(dir.path(), "web/app.js", "1\n2\n", "2024-01-02T13:00:00Z");
+
+    let run = || {
+        let out = Command::cargo_bin("gmap")
+            .unwrap()
+            .current_dir(dir.path())
+            .arg("--repo")
+            .arg(dir.path())
+            .arg("--debug")
+            .args(["export", "--json", "--group-by-week"])
+            .assert()
+            .success()
+            .get_output()
+            .clone();
+        let weeks =
+            serde_json::from_slice::<serde_json::Value>(&out.stdout).unwrap()["weeks"][0].clone();
+        (weeks, String::from_utf8(out.stderr).unwrap())
+    };
+
+    let (weeks, _) = run();
+    assert_eq!(weeks["lines_added"], 3);
+    let (_, stderr) = run();
+    assert!(stderr.contains("weekly aggregate: cache hit"), "{stderr}");
+
+    std::fs::write(dir.path().join("web/.gitignore"), "*.js\n").unwrap();
+    let (weeks, stderr) = run();
+    assert!(stderr.contains("weekly aggregate: cache miss"), "{stderr}");
+    assert_eq!(weeks["lines_added"], 1);
+}
+
+#[test]
+fn committed_ignored_files_are_skipped_by_heat_churn_and_the_tui_alike() {
+    use gmap::cache::Cache;
+    use gmap::filter::CommitFilter;
+    use gmap::heat::aggregate_weeks;
+    use gmap::model::{DateKind, DateRange};
+    use gmap::util::{GitIgnoreMatcher, PeriodSpec};
+
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+    commit_file_at(dir.path(), "src/a.rs", "1\n", "2024-01-02T12:00:00Z");
+
+    // Force-add files ignored by the root and a nested .gitignore.
+    let force_commit = |files: &[(&str, &str)], date: &str| {
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+            git(dir.path(), &["add", "-f", name]);
+        }
+        let status = Command::new("git")
+            .args(["commit", "-m", "forced"])
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .current_dir(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    force_commit(
+        &[
+            ("src/.gitignore", "gen.rs\n"),
+            ("build.log", "x\ny\n"),
+            ("src/b.rs", "1\n"),
+        ],
+        "2024-01-09T12:00:00Z",
+    );
+    force_commit(&[("src/gen.rs", "1\n2\n3\n")], "2024-01-16T12:00:00Z");
+
+    let churn = gmap_stdout(dir.path(), &["churn", "--json"]);
+    let churn: serde_json::Value = serde_json::from_slice(&churn).unwrap();
+    let mut paths: Vec<_> = churn["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["path"].as_str().unwrap().to_string())
+        .collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        [".gitignore", "src/.gitignore", "src/a.rs", "src/b.rs"]
+    );
+
+    let cache = Cache::new(None::<&Path>, dir.path(), DateKind::Committer).unwrap();
+    let stats = cache.get_commit_stats(&DateRange::default()).unwrap();
+    let gi = GitIgnoreMatcher::new(dir.path());
+    for prefix in [None, Some("src")] {
+        let mut args = vec!["heat", "--json"];
+        args.extend(prefix);
+        let heat = gmap_stdout(dir.path(), &args);
+        let heat: serde_json::Value = serde_json::from_slice(&heat).unwrap();
+        let heat: Vec<_> = heat["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| {
+                (
+                    b["week"].as_str().unwrap().to_string(),
+                    b["commit_count"].as_u64().unwrap() as usize,
+                    b["lines_added"].as_u64().unwrap() as usize,
+                )
+            })
+            .collect();
+
+        let weeks = aggregate_weeks(
+            &stats,
+            &cache,
+            prefix,
+            &CommitFilter::default(),
+            PeriodSpec::default(),
+            &[],
+            Some(&gi),
+        );
+        let tui: Vec<_> = weeks
+            .iter()
+            .map(|w| (w.week.clone(), w.commits, w.lines_added))
+            .collect();
+        assert_eq!(heat, tui, "prefix {prefix:?}");
+        assert!(weeks
+            .iter()
+            .flat_map(|w| &w.file_activity)
+            .all(|f| f.path != "build.log" && f.path != "src/gen.rs"));
+    }
+}
+
+#[test]
+fn gitignored_directories_drop_the_files_under_them() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    fs::write(dir.path().join(".gitignore"), "dist/\n*.log\n").unwrap();
+    fs::create_dir(dir.path().join("dist")).unwrap();
+    fs::write(dir.path().join("dist/a.js"), "1\n").unwrap();
+    fs::write(dir.path().join("build.log"), "x\n").unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+    git(dir.path(), &["add", "-f", "."]);
+    git(dir.path(), &["commit", "-m", "force-add build output"]);
+
+    let churn: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["churn", "--json"])).unwrap();
+    let mut paths: Vec<_> = churn["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["path"].as_str().unwrap().to_string())
+        .collect();
+    paths.sort();
+    assert_eq!(paths, [".gitignore", "main.rs"]);
+}
+
+#[test]
+fn no_gitignore_counts_committed_ignored_files() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    fs::write(dir.path().join(".gitignore"), "generated.rs\n").unwrap();
+    commit_file(dir.path(), "lib.rs", "fn a() {}\n");
+    fs::write(dir.path().join("generated.rs"), "1\n2\n").unwrap();
+    git(dir.path(), &["add", "-f", "generated.rs"]);
+    git(dir.path(), &["commit", "-m", "check in generated code"]);
+
+    let churn_paths = |extra: &[&str]| {
+        let mut args = extra.to_vec();
+        args.extend(["churn", "--json"]);
+        let out = gmap_stdout(dir.path(), &args);
+        let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let mut paths: Vec<_> = v["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["path"].as_str().unwrap().to_string())
+            .collect();
+        paths.sort();
+        paths
+    };
+    assert_eq!(churn_paths(&[]), [".gitignore", "lib.rs"]);
+    assert_eq!(
+        churn_paths(&["--no-gitignore"]),
+        [".gitignore", "generated.rs", "lib.rs"]
+    );
+
+    let added = |extra: &[&str]| {
+        let mut args = extra.to_vec();
+        args.extend(["heat", "--json"]);
+        let out = gmap_stdout(dir.path(), &args);
+        let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        v["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["lines_added"].as_u64().unwrap())
+            .sum::<u64>()
+    };
+    assert_eq!(added(&[]), 2);
+    assert_eq!(added(&["--no-gitignore"]), 4);
+}
+
+#[test]
+fn ndjson_header_line_precedes_the_records() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "a.rs", "1\n");
+    commit_file(dir.path(), "a.rs", "1\n2\n");
+
+    for args in [
+        &["heat", "--ndjson"][..],
+        &["churn", "--ndjson"],
+        &["export", "--ndjson"],
+        &["export", "--ndjson", "--summary-entries"],
+    ] {
+        let plain = String::from_utf8(gmap_stdout(dir.path(), args)).unwrap();
+        let mut with_header = args.to_vec();
+        with_header.push("--ndjson-header");
+        let out = String::from_utf8(gmap_stdout(dir.path(), &with_header)).unwrap();
+
+        let (first, records) = out.split_once('\n').unwrap();
+        let meta: serde_json::Value = serde_json::from_str(first).unwrap();
+        assert_eq!(meta["type"], "meta", "{args:?}");
+        assert_eq!(meta["version"], gmap::model::SCHEMA_VERSION);
+        assert_eq!(meta["command"], args[0]);
+        assert!(meta["repository_path"].is_string());
+        assert_eq!(records, plain, "{args:?}");
+        assert!(!plain.contains("\"meta\""));
+    }
+}
+
+#[test]
+fn churn_spark_series_follows_each_file_per_week() {
+    use gmap::cache::Cache;
+    use gmap::churn::{churn_series, ChurnScope};
+    use gmap::filter::CommitFilter;
+    use gmap::model::{DateKind, DateRange, PeriodSeries};
+    use gmap::util::PeriodSpec;
+
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.rs", "1\n", "2024-01-02T12:00:00Z");
+    commit_file_at(dir.path(), "b.rs", "1\n", "2024-01-09T12:00:00Z");
+    commit_file_at(dir.path(), "a.rs", "2\n3\n", "2024-01-16T12:00:00Z");
+
+    let table = String::from_utf8(gmap_stdout(dir.path(), &["churn", "--spark"])).unwrap();
+    assert!(table.contains("Commit trend"), "{table}");
+
+    let cache = Cache::new(None::<&Path>, dir.path(), DateKind::Committer).unwrap();
+    let stats = cache.get_commit_stats(&DateRange::default()).unwrap();
+    let filter = CommitFilter::default();
+    let scope = ChurnScope {
+        cache: &cache,
+        depth: None,
+        path_prefix: None,
+        filter: &filter,
+        excludes: &[],
+        git_ignore: None,
+    };
+    let series = churn_series(&stats, &scope, PeriodSpec::default()).unwrap();
+    // W01 to W03, with a.rs untouched in W02.
+    assert_eq!(
+        series["a.rs"],
+        PeriodSeries {
+            commits: vec![1, 0, 1],
+            lines: vec![1, 0, 3],
+        }
+    );
+    assert_eq!(series["b.rs"].commits, [0, 1, 0]);
+
+    let err = Command::cargo_bin("gmap")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["--repo"])
+        .arg(dir.path())
+        .args(["churn", "--spark", "--json"])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    assert!(String::from_utf8_lossy(&err).contains("--spark supports table output only"));
+}
+
+#[test]
+fn max_files_per_commit_summarizes_a_huge_commit() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "small.rs", "1\n");
+    std::fs::create_dir(dir.path().join("vendor")).unwrap();
+    for i in 0..30 {
+        std::fs::write(dir.path().join(format!("vendor/f{i}.rs")), "x\n").unwrap();
+    }
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-m", "vendor import"]);
+
+    let churn = |args: &[&str]| {
+        let out = Command::cargo_bin("gmap")
+            .unwrap()
+            .current_dir(dir.path())
+            .arg("--repo")
+            .arg(dir.path())
+            .args(args)
+            .args(["churn", "--json"])
+            .assert()
+            .success()
+            .get_output()
+            .clone();
+        let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        let mut paths: Vec<(String, u64)> = v["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                let path = e["path"].as_str().unwrap().to_string();
+                (path, e["total_lines"].as_u64().unwrap())
+            })
+            .collect();
+        paths.sort();
+        (paths, String::from_utf8(out.stderr).unwrap())
+    };
+
+    // The summary has no path, so it stays out of churn, and none of the
+    // vendored blobs is read to build it.
+    let (summarized, stderr) = churn(&["--debug", "--max-files-per-commit", "10"]);
+    assert_eq!(summarized, [("small.rs".to_string(), 1)]);
+    assert!(
+        stderr.contains("changes 30 files, over --max-files-per-commit 10"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("blobs read: 1"), "{stderr}");
+
+    let export: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &["--max-files-per-commit", "10", "export", "--json"],
+    ))
+    .unwrap();
+    let vendor = export["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["message"].as_str().unwrap().trim() == "vendor import")
+        .unwrap();
+    assert_eq!(vendor["summarized_files"], 30);
+    assert_eq!(vendor["files"].as_array().unwrap().len(), 0);
+
+    // Without the limit the cached summary is analyzed again in full, and a
+    // limit on the full rows summarizes them once more.
+    let (full, stderr) = churn(&[]);
+    assert_eq!(full.len(), 31);
+    assert!(!stderr.contains("warning"), "{stderr}");
+    assert_eq!(churn(&["--max-files-per-commit", "10"]).0, summarized);
+    assert_eq!(churn(&["--max-files-per-commit", "30"]).0.len(), 31);
+}
+
+#[test]
+fn exclude_root_drops_the_initial_import() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(
+        dir.path(),
+        "import.rs",
+        &"x\n".repeat(500),
+        "2024-01-02T12:00:00Z",
+    );
+    commit_file_at(dir.path(), "fix.rs", "1\n2\n", "2024-01-03T12:00:00Z");
+
+    let json = |args: &[&str]| -> serde_json::Value {
+        let mut full = vec!["--exclude-root"];
+        full.extend(args);
+        serde_json::from_slice(&gmap_stdout(dir.path(), &full)).unwrap()
+    };
+
+    let heat = json(&["heat", "--json"]);
+    assert_eq!(heat["buckets"][0]["commit_count"], 1);
+    assert_eq!(heat["buckets"][0]["lines_changed"], 2);
+
+    let churn = json(&["churn", "--json"]);
+    let paths: Vec<&str> = churn["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["fix.rs"]);
+
+    let stats = json(&["stats", "--json"]);
+    assert_eq!(stats["root_commits_excluded"], 1);
+    assert_eq!(stats["summary"]["lines_added"], 2);
+
+    // Without the flag the import dominates, and stats say nothing about roots.
+    let stats: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["stats", "--json"])).unwrap();
+    assert_eq!(stats["summary"]["lines_added"], 502);
+    assert!(stats.get("root_commits_excluded").is_none());
+}
+
+#[test]
+fn binary_files_are_counted_per_period_without_line_counts() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(
+        dir.path(),
+        "main.rs",
+        "fn main() {}\n",
+        "2024-01-02T12:00:00Z",
+    );
+    std::fs::write(
+        dir.path().join("logo.png"),
+        b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR",
+    )
+    .unwrap();
+    git(dir.path(), &["add", "logo.png"]);
+    assert!(Command::new("git")
+        .args(["commit", "-m", "add logo"])
+        .envs([
+            ("GIT_AUTHOR_DATE", "2024-01-03T12:00:00Z"),
+            ("GIT_COMMITTER_DATE", "2024-01-03T12:00:00Z"),
+        ])
+        .current_dir(dir.path())
+        .status()
+        .unwrap()
+        .success());
+
+    for extra in [&[][..], &["--binary"][..]] {
+        let mut args = extra.to_vec();
+        args.extend(["heat", "--json"]);
+        let heat: serde_json::Value =
+            serde_json::from_slice(&gmap_stdout(dir.path(), &args)).unwrap();
+        let bucket = &heat["buckets"][0];
+        assert_eq!(bucket["commit_count"], 2, "{extra:?}");
+        assert_eq!(bucket["binary_files_changed"], 1, "{extra:?}");
+        assert_eq!(bucket["lines_changed"], 1, "{extra:?}");
+    }
+
+    // The image stays out of churn unless --binary asks for it.
+    let churn: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["churn", "--json"])).unwrap();
+    assert_eq!(churn["entries"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn only_binary_counts_binary_changes_by_size() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    let commit_at = |message: &str, date: &str| {
+        git(dir.path(), &["add", "-A"]);
+        assert!(Command::new("git")
+            .args(["commit", "-m", message])
+            .envs([("GIT_AUTHOR_DATE", date), ("GIT_COMMITTER_DATE", date)])
+            .current_dir(dir.path())
+            .status()
+            .unwrap()
+            .success());
+    };
+    commit_file_at(
+        dir.path(),
+        "main.rs",
+        "fn main() {}\n",
+        "2024-01-02T12:00:00Z",
+    );
+    fs::write(dir.path().join("hero.png"), [0u8; 100]).unwrap();
+    fs::write(dir.path().join("lib.rs"), "pub fn f() {}\n").unwrap();
+    commit_at("assets and code", "2024-01-03T12:00:00Z");
+    fs::write(dir.path().join("hero.png"), [0u8; 40]).unwrap();
+    commit_at("shrink hero", "2024-01-10T12:00:00Z");
+    commit_file_at(
+        dir.path(),
+        "main.rs",
+        "fn main() { f() }\n",
+        "2024-01-17T12:00:00Z",
+    );
+
+    let heat: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &["--only-binary", "heat", "--json"],
+    ))
+    .unwrap();
+    let buckets: Vec<_> = heat["buckets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| {
+            (
+                b["week"].as_str().unwrap().to_string(),
+                b["commit_count"].as_u64().unwrap(),
+                b["lines_changed"].as_u64().unwrap(),
+                b["bytes_added"].as_u64().unwrap(),
+                b["bytes_deleted"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    // The code-only commits are left out and lines stay at zero.
+    assert_eq!(
+        buckets,
+        [
+            ("2024-W01".to_string(), 1, 0, 100, 0),
+            ("2024-W02".to_string(), 1, 0, 0, 60),
+        ]
+    );
+
+    let table = String::from_utf8(gmap_stdout(dir.path(), &["--only-binary", "heat"])).unwrap();
+    assert!(table.contains("bytes:    100"), "{table}");
+
+    let churn: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &["--only-binary", "churn", "--json"],
+    ))
+    .unwrap();
+    let paths: Vec<&str> = churn["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["hero.png"]);
+}
+
+#[test]
+fn binary_churn_is_measured_in_bytes() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file(dir.path(), "main.rs", "fn main() {}\n");
+    fs::write(dir.path().join("logo.bin"), [0u8; 100]).unwrap();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-m", "add logo"]);
+    fs::write(dir.path().join("logo.bin"), [0u8; 250]).unwrap();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-m", "bigger logo"]);
+
+    // The replacement records only the growth.
+    let export: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["--binary", "export", "--json"]))
+            .unwrap();
+    let logo: Vec<_> = export["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|e| e["files"].as_array().unwrap())
+        .filter(|f| f["path"] == "logo.bin")
+        .map(|f| {
+            (
+                f["bytes_added"].as_u64().unwrap(),
+                f["bytes_deleted"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(logo.len(), 2);
+    assert!(logo.contains(&(150, 0)), "{logo:?}");
+    assert!(logo.contains(&(100, 0)), "{logo:?}");
+
+    let churn: serde_json::Value =
+        serde_json::from_slice(&gmap_stdout(dir.path(), &["--binary", "churn", "--json"])).unwrap();
+    let entry = churn["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["path"] == "logo.bin")
+        .unwrap();
+    assert_eq!(entry["total_lines"], 0);
+    assert_eq!(entry["bytes_added"], 250);
+    assert_eq!(entry["bytes_deleted"], 0);
+
+    let table = String::from_utf8(gmap_stdout(dir.path(), &["--binary", "churn"])).unwrap();
+    assert!(table.contains("Bytes +"), "{table}");
+    let text = String::from_utf8(gmap_stdout(dir.path(), &["churn"])).unwrap();
+    assert!(!text.contains("Bytes +"), "{text}");
+}
+
+#[test]
+fn sprints_bucket_commits_from_the_start_date() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.rs", "1\n", "2024-01-03T12:00:00Z");
+    commit_file_at(dir.path(), "a.rs", "2\n", "2024-01-14T12:00:00Z");
+    commit_file_at(dir.path(), "a.rs", "3\n", "2024-01-15T12:00:00Z");
+    commit_file_at(dir.path(), "a.rs", "4\n", "2024-02-20T12:00:00Z");
+
+    let heat: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &[
+            "--sprint-start",
+            "2024-01-01",
+            "--sprint-length",
+            "14",
+            "heat",
+            "--json",
+            "--fill-gaps",
+        ],
+    ))
+    .unwrap();
+    let buckets: Vec<_> = heat["buckets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| {
+            (
+                b["label"].as_str().unwrap().to_string(),
+                b["commit_count"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        buckets,
+        [
+            ("Sprint 1".to_string(), 2),
+            ("Sprint 2".to_string(), 1),
+            ("Sprint 3".to_string(), 0),
+            ("Sprint 4".to_string(), 1),
+        ]
+    );
+
+    Command::cargo_bin("gmap")
+        .unwrap()
+        .args(["--repo", dir.path().to_str().unwrap()])
+        .args(["--sprint-start", "2024-01-01", "heat"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn month_weeks_keep_weeks_inside_their_month() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    // 2024-02-01 falls in ISO week 5, which starts in January.
+    commit_file_at(dir.path(), "a.rs", "1\n", "2024-02-01T12:00:00Z");
+    commit_file_at(dir.path(), "a.rs", "2\n", "2024-02-28T12:00:00Z");
+
+    let heat: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &["--month-weeks", "heat", "--json"],
+    ))
+    .unwrap();
+    let weeks: Vec<&str> = heat["buckets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["week"].as_str().unwrap())
+        .collect();
+    assert_eq!(weeks, ["2024-02-W1", "2024-02-W4"]);
+}
+
+#[test]
+fn stats_vs_previous_period_derives_the_baseline() {
+    let dir = tempdir().unwrap();
+    if !has_git() {
+        return;
+    }
+    init_git_repo(dir.path());
+    commit_file_at(dir.path(), "a.rs", "1\n", "2023-12-30T12:00:00Z");
+    commit_file_at(dir.path(), "a.rs", "2\n", "2024-01-05T12:00:00Z");
+    commit_file_at(dir.path(), "a.rs", "3\n", "2024-01-12T12:00:00Z");
+    commit_file_at(dir.path(), "a.rs", "4\n", "2024-01-13T12:00:00Z");
+
+    let stats: serde_json::Value = serde_json::from_slice(&gmap_stdout(
+        dir.path(),
+        &[
+            "--since",
+            "2024-01-10",
+            "--until",
+            "2024-01-17",
+            "stats",
+            "--json",
+            "--vs-previous-period",
+        ],
+    ))
+    .unwrap();
+    let previous = &stats["previous_period"];
+    assert_eq!(previous["baseline"]["since"], "2024-01-02T23:59:59Z");
+    assert_eq!(previous["baseline"]["until"], "2024-01-09T23:59:59Z");
+    assert_eq!(previous["baseline"]["commits"], 1);
+    assert_eq!(previous["current"]["commits"], 2);
+    assert_eq!(previous["delta"]["commits"], 1);
+
+    // The current window is fetched once, so each commit is counted once.
+    let out = Command::cargo_bin("gmap")
+        .unwrap()
+        .args(["--repo", dir.path().to_str().unwrap(), "-v"])
+        .args(["--since", "2024-01-10", "--until", "2024-01-17"])
+        .args(["stats", "--json", "--vs-previous-period"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.contains("3 commits from cache, 0 newly analyzed"),
+        "{stderr}"
+    );
+
+    let table = String::from_utf8(gmap_stdout(
+        dir.path(),
+        &["--since", "2024-01-10", "stats", "--vs-previous-period"],
+    ))
+    .unwrap();
+    assert!(table.contains("Activity Comparison"), "{table}");
+
+    Command::cargo_bin("gmap")
+        .unwrap()
+        .args(["--repo", dir.path().to_str().unwrap()])
+        .args(["stats", "--vs-previous-period"])
+        .assert()
+        .failure();
+}